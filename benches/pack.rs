@@ -0,0 +1,94 @@
+//! Per-offset pack/unpack benchmarks.
+//!
+//! Run with `cargo bench --features bench`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use packed::bench_support::{self, Aligned, Telemetry};
+use packed::prelude::*;
+
+macro_rules! primitive {
+    ($c: expr, $($ty: ty),+) => {
+        $({
+            let mut buf = [0u8; <$ty>::SIZE / 8 + 2];
+            let value = black_box(<$ty>::default());
+            $c.bench_function(concat!("pack ", stringify!($ty)), |b| {
+                b.iter(|| {
+                    black_box(bench_support::pack_at_all_offsets(&value, &mut buf));
+                })
+            });
+            $c.bench_function(concat!("unpack ", stringify!($ty)), |b| {
+                b.iter(|| black_box(bench_support::unpack_at_all_offsets::<$ty>(black_box(&buf))))
+            });
+        })+
+    };
+}
+
+fn primitives(c: &mut Criterion) {
+    primitive!(c, bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+    primitive!(c, [bool; 16], (u8, bool, u16));
+}
+
+fn pack_at(c: &mut Criterion) {
+    let mut buf = [0u8; 6];
+    let value = black_box(0x1234_5678u32);
+    c.bench_function("pack_at::<8> u32", |b| {
+        b.iter(|| {
+            value.pack_at::<8>(&mut buf);
+            black_box(&buf);
+        })
+    });
+    c.bench_function("pack_at::<3> u32", |b| {
+        b.iter(|| {
+            value.pack_at::<3>(&mut buf);
+            black_box(&buf);
+        })
+    });
+    c.bench_function("unpack_at::<3> u32", |b| {
+        b.iter(|| black_box(u32::unpack_at::<3>(black_box(&buf))))
+    });
+}
+
+fn records(c: &mut Criterion) {
+    let records = bench_support::telemetry(1024);
+    let mut buf = vec![0u8; records.len() * Telemetry::SIZE / 8 + 2];
+    c.bench_function("pack 1024 Telemetry", |b| {
+        b.iter(|| {
+            for (i, r) in records.iter().enumerate() {
+                r.pack(&mut buf, i * Telemetry::SIZE);
+            }
+            black_box(&buf);
+        })
+    });
+    c.bench_function("unpack 1024 Telemetry", |b| {
+        b.iter(|| {
+            for i in 0..records.len() {
+                black_box(Telemetry::unpack(black_box(&buf), i * Telemetry::SIZE));
+            }
+        })
+    });
+
+    let aligned = black_box(Aligned {
+        version: 1,
+        len: 2,
+        id: 3,
+        seq: 4,
+    });
+    let mut buf = [0u8; Aligned::SIZE / 8 + 1];
+    c.bench_function("pack Aligned", |b| {
+        b.iter(|| {
+            black_box(bench_support::pack_at_all_offsets(&aligned, &mut buf));
+        })
+    });
+    c.bench_function("unpack Aligned", |b| {
+        b.iter(|| {
+            black_box(bench_support::unpack_at_all_offsets::<Aligned>(black_box(
+                &buf,
+            )))
+        })
+    });
+}
+
+criterion_group!(benches, primitives, pack_at, records);
+criterion_main!(benches);
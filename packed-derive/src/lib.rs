@@ -0,0 +1,1051 @@
+//! Derive macros for `packed::Packed`, `packed::Pack` and `packed::Unpack`.
+//!
+//! This crate is re-exported from `packed` behind the `derive` feature and
+//! should not be used directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Expr, Ident, Lifetime, LitInt, Member, Type, parse_macro_input,
+    spanned::Spanned,
+};
+
+#[proc_macro_derive(Packed, attributes(packed))]
+pub fn derive_packed(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input, true, true)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Only derives `Pack`, which can't be combined with deriving `Unpack`
+#[proc_macro_derive(Pack, attributes(packed))]
+pub fn derive_pack(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input, true, false)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Only derives `Unpack`, which can't be combined with deriving `Pack`
+#[proc_macro_derive(Unpack, attributes(packed))]
+pub fn derive_unpack(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input, false, true)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(PackedRef, attributes(packed))]
+pub fn derive_packed_ref(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_ref(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `PartialEq`, `Eq` and `Hash` over the bits a value packs to
+#[proc_macro_derive(PackedEq)]
+pub fn derive_packed_eq(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_eq(input).into()
+}
+
+/// Implements `Debug`, showing each field with the bits it is packed into
+#[proc_macro_derive(PackedDebug, attributes(packed))]
+pub fn derive_packed_debug(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_debug(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Fingerprint` from the layout of the fields
+#[proc_macro_derive(Fingerprint, attributes(packed))]
+pub fn derive_fingerprint(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_fingerprint(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Layout`, describing where each field is packed
+#[proc_macro_derive(Layout, attributes(packed))]
+pub fn derive_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_layout(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct Field {
+    member: Member,
+    /// Local variable the field is unpacked into
+    binding: Ident,
+    ty: Type,
+    /// `#[packed(bits = N)]`
+    bits: Option<LitInt>,
+    /// `T` of an `Option<T>` field marked `#[packed(niche)]`
+    niche: Option<Type>,
+    /// `#[packed(endian = "...")]`, or the struct's
+    endian: Option<Endian>,
+    /// `#[packed(since = N)]`, the first version the field is packed in
+    since: Option<u64>,
+    /// `#[packed(default = "...")]`, the value of the field in versions
+    /// before `since`
+    default: Option<Expr>,
+    /// `#[packed(prepacked = "...")]`, a `PrepackedConst` to copy when the
+    /// field holds its value
+    prepacked: Option<Expr>,
+    /// `#[packed(order = N)]`, the position of the field on the wire
+    order: Option<LitInt>,
+}
+
+/// Byte order forced on a field by `#[packed(endian = "...")]`
+#[derive(Clone, Copy)]
+enum Endian {
+    Big,
+    Little,
+}
+
+impl Endian {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let lit: syn::LitStr = meta.value()?.parse()?;
+        match lit.value().as_str() {
+            "big" => Ok(Self::Big),
+            "little" => Ok(Self::Little),
+            _ => Err(syn::Error::new(
+                lit.span(),
+                "expected `endian = \"big\"` or `endian = \"little\"`",
+            )),
+        }
+    }
+}
+
+impl Field {
+    fn parse(index: usize, field: &syn::Field, endian: Option<Endian>) -> syn::Result<Self> {
+        let (member, binding) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.clone()),
+            None => (
+                Member::Unnamed(index.into()),
+                format_ident!("__field{index}"),
+            ),
+        };
+        let mut out = Self {
+            member,
+            binding,
+            ty: field.ty.clone(),
+            bits: None,
+            niche: None,
+            endian,
+            since: None,
+            default: None,
+            prepacked: None,
+            order: None,
+        };
+        let mut niche = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("packed")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bits") {
+                    out.bits = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("niche") {
+                    niche = true;
+                    Ok(())
+                } else if meta.path.is_ident("endian") {
+                    out.endian = Some(Endian::parse(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("since") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    out.since = Some(lit.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    out.default = Some(lit.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("prepacked") {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    out.prepacked = Some(lit.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("order") {
+                    out.order = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown packed attribute"))
+                }
+            })?;
+        }
+        if out.default.is_some() && out.since.is_none() {
+            return Err(syn::Error::new(
+                field.span(),
+                "`default` is only used by fields with `since`",
+            ));
+        }
+        if out.prepacked.is_some() && (niche || out.bits.is_some()) {
+            return Err(syn::Error::new(
+                field.span(),
+                "`prepacked` fields can't be narrowed or niches",
+            ));
+        }
+        if niche {
+            out.niche = Some(option_inner(&field.ty).ok_or_else(|| {
+                syn::Error::new(field.ty.span(), "`niche` fields must be `Option`s")
+            })?);
+        }
+
+        Ok(out)
+    }
+
+    /// The configuration the field is packed with
+    fn cfg(&self) -> TokenStream2 {
+        match self.endian {
+            None => quote! { __C },
+            Some(Endian::Big) => quote! { ::packed::cfg::BigEndian<__C> },
+            Some(Endian::Little) => quote! { ::packed::cfg::LittleEndian<__C> },
+        }
+    }
+
+    /// Whether the field is packed as its own type rather than narrowed or
+    /// through a niche
+    fn is_plain(&self) -> bool {
+        self.bits.is_none() && self.niche.is_none()
+    }
+
+    /// Width a niche field is packed into
+    fn niche_bits(&self, inner: &Type) -> TokenStream2 {
+        match &self.bits {
+            Some(bits) => quote! { #bits },
+            None => quote! { <#inner as ::packed::niche::Niche>::BITS },
+        }
+    }
+
+    fn size(&self) -> TokenStream2 {
+        self.size_as(quote!(::packed::PackedSize))
+    }
+
+    /// Size of the field, taking the size of full-width fields from `trait_`
+    fn size_as(&self, trait_: TokenStream2) -> TokenStream2 {
+        let ty = &self.ty;
+        if let Some(inner) = &self.niche {
+            return match &self.bits {
+                Some(bits) => quote! {
+                    {
+                        ::core::assert!(
+                            #bits <= <#inner as ::packed::niche::Niche>::BITS,
+                            "`bits` is wider than the field type",
+                        );
+                        #bits
+                    }
+                },
+                None => quote! { <#inner as ::packed::niche::Niche>::BITS },
+            };
+        }
+        match &self.bits {
+            Some(bits) => quote! {
+                {
+                    ::core::assert!(
+                        #bits <= <#ty as ::packed::narrow::NarrowInt>::BITS,
+                        "`bits` is wider than the field type",
+                    );
+                    #bits
+                }
+            },
+            None => quote! { <#ty as #trait_>::SIZE },
+        }
+    }
+
+    fn unpack(&self) -> TokenStream2 {
+        let cfg = self.cfg();
+        let ty = &self.ty;
+        if let Some(inner) = &self.niche {
+            let bits = self.niche_bits(inner);
+            return quote! {
+                <#inner as ::packed::niche::Niche>::unpack_niche::<#cfg>(#bits, bytes, offset)
+            };
+        }
+        match &self.bits {
+            Some(bits) => quote! {
+                <#ty as ::packed::narrow::NarrowInt>::unpack_narrow::<#cfg>(#bits, bytes, offset)
+            },
+            None => quote! { <#ty as ::packed::Unpack>::unpack_with::<#cfg>(bytes, offset) },
+        }
+    }
+
+    fn unpack_ref(&self, lt: &Lifetime) -> TokenStream2 {
+        let cfg = self.cfg();
+        let ty = &self.ty;
+        if !self.is_plain() {
+            return self.unpack();
+        }
+        quote! {
+            <#ty as ::packed::borrow::PackedRef<#lt>>::unpack_ref_with::<#cfg>(bytes, offset)?
+        }
+    }
+
+    fn unpack_into(&self) -> TokenStream2 {
+        let cfg = self.cfg();
+        let ty = &self.ty;
+        let member = &self.member;
+        if !self.is_plain() {
+            // narrow integers and niches own no allocations worth reusing
+            let unpack = self.unpack();
+            return quote! { self.#member = #unpack };
+        }
+        quote! {
+            <#ty as ::packed::Unpack>::unpack_into_with::<#cfg>(&mut self.#member, bytes, offset)
+        }
+    }
+
+    fn pack(&self, value: TokenStream2) -> TokenStream2 {
+        let cfg = self.cfg();
+        let ty = &self.ty;
+        if let Some(inner) = &self.niche {
+            let bits = self.niche_bits(inner);
+            return quote! {
+                <#inner as ::packed::niche::Niche>::pack_niche::<#cfg>(#value, #bits, bytes, offset)
+            };
+        }
+        if let Some(prepacked) = &self.prepacked {
+            return quote! {
+                if #value == *::packed::prepacked::PrepackedConst::get(&#prepacked) {
+                    ::packed::Pack::pack_with::<#cfg>(&#prepacked, bytes, offset)
+                } else {
+                    <#ty as ::packed::Pack>::pack_with::<#cfg>(&#value, bytes, offset)
+                }
+            };
+        }
+        match &self.bits {
+            Some(bits) => quote! {
+                <#ty as ::packed::narrow::NarrowInt>::pack_narrow::<#cfg>(#value, #bits, bytes, offset)
+            },
+            None => quote! { <#ty as ::packed::Pack>::pack_with::<#cfg>(&#value, bytes, offset) },
+        }
+    }
+
+    fn pack_ref(&self, value: TokenStream2, lt: &Lifetime) -> TokenStream2 {
+        let cfg = self.cfg();
+        let ty = &self.ty;
+        if !self.is_plain() {
+            return self.pack(value);
+        }
+        quote! {
+            <#ty as ::packed::borrow::PackedRef<#lt>>::pack_ref_with::<#cfg>(&#value, bytes, offset)
+        }
+    }
+
+    fn check_reserved(&self) -> TokenStream2 {
+        let cfg = self.cfg();
+        let ty = &self.ty;
+        if !self.is_plain() {
+            // narrow integers and niches have no reserved bits
+            return quote! {};
+        }
+        let name = match &self.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        quote! {
+            <#ty as ::packed::Unpack>::check_reserved_with::<#cfg>(bytes, offset)
+                .map_err(|e| e.in_field(#name))?;
+        }
+    }
+
+    /// Description of the field for `::packed::c_abi::layout`
+    fn c_field(&self) -> TokenStream2 {
+        let ty = self.niche.as_ref().unwrap_or(&self.ty);
+        let size = self.size();
+        let storage = quote! { ::core::mem::size_of::<#ty>() * 8 };
+        let align = quote! { ::core::mem::align_of::<#ty>() * 8 };
+        match &self.bits {
+            Some(_) => quote! {
+                ::packed::c_abi::Field::bitfield(#size, #storage, #align)
+            },
+            None => quote! { ::packed::c_abi::Field::member(#size, #align) },
+        }
+    }
+
+    /// Bound needed for the field, with full-width fields implementing `trait_`
+    fn bound(&self, trait_: &TokenStream2) -> TokenStream2 {
+        let ty = &self.ty;
+        if let Some(inner) = &self.niche {
+            return quote! { #inner: ::packed::niche::Niche };
+        }
+        match &self.bits {
+            Some(_) => quote! { #ty: ::packed::narrow::NarrowInt },
+            None => quote! { #ty: #trait_ },
+        }
+    }
+
+    fn bound_ref(&self, lt: &Lifetime) -> TokenStream2 {
+        self.bound(&quote!(::packed::borrow::PackedRef<#lt>))
+    }
+}
+
+/// How the fields of a struct are laid out
+enum Layout {
+    /// One after the other with no padding
+    Packed,
+    /// Like the bitfields of a C struct, `#[packed(c_abi)]`
+    CAbi,
+}
+
+/// `#[packed(...)]` attributes of the struct itself
+struct Attrs {
+    layout: Layout,
+    /// Byte order of every field without its own
+    endian: Option<Endian>,
+}
+
+impl Attrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut out = Self {
+            layout: Layout::Packed,
+            endian: None,
+        };
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("packed")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("c_abi") {
+                    out.layout = Layout::CAbi;
+                    Ok(())
+                } else if meta.path.is_ident("endian") {
+                    out.endian = Some(Endian::parse(&meta)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown packed attribute"))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+impl Layout {
+    /// Total size of the fields
+    fn size(&self, fields: &[Field]) -> TokenStream2 {
+        match self {
+            Self::Packed => {
+                let sizes = fields.iter().map(Field::size);
+                quote! { 0 #(+ #sizes)* }
+            }
+            Self::CAbi => {
+                let layout = Self::c_layout(fields);
+                quote! { #layout.1 }
+            }
+        }
+    }
+
+    fn c_layout(fields: &[Field]) -> TokenStream2 {
+        let c_fields = fields.iter().map(Field::c_field);
+        quote! { ::packed::c_abi::layout([#(#c_fields),*]) }
+    }
+
+    /// Code run before the fields, with `offset` at the start of the struct
+    fn prelude(&self, fields: &[Field]) -> TokenStream2 {
+        match self {
+            Self::Packed => quote! {},
+            Self::CAbi => {
+                let layout = Self::c_layout(fields);
+                quote! {
+                    let __base = offset;
+                    let __offsets = const { #layout }.0;
+                }
+            }
+        }
+    }
+
+    /// Wrap the code handling field `i` at `offset`
+    fn place(&self, i: usize, field: &Field, body: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Packed => {
+                let size = field.size();
+                quote! {
+                    #body
+                    offset += #size;
+                }
+            }
+            Self::CAbi => {
+                let lead = match &field.bits {
+                    Some(_) => quote! { 0 },
+                    None => {
+                        let size = field.size();
+                        let cfg = field.cfg();
+                        quote! { ::packed::c_abi::lead::<#cfg>(#size) }
+                    }
+                };
+                quote! {
+                    offset = __base + __offsets[#i] + #lead;
+                    #body
+                }
+            }
+        }
+    }
+}
+
+/// `T` if `ty` is `Option<T>`
+fn option_inner(ty: &Type) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner.clone()),
+        _ => None,
+    }
+}
+
+/// Fields of a struct, defaulting to the struct's byte order.  Unit structs
+/// have none.
+fn parse_fields(input: &DeriveInput, attrs: &Attrs) -> syn::Result<Vec<Field>> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "`Packed` can only be derived for structs",
+            ));
+        }
+    };
+    let mut fields = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| Field::parse(i, f, attrs.endian))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // `order` puts the fields in wire order, and has to be given for all of
+    // them or none
+    if fields.iter().all(|f| f.order.is_none()) {
+        return Ok(fields);
+    }
+    let mut keyed = Vec::new();
+    for (f, field) in fields.drain(..).zip(&data.fields) {
+        let Some(order) = &f.order else {
+            return Err(syn::Error::new(
+                field.span(),
+                "`order` has to be given for every field or none",
+            ));
+        };
+        let key = order.base10_parse::<u64>()?;
+        if keyed.iter().any(|(k, _)| *k == key) {
+            return Err(syn::Error::new(
+                order.span(),
+                "another field has the same `order`",
+            ));
+        }
+        keyed.push((key, f));
+    }
+    keyed.sort_by_key(|(k, _)| *k);
+    Ok(keyed.into_iter().map(|(_, f)| f).collect())
+}
+
+fn expand(input: DeriveInput, pack: bool, unpack: bool) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = Attrs::parse(&input)?;
+    let fields = parse_fields(&input, &attrs)?;
+    let layout = attrs.layout;
+
+    let size = layout.size(&fields);
+    let prelude = layout.prelude(&fields);
+    let members = fields.iter().map(|f| &f.member).collect::<Vec<_>>();
+    let bindings = fields.iter().map(|f| &f.binding).collect::<Vec<_>>();
+
+    let unpacks = fields.iter().enumerate().map(|(i, f)| {
+        let binding = &f.binding;
+        let unpack = f.unpack();
+        layout.place(i, f, quote! { let #binding = #unpack; })
+    });
+    let unpack_intos = fields.iter().enumerate().map(|(i, f)| {
+        let unpack_into = f.unpack_into();
+        layout.place(i, f, quote! { #unpack_into; })
+    });
+    let packs = fields.iter().enumerate().map(|(i, f)| {
+        let member = &f.member;
+        let pack = f.pack(quote!(self.#member));
+        layout.place(i, f, quote! { #pack; })
+    });
+    let checks = fields.iter().enumerate().map(|(i, f)| {
+        let check = f.check_reserved();
+        layout.place(i, f, check)
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // each impl only needs its own trait from the fields
+    let where_for = |trait_: TokenStream2| {
+        let mut where_clause = where_clause
+            .cloned()
+            .unwrap_or_else(|| syn::parse_quote!(where));
+        for f in &fields {
+            let bound = f.bound(&trait_);
+            where_clause.predicates.push(syn::parse_quote!(#bound));
+        }
+        where_clause
+    };
+
+    let mut out = {
+        let where_clause = where_for(quote!(::packed::PackedSize));
+        quote! {
+            impl #impl_generics ::packed::PackedSize for #name #ty_generics #where_clause {
+                const SIZE: usize = #size;
+            }
+        }
+    };
+    if pack {
+        let where_clause = where_for(quote!(::packed::Pack));
+        out.extend(quote! {
+            impl #impl_generics ::packed::Pack for #name #ty_generics #where_clause {
+                #[allow(unused_assignments, unused_mut)]
+                fn pack_with<__C: ::packed::cfg::Cfg>(&self, bytes: &mut [u8], mut offset: usize) {
+                    ::core::debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
+                    #prelude
+                    #(#packs)*
+                }
+            }
+        });
+    }
+    if unpack {
+        let where_clause = where_for(quote!(::packed::Unpack));
+        out.extend(quote! {
+            impl #impl_generics ::packed::Unpack for #name #ty_generics #where_clause {
+                #[allow(unused_assignments, unused_mut)]
+                fn unpack_with<__C: ::packed::cfg::Cfg>(bytes: &[u8], mut offset: usize) -> Self {
+                    ::core::debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
+                    #prelude
+                    #(#unpacks)*
+                    Self { #(#members: #bindings),* }
+                }
+
+                #[allow(unused_assignments, unused_mut)]
+                fn unpack_into_with<__C: ::packed::cfg::Cfg>(&mut self, bytes: &[u8], mut offset: usize) {
+                    ::core::debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
+                    #prelude
+                    #(#unpack_intos)*
+                }
+
+                #[allow(unused_assignments, unused_mut)]
+                fn check_reserved_with<__C: ::packed::cfg::Cfg>(
+                    bytes: &[u8],
+                    mut offset: usize,
+                ) -> ::core::result::Result<(), ::packed::UnpackError> {
+                    #prelude
+                    #(#checks)*
+                    ::core::result::Result::Ok(())
+                }
+            }
+        });
+    }
+    if unpack && fields.iter().any(|f| f.since.is_some()) {
+        if let Layout::CAbi = layout {
+            return Err(syn::Error::new(
+                input.span(),
+                "`since` can't be used with `c_abi`",
+            ));
+        }
+        let where_clause = where_for(quote!(::packed::Unpack));
+        out.extend(expand_versions(
+            name,
+            &input.generics,
+            &where_clause,
+            &fields,
+        ));
+    }
+    Ok(out)
+}
+
+/// Implements `VersionedLayout` for a struct with `since` fields
+fn expand_versions(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+    where_clause: &syn::WhereClause,
+    fields: &[Field],
+) -> TokenStream2 {
+    let latest = fields.iter().filter_map(|f| f.since).max().unwrap_or(0);
+    let members = fields.iter().map(|f| &f.member);
+    let bindings = fields.iter().map(|f| &f.binding);
+    // fields since version 0 are in every version
+    let since = |f: &Field| f.since.filter(|&since| since > 0);
+    let sizes = fields.iter().map(|f| {
+        let size = f.size();
+        match since(f) {
+            Some(since) => quote! { if version >= #since { #size } else { 0 } },
+            None => size,
+        }
+    });
+    let unpacks = fields.iter().map(|f| {
+        let binding = &f.binding;
+        let unpack = f.unpack();
+        let size = f.size();
+        let present = quote! {
+            let value = #unpack;
+            offset += #size;
+            value
+        };
+        let value = match since(f) {
+            Some(since) => {
+                let default = match &f.default {
+                    Some(default) => quote! { #default },
+                    None => quote! { ::core::default::Default::default() },
+                };
+                quote! { if version >= #since { #present } else { #default } }
+            }
+            None => quote! { { #present } },
+        };
+        quote! { let #binding = #value; }
+    });
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::packed::version::VersionedLayout for #name #ty_generics #where_clause {
+            const LATEST: u64 = #latest;
+
+            fn size_for(version: u64) -> usize {
+                0 #(+ #sizes)*
+            }
+
+            #[allow(unused_assignments, unused_mut)]
+            fn unpack_version_with<__C: ::packed::cfg::Cfg>(
+                version: u64,
+                bytes: &[u8],
+                mut offset: usize,
+            ) -> Self {
+                ::core::debug_assert!(bytes.len() * 8 - offset >= Self::size_for(version));
+                #(#unpacks)*
+                Self { #(#members: #bindings),* }
+            }
+        }
+    }
+}
+
+fn expand_ref(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = Attrs::parse(&input)?;
+    let fields = parse_fields(&input, &attrs)?;
+    if let Layout::CAbi = attrs.layout {
+        return Err(syn::Error::new(
+            input.span(),
+            "`PackedRef` can't be derived with `c_abi`",
+        ));
+    }
+
+    // borrow from the buffer for the struct's first lifetime, or for any
+    // lifetime if it has none
+    let mut generics = input.generics.clone();
+    let lt = match input.generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lt: Lifetime = syn::parse_quote!('__a);
+            generics.params.insert(0, syn::parse_quote!(#lt));
+            lt
+        }
+    };
+
+    let trait_ = quote!(::packed::borrow::PackedRef<#lt>);
+    let sizes = fields.iter().map(|f| f.size_as(trait_.clone()));
+    let bounds = fields.iter().map(|f| f.bound_ref(&lt));
+    let members = fields.iter().map(|f| &f.member).collect::<Vec<_>>();
+    let bindings = fields.iter().map(|f| &f.binding).collect::<Vec<_>>();
+
+    let unpacks = fields.iter().map(|f| {
+        let binding = &f.binding;
+        let size = f.size_as(trait_.clone());
+        let unpack = f.unpack_ref(&lt);
+        quote! {
+            let #binding = #unpack;
+            offset += #size;
+        }
+    });
+    let packs = fields.iter().map(|f| {
+        let member = &f.member;
+        let size = f.size_as(trait_.clone());
+        let pack = f.pack_ref(quote!(self.#member), &lt);
+        quote! {
+            #pack;
+            offset += #size;
+        }
+    });
+
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    for bound in bounds {
+        where_clause.predicates.push(syn::parse_quote!(#bound));
+    }
+
+    Ok(quote! {
+        impl #impl_generics #trait_ for #name #ty_generics #where_clause {
+            const SIZE: usize = 0 #(+ #sizes)*;
+
+            #[allow(unused_assignments, unused_mut)]
+            fn unpack_ref_with<__C: ::packed::cfg::Cfg>(
+                bytes: &#lt [u8],
+                mut offset: usize,
+            ) -> ::core::result::Result<Self, ::packed::UnpackError> {
+                if offset
+                    .checked_add(<Self as #trait_>::SIZE)
+                    .is_none_or(|end| end > bytes.len() * 8)
+                {
+                    return ::core::result::Result::Err(
+                        ::packed::UnpackErrorKind::UnexpectedEnd { offset: bytes.len() * 8 }.into(),
+                    );
+                }
+                #(#unpacks)*
+                ::core::result::Result::Ok(Self { #(#members: #bindings),* })
+            }
+
+            #[allow(unused_assignments, unused_mut)]
+            fn pack_ref_with<__C: ::packed::cfg::Cfg>(&self, bytes: &mut [u8], mut offset: usize) {
+                ::core::debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
+                #(#packs)*
+            }
+        }
+    })
+}
+
+fn expand_eq(input: DeriveInput) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    where_clause
+        .predicates
+        .push(syn::parse_quote!(#name #ty_generics: ::packed::Pack));
+
+    quote! {
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                ::packed::compare::packed_eq(self, other)
+            }
+        }
+
+        impl #impl_generics ::core::cmp::Eq for #name #ty_generics #where_clause {}
+
+        impl #impl_generics ::core::hash::Hash for #name #ty_generics #where_clause {
+            fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+                ::packed::compare::packed_hash(self, state);
+            }
+        }
+    }
+}
+
+fn expand_fingerprint(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = Attrs::parse(&input)?;
+    let fields = parse_fields(&input, &attrs)?;
+
+    let layout = match attrs.layout {
+        Layout::Packed => "struct",
+        Layout::CAbi => "c_abi",
+    };
+    let mixes = fields.iter().map(|f| {
+        let label = match &f.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        let ty = &f.ty;
+        // niche types only know their width
+        let inner = match &f.niche {
+            Some(inner) => quote! { <#inner as ::packed::niche::Niche>::BITS as u64 },
+            None => quote! { <#ty as ::packed::fingerprint::Fingerprint>::LAYOUT_HASH },
+        };
+        let bits = match &f.bits {
+            Some(bits) => quote! { 1 + #bits as u64 },
+            None => quote! { 0 },
+        };
+        let niche = if f.niche.is_some() { "niche" } else { "" };
+        let endian = match f.endian {
+            None => "",
+            Some(Endian::Big) => "big",
+            Some(Endian::Little) => "little",
+        };
+        let since = f.since.map_or(quote! { 0 }, |since| quote! { 1 + #since });
+        quote! {
+            let hash = ::packed::fingerprint::mix_str(hash, #label);
+            let hash = ::packed::fingerprint::mix(hash, #inner);
+            let hash = ::packed::fingerprint::mix(hash, #bits);
+            let hash = ::packed::fingerprint::mix_str(hash, #niche);
+            let hash = ::packed::fingerprint::mix_str(hash, #endian);
+            let hash = ::packed::fingerprint::mix(hash, #since);
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    where_clause
+        .predicates
+        .push(syn::parse_quote!(#name #ty_generics: ::packed::PackedSize));
+    for f in fields.iter().filter(|f| f.niche.is_none()) {
+        let ty = &f.ty;
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ty: ::packed::fingerprint::Fingerprint));
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::packed::fingerprint::Fingerprint for #name #ty_generics #where_clause {
+            const LAYOUT_HASH: u64 = {
+                let hash = ::packed::fingerprint::mix_str(::packed::fingerprint::SEED, #layout);
+                #(#mixes)*
+                ::packed::fingerprint::mix(hash, <Self as ::packed::PackedSize>::SIZE as u64)
+            };
+        }
+    })
+}
+
+fn expand_layout(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = Attrs::parse(&input)?;
+    let fields = parse_fields(&input, &attrs)?;
+    let layout = attrs.layout;
+
+    let count = fields.len();
+    let prelude = layout.prelude(&fields);
+    let infos = fields.iter().enumerate().map(|(i, f)| {
+        let label = match &f.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        let ty = &f.ty;
+        let ty_name = type_name(ty);
+        let size = f.size();
+        let reserved = is_reserved(ty);
+        layout.place(
+            i,
+            f,
+            quote! {
+                fields[#i] = ::packed::layout::FieldInfo {
+                    name: #label,
+                    ty: #ty_name,
+                    offset,
+                    bits: #size,
+                    reserved: #reserved,
+                };
+            },
+        )
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    where_clause
+        .predicates
+        .push(syn::parse_quote!(#name #ty_generics: ::packed::PackedSize));
+    for f in &fields {
+        where_clause
+            .predicates
+            .push(syn::parse2(f.bound(&quote!(::packed::PackedSize)))?);
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::packed::layout::Layout for #name #ty_generics #where_clause {
+            #[allow(unused_assignments, unused_mut)]
+            const FIELDS: &'static [::packed::layout::FieldInfo] = &{
+                // offsets are those of the default configuration
+                type __C = ::packed::cfg::DefaultCfg;
+                let mut fields = [::packed::layout::FieldInfo::EMPTY; #count];
+                let mut offset = 0usize;
+                #prelude
+                #(#infos)*
+                fields
+            };
+        }
+    })
+}
+
+/// `ty` as it would be written, without the spaces tokens are printed with
+fn type_name(ty: &Type) -> String {
+    let mut name = quote!(#ty).to_string();
+    for (from, to) in [
+        (" :: ", "::"),
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ;", ";"),
+        (" ,", ","),
+    ] {
+        name = name.replace(from, to);
+    }
+    name
+}
+
+/// Whether `ty` is `Reserved<...>`
+fn is_reserved(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path
+        .segments
+        .last()
+        .is_some_and(|last| last.ident == "Reserved")
+}
+
+fn expand_debug(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = Attrs::parse(&input)?;
+    let fields = parse_fields(&input, &attrs)?;
+    let layout = attrs.layout;
+
+    let prelude = layout.prelude(&fields);
+    let shows = fields.iter().enumerate().map(|(i, f)| {
+        let member = &f.member;
+        let label = match member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        let size = f.size();
+        layout.place(
+            i,
+            f,
+            quote! {
+                out.field(
+                    #label,
+                    &::packed::debug::FieldBits::new(&self.#member, &bytes, offset, #size),
+                );
+            },
+        )
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote!(where));
+    where_clause
+        .predicates
+        .push(syn::parse_quote!(#name #ty_generics: ::packed::Pack));
+    for f in &fields {
+        let ty = &f.ty;
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ty: ::core::fmt::Debug));
+    }
+    let label = name.to_string();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            #[allow(unused_assignments, unused_mut)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                // bits are shown as packed with the default configuration
+                type __C = ::packed::cfg::DefaultCfg;
+                let mut bytes = ::std::vec![0u8; <Self as ::packed::PackedSize>::SIZE.div_ceil(8)];
+                ::packed::Pack::pack_with::<__C>(self, &mut bytes, 0);
+                let mut offset = 0usize;
+                #prelude
+                let mut out = f.debug_struct(#label);
+                #(#shows)*
+                out.finish()
+            }
+        }
+    })
+}
@@ -0,0 +1,80 @@
+//! Inputs shared by the benchmarks.
+//!
+//! Public so that forks can benchmark their changes against the same types
+//! and inputs.
+
+use crate::{Pack, Packed, PackedSize, Unpack};
+
+/// A representative sensor record mixing widths and alignments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct Telemetry {
+    pub valid: bool,
+    #[packed(bits = 3)]
+    pub kind: u8,
+    #[packed(bits = 12)]
+    pub temperature: i16,
+    pub timestamp: u32,
+    pub flags: [bool; 5],
+    pub counter: u16,
+    #[packed(bits = 19)]
+    pub position: i32,
+}
+
+/// A byte-aligned header, the best case for the packing code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct Aligned {
+    pub version: u8,
+    pub len: u16,
+    pub id: u32,
+    pub seq: u64,
+}
+
+/// Every offset within a byte, which together cover each shift the
+/// packing code has to handle
+pub const OFFSETS: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+
+/// Deterministic pseudo-random telemetry records
+pub fn telemetry(n: usize) -> Vec<Telemetry> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..n)
+        .map(|_| {
+            let x = next();
+            Telemetry {
+                valid: x & 1 != 0,
+                kind: (x >> 1) as u8 & 0x7,
+                temperature: ((x >> 4) as i16) >> 4,
+                timestamp: (x >> 16) as u32,
+                flags: std::array::from_fn(|i| x >> (48 + i) & 1 != 0),
+                counter: next() as u16,
+                position: (next() as i32) >> 13,
+            }
+        })
+        .collect()
+}
+
+/// Pack `value` at every offset in [`OFFSETS`], returning the buffer so the
+/// work can't be optimised out
+pub fn pack_at_all_offsets<'a, T>(value: &T, buf: &'a mut [u8]) -> &'a [u8]
+where
+    T: Pack,
+{
+    for offset in OFFSETS {
+        value.pack(buf, offset);
+    }
+    buf
+}
+
+/// Unpack a value at every offset in [`OFFSETS`]
+pub fn unpack_at_all_offsets<T>(buf: &[u8]) -> [T; 8]
+where
+    T: Unpack,
+{
+    OFFSETS.map(|offset| T::unpack(buf, offset))
+}
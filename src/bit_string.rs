@@ -0,0 +1,201 @@
+//! Opaque strings of bits.
+//!
+//! [`Bits`] holds `N` raw bits with no numeric meaning, in the order they
+//! are packed, so unlike an integer its bits land in the same order
+//! whatever byte order they are packed with.  It suits fields which are
+//! only ever echoed back or compared, like reserved bits a peer expects to
+//! get back unchanged.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::Bits;
+//! let echo = Bits::<11>::unpack(&[0xab, 0xcd], 0);
+//! assert_eq!(echo, Bits::new(0b1010_1011_110));
+//! let (head, tail) = (echo.slice::<0, 4>(), echo.slice::<4, 7>());
+//! assert_eq!(head.to_string(), "1010");
+//! assert_eq!(head.concat::<7, 11>(tail), echo);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::cfg::{BitOrder, Cfg};
+use crate::error::bit_len;
+use crate::narrow::{read_bits, write_bits};
+use crate::{Pack, PackedSize, Unpack};
+
+/// `N` bits with no numeric meaning, kept in the order they are packed.  `N`
+/// can be at most 128.  With the `serde` feature it is serialized as its
+/// [`value`](Self::value).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bits<const N: usize>(
+    /// First bit in the most significant bit, with the bits after the last
+    /// zeroed
+    u128,
+);
+
+impl<const N: usize> Bits<N> {
+    /// All bits zero
+    pub const ZERO: Self = Self::new(0);
+    /// All bits one
+    pub const ONES: Self = Self::new(u128::MAX);
+
+    /// Mask of the bits in use
+    const fn mask() -> u128 {
+        const { assert!(N <= 128, "`Bits` can hold at most 128 bits") };
+        match N {
+            0 => 0,
+            _ => u128::MAX << (128 - N),
+        }
+    }
+
+    /// The low `N` bits of `value`, the most significant first
+    pub const fn new(value: u128) -> Self {
+        match N {
+            0 => Self(0),
+            _ => Self(value << (128 - N) & Self::mask()),
+        }
+    }
+
+    /// The bits as the low `N` bits of an integer, the first the most
+    /// significant
+    pub const fn value(self) -> u128 {
+        match N {
+            0 => 0,
+            _ => self.0 >> (128 - N),
+        }
+    }
+
+    /// Bit `i`, in packing order
+    ///
+    /// # Panics
+    ///
+    /// If `i` isn't less than `N`
+    pub const fn get(self, i: usize) -> bool {
+        assert!(i < N, "bit index out of range");
+        self.0 & 1 << (127 - i) != 0
+    }
+
+    /// Set bit `i`, in packing order
+    ///
+    /// # Panics
+    ///
+    /// If `i` isn't less than `N`
+    pub const fn set(&mut self, i: usize, bit: bool) {
+        assert!(i < N, "bit index out of range");
+        let mask = 1 << (127 - i);
+        self.0 = if bit { self.0 | mask } else { self.0 & !mask };
+    }
+
+    /// These bits followed by `other`, where `R` must be `N + M`
+    pub const fn concat<const M: usize, const R: usize>(self, other: Bits<M>) -> Bits<R> {
+        const { assert!(R == N + M, "`R` must be `N + M`") };
+        const { assert!(R <= 128, "`Bits` can hold at most 128 bits") };
+        match N {
+            128 => Bits(self.0),
+            _ => Bits(self.0 | other.0 >> N),
+        }
+    }
+
+    /// The `LEN` bits starting at bit `START`
+    pub const fn slice<const START: usize, const LEN: usize>(self) -> Bits<LEN> {
+        const { assert!(START + LEN <= N, "slice out of range") };
+        match START {
+            128 => Bits(0),
+            _ => Bits(self.0 << START & Bits::<LEN>::mask()),
+        }
+    }
+
+    /// The bits in packing order
+    pub fn iter(self) -> impl ExactSizeIterator<Item = bool> {
+        (0..N).map(move |i| self.get(i))
+    }
+}
+
+impl<const N: usize> fmt::Debug for Bits<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bits<{N}>({self})")
+    }
+}
+
+/// The bits as `0`s and `1`s
+impl<const N: usize> fmt::Display for Bits<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter()
+            .try_for_each(|bit| f.write_str(if bit { "1" } else { "0" }))
+    }
+}
+
+impl<const N: usize> PackedSize for Bits<N> {
+    const SIZE: usize = N;
+}
+
+/// `byte` such that packing it with `C` packs its bits most significant
+/// first
+fn in_order<C: Cfg>(byte: u8) -> u8 {
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => byte,
+        BitOrder::Lsb0 => byte.reverse_bits(),
+    }
+}
+
+impl<const N: usize> Pack for Bits<N> {
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        // bytes one at a time, as byte order doesn't apply
+        let raw = self.0.to_be_bytes();
+        for (i, &byte) in raw[..N / 8].iter().enumerate() {
+            in_order::<C>(byte).pack_with::<C>(bytes, offset + i * 8);
+        }
+        let tail = N % 8;
+        if tail > 0 {
+            let byte = in_order::<C>(raw[N / 8]);
+            let low = match C::BIT_ORDER {
+                BitOrder::Msb0 => byte >> (8 - tail),
+                BitOrder::Lsb0 => byte & ((1 << tail) - 1),
+            };
+            write_bits::<C>(low.into(), tail, bytes, offset + N / 8 * 8);
+        }
+    }
+}
+
+impl<const N: usize> Unpack for Bits<N> {
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        let mut raw = [0u8; 16];
+        for (i, byte) in raw[..N / 8].iter_mut().enumerate() {
+            *byte = in_order::<C>(u8::unpack_with::<C>(bytes, offset + i * 8));
+        }
+        let tail = N % 8;
+        if tail > 0 {
+            let low = read_bits::<C>(tail, bytes, offset + N / 8 * 8) as u8;
+            raw[N / 8] = in_order::<C>(match C::BIT_ORDER {
+                BitOrder::Msb0 => low << (8 - tail),
+                BitOrder::Lsb0 => low,
+            });
+        }
+        Self(u128::from_be_bytes(raw) & Self::mask())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Bits<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Bits<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u128::deserialize(deserializer)?;
+        if Self::new(value).value() != value {
+            return Err(serde::de::Error::custom(format_args!(
+                "{value} doesn't fit in {N} bits"
+            )));
+        }
+        Ok(Self::new(value))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::cfg::{Cfg, Lsb0Be, Lsb0Le, Msb0Be, Msb0Le};
+use crate::{Bits, Pack, Unpack};
+
+/// Pack `bits` against packing each of its bits as a `bool`
+fn check_bits<const N: usize, C: Cfg>(bits: Bits<N>, offset: usize) {
+    let mut expected = [0u8; 18];
+    for (i, bit) in bits.iter().enumerate() {
+        bit.pack_with::<C>(&mut expected, offset + i);
+    }
+    let mut buf = [0u8; 18];
+    bits.pack_with::<C>(&mut buf, offset);
+    assert_eq!(buf, expected);
+    assert_eq!(Bits::<N>::unpack_with::<C>(&buf, offset), bits);
+}
+
+fn check_all<const N: usize>(value: u128, offset: usize) {
+    let bits = Bits::<N>::new(value);
+    check_bits::<N, Msb0Be>(bits, offset);
+    check_bits::<N, Msb0Le>(bits, offset);
+    check_bits::<N, Lsb0Be>(bits, offset);
+    check_bits::<N, Lsb0Le>(bits, offset);
+}
+
+proptest::proptest! {
+    #[test]
+    fn packs_in_order(value: u128, offset in 0usize..16) {
+        check_all::<0>(value, offset);
+        check_all::<1>(value, offset);
+        check_all::<7>(value, offset);
+        check_all::<11>(value, offset);
+        check_all::<64>(value, offset);
+        check_all::<100>(value, offset);
+        check_all::<128>(value, offset);
+    }
+}
+
+#[test]
+fn bit_access() {
+    let mut bits = Bits::<5>::new(0b10110);
+    assert_eq!(bits.value(), 0b10110);
+    assert_eq!(
+        bits.iter().collect::<Vec<_>>(),
+        [true, false, true, true, false]
+    );
+    bits.set(1, true);
+    bits.set(4, true);
+    bits.set(0, false);
+    assert_eq!(bits, Bits::new(0b01111));
+    assert_eq!(Bits::<5>::new(u128::MAX), Bits::ONES);
+    assert_eq!(Bits::<5>::ONES.value(), 0b11111);
+    assert_eq!(Bits::<128>::ONES.value(), u128::MAX);
+    assert_eq!(Bits::<0>::ONES, Bits::ZERO);
+    assert_eq!(format!("{bits:?}"), "Bits<5>(01111)");
+}
+
+#[test]
+fn concat_and_slice() {
+    let a = Bits::<3>::new(0b101);
+    let b = Bits::<6>::new(0b000111);
+    let ab = a.concat::<6, 9>(b);
+    assert_eq!(ab.to_string(), "101000111");
+    assert_eq!(ab.slice::<0, 3>(), a);
+    assert_eq!(ab.slice::<3, 6>(), b);
+    assert_eq!(ab.slice::<2, 3>(), Bits::new(0b100));
+    assert_eq!(ab.slice::<9, 0>(), Bits::ZERO);
+
+    let wide = Bits::<128>::ONES;
+    assert_eq!(wide.concat::<0, 128>(Bits::ZERO), wide);
+    assert_eq!(wide.slice::<120, 8>(), Bits::ONES);
+    assert_eq!(
+        Bits::<64>::new(1).concat::<64, 128>(Bits::new(2)).value(),
+        1 << 64 | 2
+    );
+}
+
+#[test]
+#[should_panic = "out of range"]
+fn get_out_of_range() {
+    Bits::<3>::ZERO.get(3);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_roundtrip() {
+    let bits = Bits::<11>::new(0b101_0101_0101);
+    let json = serde_json::to_string(&bits).unwrap();
+    assert_eq!(json, "1365");
+    assert_eq!(serde_json::from_str::<Bits<11>>(&json).unwrap(), bits);
+    assert!(serde_json::from_str::<Bits<11>>("2048").is_err());
+    assert_eq!(serde_json::from_str::<Bits<0>>("0").unwrap(), Bits::ZERO);
+}
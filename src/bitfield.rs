@@ -0,0 +1,82 @@
+//! Gathering and scattering the bits of a word selected by a mask.
+//!
+//! [`get_bits`] packs the bits of a word selected by a mask into the low
+//! bits of the result, and [`set_bits`] does the reverse.  On x86_64 CPUs
+//! with BMI2 these are single `PEXT`/`PDEP` instructions, detected at
+//! runtime, and other CPUs fall back to a loop over the mask.  They also
+//! back the packing of narrow fields which fit in a 64-bit window.
+//!
+//! ```rust
+//! # use packed::bitfield::{get_bits, set_bits};
+//! let word = 0b1011_0110;
+//! assert_eq!(get_bits(word, 0b1111_0000), 0b1011);
+//! assert_eq!(get_bits(word, 0b1010_1010), 0b1101);
+//! assert_eq!(set_bits(word, 0b0000_1111, 0b1001), 0b1011_1001);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+/// The bits of `word` selected by `mask`, moved down to the low bits
+pub fn get_bits(word: u64, mask: u64) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("bmi2") {
+        // SAFETY: the CPU supports BMI2
+        return unsafe { bmi2::pext(word, mask) };
+    }
+    get_bits_portable(word, mask)
+}
+
+/// `word` with the bits selected by `mask` replaced by the low bits of
+/// `value`, in order
+pub fn set_bits(word: u64, mask: u64, value: u64) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("bmi2") {
+        // SAFETY: the CPU supports BMI2
+        return word & !mask | unsafe { bmi2::pdep(value, mask) };
+    }
+    word & !mask | set_bits_portable(value, mask)
+}
+
+/// [`get_bits`] without BMI2
+fn get_bits_portable(word: u64, mut mask: u64) -> u64 {
+    let mut out = 0;
+    let mut i = 0;
+    while mask != 0 {
+        if word & mask & mask.wrapping_neg() != 0 {
+            out |= 1 << i;
+        }
+        mask &= mask - 1;
+        i += 1;
+    }
+    out
+}
+
+/// Scatter the low bits of `value` to the bits of `mask`, without BMI2
+fn set_bits_portable(value: u64, mut mask: u64) -> u64 {
+    let mut out = 0;
+    let mut i = 0;
+    while mask != 0 {
+        if value >> i & 1 != 0 {
+            out |= mask & mask.wrapping_neg();
+        }
+        mask &= mask - 1;
+        i += 1;
+    }
+    out
+}
+
+#[cfg(target_arch = "x86_64")]
+mod bmi2 {
+    use std::arch::x86_64::{_pdep_u64, _pext_u64};
+
+    #[target_feature(enable = "bmi2")]
+    pub(super) fn pext(word: u64, mask: u64) -> u64 {
+        _pext_u64(word, mask)
+    }
+
+    #[target_feature(enable = "bmi2")]
+    pub(super) fn pdep(value: u64, mask: u64) -> u64 {
+        _pdep_u64(value, mask)
+    }
+}
@@ -0,0 +1,22 @@
+use super::{get_bits, get_bits_portable, set_bits, set_bits_portable};
+
+#[test]
+fn full_and_empty_masks() {
+    assert_eq!(get_bits(u64::MAX, 0), 0);
+    assert_eq!(get_bits(0x1234, u64::MAX), 0x1234);
+    assert_eq!(set_bits(0x1234, 0, u64::MAX), 0x1234);
+    assert_eq!(set_bits(0x1234, u64::MAX, 7), 7);
+    assert_eq!(get_bits(1 << 63, 1 << 63), 1);
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_portable(word: u64, mask: u64, value: u64) {
+        assert_eq!(get_bits(word, mask), get_bits_portable(word, mask));
+        assert_eq!(
+            set_bits(word, mask, value),
+            word & !mask | set_bits_portable(value, mask)
+        );
+        assert_eq!(get_bits(set_bits(word, mask, value), mask), value & get_bits(u64::MAX, mask));
+    }
+}
@@ -0,0 +1,262 @@
+//! Typed bit offsets and lengths.
+//!
+//! Offsets throughout the crate are counted in bits, which is easy to mix up
+//! with byte counts when both are plain `usize`s.  [`BitOffset`] and
+//! [`BitLen`] only combine in ways that make sense, and are accepted by
+//! [`Pack::pack_to`](crate::Pack::pack_to) and
+//! [`Unpack::unpack_from`](crate::Unpack::unpack_from).
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::{BitLen, BitOffset};
+//! let mut buf = [0u8; 4];
+//! let start = BitOffset::from_bytes(1) + BitLen(3);
+//! 0xabu8.pack_to(&mut buf, start);
+//! assert_eq!(u8::unpack_from(&buf, start), 0xab);
+//! assert_eq!((start + u8::LEN) - start, BitLen(8));
+//! ```
+//!
+//! Bit offsets overflow `usize` on 32-bit targets once a buffer passes
+//! 512 MiB, which mapped files easily do.  A [`WideBitOffset`] holds the
+//! offset as a `u64`, and splits into the byte to slice the buffer from and
+//! an offset within that byte.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::WideBitOffset;
+//! let mut map = vec![0u8; 1 << 10];
+//! let (byte, bit) = WideBitOffset(8_000 + 3).split().unwrap();
+//! 0xabu8.pack_checked(&mut map[byte..], bit.0).unwrap();
+//! assert_eq!(u8::unpack_strict(&map[byte..], bit.0), Ok(0xab));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+
+/// A position in a buffer, in bits from the start of its first byte
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitOffset(pub usize);
+
+/// A position in a buffer too big for bit offsets to fit in `usize`
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WideBitOffset(pub u64);
+
+/// An amount of bits
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitLen(pub usize);
+
+impl BitOffset {
+    /// The offset of the start of byte `n`
+    pub const fn from_bytes(n: usize) -> Self {
+        Self(n * 8)
+    }
+
+    /// Index of the byte holding the bit at this offset
+    pub const fn byte(self) -> usize {
+        self.0 / 8
+    }
+
+    /// Offset within the byte holding the bit at this offset
+    pub const fn bit(self) -> usize {
+        self.0 % 8
+    }
+
+    pub const fn is_byte_aligned(self) -> bool {
+        self.0.is_multiple_of(8)
+    }
+}
+
+impl WideBitOffset {
+    /// The offset of the start of byte `n`
+    ///
+    /// # Panics
+    ///
+    /// If the offset doesn't fit in a `u64`
+    pub const fn from_bytes(n: u64) -> Self {
+        match Self::checked_from_bytes(n) {
+            Some(offset) => offset,
+            None => panic!("bit offset overflows a u64"),
+        }
+    }
+
+    /// The offset of the start of byte `n`, or `None` if it doesn't fit in a
+    /// `u64`
+    pub const fn checked_from_bytes(n: u64) -> Option<Self> {
+        match n.checked_mul(8) {
+            Some(bits) => Some(Self(bits)),
+            None => None,
+        }
+    }
+
+    /// The offset `len` bits further on, or `None` if it doesn't fit in a
+    /// `u64`
+    pub const fn checked_add(self, len: BitLen) -> Option<Self> {
+        match self.0.checked_add(len.0 as u64) {
+            Some(bits) => Some(Self(bits)),
+            None => None,
+        }
+    }
+
+    /// Index of the byte holding the bit at this offset
+    pub const fn byte(self) -> u64 {
+        self.0 / 8
+    }
+
+    /// Offset within the byte holding the bit at this offset
+    pub const fn bit(self) -> usize {
+        (self.0 % 8) as usize
+    }
+
+    /// The index of the byte holding the bit at this offset, and the offset
+    /// within that byte, or `None` if the index doesn't fit in `usize`
+    pub fn split(self) -> Option<(usize, BitOffset)> {
+        let byte = usize::try_from(self.byte()).ok()?;
+        Some((byte, BitOffset(self.bit())))
+    }
+}
+
+impl BitLen {
+    /// The length of `n` bytes
+    pub const fn from_bytes(n: usize) -> Self {
+        Self(n * 8)
+    }
+
+    /// Amount of bytes needed to hold this many bits
+    pub const fn bytes_ceil(self) -> usize {
+        self.0.div_ceil(8)
+    }
+}
+
+impl fmt::Display for BitOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bit {}", self.0)
+    }
+}
+
+impl fmt::Display for BitLen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bits", self.0)
+    }
+}
+
+impl From<BitOffset> for usize {
+    fn from(value: BitOffset) -> Self {
+        value.0
+    }
+}
+
+impl From<BitOffset> for WideBitOffset {
+    fn from(value: BitOffset) -> Self {
+        Self(value.0 as u64)
+    }
+}
+
+impl TryFrom<WideBitOffset> for BitOffset {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: WideBitOffset) -> Result<Self, Self::Error> {
+        usize::try_from(value.0).map(Self)
+    }
+}
+
+impl fmt::Display for WideBitOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bit {}", self.0)
+    }
+}
+
+impl Add<BitLen> for WideBitOffset {
+    type Output = WideBitOffset;
+
+    fn add(self, rhs: BitLen) -> WideBitOffset {
+        self.checked_add(rhs).expect("bit offset overflows a u64")
+    }
+}
+
+impl From<BitLen> for usize {
+    fn from(value: BitLen) -> Self {
+        value.0
+    }
+}
+
+impl Add<BitLen> for BitOffset {
+    type Output = BitOffset;
+
+    fn add(self, rhs: BitLen) -> BitOffset {
+        BitOffset(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign<BitLen> for BitOffset {
+    fn add_assign(&mut self, rhs: BitLen) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub<BitLen> for BitOffset {
+    type Output = BitOffset;
+
+    fn sub(self, rhs: BitLen) -> BitOffset {
+        BitOffset(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign<BitLen> for BitOffset {
+    fn sub_assign(&mut self, rhs: BitLen) {
+        self.0 -= rhs.0;
+    }
+}
+
+/// The distance between two offsets
+impl Sub for BitOffset {
+    type Output = BitLen;
+
+    fn sub(self, rhs: BitOffset) -> BitLen {
+        BitLen(self.0 - rhs.0)
+    }
+}
+
+impl Add for BitLen {
+    type Output = BitLen;
+
+    fn add(self, rhs: BitLen) -> BitLen {
+        BitLen(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for BitLen {
+    fn add_assign(&mut self, rhs: BitLen) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for BitLen {
+    type Output = BitLen;
+
+    fn sub(self, rhs: BitLen) -> BitLen {
+        BitLen(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for BitLen {
+    fn sub_assign(&mut self, rhs: BitLen) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Mul<usize> for BitLen {
+    type Output = BitLen;
+
+    fn mul(self, rhs: usize) -> BitLen {
+        BitLen(self.0 * rhs)
+    }
+}
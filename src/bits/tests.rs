@@ -0,0 +1,73 @@
+use crate::{BitLen, BitOffset, WideBitOffset};
+
+#[test]
+fn offset_arithmetic() {
+    let mut offset = BitOffset::from_bytes(2);
+    offset += BitLen(5);
+    assert_eq!(offset, BitOffset(21));
+    assert_eq!((offset.byte(), offset.bit()), (2, 5));
+    assert!(!offset.is_byte_aligned());
+    assert_eq!(offset - BitOffset(1), BitLen(20));
+    assert_eq!(offset - BitLen(5), BitOffset::from_bytes(2));
+    assert_eq!(BitLen(3) * 4 + BitLen::from_bytes(1), BitLen(20));
+    assert_eq!(BitLen(17).bytes_ceil(), 3);
+    assert_eq!(format!("{offset}, {}", BitLen(4)), "bit 21, 4 bits");
+}
+
+#[test]
+fn wide_offsets() {
+    let offset = WideBitOffset::from_bytes(1 << 33) + BitLen(11);
+    assert_eq!((offset.byte(), offset.bit()), ((1 << 33) + 1, 3));
+    assert_eq!(offset.to_string(), "bit 68719476747");
+    if usize::BITS >= 64 {
+        assert_eq!(offset.split(), Some(((1 << 33) + 1, BitOffset(3))));
+        assert_eq!(BitOffset::try_from(offset), Ok(BitOffset(68_719_476_747)));
+    } else {
+        assert_eq!(offset.split(), None);
+        assert!(BitOffset::try_from(offset).is_err());
+    }
+    assert_eq!(WideBitOffset::from(BitOffset(9)), WideBitOffset(9));
+}
+
+#[test]
+fn wide_offsets_overflow() {
+    let last = u64::MAX / 8;
+    assert_eq!(
+        WideBitOffset::checked_from_bytes(last),
+        Some(WideBitOffset(u64::MAX - 7))
+    );
+    assert_eq!(WideBitOffset::checked_from_bytes(last + 1), None);
+    assert_eq!(WideBitOffset::checked_from_bytes(u64::MAX), None);
+    let end = WideBitOffset(u64::MAX - 7);
+    assert_eq!(end.checked_add(BitLen(7)), Some(WideBitOffset(u64::MAX)));
+    assert_eq!(end.checked_add(BitLen(8)), None);
+    assert_eq!(end + BitLen(7), WideBitOffset(u64::MAX));
+}
+
+#[test]
+#[should_panic = "overflows"]
+fn wide_offset_add_overflows() {
+    let _ = WideBitOffset(u64::MAX) + BitLen(1);
+}
+
+#[test]
+#[should_panic = "overflows"]
+fn wide_offset_from_bytes_overflows() {
+    WideBitOffset::from_bytes(u64::MAX);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_roundtrip() {
+    let offset = BitOffset(21);
+    let json = serde_json::to_string(&offset).unwrap();
+    assert_eq!(json, "21");
+    assert_eq!(serde_json::from_str::<BitOffset>(&json).unwrap(), offset);
+    let len = serde_json::to_string(&BitLen(7)).unwrap();
+    assert_eq!(serde_json::from_str::<BitLen>(&len).unwrap(), BitLen(7));
+    let wide = serde_json::to_string(&WideBitOffset(u64::MAX)).unwrap();
+    assert_eq!(
+        serde_json::from_str::<WideBitOffset>(&wide).unwrap(),
+        WideBitOffset(u64::MAX)
+    );
+}
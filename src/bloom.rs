@@ -0,0 +1,152 @@
+//! Bloom filters stored as packed bits.
+//!
+//! A [`PackedBloom`] is [`Packed`](crate::Packed) as its raw bits, so it
+//! can be embedded in a packed struct or sent as is.  Items are hashed with
+//! a fixed FNV-1a hash which doesn't depend on the platform, so a filter
+//! built on one machine can be checked on another.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::bloom::PackedBloom;
+//! // 256 bits checked 4 times per item
+//! let mut seen = PackedBloom::<32, 4>::new();
+//! seen.insert("alice");
+//! seen.insert(&42u32);
+//! assert!(seen.contains("alice") && seen.contains(&42u32));
+//!
+//! let mut buf = [0u8; 32];
+//! seen.pack(&mut buf, 0);
+//! assert_eq!(PackedBloom::<32, 4>::unpack(&buf, 0), seen);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::hash::{Hash, Hasher};
+
+use crate::cfg::Cfg;
+use crate::{Pack, PackedSize, Unpack};
+
+/// FNV-1a, with integers hashed as little-endian bytes
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// A Bloom filter of `BYTES * 8` bits, setting `K` of them for each item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedBloom<const BYTES: usize, const K: usize> {
+    bits: [u8; BYTES],
+}
+
+impl<const BYTES: usize, const K: usize> PackedBloom<BYTES, K> {
+    /// An empty filter
+    pub const fn new() -> Self {
+        const { assert!(BYTES > 0 && K > 0, "filters need bits and hashes") };
+        Self { bits: [0; BYTES] }
+    }
+
+    /// Indices of the `K` bits for `item`, by double hashing
+    fn indices<T: Hash + ?Sized>(item: &T) -> impl Iterator<Item = usize> {
+        let mut hasher = Fnv(0xcbf2_9ce4_8422_2325);
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+        let (a, b) = (hash & 0xffff_ffff, hash >> 32 | 1);
+        (0..K as u64)
+            .map(move |i| (a.wrapping_add(i.wrapping_mul(b)) % (BYTES as u64 * 8)) as usize)
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for i in Self::indices(item) {
+            self.bits[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+
+    /// Whether `item` may have been inserted.  Items which were inserted are
+    /// always found, but others can be found by mistake.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        Self::indices(item).all(|i| self.bits[i / 8] & 0x80 >> (i % 8) != 0)
+    }
+
+    /// Add every item of `other` to this filter
+    pub fn union(&mut self, other: &Self) {
+        for (a, b) in self.bits.iter_mut().zip(other.bits) {
+            *a |= b;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bits = [0; BYTES];
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&b| b == 0)
+    }
+
+    /// Amount of bits which are set
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// The bits of the filter, MSB-first
+    pub fn as_bytes(&self) -> &[u8; BYTES] {
+        &self.bits
+    }
+
+    pub const fn from_bytes(bits: [u8; BYTES]) -> Self {
+        Self { bits }
+    }
+}
+
+impl<const BYTES: usize, const K: usize> Default for PackedBloom<BYTES, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BYTES: usize, const K: usize> PackedSize for PackedBloom<BYTES, K> {
+    const SIZE: usize = BYTES * 8;
+}
+
+impl<const BYTES: usize, const K: usize> Pack for PackedBloom<BYTES, K> {
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        self.bits.pack_with::<C>(bytes, offset);
+    }
+}
+
+impl<const BYTES: usize, const K: usize> Unpack for PackedBloom<BYTES, K> {
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Self {
+            bits: <[u8; BYTES]>::unpack_with::<C>(bytes, offset),
+        }
+    }
+}
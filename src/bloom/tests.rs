@@ -0,0 +1,41 @@
+use super::PackedBloom;
+use crate::cfg::Lsb0Le;
+use crate::{Pack, Unpack};
+
+#[test]
+fn stable_bits() {
+    // the filter is meant to be stored, so its bits mustn't change
+    let mut filter = PackedBloom::<4, 3>::new();
+    filter.insert(&1u64);
+    filter.insert("packed");
+    assert_eq!(filter.as_bytes(), &[0x09, 0x04, 0x0a, 0x20]);
+}
+
+#[test]
+fn union_and_clear() {
+    let mut a = PackedBloom::<16, 2>::new();
+    let mut b = PackedBloom::<16, 2>::default();
+    a.insert(&1u8);
+    b.insert(&2u8);
+    a.union(&b);
+    assert!(a.contains(&1u8) && a.contains(&2u8));
+    assert!(a.count_ones() <= 4);
+    a.clear();
+    assert!(a.is_empty());
+}
+
+proptest::proptest! {
+    #[test]
+    fn no_false_negatives(items: Vec<u32>, offset in 0usize..8) {
+        let mut filter = PackedBloom::<64, 5>::new();
+        for x in &items {
+            filter.insert(x);
+        }
+        let mut buf = [0u8; 65];
+        filter.pack_with::<Lsb0Le>(&mut buf, offset);
+        let filter = PackedBloom::<64, 5>::unpack_with::<Lsb0Le>(&buf, offset);
+        for x in &items {
+            assert!(filter.contains(x));
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Zero-copy unpacking.
+//!
+//! [`PackedRef`] is like [`Packed`], but values may borrow from the buffer
+//! they are unpacked from.  Byte arrays at byte-aligned offsets are unpacked
+//! as `&[u8; N]` pointing into the buffer, so payloads don't have to be
+//! copied.  Every [`Packed`] type is also a [`PackedRef`] and is copied out
+//! as usual.
+//!
+//! With the `derive` feature, `PackedRef` can be derived for structs with
+//! borrowed fields:
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::borrow::PackedRef;
+//! #[derive(PackedRef)]
+//! struct Frame<'a> {
+//!     kind: u8,
+//!     payload: &'a [u8; 4],
+//! }
+//!
+//! let buf = [7, 1, 2, 3, 4];
+//! let frame = Frame::unpack_ref(&buf, 0).unwrap();
+//! assert_eq!(frame.kind, 7);
+//! assert!(std::ptr::eq(frame.payload.as_ptr(), buf[1..].as_ptr()));
+//! # }
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::check_len;
+use crate::{Pack, Packed, UnpackError, UnpackErrorKind};
+
+#[cfg(feature = "derive")]
+pub use packed_derive::PackedRef;
+
+/// A value which can be unpacked by borrowing from the source buffer
+pub trait PackedRef<'a>: Sized {
+    /// Amount of bits that the packed value takes up
+    const SIZE: usize;
+
+    /// Unpack a value borrowing from `bytes`.  Fails if a borrowed part of
+    /// the value isn't byte-aligned, or if `bytes` ends before the value
+    /// does.
+    fn unpack_ref_with<C: Cfg>(bytes: &'a [u8], offset: usize) -> Result<Self, UnpackError>;
+
+    fn pack_ref_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize);
+
+    /// Unpack using [`DefaultCfg`]
+    fn unpack_ref(bytes: &'a [u8], offset: usize) -> Result<Self, UnpackError> {
+        Self::unpack_ref_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Pack using [`DefaultCfg`]
+    fn pack_ref(&self, bytes: &mut [u8], offset: usize) {
+        self.pack_ref_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+impl<'a, T> PackedRef<'a> for T
+where
+    T: Packed,
+{
+    const SIZE: usize = T::SIZE;
+
+    fn unpack_ref_with<C: Cfg>(bytes: &'a [u8], offset: usize) -> Result<Self, UnpackError> {
+        check_len(bytes, offset, T::SIZE)?;
+        Ok(T::unpack_with::<C>(bytes, offset))
+    }
+
+    fn pack_ref_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        self.pack_with::<C>(bytes, offset);
+    }
+}
+
+impl<'a, const N: usize> PackedRef<'a> for &'a [u8; N] {
+    const SIZE: usize = N * 8;
+
+    fn unpack_ref_with<C: Cfg>(bytes: &'a [u8], offset: usize) -> Result<Self, UnpackError> {
+        check_len(bytes, offset, N * 8)?;
+        if !offset.is_multiple_of(8) {
+            return Err(UnpackErrorKind::Unaligned { offset }.into());
+        }
+        let start = offset / 8;
+        Ok(bytes[start..start + N].try_into().unwrap())
+    }
+
+    fn pack_ref_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        (**self).pack_with::<C>(bytes, offset);
+    }
+}
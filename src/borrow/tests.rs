@@ -0,0 +1,54 @@
+use super::PackedRef;
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+
+#[derive(Debug, PartialEq, Eq, PackedRef)]
+struct Message<'a> {
+    flag: bool,
+    #[packed(bits = 7)]
+    len: u8,
+    id: u16,
+    payload: &'a [u8; 3],
+}
+
+#[test]
+fn borrows_payload() {
+    let buf = [0x83, 0x12, 0x34, 1, 2, 3, 0xff];
+    let msg = Message::unpack_ref(&buf, 0).unwrap();
+    assert_eq!(
+        msg,
+        Message {
+            flag: true,
+            len: 3,
+            id: 0x1234,
+            payload: &[1, 2, 3],
+        }
+    );
+    assert!(std::ptr::eq(msg.payload.as_ptr(), buf[3..].as_ptr()));
+    assert_eq!(Message::SIZE, 48);
+}
+
+#[test]
+fn rejects_unaligned() {
+    let buf = [0u8; 8];
+    assert_eq!(
+        Message::unpack_ref(&buf, 4),
+        Err(UnpackErrorKind::Unaligned { offset: 28 }.into())
+    );
+    assert_eq!(
+        <&[u8; 2]>::unpack_ref(&buf, 9),
+        Err(UnpackErrorKind::Unaligned { offset: 9 }.into())
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn pack_unpack_ref(flag: bool, len in 0u8..128, id: u16, payload: [u8; 3], offset in 0usize..4) {
+        let msg = Message { flag, len, id, payload: &payload };
+        let mut buf = [0u8; 9];
+        let offset = offset * 8;
+        msg.pack_ref_with::<Lsb0Le>(&mut buf, offset);
+        let out = Message::unpack_ref_with::<Lsb0Le>(&buf, offset).unwrap();
+        assert_eq!(out, Message { flag, len, id, payload: &payload });
+    }
+}
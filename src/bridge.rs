@@ -0,0 +1,535 @@
+//! Packing types which convert to and from an integer or byte array.
+//!
+//! Bitfield structs generated by crates such as `bitfield-struct` and
+//! `modular-bitfield` are newtypes around an integer or byte array, with
+//! `From` impls both ways.  [`packed_via!`](crate::packed_via) implements
+//! [`Pack`] and [`Unpack`] for such a type by packing its representation,
+//! optionally narrowed to fewer bits, so it can be used as a field of
+//! derived structs.  Types from other crates can't be given impls, so they
+//! are wrapped in [`Via`] instead.  Conversions which aren't `From` impls,
+//! such as scaling a float to fixed point, are named by implementing
+//! [`Mapping`] and used through [`MapPacked`].  Fieldless enums and
+//! wrappers around a single field can be given impls without the derive
+//! with [`impl_packed_for_enum!`](crate::impl_packed_for_enum) and
+//! [`impl_packed_delegate!`](crate::impl_packed_delegate).
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! // stands in for a type generated by a bitfield crate
+//! #[derive(Debug, Clone, Copy, PartialEq)]
+//! struct Status(u16);
+//! impl From<u16> for Status {
+//!     fn from(bits: u16) -> Self {
+//!         Self(bits)
+//!     }
+//! }
+//! impl From<Status> for u16 {
+//!     fn from(status: Status) -> Self {
+//!         status.0
+//!     }
+//! }
+//!
+//! // the top 4 bits of the register are always zero
+//! packed::packed_via!(Status => u16, bits = 12);
+//!
+//! #[derive(Packed)]
+//! struct Frame {
+//!     kind: u8,
+//!     status: Status,
+//! }
+//!
+//! assert_eq!(Frame::SIZE, 20);
+//! let mut buf = [0u8; 3];
+//! Frame { kind: 0x12, status: Status(0x345) }.pack(&mut buf, 0);
+//! assert_eq!(buf, [0x12, 0x34, 0x50]);
+//! # }
+//! ```
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::bridge::{MapPacked, Mapping};
+//! /// Temperatures packed in tenths of a degree
+//! struct Tenths;
+//! impl Mapping<i16, f32> for Tenths {
+//!     fn to_packed(value: &f32) -> i16 {
+//!         (value * 10.0).round() as i16
+//!     }
+//!     fn from_packed(packed: i16) -> f32 {
+//!         f32::from(packed) / 10.0
+//!     }
+//! }
+//!
+//! type Celsius = MapPacked<i16, f32, Tenths>;
+//! let mut buf = [0u8; 2];
+//! Celsius::new(-12.5).pack(&mut buf, 0);
+//! assert_eq!(i16::unpack(&buf, 0), -125);
+//! assert_eq!(Celsius::unpack(&buf, 0).into_inner(), -12.5);
+//! ```
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::UnpackErrorKind;
+//! #[derive(Debug, Clone, Copy, PartialEq)]
+//! enum Category {
+//!     Zero,
+//!     Normal,
+//!     Nan,
+//! }
+//!
+//! packed::impl_packed_for_enum!(Category: u8, bits = 2 {
+//!     Zero = 0,
+//!     Normal = 1,
+//!     Nan = 3,
+//!     _ => Nan,
+//! });
+//!
+//! assert_eq!(Category::SIZE, 2);
+//! let mut buf = [0u8; 1];
+//! Category::Normal.pack(&mut buf, 0);
+//! assert_eq!(buf, [0b0100_0000]);
+//! assert_eq!(Category::unpack(&[0b1000_0000], 0), Category::Nan);
+//! assert_eq!(
+//!     Category::unpack_strict(&[0b1000_0000], 0),
+//!     Err(UnpackErrorKind::UnknownVariant { offset: 0, value: 2 }.into()),
+//! );
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::cfg::Cfg;
+use crate::{Pack, PackedSize, Unpack, UnpackError};
+
+/// Implement [`Pack`](crate::Pack) and [`Unpack`](crate::Unpack) for a
+/// `Clone` type by converting it to and from `$repr` with `From`.  With
+/// `bits = N`, an integer representation is narrowed to its `N` least
+/// significant bits, see [`narrow`](crate::narrow).  Strict unpacking checks
+/// the reserved bits of `$repr`, which narrowed integers don't have.
+#[macro_export]
+macro_rules! packed_via {
+    ($ty: ty => $repr: ty) => {
+        impl $crate::PackedSize for $ty {
+            const SIZE: usize = <$repr as $crate::PackedSize>::SIZE;
+        }
+
+        impl $crate::Pack for $ty {
+            fn pack_with<C: $crate::cfg::Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                let repr =
+                    <$repr as ::core::convert::From<$ty>>::from(::core::clone::Clone::clone(self));
+                $crate::Pack::pack_with::<C>(&repr, bytes, offset);
+            }
+        }
+
+        impl $crate::Unpack for $ty {
+            fn unpack_with<C: $crate::cfg::Cfg>(bytes: &[u8], offset: usize) -> Self {
+                let repr = <$repr as $crate::Unpack>::unpack_with::<C>(bytes, offset);
+                <$ty as ::core::convert::From<$repr>>::from(repr)
+            }
+
+            fn check_reserved_with<C: $crate::cfg::Cfg>(
+                bytes: &[u8],
+                offset: usize,
+            ) -> ::core::result::Result<(), $crate::UnpackError> {
+                <$repr as $crate::Unpack>::check_reserved_with::<C>(bytes, offset)
+            }
+        }
+    };
+    ($ty: ty => $repr: ty, bits = $bits: expr) => {
+        impl $crate::PackedSize for $ty {
+            const SIZE: usize = {
+                ::core::assert!(
+                    $bits <= <$repr as $crate::narrow::NarrowInt>::BITS,
+                    "`bits` is wider than the representation",
+                );
+                $bits
+            };
+        }
+
+        impl $crate::Pack for $ty {
+            fn pack_with<C: $crate::cfg::Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                let repr =
+                    <$repr as ::core::convert::From<$ty>>::from(::core::clone::Clone::clone(self));
+                $crate::narrow::NarrowInt::pack_narrow::<C>(repr, $bits, bytes, offset);
+            }
+        }
+
+        impl $crate::Unpack for $ty {
+            fn unpack_with<C: $crate::cfg::Cfg>(bytes: &[u8], offset: usize) -> Self {
+                let repr =
+                    <$repr as $crate::narrow::NarrowInt>::unpack_narrow::<C>($bits, bytes, offset);
+                <$ty as ::core::convert::From<$repr>>::from(repr)
+            }
+
+            fn check_reserved_with<C: $crate::cfg::Cfg>(
+                _bytes: &[u8],
+                _offset: usize,
+            ) -> ::core::result::Result<(), $crate::UnpackError> {
+                // narrow integers have no reserved bits
+                ::core::result::Result::Ok(())
+            }
+        }
+    };
+}
+
+/// Implement [`Pack`](crate::Pack) and [`Unpack`](crate::Unpack) for a
+/// fieldless enum by packing the value given for each variant as `$repr`,
+/// narrowed to `bits = N` bits if given.  Plain unpacking gives the variant
+/// after `_ =>` for values matching no variant, and strict unpacking fails
+/// with [`UnpackErrorKind::UnknownVariant`](crate::UnpackErrorKind).
+///
+/// Every value must fit in `bits` bits, and no two variants may share one:
+///
+/// ```rust,compile_fail
+/// # #[derive(Clone, Copy)]
+/// enum Level {
+///     Low,
+///     High,
+/// }
+///
+/// packed::impl_packed_for_enum!(Level: u8, bits = 2 {
+///     Low = 0,
+///     High = 4,
+///     _ => Low,
+/// });
+/// ```
+///
+/// ```rust,compile_fail
+/// # #[derive(Clone, Copy)]
+/// enum Level {
+///     Low,
+///     High,
+/// }
+///
+/// packed::impl_packed_for_enum!(Level: i8 {
+///     Low = -1,
+///     High = -1,
+///     _ => Low,
+/// });
+/// ```
+#[macro_export]
+macro_rules! impl_packed_for_enum {
+    ($ty: ident: $repr: ty { $($body: tt)* }) => {
+        $crate::impl_packed_for_enum!(
+            @impl $ty, $repr, <$repr as $crate::narrow::NarrowInt>::BITS, { $($body)* }
+        );
+    };
+    ($ty: ident: $repr: ty, bits = $bits: literal { $($body: tt)* }) => {
+        $crate::impl_packed_for_enum!(@impl $ty, $repr, $bits, { $($body)* });
+    };
+    (@impl $ty: ident, $repr: ty, $bits: expr, {
+        $($variant: ident = $value: expr,)+
+        _ => $fallback: ident $(,)?
+    }) => {
+        impl $crate::PackedSize for $ty {
+            const SIZE: usize = {
+                ::core::assert!(
+                    $bits <= <$repr as $crate::narrow::NarrowInt>::BITS,
+                    "`bits` is wider than the representation",
+                );
+                $bits
+            };
+        }
+
+        impl $crate::Pack for $ty {
+            fn pack_with<C: $crate::cfg::Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                let repr: $repr = match self {
+                    $($ty::$variant => $value,)+
+                };
+                $crate::narrow::NarrowInt::pack_narrow::<C>(
+                    repr,
+                    <Self as $crate::PackedSize>::SIZE,
+                    bytes,
+                    offset,
+                );
+            }
+        }
+
+        impl $crate::Unpack for $ty {
+            fn unpack_with<C: $crate::cfg::Cfg>(bytes: &[u8], offset: usize) -> Self {
+                let repr = <$repr as $crate::narrow::NarrowInt>::unpack_narrow::<C>(
+                    <Self as $crate::PackedSize>::SIZE,
+                    bytes,
+                    offset,
+                );
+                $(if repr == $value {
+                    return $ty::$variant;
+                })+
+                $ty::$fallback
+            }
+
+            fn check_reserved_with<C: $crate::cfg::Cfg>(
+                bytes: &[u8],
+                offset: usize,
+            ) -> ::core::result::Result<(), $crate::UnpackError> {
+                let repr = <$repr as $crate::narrow::NarrowInt>::unpack_narrow::<C>(
+                    <Self as $crate::PackedSize>::SIZE,
+                    bytes,
+                    offset,
+                );
+                if $(repr == $value)||+ {
+                    return ::core::result::Result::Ok(());
+                }
+                ::core::result::Result::Err(
+                    $crate::UnpackErrorKind::UnknownVariant {
+                        offset,
+                        value: repr as u64,
+                    }
+                    .into(),
+                )
+            }
+        }
+
+        $(const _: () = ::core::assert!(
+            $crate::impl_packed_for_enum!(@fits $repr, $bits, $value),
+            ::core::concat!("the value of `", ::core::stringify!($variant), "` doesn't fit in `bits`"),
+        );)+
+
+        const _: () = {
+            let values: &[$repr] = &[$($value),+];
+            let mut i = 0;
+            while i < values.len() {
+                let mut j = i + 1;
+                while j < values.len() {
+                    ::core::assert!(values[i] != values[j], "two variants have the same value");
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+    };
+    // whether `$value` survives being narrowed to `$bits` bits and back
+    (@fits $repr: ty, $bits: expr, $value: expr) => {{
+        let value: $repr = $value;
+        let bits: usize = $bits;
+        if bits >= <$repr as $crate::narrow::NarrowInt>::BITS {
+            true
+        } else if bits == 0 {
+            value == 0
+        } else if <$repr>::MIN != 0 {
+            // signed values are sign-extended from their top bit
+            let high = value >> (bits - 1);
+            high == 0 || high == !0
+        } else {
+            value >> bits == 0
+        }
+    }};
+}
+
+/// Implement [`Pack`](crate::Pack) and [`Unpack`](crate::Unpack) for a
+/// struct with a single field, `Wrapper(Inner)` or `Wrapper { field: Inner }`,
+/// by packing the field
+#[macro_export]
+macro_rules! impl_packed_delegate {
+    ($ty: ident($inner: ty)) => {
+        $crate::impl_packed_delegate!(@impl $ty, $inner, 0);
+    };
+    ($ty: ident { $field: ident: $inner: ty $(,)? }) => {
+        $crate::impl_packed_delegate!(@impl $ty, $inner, $field);
+    };
+    (@impl $ty: ident, $inner: ty, $field: tt) => {
+        impl $crate::PackedSize for $ty {
+            const SIZE: usize = <$inner as $crate::PackedSize>::SIZE;
+        }
+
+        impl $crate::Pack for $ty {
+            fn pack_with<C: $crate::cfg::Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                $crate::Pack::pack_with::<C>(&self.$field, bytes, offset);
+            }
+        }
+
+        impl $crate::Unpack for $ty {
+            fn unpack_with<C: $crate::cfg::Cfg>(bytes: &[u8], offset: usize) -> Self {
+                $ty {
+                    $field: <$inner as $crate::Unpack>::unpack_with::<C>(bytes, offset),
+                }
+            }
+
+            fn unpack_into_with<C: $crate::cfg::Cfg>(&mut self, bytes: &[u8], offset: usize) {
+                $crate::Unpack::unpack_into_with::<C>(&mut self.$field, bytes, offset);
+            }
+
+            fn check_reserved_with<C: $crate::cfg::Cfg>(
+                bytes: &[u8],
+                offset: usize,
+            ) -> ::core::result::Result<(), $crate::UnpackError> {
+                <$inner as $crate::Unpack>::check_reserved_with::<C>(bytes, offset)
+            }
+        }
+    };
+}
+
+/// A `T` packed as its representation `R`, for types from other crates
+/// which [`packed_via!`](crate::packed_via) can't implement the traits for
+pub struct Via<R, T> {
+    value: T,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R, T> Via<R, T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<R, T: Clone> Clone for Via<R, T> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<R, T: Copy> Copy for Via<R, T> {}
+
+impl<R, T: PartialEq> PartialEq for Via<R, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<R, T: Eq> Eq for Via<R, T> {}
+
+impl<R, T: fmt::Debug> fmt::Debug for Via<R, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Via").field(&self.value).finish()
+    }
+}
+
+impl<R, T> From<T> for Via<R, T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<R, T> PackedSize for Via<R, T>
+where
+    R: PackedSize,
+{
+    const SIZE: usize = R::SIZE;
+}
+
+impl<R, T> Pack for Via<R, T>
+where
+    R: Pack + From<T>,
+    T: Clone,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        R::from(self.value.clone()).pack_with::<C>(bytes, offset);
+    }
+}
+
+impl<R, T> Unpack for Via<R, T>
+where
+    R: Unpack,
+    T: From<R>,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Self::new(T::from(R::unpack_with::<C>(bytes, offset)))
+    }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        R::check_reserved_with::<C>(bytes, offset)
+    }
+}
+
+/// Conversions between a value of type `U` and the `T` it is packed as,
+/// implemented on a marker type naming the encoding
+pub trait Mapping<T, U> {
+    fn to_packed(value: &U) -> T;
+    fn from_packed(packed: T) -> U;
+}
+
+/// A `U` packed as a `T`, converted with the [`Mapping`] `M`
+pub struct MapPacked<T, U, M> {
+    value: U,
+    _marker: PhantomData<fn() -> (T, M)>,
+}
+
+impl<T, U, M> MapPacked<T, U, M> {
+    pub fn new(value: U) -> Self {
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self) -> &U {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> U {
+        self.value
+    }
+}
+
+impl<T, U: Clone, M> Clone for MapPacked<T, U, M> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T, U: Copy, M> Copy for MapPacked<T, U, M> {}
+
+impl<T, U: PartialEq, M> PartialEq for MapPacked<T, U, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T, U: Eq, M> Eq for MapPacked<T, U, M> {}
+
+impl<T, U: fmt::Debug, M> fmt::Debug for MapPacked<T, U, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MapPacked").field(&self.value).finish()
+    }
+}
+
+impl<T, U, M> From<U> for MapPacked<T, U, M> {
+    fn from(value: U) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, U, M> PackedSize for MapPacked<T, U, M>
+where
+    T: PackedSize,
+{
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T, U, M> Pack for MapPacked<T, U, M>
+where
+    T: Pack,
+    M: Mapping<T, U>,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        M::to_packed(&self.value).pack_with::<C>(bytes, offset);
+    }
+}
+
+impl<T, U, M> Unpack for MapPacked<T, U, M>
+where
+    T: Unpack,
+    M: Mapping<T, U>,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Self::new(M::from_packed(T::unpack_with::<C>(bytes, offset)))
+    }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        T::check_reserved_with::<C>(bytes, offset)
+    }
+}
@@ -0,0 +1,339 @@
+use super::{MapPacked, Mapping, Via};
+#[cfg(feature = "derive")]
+use crate::Packed;
+use crate::cfg::Lsb0Le;
+use crate::{Pack, PackedSize, Reserved, Unpack, UnpackErrorKind};
+
+/// Like a `bitfield-struct` type: a `u32` with accessors for its bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Control(u32);
+
+impl Control {
+    fn enabled(&self) -> bool {
+        self.0 & 1 != 0
+    }
+
+    fn divider(&self) -> u32 {
+        self.0 >> 1 & 0xff
+    }
+}
+
+impl From<u32> for Control {
+    fn from(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<Control> for u32 {
+    fn from(control: Control) -> Self {
+        control.0
+    }
+}
+
+crate::packed_via!(Control => u32);
+
+/// Like a `modular-bitfield` type: a byte array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Flags([u8; 2]);
+
+impl From<[u8; 2]> for Flags {
+    fn from(bytes: [u8; 2]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Flags> for [u8; 2] {
+    fn from(flags: Flags) -> Self {
+        flags.0
+    }
+}
+
+/// A narrowed register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mode(u8);
+
+impl From<u8> for Mode {
+    fn from(bits: u8) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<Mode> for u8 {
+    fn from(mode: Mode) -> Self {
+        mode.0
+    }
+}
+
+crate::packed_via!(Mode => u8, bits = 3);
+
+/// A 7 bit code behind a reserved bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Code(u8);
+
+impl From<(Reserved<1>, u8)> for Code {
+    fn from(packed: (Reserved<1>, u8)) -> Self {
+        Self(packed.1)
+    }
+}
+
+impl From<Code> for (Reserved<1>, u8) {
+    fn from(code: Code) -> Self {
+        (Reserved, code.0)
+    }
+}
+
+crate::packed_via!(Code => (Reserved<1>, u8));
+
+#[test]
+fn packs_repr() {
+    assert_eq!(Control::SIZE, 32);
+    let control = Control(0x0000_0155);
+    let mut buf = [0u8; 5];
+    control.pack(&mut buf, 4);
+    assert_eq!(buf, [0x00, 0x00, 0x00, 0x15, 0x50]);
+    let control = Control::unpack(&buf, 4);
+    assert!(control.enabled());
+    assert_eq!(control.divider(), 0xaa);
+
+    let mut buf = [0u8; 4];
+    control.pack_with::<Lsb0Le>(&mut buf, 0);
+    assert_eq!(buf, [0x55, 0x01, 0x00, 0x00]);
+    assert_eq!(Control::unpack_with::<Lsb0Le>(&buf, 0), control);
+}
+
+#[test]
+fn narrow() {
+    assert_eq!(Mode::SIZE, 3);
+    let mut buf = [0xffu8];
+    Mode(0b101).pack(&mut buf, 2);
+    assert_eq!(buf, [0b1110_1111]);
+    assert_eq!(Mode::unpack(&buf, 2), Mode(0b101));
+    // bits above the width are dropped
+    Mode(0b1010).pack(&mut buf, 0);
+    assert_eq!(Mode::unpack(&buf, 0), Mode(0b010));
+    assert_eq!(Mode::unpack_strict(&[0xff], 5), Ok(Mode(0b111)));
+}
+
+#[test]
+fn via_macro_checks_reserved() {
+    assert_eq!(Code::SIZE, 9);
+    let mut buf = [0u8; 2];
+    Code(0x61).pack(&mut buf, 0);
+    assert_eq!(Code::unpack_strict(&buf, 0), Ok(Code(0x61)));
+    buf[0] |= 0x80;
+    assert_eq!(
+        Code::unpack_strict(&buf, 0),
+        Err(UnpackErrorKind::Reserved {
+            offset: 0,
+            expected: false
+        }
+        .into())
+    );
+}
+
+#[test]
+fn via() {
+    type Wrapped = Via<[u8; 2], Flags>;
+    assert_eq!(Wrapped::SIZE, 16);
+    let mut buf = [0u8; 3];
+    Wrapped::new(Flags([0xab, 0xcd])).pack(&mut buf, 4);
+    assert_eq!(buf, [0x0a, 0xbc, 0xd0]);
+    let flags = Wrapped::unpack(&buf, 4);
+    assert_eq!(flags.get(), &Flags([0xab, 0xcd]));
+    assert_eq!(flags.into_inner(), Flags([0xab, 0xcd]));
+    assert_eq!(
+        Via::<u32, Control>::unpack(&[0, 0, 0, 3], 0),
+        Via::from(Control(3))
+    );
+}
+
+#[test]
+fn via_checks_reserved() {
+    type Wrapped = Via<(Reserved<1>, u8), Code>;
+    let mut buf = [0u8; 2];
+    Wrapped::new(Code(0x61)).pack(&mut buf, 0);
+    assert_eq!(
+        Wrapped::unpack_strict(&buf, 0),
+        Ok(Wrapped::new(Code(0x61)))
+    );
+    buf[0] |= 0x80;
+    assert_eq!(
+        Wrapped::unpack_strict(&buf, 0),
+        Err(UnpackErrorKind::Reserved {
+            offset: 0,
+            expected: false
+        }
+        .into())
+    );
+}
+
+/// Stores a `bool` inverted, as active-low signals are
+#[cfg(feature = "derive")]
+struct ActiveLow;
+
+#[cfg(feature = "derive")]
+impl Mapping<bool, bool> for ActiveLow {
+    fn to_packed(value: &bool) -> bool {
+        !value
+    }
+
+    fn from_packed(packed: bool) -> bool {
+        !packed
+    }
+}
+
+/// Packs a `char` as a 7 bit ASCII code with a reserved bit in front
+struct Ascii;
+
+impl Mapping<(Reserved<1>, u8), char> for Ascii {
+    fn to_packed(value: &char) -> (Reserved<1>, u8) {
+        (Reserved, *value as u8)
+    }
+
+    fn from_packed(packed: (Reserved<1>, u8)) -> char {
+        char::from(packed.1)
+    }
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Packed)]
+struct Pins {
+    reset: MapPacked<bool, bool, ActiveLow>,
+    enable: MapPacked<bool, bool, ActiveLow>,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn map_packed() {
+    assert_eq!(Pins::SIZE, 2);
+    let pins = Pins {
+        reset: true.into(),
+        enable: MapPacked::new(false),
+    };
+    let mut buf = [0u8; 1];
+    pins.pack(&mut buf, 0);
+    assert_eq!(buf, [0b0100_0000]);
+    let pins = Pins::unpack(&buf, 0);
+    assert!(*pins.reset.get());
+    assert!(!pins.enable.into_inner());
+}
+
+#[test]
+fn map_packed_checks_reserved() {
+    type Letter = MapPacked<(Reserved<1>, u8), char, Ascii>;
+    assert_eq!(Letter::SIZE, 9);
+    let mut buf = [0u8; 2];
+    Letter::new('a').pack(&mut buf, 0);
+    assert_eq!(buf, [0x30, 0x80]);
+    assert_eq!(Letter::unpack_strict(&buf, 0), Ok(Letter::new('a')));
+    buf[0] |= 0x80;
+    assert_eq!(
+        Letter::unpack_strict(&buf, 0),
+        Err(UnpackErrorKind::Reserved {
+            offset: 0,
+            expected: false
+        }
+        .into())
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signed {
+    Low,
+    Mid,
+    High,
+    Other,
+}
+
+crate::impl_packed_for_enum!(Signed: i16 {
+    Low = -300,
+    Mid = 0,
+    High = 300,
+    Other = 1,
+    _ => Other,
+});
+
+/// Narrowed signed values which only fit once sign-extended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Trim {
+    Down,
+    Flat,
+    Up,
+}
+
+crate::impl_packed_for_enum!(Trim: i8, bits = 3 {
+    Down = -4,
+    Flat = 0,
+    Up = 3,
+    _ => Flat,
+});
+
+#[test]
+fn narrow_signed_enum() {
+    let mut buf = [0u8; 1];
+    Trim::Down.pack(&mut buf, 0);
+    assert_eq!(buf, [0b1000_0000]);
+    assert_eq!(Trim::unpack(&buf, 0), Trim::Down);
+    Trim::Up.pack(&mut buf, 0);
+    assert_eq!(Trim::unpack_strict(&buf, 0), Ok(Trim::Up));
+}
+
+#[test]
+fn enum_impls() {
+    assert_eq!(Signed::SIZE, 16);
+    let mut buf = [0u8; 2];
+    Signed::Low.pack_with::<Lsb0Le>(&mut buf, 0);
+    assert_eq!(buf, (-300i16).to_le_bytes());
+    assert_eq!(Signed::unpack_with::<Lsb0Le>(&buf, 0), Signed::Low);
+    for (value, variant) in [(300, Signed::High), (1, Signed::Other), (2, Signed::Other)] {
+        assert_eq!(Signed::unpack(&i16::to_be_bytes(value), 0), variant);
+    }
+    assert_eq!(
+        Signed::unpack_strict(&2i16.to_be_bytes(), 0),
+        Err(UnpackErrorKind::UnknownVariant {
+            offset: 0,
+            value: 2
+        }
+        .into())
+    );
+    assert_eq!(
+        <(u8, Signed)>::unpack_strict(&[0, 0xff, 0xff], 0).map_err(|e| e.offset()),
+        Err(8)
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Meters(u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Padded {
+    inner: (Reserved<4>, [bool; 4]),
+}
+
+crate::impl_packed_delegate!(Meters(u16));
+crate::impl_packed_delegate!(Padded {
+    inner: (Reserved<4>, [bool; 4])
+});
+
+#[test]
+fn delegate_impls() {
+    assert_eq!(Meters::SIZE, 16);
+    let mut buf = [0u8; 3];
+    Meters(0x1234).pack(&mut buf, 4);
+    assert_eq!(buf, [0x01, 0x23, 0x40]);
+    assert_eq!(Meters::unpack(&buf, 4), Meters(0x1234));
+
+    let padded = Padded {
+        inner: (Reserved, [true, false, true, true]),
+    };
+    let mut buf = [0u8; 1];
+    padded.pack(&mut buf, 0);
+    assert_eq!(buf, [0x0b]);
+    assert_eq!(Padded::unpack_strict(&buf, 0), Ok(padded));
+    assert!(Padded::unpack_strict(&[0x8b], 0).is_err());
+    let mut out = Padded {
+        inner: (Reserved, [false; 4]),
+    };
+    out.unpack_into(&buf, 0);
+    assert_eq!(out, padded);
+}
@@ -0,0 +1,188 @@
+//! Packing runs of bools and bytes many at a time.
+//!
+//! [`pack_bools`] packs a slice of bools as if each were packed on its own,
+//! and [`pack_bytes`] does the same for bytes at offsets which aren't byte
+//! aligned.  Both work on whole bytes or vectors rather than bits.  On
+//! aarch64 CPUs with NEON, detected at runtime, 16 values are handled at
+//! once.
+//!
+//! ```rust
+//! # use packed::bulk;
+//! let mut buf = [0u8; 3];
+//! let flags = [true, false, true, true, false, false, false, true, true];
+//! assert_eq!(bulk::pack_bools(&flags, &mut buf, 4), 9);
+//! assert_eq!(bulk::pack_bytes(&[0xab], &mut buf, 13), 8);
+//! assert_eq!(buf, [0b0000_1011, 0b0001_1101, 0b0101_1000]);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::Pack;
+use crate::cfg::{BitOrder, Cfg, DefaultCfg};
+use crate::error::bit_len;
+
+/// The byte holding 8 bools in packing order
+fn bools_byte<C: Cfg>(bools: &[bool]) -> u8 {
+    bools.iter().enumerate().fold(0, |byte, (i, &b)| {
+        byte | u8::from(b)
+            << match C::BIT_ORDER {
+                BitOrder::Msb0 => 7 - i,
+                BitOrder::Lsb0 => i,
+            }
+    })
+}
+
+/// Fill `out` with the bytes holding `bools`, 8 to a byte
+fn bools_bytes<C: Cfg>(bools: &[bool], out: &mut [u8]) {
+    debug_assert_eq!(bools.len(), out.len() * 8);
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        // SAFETY: the CPU supports NEON
+        return unsafe { neon::bools_bytes::<C>(bools, out) };
+    }
+    for (byte, bools) in out.iter_mut().zip(bools.chunks_exact(8)) {
+        *byte = bools_byte::<C>(bools);
+    }
+}
+
+/// Pack `bools` one after another at `offset`, returning the amount of bits
+/// written
+pub fn pack_bools_with<C: Cfg>(bools: &[bool], bytes: &mut [u8], offset: usize) -> usize {
+    debug_assert!(bit_len(bytes) - offset >= bools.len());
+    let mut buf = [0u8; 64];
+    let mut chunks = bools.chunks_exact(buf.len() * 8);
+    let mut written = 0;
+    for chunk in &mut chunks {
+        bools_bytes::<C>(chunk, &mut buf);
+        written += pack_bytes_with::<C>(&buf, bytes, offset + written);
+    }
+    let rest = chunks.remainder();
+    let whole = rest.len() / 8;
+    bools_bytes::<C>(&rest[..whole * 8], &mut buf[..whole]);
+    written += pack_bytes_with::<C>(&buf[..whole], bytes, offset + written);
+    for b in &rest[whole * 8..] {
+        b.pack_with::<C>(bytes, offset + written);
+        written += 1;
+    }
+    written
+}
+
+/// Pack `src` one byte after another at `offset`, returning the amount of
+/// bits written
+pub fn pack_bytes_with<C: Cfg>(src: &[u8], bytes: &mut [u8], offset: usize) -> usize {
+    debug_assert!(bit_len(bytes) - offset >= src.len() * 8);
+    if src.is_empty() {
+        return 0;
+    }
+    let (i, shift) = (offset / 8, offset % 8);
+    if cfg!(feature = "paranoid") {
+        for (k, x) in src.iter().enumerate() {
+            x.pack_with::<C>(bytes, offset + k * 8);
+        }
+        return src.len() * 8;
+    }
+    if shift == 0 {
+        bytes[i..i + src.len()].copy_from_slice(src);
+        return src.len() * 8;
+    }
+    let n = src.len();
+    let out = &mut bytes[i..=i + n];
+    // the bits of `out[0]` before the offset and of `out[n]` after the end
+    let keep: u8 = match C::BIT_ORDER {
+        BitOrder::Msb0 => !(0xff >> shift),
+        BitOrder::Lsb0 => !(0xff << shift),
+    };
+    let (first, last) = (out[0] & keep, out[n] & !keep);
+    #[cfg(target_arch = "aarch64")]
+    let start = if std::arch::is_aarch64_feature_detected!("neon") {
+        // SAFETY: the CPU supports NEON
+        unsafe { neon::shift_bytes::<C>(src, out, shift) }
+    } else {
+        1
+    };
+    #[cfg(not(target_arch = "aarch64"))]
+    let start = 1;
+    for k in start..n {
+        out[k] = join::<C>(src[k - 1], src[k], shift);
+    }
+    out[0] = first | join::<C>(0, src[0], shift);
+    out[n] = join::<C>(src[n - 1], 0, shift) | last;
+    n * 8
+}
+
+/// The byte at `shift` bits into the bytes `a` then `b`
+fn join<C: Cfg>(a: u8, b: u8, shift: usize) -> u8 {
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => a << (8 - shift) | b >> shift,
+        BitOrder::Lsb0 => a >> (8 - shift) | b << shift,
+    }
+}
+
+/// Pack bools using [`DefaultCfg`]
+pub fn pack_bools(bools: &[bool], bytes: &mut [u8], offset: usize) -> usize {
+    pack_bools_with::<DefaultCfg>(bools, bytes, offset)
+}
+
+/// Pack bytes using [`DefaultCfg`]
+pub fn pack_bytes(src: &[u8], bytes: &mut [u8], offset: usize) -> usize {
+    pack_bytes_with::<DefaultCfg>(src, bytes, offset)
+}
+
+#[cfg(target_arch = "aarch64")]
+#[allow(unused_unsafe)]
+mod neon {
+    use std::arch::aarch64::{
+        vaddv_u8, vdupq_n_s8, vget_high_u8, vget_low_u8, vld1q_s8, vld1q_u8, vorrq_u8, vshlq_u8,
+        vst1q_u8,
+    };
+
+    use crate::cfg::{BitOrder, Cfg};
+
+    /// [`super::bools_bytes`] 16 bools at a time
+    #[target_feature(enable = "neon")]
+    pub(super) fn bools_bytes<C: Cfg>(bools: &[bool], out: &mut [u8]) {
+        let shifts: [i8; 16] = match C::BIT_ORDER {
+            BitOrder::Msb0 => [7, 6, 5, 4, 3, 2, 1, 0, 7, 6, 5, 4, 3, 2, 1, 0],
+            BitOrder::Lsb0 => [0, 1, 2, 3, 4, 5, 6, 7, 0, 1, 2, 3, 4, 5, 6, 7],
+        };
+        let mut chunks = bools.chunks_exact(16);
+        let mut k = 0;
+        // SAFETY: each load reads 16 bools, which are bytes of 0 or 1
+        unsafe {
+            let shifts = vld1q_s8(shifts.as_ptr());
+            for chunk in &mut chunks {
+                let bits = vshlq_u8(vld1q_u8(chunk.as_ptr().cast()), shifts);
+                out[k] = vaddv_u8(vget_low_u8(bits));
+                out[k + 1] = vaddv_u8(vget_high_u8(bits));
+                k += 2;
+            }
+        }
+        for (byte, bools) in out[k..].iter_mut().zip(chunks.remainder().chunks_exact(8)) {
+            *byte = super::bools_byte::<C>(bools);
+        }
+    }
+
+    /// Fill `out[1..]` 16 bytes at a time as [`super::pack_bytes_with`] does,
+    /// returning the index of the first byte left to fill
+    #[target_feature(enable = "neon")]
+    pub(super) fn shift_bytes<C: Cfg>(src: &[u8], out: &mut [u8], shift: usize) -> usize {
+        let shift = shift as i8;
+        let (a_shift, b_shift) = match C::BIT_ORDER {
+            BitOrder::Msb0 => (8 - shift, -shift),
+            BitOrder::Lsb0 => (shift - 8, shift),
+        };
+        let mut k = 1;
+        // SAFETY: `k - 1 + 16 < src.len()` and `k + 16 <= src.len() < out.len()`
+        unsafe {
+            let (a_shift, b_shift) = (vdupq_n_s8(a_shift), vdupq_n_s8(b_shift));
+            while k + 16 <= src.len() {
+                let a = vshlq_u8(vld1q_u8(src[k - 1..].as_ptr()), a_shift);
+                let b = vshlq_u8(vld1q_u8(src[k..].as_ptr()), b_shift);
+                vst1q_u8(out[k..].as_mut_ptr(), vorrq_u8(a, b));
+                k += 16;
+            }
+        }
+        k
+    }
+}
@@ -0,0 +1,42 @@
+use super::{pack_bools_with, pack_bytes_with};
+use crate::Pack;
+use crate::cfg::{Cfg, Lsb0Le, Msb0Be};
+
+fn matches_each<C: Cfg>(bools: &[bool], src: &[u8], offset: usize, background: u8) {
+    let len = (offset + bools.len().max(src.len() * 8)).div_ceil(8) + 1;
+    let mut bulk = vec![background; len];
+    let mut each = vec![background; len];
+    assert_eq!(pack_bools_with::<C>(bools, &mut bulk, offset), bools.len());
+    for (i, b) in bools.iter().enumerate() {
+        b.pack_with::<C>(&mut each, offset + i);
+    }
+    assert_eq!(bulk, each);
+    assert_eq!(pack_bytes_with::<C>(src, &mut bulk, offset), src.len() * 8);
+    for (i, x) in src.iter().enumerate() {
+        x.pack_with::<C>(&mut each, offset + i * 8);
+    }
+    assert_eq!(bulk, each);
+}
+
+#[test]
+fn long_runs() {
+    let bools: Vec<bool> = (0..1100).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+    let src: Vec<u8> = (0..100).map(|i| (i * 37) as u8).collect();
+    for offset in 0..8 {
+        matches_each::<Msb0Be>(&bools, &src, offset, 0x5a);
+        matches_each::<Lsb0Le>(&bools, &src, offset, 0x5a);
+    }
+}
+
+proptest::proptest! {
+    #[test]
+    fn bulk_matches_each(
+        bools in proptest::collection::vec(proptest::bool::ANY, 0..100),
+        src in proptest::collection::vec(proptest::num::u8::ANY, 0..40),
+        offset in 0usize..24,
+        background: u8,
+    ) {
+        matches_each::<Msb0Be>(&bools, &src, offset, background);
+        matches_each::<Lsb0Le>(&bools, &src, offset, background);
+    }
+}
@@ -0,0 +1,118 @@
+//! C bitfield layout.
+//!
+//! Structs deriving `Packed` with `#[packed(c_abi)]` are laid out the way
+//! GCC and Clang lay out a C struct with the same members, so structs dumped
+//! by C programs can be read directly.  Narrow fields are bitfields of their
+//! declared type and all other fields are ordinary members:
+//!
+//! * a bitfield which would cross a boundary of its declared type's size
+//!   starts at the next such boundary, and a zero-width bitfield moves to the
+//!   next boundary
+//! * members are aligned to the alignment of their type, and take up a
+//!   whole number of bytes
+//! * the struct is padded to a multiple of its largest alignment
+//!
+//! Use [`Lsb0Le`](crate::cfg::Lsb0Le) for little-endian targets, where
+//! bitfields are allocated from the least significant bit, and
+//! [`Msb0Be`](crate::cfg::Msb0Be) for big-endian ones.  Alignments are taken
+//! from the Rust types, which match C for integers and arrays and structs of
+//! them on the same target.  Members are still packed as their `Packed`
+//! impl packs them, so arrays of `bool` take one bit per element rather than
+//! one byte as in C.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::cfg::Lsb0Le;
+//! // struct flags { uint8_t a : 3; uint8_t b : 6; uint16_t c; };
+//! #[derive(Packed)]
+//! #[packed(c_abi)]
+//! struct Flags {
+//!     #[packed(bits = 3)]
+//!     a: u8,
+//!     #[packed(bits = 6)]
+//!     b: u8,
+//!     c: u16,
+//! }
+//!
+//! assert_eq!(Flags::SIZE, 32);
+//! let flags = Flags::unpack_with::<Lsb0Le>(&[0x05, 0x2a, 0x34, 0x12], 0);
+//! assert_eq!((flags.a, flags.b, flags.c), (5, 0x2a, 0x1234));
+//! # }
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use crate::cfg::{BitOrder, Cfg};
+
+/// A field of a struct laid out by [`layout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    /// Width of the field in bits
+    bits: usize,
+    /// Size of the declared type of a bitfield, `None` for other members
+    storage: Option<usize>,
+    /// Alignment of the declared type in bits
+    align: usize,
+}
+
+impl Field {
+    /// A bitfield `bits` wide whose declared type is `storage` bits wide and
+    /// aligned to `align` bits
+    pub const fn bitfield(bits: usize, storage: usize, align: usize) -> Self {
+        Self {
+            bits,
+            storage: Some(storage),
+            align,
+        }
+    }
+
+    /// An ordinary member `bits` wide and aligned to `align` bits
+    pub const fn member(bits: usize, align: usize) -> Self {
+        Self {
+            bits: bits.next_multiple_of(8),
+            storage: None,
+            align: if align == 0 { 8 } else { align },
+        }
+    }
+}
+
+/// Bit offsets of `fields` laid out like a C struct, and the size of the
+/// struct
+pub const fn layout<const N: usize>(fields: [Field; N]) -> ([usize; N], usize) {
+    let mut offsets = [0; N];
+    let mut offset = 0usize;
+    let mut align = 8;
+    let mut i = 0;
+    while i < N {
+        let field = fields[i];
+        match field.storage {
+            Some(storage) if field.bits == 0 => offset = offset.next_multiple_of(storage),
+            Some(storage) => {
+                if offset / storage != (offset + field.bits - 1) / storage {
+                    offset = offset.next_multiple_of(storage);
+                }
+            }
+            None => offset = offset.next_multiple_of(field.align),
+        }
+        // unnamed zero-width bitfields don't affect the struct's alignment
+        if field.bits > 0 && field.align > align {
+            align = field.align;
+        }
+        offsets[i] = offset;
+        offset += field.bits;
+        i += 1;
+    }
+    (offsets, offset.next_multiple_of(align))
+}
+
+/// Bits in front of a member `bits` wide within its whole bytes.  Members
+/// narrower than their storage, like `bool`, sit in its least significant
+/// bits.
+pub const fn lead<C: Cfg>(bits: usize) -> usize {
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => bits.next_multiple_of(8) - bits,
+        BitOrder::Lsb0 => 0,
+    }
+}
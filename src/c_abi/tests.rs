@@ -0,0 +1,83 @@
+use super::{Field, layout};
+use crate::cfg::{Lsb0Le, Msb0Be};
+use crate::{Pack, Packed, PackedSize, Unpack};
+
+// struct mixed {
+//     uint8_t a : 3;
+//     uint8_t b : 6;
+//     uint16_t c;
+//     uint32_t d : 20;
+//     uint32_t e : 20;
+//     bool f;
+// };
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+#[packed(c_abi)]
+struct Mixed {
+    #[packed(bits = 3)]
+    a: u8,
+    #[packed(bits = 6)]
+    b: u8,
+    c: u16,
+    #[packed(bits = 20)]
+    d: u32,
+    #[packed(bits = 20)]
+    e: u32,
+    f: bool,
+}
+
+const MIXED: Mixed = Mixed {
+    a: 5,
+    b: 0x2a,
+    c: 0x1234,
+    d: 0xabcde,
+    e: 0x12345,
+    f: true,
+};
+
+#[test]
+fn offsets() {
+    let (offsets, size) = layout([
+        Field::bitfield(3, 8, 8),
+        Field::bitfield(6, 8, 8),
+        Field::member(16, 16),
+        Field::bitfield(20, 32, 32),
+        Field::bitfield(20, 32, 32),
+        Field::member(1, 8),
+    ]);
+    assert_eq!(offsets, [0, 8, 16, 32, 64, 88]);
+    assert_eq!(size, 96);
+    assert_eq!(Mixed::SIZE, 96);
+}
+
+#[test]
+fn zero_width() {
+    let (offsets, size) = layout([
+        Field::bitfield(1, 32, 32),
+        Field::bitfield(0, 32, 32),
+        Field::bitfield(1, 8, 8),
+    ]);
+    assert_eq!(offsets, [0, 32, 32]);
+    assert_eq!(size, 64);
+}
+
+#[test]
+fn little_endian() {
+    let bytes = [
+        0x05, 0x2a, 0x34, 0x12, 0xde, 0xbc, 0x0a, 0x00, 0x45, 0x23, 0x01, 0x01,
+    ];
+    assert_eq!(Mixed::unpack_with::<Lsb0Le>(&bytes, 0), MIXED);
+    let mut buf = [0u8; 12];
+    MIXED.pack_with::<Lsb0Le>(&mut buf, 0);
+    assert_eq!(buf, bytes);
+}
+
+#[test]
+fn big_endian() {
+    let bytes = [
+        0xa0, 0xa8, 0x12, 0x34, 0xab, 0xcd, 0xe0, 0x00, 0x12, 0x34, 0x50, 0x01,
+    ];
+    assert_eq!(Mixed::unpack_with::<Msb0Be>(&bytes, 0), MIXED);
+    let mut buf = [0u8; 12];
+    MIXED.pack_with::<Msb0Be>(&mut buf, 0);
+    assert_eq!(buf, bytes);
+}
@@ -0,0 +1,140 @@
+//! Wire conventions for packing.
+//!
+//! Every [`Packed`](crate::Packed) impl is generic over a zero-sized
+//! configuration type implementing [`Cfg`], which decides how bit offsets map
+//! onto bytes and in which order the bytes of an integer are laid out.  The
+//! plain `pack`/`unpack` methods use [`DefaultCfg`].
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::cfg::Lsb0Le;
+//! let mut buf = [0u8; 3];
+//! 0x1234u16.pack_with::<Lsb0Le>(&mut buf, 4);
+//! assert_eq!(buf, [0x40, 0x23, 0x01]);
+//! ```
+
+use std::marker::PhantomData;
+
+/// How bit offsets are numbered within a byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Offset 0 is the most significant bit of the first byte and values are
+    /// written most significant bit first
+    Msb0,
+    /// Offset 0 is the least significant bit of the first byte and values
+    /// are written least significant bit first
+    Lsb0,
+}
+
+/// In which order the bytes of a multi-byte integer are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Big,
+    Little,
+}
+
+impl ByteOrder {
+    /// The byte order of the target.  Packing never depends on it: integers
+    /// only become bytes through [`OrderedBytes`], in an explicit order, so
+    /// values pack the same on big- and little-endian targets.
+    pub const NATIVE: Self = if cfg!(target_endian = "big") {
+        Self::Big
+    } else {
+        Self::Little
+    };
+}
+
+/// Integers converted to and from bytes in an explicit byte order
+pub trait OrderedBytes: Copy {
+    /// `[u8; N]` for an `N` byte integer
+    type Bytes;
+
+    fn to_bytes(self, order: ByteOrder) -> Self::Bytes;
+    fn from_bytes(bytes: Self::Bytes, order: ByteOrder) -> Self;
+}
+
+macro_rules! ordered_bytes {
+    ($($ty: ident),+) => {
+        $(impl OrderedBytes for $ty {
+            type Bytes = [u8; ::core::mem::size_of::<$ty>()];
+
+            fn to_bytes(self, order: ByteOrder) -> Self::Bytes {
+                match order {
+                    ByteOrder::Big => self.to_be_bytes(),
+                    ByteOrder::Little => self.to_le_bytes(),
+                }
+            }
+
+            fn from_bytes(bytes: Self::Bytes, order: ByteOrder) -> Self {
+                match order {
+                    ByteOrder::Big => Self::from_be_bytes(bytes),
+                    ByteOrder::Little => Self::from_le_bytes(bytes),
+                }
+            }
+        })+
+    };
+}
+
+ordered_bytes!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+pub trait Cfg {
+    const BIT_ORDER: BitOrder;
+    const BYTE_ORDER: ByteOrder;
+}
+
+macro_rules! cfg_type {
+    ($name: ident, $bit: ident, $byte: ident, $doc: literal) => {
+        #[doc = $doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct $name;
+
+        impl Cfg for $name {
+            const BIT_ORDER: BitOrder = BitOrder::$bit;
+            const BYTE_ORDER: ByteOrder = ByteOrder::$byte;
+        }
+    };
+}
+
+cfg_type!(
+    Msb0Be,
+    Msb0,
+    Big,
+    "MSB-first bits, big-endian bytes (network order)"
+);
+cfg_type!(Msb0Le, Msb0, Little, "MSB-first bits, little-endian bytes");
+cfg_type!(Lsb0Be, Lsb0, Big, "LSB-first bits, big-endian bytes");
+cfg_type!(Lsb0Le, Lsb0, Little, "LSB-first bits, little-endian bytes");
+
+/// The configuration used by [`Pack::pack`](crate::Pack::pack) and
+/// [`Unpack::unpack`](crate::Unpack::unpack)
+pub type DefaultCfg = Msb0Be;
+
+/// `C` with its byte order replaced by big-endian, used by the derive for
+/// `#[packed(endian = "big")]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BigEndian<C>(PhantomData<C>);
+
+impl<C: Cfg> Cfg for BigEndian<C> {
+    const BIT_ORDER: BitOrder = C::BIT_ORDER;
+    const BYTE_ORDER: ByteOrder = ByteOrder::Big;
+}
+
+/// `C` with its byte order replaced by little-endian, used by the derive for
+/// `#[packed(endian = "little")]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LittleEndian<C>(PhantomData<C>);
+
+impl<C: Cfg> Cfg for LittleEndian<C> {
+    const BIT_ORDER: BitOrder = C::BIT_ORDER;
+    const BYTE_ORDER: ByteOrder = ByteOrder::Little;
+}
+
+/// Mask selecting the bit at `offset` within its byte
+pub(crate) const fn bit_mask<C: Cfg>(offset: usize) -> u8 {
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => 1 << (7 - offset % 8),
+        BitOrder::Lsb0 => 1 << (offset % 8),
+    }
+}
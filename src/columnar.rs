@@ -0,0 +1,152 @@
+//! Rows stored column by column, each column packed at its own bit width.
+//!
+//! Like the bit-packed columns of analytics formats such as Parquet, every
+//! value of a column takes the same amount of bits, so any row can be read
+//! without unpacking the rows before it.  The packed buffer of each column
+//! can be stored or sent on its own and read back with
+//! [`Table::from_parts`].
+//!
+//! ```rust
+//! # use packed::columnar::Table;
+//! // status code, 12 bit latency in ms, 1 bit cache hit
+//! let mut table = Table::new(&[3, 12, 1]);
+//! table.push(&[2, 180, 1]);
+//! table.push(&[4, 2048, 0]);
+//! table.push(&[2, 95, 1]);
+//! assert_eq!(table.get(1, 1), Some(2048));
+//! assert_eq!(table.row(2), Some(vec![2, 95, 1]));
+//! assert_eq!(table.column(0), [0b010_100_01, 0]);
+//! assert_eq!(table.column(2), [0b101_00000]);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::narrow::NarrowInt;
+use crate::{UnpackError, UnpackErrorKind};
+
+/// Rows of integers stored as one packed buffer per column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table<C = DefaultCfg> {
+    widths: Vec<usize>,
+    columns: Vec<Vec<u8>>,
+    len: usize,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl Table {
+    /// An empty table with a column for each of `widths`, in bits
+    ///
+    /// # Panics
+    ///
+    /// If a width is more than 64 bits
+    pub fn new(widths: &[usize]) -> Self {
+        Self::with_cfg(widths)
+    }
+}
+
+impl<C: Cfg> Table<C> {
+    /// An empty table packing its columns with the configuration `C`
+    ///
+    /// # Panics
+    ///
+    /// If a width is more than 64 bits
+    pub fn with_cfg(widths: &[usize]) -> Self {
+        for &w in widths {
+            assert!(w <= 64, "columns can't be {w} bits wide");
+        }
+        Self {
+            widths: widths.to_vec(),
+            columns: vec![Vec::new(); widths.len()],
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// A table of `len` rows from the packed buffers of its columns, as
+    /// returned by [`column`](Self::column)
+    ///
+    /// # Panics
+    ///
+    /// If a width is more than 64 bits, or there isn't a buffer for every
+    /// column
+    pub fn from_parts(
+        widths: &[usize],
+        columns: Vec<Vec<u8>>,
+        len: usize,
+    ) -> Result<Self, UnpackError> {
+        assert_eq!(widths.len(), columns.len(), "expected a buffer per column");
+        let mut table = Self::with_cfg(widths);
+        for (&w, column) in widths.iter().zip(&columns) {
+            if len
+                .checked_mul(w)
+                .is_none_or(|bits| bits > column.len() * 8)
+            {
+                return Err(UnpackErrorKind::UnexpectedEnd {
+                    offset: column.len() * 8,
+                }
+                .into());
+            }
+        }
+        table.columns = columns;
+        table.len = len;
+        Ok(table)
+    }
+
+    /// Append a row with a value for each column.  Bits of a value above
+    /// the width of its column are ignored.
+    ///
+    /// # Panics
+    ///
+    /// If the row doesn't have a value for every column
+    pub fn push(&mut self, row: &[u64]) {
+        assert_eq!(row.len(), self.widths.len(), "expected a value per column");
+        for ((&w, column), x) in self.widths.iter().zip(&mut self.columns).zip(row) {
+            column.resize(((self.len + 1) * w).div_ceil(8), 0);
+            x.pack_narrow::<C>(w, column, self.len * w);
+        }
+        self.len += 1;
+    }
+
+    /// The value of column `col` in row `row`
+    pub fn get(&self, row: usize, col: usize) -> Option<u64> {
+        let w = *self.widths.get(col)?;
+        if row >= self.len {
+            return None;
+        }
+        Some(u64::unpack_narrow::<C>(w, &self.columns[col], row * w))
+    }
+
+    /// Every value of row `row`
+    pub fn row(&self, row: usize) -> Option<Vec<u64>> {
+        (0..self.widths.len())
+            .map(|col| self.get(row, col))
+            .collect()
+    }
+
+    /// The packed values of column `col`
+    ///
+    /// # Panics
+    ///
+    /// If there is no column `col`
+    pub fn column(&self, col: usize) -> &[u8] {
+        &self.columns[col]
+    }
+
+    /// Width of each column in bits
+    pub fn widths(&self) -> &[usize] {
+        &self.widths
+    }
+
+    /// Amount of rows
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
@@ -0,0 +1,63 @@
+use super::Table;
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn out_of_range() {
+    let mut table = Table::new(&[4, 0]);
+    table.push(&[0x1f, 7]);
+    assert_eq!(table.row(0), Some(vec![0xf, 0]));
+    assert_eq!(table.get(1, 0), None);
+    assert_eq!(table.get(0, 2), None);
+    assert_eq!(table.row(1), None);
+    assert_eq!(table.column(1), [0u8; 0]);
+}
+
+#[test]
+fn from_parts() {
+    let mut table = Table::new(&[5, 64]);
+    table.push(&[3, u64::MAX]);
+    table.push(&[30, 1]);
+    let columns = vec![table.column(0).to_vec(), table.column(1).to_vec()];
+    assert_eq!(Table::from_parts(&[5, 64], columns.clone(), 2), Ok(table));
+    assert_eq!(
+        Table::<Lsb0Le>::from_parts(&[5, 64], columns, 3),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 128 }.into())
+    );
+}
+
+#[test]
+#[should_panic = "a value per column"]
+fn short_row() {
+    Table::new(&[1, 2]).push(&[1]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn random_access(
+        widths in proptest::collection::vec(0usize..=64, 1..5),
+        values in proptest::collection::vec(proptest::collection::vec(proptest::num::u64::ANY, 5), 0..10),
+    ) {
+        let mut table = Table::<Lsb0Le>::with_cfg(&widths);
+        let rows: Vec<Vec<u64>> = values
+            .iter()
+            .map(|row| {
+                widths
+                    .iter()
+                    .zip(row)
+                    .map(|(&w, x)| if w == 64 { *x } else { x & ((1 << w) - 1) })
+                    .collect()
+            })
+            .collect();
+        for row in &values {
+            table.push(&row[..widths.len()]);
+        }
+        assert_eq!(table.len(), rows.len());
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(table.row(i).as_ref(), Some(row));
+        }
+        for (col, &w) in widths.iter().enumerate() {
+            assert_eq!(table.column(col).len(), (rows.len() * w).div_ceil(8));
+        }
+    }
+}
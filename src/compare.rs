@@ -0,0 +1,119 @@
+//! Equality and hashing by packed representation.
+//!
+//! Two values are [`packed_eq`] when they pack to the same bits, and
+//! [`packed_hash`] feeds those bits to a hasher, so values which only differ
+//! in what the wire format drops are treated as the same.  This suits caches
+//! and dedup tables keyed by what was sent.  Values of up to
+//! [`STACK_BITS`](crate::display::STACK_BITS) bits are packed on the stack,
+//! so this doesn't allocate for them.
+//!
+//! [`ByBits`] wraps a value to use these as its `PartialEq`, `Eq` and `Hash`,
+//! and deriving [`PackedEq`] implements them on a struct directly.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::compare::{self, PackedEq};
+//! # use std::collections::HashSet;
+//! #[derive(Packed, PackedEq)]
+//! struct Reading {
+//!     // only the low 4 bits go on the wire
+//!     #[packed(bits = 4)]
+//!     level: u8,
+//! }
+//!
+//! assert!(compare::packed_eq(&0x1234u16, &0x1234u16));
+//! assert!(Reading { level: 0x13 } == Reading { level: 0x03 });
+//! let seen: HashSet<_> = [3, 0x13, 0x23, 4].map(|level| Reading { level }).into();
+//! assert_eq!(seen.len(), 2);
+//! # }
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::Pack;
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::display::with_packed;
+
+#[cfg(feature = "derive")]
+pub use packed_derive::PackedEq;
+
+/// Whether `a` and `b` pack to the same bits with `C`
+pub fn packed_eq_with<C, T>(a: &T, b: &T) -> bool
+where
+    C: Cfg,
+    T: Pack + ?Sized,
+{
+    with_packed::<C, T, _>(a, |a| with_packed::<C, T, _>(b, |b| a == b))
+}
+
+/// Feed the bits `value` packs to with `C` into `state`
+pub fn packed_hash_with<C, T, H>(value: &T, state: &mut H)
+where
+    C: Cfg,
+    T: Pack + ?Sized,
+    H: Hasher,
+{
+    with_packed::<C, T, _>(value, |bytes| state.write(bytes));
+}
+
+/// Compare packed values using [`DefaultCfg`]
+pub fn packed_eq<T>(a: &T, b: &T) -> bool
+where
+    T: Pack + ?Sized,
+{
+    packed_eq_with::<DefaultCfg, T>(a, b)
+}
+
+/// Hash a packed value using [`DefaultCfg`]
+pub fn packed_hash<T, H>(value: &T, state: &mut H)
+where
+    T: Pack + ?Sized,
+    H: Hasher,
+{
+    packed_hash_with::<DefaultCfg, T, H>(value, state)
+}
+
+/// A value compared and hashed by the bits it packs to with `C`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByBits<T, C = DefaultCfg> {
+    pub value: T,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<T, C> ByBits<T, C> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, C> From<T> for ByBits<T, C> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Pack, C: Cfg> PartialEq for ByBits<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        packed_eq_with::<C, T>(&self.value, &other.value)
+    }
+}
+
+impl<T: Pack, C: Cfg> Eq for ByBits<T, C> {}
+
+impl<T: Pack, C: Cfg> Hash for ByBits<T, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        packed_hash_with::<C, T, H>(&self.value, state);
+    }
+}
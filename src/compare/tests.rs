@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash, RandomState};
+
+use super::{ByBits, PackedEq, packed_eq, packed_eq_with};
+use crate::cfg::Lsb0Le;
+use crate::{Packed, PackedSize};
+
+#[derive(Debug, Packed, PackedEq)]
+struct Sample {
+    id: u16,
+    #[packed(bits = 3)]
+    level: u8,
+    flags: [bool; 2],
+}
+
+#[test]
+fn eq() {
+    assert!(packed_eq(&(1u8, true), &(1u8, true)));
+    assert!(!packed_eq(&(1u8, true), &(1u8, false)));
+    assert!(packed_eq(&(), &()));
+    assert!(packed_eq_with::<Lsb0Le, _>(&[0u64; 20], &[0u64; 20]));
+    assert!(!packed_eq(&[0u64; 20], &[1u64; 20]));
+
+    let a = Sample {
+        id: 7,
+        level: 0b1101,
+        flags: [true, false],
+    };
+    let b = Sample { level: 0b0101, ..a };
+    assert_eq!(a, b);
+    assert_ne!(a, Sample { id: 8, ..b });
+}
+
+#[test]
+fn hash() {
+    let state = RandomState::new();
+    let a = ByBits::<i8>::new(-1);
+    assert_eq!(state.hash_one(a), state.hash_one(ByBits::<i8>::new(-1)));
+    assert_eq!(
+        state.hash_one(Sample {
+            id: 1,
+            level: 9,
+            flags: [false; 2],
+        }),
+        state.hash_one(Sample {
+            id: 1,
+            level: 1,
+            flags: [false; 2],
+        })
+    );
+
+    // a `u16` and a `(u8, u8)` which pack the same are equal and hash the
+    // same too, even though they're different types
+    let mut hasher = state.build_hasher();
+    ByBits::<u16>::new(0x1234).hash(&mut hasher);
+    let mut other = state.build_hasher();
+    ByBits::<(u8, u8)>::new((0x12, 0x34)).hash(&mut other);
+    assert_eq!(
+        std::hash::Hasher::finish(&hasher),
+        std::hash::Hasher::finish(&other)
+    );
+
+    let set: HashSet<ByBits<(u8, u8), Lsb0Le>> = [(1, 2), (1, 2), (2, 1)]
+        .into_iter()
+        .map(ByBits::from)
+        .collect();
+    assert_eq!(set.len(), 2);
+}
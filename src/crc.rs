@@ -0,0 +1,106 @@
+//! Cyclic redundancy checks over bit streams.
+//!
+//! A [`Crc`] is fed one bit at a time, in the order the bits are packed, so
+//! it can checksum fields which don't end on byte boundaries.  It can be
+//! attached to a [`BitWriter`](crate::cursor::BitWriter) to checksum
+//! everything written without a second pass over the buffer.
+//!
+//! ```rust
+//! # use packed::crc::Crc;
+//! # use packed::cursor::BitWriter;
+//! let mut buf = [0u8; 4];
+//! let mut writer = BitWriter::new(&mut buf);
+//! writer.attach_crc(Crc::CRC16_IBM_3740);
+//! writer.write(*b"12").unwrap();
+//! writer.finalize_crc::<u16>().unwrap();
+//!
+//! let mut crc = Crc::CRC16_IBM_3740;
+//! crc.update_bytes(b"12");
+//! assert_eq!(crc.value(), 0x3dba);
+//! assert_eq!(buf, [b'1', b'2', 0x3d, 0xba]);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::Unpack;
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::bit_len;
+
+/// A CRC in progress, processing bits most significant first without
+/// reflection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc {
+    width: u32,
+    poly: u64,
+    init: u64,
+    xor_out: u64,
+    state: u64,
+}
+
+impl Crc {
+    /// CRC-8/SMBUS
+    pub const CRC8_SMBUS: Self = Self::new(8, 0x07, 0, 0);
+    /// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE
+    pub const CRC16_IBM_3740: Self = Self::new(16, 0x1021, 0xffff, 0);
+    /// CRC-16/XMODEM
+    pub const CRC16_XMODEM: Self = Self::new(16, 0x1021, 0, 0);
+    /// CRC-32/BZIP2
+    pub const CRC32_BZIP2: Self = Self::new(32, 0x04c1_1db7, 0xffff_ffff, 0xffff_ffff);
+
+    /// A `width` bit CRC with the generator polynomial `poly`, starting from
+    /// `init` and XORed with `xor_out` at the end
+    ///
+    /// # Panics
+    ///
+    /// If `width` isn't between 1 and 64
+    pub const fn new(width: u32, poly: u64, init: u64, xor_out: u64) -> Self {
+        assert!(width >= 1 && width <= 64, "CRCs are 1 to 64 bits wide");
+        let mask = u64::MAX >> (64 - width);
+        Self {
+            width,
+            poly: poly & mask,
+            init: init & mask,
+            xor_out: xor_out & mask,
+            state: init & mask,
+        }
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Fold in the next bit
+    pub fn update(&mut self, bit: bool) {
+        let top = self.state >> (self.width - 1) & 1 != 0;
+        self.state <<= 1;
+        if top != bit {
+            self.state ^= self.poly;
+        }
+        self.state &= u64::MAX >> (64 - self.width);
+    }
+
+    /// Fold in the `len` bits at `offset`, in the order they are packed with
+    /// `C`
+    pub fn update_bits<C: Cfg>(&mut self, bytes: &[u8], offset: usize, len: usize) {
+        debug_assert!(bit_len(bytes) - offset >= len);
+        for i in offset..offset + len {
+            self.update(bool::unpack_with::<C>(bytes, i));
+        }
+    }
+
+    /// Fold in whole bytes, most significant bit first
+    pub fn update_bytes(&mut self, bytes: &[u8]) {
+        self.update_bits::<DefaultCfg>(bytes, 0, bit_len(bytes));
+    }
+
+    /// The CRC of the bits so far
+    pub fn value(&self) -> u64 {
+        self.state ^ self.xor_out
+    }
+
+    /// Start over as if no bits had been folded in
+    pub fn reset(&mut self) {
+        self.state = self.init;
+    }
+}
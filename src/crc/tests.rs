@@ -0,0 +1,97 @@
+use super::Crc;
+use crate::cfg::Lsb0Le;
+use crate::cursor::BitWriter;
+use crate::{Pack, PackError, Unpack};
+
+#[test]
+fn check_values() {
+    // the CRC of "123456789" listed for each algorithm in the CRC catalogue
+    for (crc, check) in [
+        (Crc::CRC8_SMBUS, 0xf4),
+        (Crc::CRC16_IBM_3740, 0x29b1),
+        (Crc::CRC16_XMODEM, 0x31c3),
+        (Crc::CRC32_BZIP2, 0xfc89_1918),
+    ] {
+        let mut crc = crc;
+        crc.update_bytes(b"123456789");
+        assert_eq!(crc.value(), check);
+        crc.reset();
+        crc.update_bytes(b"123456789");
+        assert_eq!(crc.value(), check);
+    }
+    let mut crc = Crc::new(64, 0x42f0_e1eb_a9ea_3693, 0, 0);
+    crc.update_bytes(b"123456789");
+    assert_eq!(crc.value(), 0x6c40_df5f_0b49_7347);
+}
+
+#[test]
+fn writer() {
+    let mut buf = [0u8; 5];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.write(true).unwrap();
+    writer.attach_crc(Crc::CRC8_SMBUS);
+    writer.write(0x1234u16).unwrap();
+    writer.skip(3).unwrap();
+
+    // undone writes are taken out of the CRC again
+    let checkpoint = writer.checkpoint();
+    writer.write(0xffu8).unwrap();
+    writer.rollback(checkpoint);
+
+    writer.finalize_crc::<u8>().unwrap();
+    assert!(writer.crc().is_none());
+    assert_eq!(writer.finish(), 28);
+
+    let mut crc = Crc::CRC8_SMBUS;
+    crc.update_bits::<crate::cfg::DefaultCfg>(&buf, 1, 19);
+    assert_eq!(u8::unpack(&buf, 20), crc.value() as u8);
+
+    // the CRC stays attached when there's no room for it
+    let mut buf = [0u8; 1];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.attach_crc(Crc::CRC16_XMODEM);
+    writer.write(0x31u8).unwrap();
+    assert_eq!(
+        writer.finalize_crc::<u16>(),
+        Err(PackError::OutOfSpace {
+            offset: 8,
+            needed: 16
+        })
+    );
+    assert!(writer.crc().is_some());
+}
+
+#[test]
+fn finalize_too_narrow() {
+    let mut buf = [0u8; 2];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.attach_crc(Crc::CRC16_XMODEM);
+    assert_eq!(
+        writer.finalize_crc::<u8>(),
+        Err(PackError::Overflow {
+            offset: 0,
+            bits: 16
+        })
+    );
+    assert!(writer.crc().is_some());
+    assert_eq!(writer.finalize_crc::<u16>(), Ok(()));
+}
+
+proptest::proptest! {
+    #[test]
+    fn writer_matches_buffer(values: (u8, bool, u32, [bool; 3]), lead in 0usize..16) {
+        let mut buf = [0u8; 10];
+        let mut writer = BitWriter::<Lsb0Le>::with_cfg(&mut buf);
+        writer.skip(lead).unwrap();
+        writer.attach_crc(Crc::CRC16_IBM_3740);
+        writer.write(values).unwrap();
+        writer.finalize_crc::<u16>().unwrap();
+
+        let mut crc = Crc::CRC16_IBM_3740;
+        crc.update_bits::<Lsb0Le>(&buf, lead, 44);
+        let mut expected = [0u8; 10];
+        values.pack_with::<Lsb0Le>(&mut expected, lead);
+        (crc.value() as u16).pack_with::<Lsb0Le>(&mut expected, lead + 44);
+        assert_eq!(buf, expected);
+    }
+}
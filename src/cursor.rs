@@ -0,0 +1,569 @@
+//! Cursors for writing and reading packed values one after another.
+//!
+//! ```rust
+//! # use packed::cursor::{BitReader, BitWriter};
+//! let mut buf = [0u8; 2];
+//! let mut writer = BitWriter::new(&mut buf);
+//! writer.write(true).unwrap();
+//! writer.write(0x5au8).unwrap();
+//! assert!(writer.write(0u8).is_err());
+//! assert_eq!(writer.finish(), 9);
+//!
+//! let mut reader = BitReader::new(&buf);
+//! assert_eq!(reader.read::<bool>(), Ok(true));
+//! assert_eq!(reader.read::<u8>(), Ok(0x5a));
+//! assert!(reader.read::<u8>().is_err());
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::crc::Crc;
+use crate::error::bit_len;
+use crate::scramble::{Plain, Scrambler};
+use crate::vectored;
+use crate::{Pack, PackError, Unpack, UnpackError, UnpackErrorKind};
+
+/// Packs values back to back into a buffer, optionally scrambling them
+/// with `S`
+#[derive(Debug)]
+pub struct BitWriter<'a, C = DefaultCfg, S = Plain> {
+    bytes: &'a mut [u8],
+    offset: usize,
+    crc: Option<Crc>,
+    scrambler: S,
+    _marker: PhantomData<C>,
+}
+
+impl<'a> BitWriter<'a> {
+    pub fn new(bytes: &'a mut [u8]) -> Self {
+        Self::with_cfg(bytes)
+    }
+}
+
+impl<'a, C> BitWriter<'a, C>
+where
+    C: Cfg,
+{
+    /// Writer packing values with the configuration `C`
+    pub fn with_cfg(bytes: &'a mut [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            crc: None,
+            scrambler: Plain,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Scramble every bit written from now on with `scrambler`.  Reserved
+    /// slots are scrambled as zeros, so they can't be filled afterwards.
+    pub fn with_scrambler<S>(self, scrambler: S) -> BitWriter<'a, C, S>
+    where
+        S: Scrambler,
+    {
+        BitWriter {
+            bytes: self.bytes,
+            offset: self.offset,
+            crc: self.crc,
+            scrambler,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, C, S> BitWriter<'a, C, S>
+where
+    C: Cfg,
+    S: Scrambler,
+{
+    /// Write `value` at the current position.  Nothing is written if it
+    /// doesn't fit.
+    pub fn write<T>(&mut self, value: T) -> Result<(), PackError>
+    where
+        T: Pack,
+    {
+        self.write_ref(&value)
+    }
+
+    /// Like [`BitWriter::write`], but doesn't take ownership of `value`
+    pub fn write_ref<T>(&mut self, value: &T) -> Result<(), PackError>
+    where
+        T: Pack,
+    {
+        self.check_space(T::SIZE)?;
+        value.pack_with::<C>(self.bytes, self.offset);
+        self.advance(T::SIZE);
+        Ok(())
+    }
+
+    /// Write every value of `values` without consuming them.  Stops at the
+    /// first value which doesn't fit, leaving the ones before it written.
+    pub fn write_all<'v, T>(
+        &mut self,
+        values: impl IntoIterator<Item = &'v T>,
+    ) -> Result<(), PackError>
+    where
+        T: Pack + 'v,
+    {
+        values.into_iter().try_for_each(|x| self.write_ref(x))
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), PackError> {
+        self.write(bit)
+    }
+
+    /// Leave room for a `T` to be written later with [`Slot::fill`], e.g. a
+    /// length or checksum that isn't known until the rest is written.  The
+    /// slot is zeroed until then.
+    pub fn reserve<T>(&mut self) -> Result<Slot<T>, PackError>
+    where
+        T: Pack,
+    {
+        let offset = self.offset;
+        self.skip(T::SIZE)?;
+        Ok(Slot {
+            offset,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Write `n` zero bits of padding.  Nothing is written if they don't
+    /// fit.
+    pub fn skip(&mut self, n: usize) -> Result<(), PackError> {
+        self.check_space(n)?;
+        for i in 0..n {
+            false.pack_with::<C>(self.bytes, self.offset + i);
+        }
+        self.advance(n);
+        Ok(())
+    }
+
+    /// Pad with zeros up to the next multiple of `n_bits`
+    ///
+    /// # Panics
+    ///
+    /// If `n_bits` is zero
+    pub fn align_to(&mut self, n_bits: usize) -> Result<(), PackError> {
+        self.skip(padding(self.offset, n_bits))
+    }
+
+    /// Pad with zeros up to the next byte boundary
+    pub fn pad_to_byte(&mut self) -> Result<(), PackError> {
+        self.align_to(8)
+    }
+
+    /// Fold every bit written from now on into `crc`, replacing any CRC
+    /// attached before.  Bits reserved with [`reserve`](Self::reserve) are
+    /// folded in as zeros, whatever they are filled with later.
+    pub fn attach_crc(&mut self, crc: Crc) {
+        self.crc = Some(crc);
+    }
+
+    /// The attached CRC, holding the bits written since it was attached
+    pub fn crc(&self) -> Option<&Crc> {
+        self.crc.as_ref()
+    }
+
+    /// Detach the CRC and write its value as a `T`.  The CRC stays attached
+    /// if there isn't room for it, or if it is wider than a `T`, which fails
+    /// with [`PackError::Overflow`].
+    ///
+    /// # Panics
+    ///
+    /// If no CRC is attached
+    pub fn finalize_crc<T>(&mut self) -> Result<(), PackError>
+    where
+        T: Pack + TryFrom<u64>,
+    {
+        let crc = self.crc.expect("no CRC is attached");
+        self.check_space(T::SIZE)?;
+        let value = match T::try_from(crc.value()) {
+            Ok(value) if crc.width() as usize <= T::SIZE => value,
+            _ => {
+                return Err(PackError::Overflow {
+                    offset: self.offset,
+                    bits: crc.width() as usize,
+                });
+            }
+        };
+        self.crc = None;
+        self.write_ref(&value)
+    }
+
+    /// Move past `n` bits just written, folding them into the CRC before
+    /// scrambling them
+    fn advance(&mut self, n: usize) {
+        if let Some(crc) = &mut self.crc {
+            crc.update_bits::<C>(self.bytes, self.offset, n);
+        }
+        if !S::IDENTITY {
+            for i in self.offset..self.offset + n {
+                let bit = bool::unpack_with::<C>(self.bytes, i);
+                self.scrambler.scramble(bit).pack_with::<C>(self.bytes, i);
+            }
+        }
+        self.offset += n;
+    }
+
+    fn check_space(&self, needed: usize) -> Result<(), PackError> {
+        if self.remaining_bits() < needed {
+            return Err(PackError::OutOfSpace {
+                offset: self.offset,
+                needed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Remember the current position and the contents of the buffer from
+    /// there on, so that later writes can be undone with
+    /// [`rollback`](Self::rollback)
+    pub fn checkpoint(&self) -> Checkpoint<S> {
+        Checkpoint {
+            offset: self.offset,
+            saved: self.bytes[self.offset / 8..].to_vec(),
+            crc: self.crc,
+            scrambler: self.scrambler.clone(),
+        }
+    }
+
+    /// Undo everything written since `checkpoint` was taken, restoring the
+    /// position, the buffer contents, the attached CRC and the state of the
+    /// scrambler
+    ///
+    /// # Panics
+    ///
+    /// If `checkpoint` doesn't match the length of this writer's buffer
+    pub fn rollback(&mut self, checkpoint: Checkpoint<S>) {
+        let start = checkpoint.offset / 8;
+        assert_eq!(
+            self.bytes.len() - start,
+            checkpoint.saved.len(),
+            "checkpoint from another writer"
+        );
+        self.bytes[start..].copy_from_slice(&checkpoint.saved);
+        self.offset = checkpoint.offset;
+        self.crc = checkpoint.crc;
+        self.scrambler = checkpoint.scrambler;
+    }
+
+    /// Bit offset of the next write
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        bit_len(self.bytes) - self.offset
+    }
+
+    /// The buffer being written to
+    pub fn bytes(&self) -> &[u8] {
+        self.bytes
+    }
+
+    /// Release the buffer, returning the amount of bits written
+    pub fn finish(self) -> usize {
+        self.offset
+    }
+}
+
+/// Room for a `T` reserved with [`BitWriter::reserve`]
+#[derive(Debug)]
+#[must_use = "a reserved slot stays zeroed unless it is filled"]
+pub struct Slot<T> {
+    offset: usize,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T> Slot<T>
+where
+    T: Pack,
+{
+    /// Bit offset of the slot
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Write `value` into the slot.  `writer` must be the writer the slot was
+    /// reserved in.
+    pub fn fill<C: Cfg>(self, writer: &mut BitWriter<'_, C>, value: T) {
+        debug_assert!(self.offset + T::SIZE <= bit_len(writer.bytes));
+        value.pack_with::<C>(writer.bytes, self.offset);
+    }
+}
+
+/// A saved [`BitWriter`] state, see [`BitWriter::checkpoint`]
+#[derive(Debug, Clone)]
+pub struct Checkpoint<S = Plain> {
+    offset: usize,
+    /// The buffer from the byte containing `offset` to its end
+    saved: Vec<u8>,
+    crc: Option<Crc>,
+    scrambler: S,
+}
+
+impl<S> Checkpoint<S> {
+    /// Bit offset the writer returns to on rollback
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Bits needed to get from `offset` to the next multiple of `n_bits`
+fn padding(offset: usize, n_bits: usize) -> usize {
+    assert!(n_bits > 0, "cannot align to 0 bits");
+    offset.next_multiple_of(n_bits) - offset
+}
+
+/// Unpacks values back to back from a buffer, optionally descrambling them
+/// with `S`
+#[derive(Debug, Clone)]
+pub struct BitReader<'a, C = DefaultCfg, S = Plain> {
+    bytes: &'a [u8],
+    offset: usize,
+    scrambler: S,
+    _marker: PhantomData<C>,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self::with_cfg(bytes)
+    }
+}
+
+impl<'a, C> BitReader<'a, C>
+where
+    C: Cfg,
+{
+    /// Reader unpacking values with the configuration `C`
+    pub fn with_cfg(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            scrambler: Plain,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Descramble every bit read from now on with `scrambler`
+    pub fn with_scrambler<S>(self, scrambler: S) -> BitReader<'a, C, S>
+    where
+        S: Scrambler,
+    {
+        BitReader {
+            bytes: self.bytes,
+            offset: self.offset,
+            scrambler,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, C, S> BitReader<'a, C, S>
+where
+    C: Cfg,
+    S: Scrambler,
+{
+    /// Read a value at the current position.  The position is left
+    /// unchanged if there aren't enough bits left.
+    pub fn read<T>(&mut self) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        self.check_remaining(T::SIZE)?;
+        let mut scrambler = self.scrambler.clone();
+        let value = self.unpack(&mut scrambler);
+        self.scrambler = scrambler;
+        self.offset += T::SIZE;
+        Ok(value)
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool, UnpackError> {
+        self.read()
+    }
+
+    /// Read a value at the current position without moving past it
+    pub fn peek<T>(&self) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        self.check_remaining(T::SIZE)?;
+        Ok(self.unpack(&mut self.scrambler.clone()))
+    }
+
+    /// Unpack the value at the current position, descrambling it with
+    /// `scrambler`
+    fn unpack<T>(&self, scrambler: &mut S) -> T
+    where
+        T: Unpack,
+    {
+        if S::IDENTITY {
+            return T::unpack_with::<C>(self.bytes, self.offset);
+        }
+        let mut plain = vec![0u8; T::SIZE.div_ceil(8)];
+        for i in 0..T::SIZE {
+            let bit = bool::unpack_with::<C>(self.bytes, self.offset + i);
+            scrambler.descramble(bit).pack_with::<C>(&mut plain, i);
+        }
+        T::unpack_with::<C>(&plain, 0)
+    }
+
+    /// Move to the bit offset `bit_pos`, which may be anywhere up to the end
+    /// of the buffer.  The position is left unchanged if it is past the end.
+    /// The scrambler is left as it is, so it must be in the state for the
+    /// new position.
+    pub fn seek(&mut self, bit_pos: usize) -> Result<(), UnpackError> {
+        if bit_pos > bit_len(self.bytes) {
+            return Err(UnpackErrorKind::UnexpectedEnd {
+                offset: bit_len(self.bytes),
+            }
+            .into());
+        }
+        self.offset = bit_pos;
+        Ok(())
+    }
+
+    /// Skip over `n` bits.  The position is left unchanged if there aren't
+    /// enough bits left.
+    pub fn skip(&mut self, n: usize) -> Result<(), UnpackError> {
+        self.check_remaining(n)?;
+        if !S::IDENTITY {
+            for i in self.offset..self.offset + n {
+                self.scrambler
+                    .descramble(bool::unpack_with::<C>(self.bytes, i));
+            }
+        }
+        self.offset += n;
+        Ok(())
+    }
+
+    /// Skip up to the next multiple of `n_bits`
+    ///
+    /// # Panics
+    ///
+    /// If `n_bits` is zero
+    pub fn align_to(&mut self, n_bits: usize) -> Result<(), UnpackError> {
+        self.skip(padding(self.offset, n_bits))
+    }
+
+    /// Skip up to the next byte boundary
+    pub fn pad_to_byte(&mut self) -> Result<(), UnpackError> {
+        self.align_to(8)
+    }
+
+    fn check_remaining(&self, needed: usize) -> Result<(), UnpackError> {
+        if self.remaining_bits() < needed {
+            return Err(UnpackErrorKind::UnexpectedEnd {
+                offset: bit_len(self.bytes),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Bit offset of the next read
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        bit_len(self.bytes) - self.offset
+    }
+
+    /// The buffer being read from
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+/// Unpacks values back to back from a list of buffers, as if they were
+/// joined together.  Values may straddle the buffers, and are unpacked as by
+/// [`vectored::unpack_vectored_with`].
+///
+/// ```rust
+/// # use packed::cursor::VectoredReader;
+/// # use std::io::IoSlice;
+/// let bufs = [IoSlice::new(&[0x12, 0x3]), IoSlice::new(&[0x45])];
+/// let mut reader = VectoredReader::new(&bufs);
+/// assert_eq!(reader.read::<u8>(), Ok(0x12));
+/// assert_eq!(reader.read::<u16>(), Ok(0x0345));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VectoredReader<'a, S, C = DefaultCfg> {
+    bufs: &'a [S],
+    offset: usize,
+    len: usize,
+    _marker: PhantomData<C>,
+}
+
+impl<'a, S> VectoredReader<'a, S>
+where
+    S: Deref<Target = [u8]>,
+{
+    pub fn new(bufs: &'a [S]) -> Self {
+        Self::with_cfg(bufs)
+    }
+}
+
+impl<'a, S, C> VectoredReader<'a, S, C>
+where
+    S: Deref<Target = [u8]>,
+    C: Cfg,
+{
+    /// Reader unpacking values with the configuration `C`
+    pub fn with_cfg(bufs: &'a [S]) -> Self {
+        Self {
+            bufs,
+            offset: 0,
+            len: bufs
+                .iter()
+                .map(|b| bit_len(b))
+                .fold(0, usize::saturating_add),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Read a value at the current position.  The position is left
+    /// unchanged if there aren't enough bits left.
+    pub fn read<T>(&mut self) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        let value = self.peek()?;
+        self.offset += T::SIZE;
+        Ok(value)
+    }
+
+    /// Read a value at the current position without moving past it
+    pub fn peek<T>(&self) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        vectored::unpack_vectored_with::<C, T, S>(self.bufs, self.offset)
+    }
+
+    /// Skip over `n` bits.  The position is left unchanged if there aren't
+    /// enough bits left.
+    pub fn skip(&mut self, n: usize) -> Result<(), UnpackError> {
+        if self.remaining_bits() < n {
+            return Err(UnpackErrorKind::UnexpectedEnd { offset: self.len }.into());
+        }
+        self.offset += n;
+        Ok(())
+    }
+
+    /// Bit offset of the next read, counted from the start of the first
+    /// buffer
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        self.len - self.offset
+    }
+}
@@ -0,0 +1,203 @@
+use super::{BitReader, BitWriter, VectoredReader};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, PackError, PackedSize, UnpackErrorKind};
+
+#[test]
+fn out_of_space_leaves_buffer() {
+    let mut buf = [0u8; 3];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.write(0xffffu16).unwrap();
+    assert_eq!(
+        writer.write(0xffffu16),
+        Err(PackError::OutOfSpace {
+            offset: 16,
+            needed: 16
+        })
+    );
+    assert_eq!(writer.position(), 16);
+    assert_eq!(writer.remaining_bits(), 8);
+    assert_eq!(buf, [0xff, 0xff, 0x00]);
+}
+
+#[test]
+fn write_all_borrows() {
+    let values = vec![[1u8, 2], [3, 4], [5, 6]];
+    let mut buf = [0u8; 5];
+    let mut writer = BitWriter::new(&mut buf);
+    assert!(writer.write_all(&values).is_err());
+    assert_eq!(writer.position(), 32);
+    assert_eq!(buf, [1, 2, 3, 4, 0]);
+    assert_eq!(values.len(), 3);
+}
+
+#[test]
+fn unexpected_end() {
+    let mut reader = BitReader::new(&[0u8; 2]);
+    reader.read::<[bool; 9]>().unwrap();
+    assert_eq!(
+        reader.read::<u8>(),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+    assert_eq!(reader.position(), 9);
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_pack(values: (u8, bool, i32, [bool; 3], u64)) {
+        let mut expected = [0u8; 15];
+        values.pack_with::<Lsb0Le>(&mut expected, 0);
+
+        let mut buf = [0u8; 15];
+        let mut writer = BitWriter::<Lsb0Le>::with_cfg(&mut buf);
+        writer.write(values.0).unwrap();
+        writer.write(values.1).unwrap();
+        writer.write(values.2).unwrap();
+        writer.write(values.3).unwrap();
+        writer.write(values.4).unwrap();
+        assert_eq!(writer.finish(), <(u8, bool, i32, [bool; 3], u64)>::SIZE);
+        assert_eq!(buf, expected);
+
+        let mut reader = BitReader::<Lsb0Le>::with_cfg(&buf);
+        assert_eq!(reader.read(), Ok(values.0));
+        assert_eq!(reader.read(), Ok(values.1));
+        assert_eq!(reader.read(), Ok(values.2));
+        assert_eq!(reader.read(), Ok(values.3));
+        assert_eq!(reader.read(), Ok(values.4));
+    }
+}
+
+#[test]
+fn align_and_skip() {
+    let mut buf = [0xffu8; 6];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.write(true).unwrap();
+    writer.pad_to_byte().unwrap();
+    assert_eq!(writer.position(), 8);
+    writer.pad_to_byte().unwrap();
+    writer.write(0x5u8).unwrap();
+    writer.skip(3).unwrap();
+    writer.align_to(16).unwrap();
+    assert_eq!(writer.position(), 32);
+    assert_eq!(
+        writer.align_to(48 + 16),
+        Err(PackError::OutOfSpace {
+            offset: 32,
+            needed: 32
+        })
+    );
+    assert_eq!(writer.finish(), 32);
+    assert_eq!(buf, [0x80, 0x05, 0x00, 0x00, 0xff, 0xff]);
+
+    let mut reader = BitReader::new(&buf);
+    assert_eq!(reader.read(), Ok(true));
+    reader.pad_to_byte().unwrap();
+    assert_eq!(reader.read(), Ok(0x5u8));
+    reader.align_to(32).unwrap();
+    assert_eq!(reader.read(), Ok(0xffffu16));
+    reader.pad_to_byte().unwrap();
+    assert_eq!(
+        reader.skip(1),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 48 }.into())
+    );
+}
+
+#[test]
+fn peek_and_seek() {
+    let buf = [0xa5, 0x3c];
+    let mut reader = BitReader::new(&buf);
+    assert_eq!(reader.peek::<[bool; 2]>(), Ok([true, false]));
+    assert_eq!(reader.position(), 0);
+    assert_eq!(reader.read(), Ok(0xa5u8));
+    assert_eq!(
+        reader.peek::<u16>(),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+
+    // rewind after a failed parse
+    let start = reader.position();
+    reader.read::<[bool; 4]>().unwrap();
+    assert!(reader.read::<u8>().is_err());
+    reader.seek(start).unwrap();
+    assert_eq!(reader.read(), Ok(0x3cu8));
+    assert_eq!(reader.remaining_bits(), 0);
+
+    reader.seek(16).unwrap();
+    assert_eq!(
+        reader.seek(17),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+    assert_eq!(reader.position(), 16);
+}
+
+#[test]
+fn rollback_restores_buffer() {
+    let mut buf = [0xaau8; 4];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.write([true; 3]).unwrap();
+    let checkpoint = writer.checkpoint();
+    assert_eq!(checkpoint.position(), 3);
+    writer.write(0u16).unwrap();
+    writer.write(0u8).unwrap();
+    assert!(writer.write(0u16).is_err());
+    writer.rollback(checkpoint);
+    assert_eq!(writer.position(), 3);
+    assert_eq!(writer.bytes(), [0b1110_1010, 0xaa, 0xaa, 0xaa]);
+    writer.write(false).unwrap();
+    assert_eq!(writer.finish(), 4);
+    assert_eq!(buf, [0b1110_1010, 0xaa, 0xaa, 0xaa]);
+}
+
+#[test]
+fn reserve_and_fill() {
+    let mut buf = [0xffu8; 6];
+    let mut writer = BitWriter::new(&mut buf);
+    writer.write([true; 4]).unwrap();
+    let len = writer.reserve::<u16>().unwrap();
+    assert_eq!(len.position(), 4);
+    assert_eq!(writer.bytes()[..3], [0xf0, 0x00, 0x0f]);
+    writer.write(0xabcdu16).unwrap();
+    let body = writer.position() - 20;
+    len.fill(&mut writer, body as u16);
+    assert!(writer.reserve::<u16>().is_err());
+    assert_eq!(buf, [0xf0, 0x01, 0x0a, 0xbc, 0xdf, 0xff]);
+}
+
+#[test]
+fn vectored_unexpected_end() {
+    let bufs: [&[u8]; 3] = [&[0xff], &[], &[0x00]];
+    let mut reader = VectoredReader::new(&bufs);
+    reader.skip(4).unwrap();
+    assert_eq!(reader.read::<u8>(), Ok(0xf0));
+    assert_eq!(
+        reader.read::<u8>(),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+    assert_eq!(reader.position(), 12);
+    assert_eq!(reader.read::<()>(), Ok(()));
+}
+
+proptest::proptest! {
+    #[test]
+    fn vectored_matches_contiguous(
+        values: (u8, bool, i32, [bool; 3], u64),
+        splits in proptest::collection::vec(0usize..=15, 0..5),
+    ) {
+        let mut buf = [0u8; 15];
+        values.pack_with::<Lsb0Le>(&mut buf, 0);
+
+        let mut splits = splits;
+        splits.push(0);
+        splits.push(buf.len());
+        splits.sort();
+        let bufs = splits.windows(2).map(|w| buf[w[0]..w[1]].to_vec()).collect::<Vec<_>>();
+
+        let mut reader = VectoredReader::<_, Lsb0Le>::with_cfg(&bufs);
+        assert_eq!(reader.read(), Ok(values.0));
+        assert_eq!(reader.read(), Ok(values.1));
+        assert_eq!(reader.read(), Ok(values.2));
+        assert_eq!(reader.peek(), Ok(values.3));
+        assert_eq!(reader.read(), Ok(values.3));
+        assert_eq!(reader.read(), Ok(values.4));
+        assert_eq!(reader.remaining_bits(), 120 - <(u8, bool, i32, [bool; 3], u64)>::SIZE);
+    }
+}
@@ -0,0 +1,329 @@
+//! CAN signals described by DBC files.
+//!
+//! A DBC file lists the messages on a CAN bus, and for each the signals it
+//! carries: where their bits are, in which byte order, and how the raw
+//! integer scales to a physical value.  [`Database::parse`] reads the
+//! messages (`BO_`) and signals (`SG_`) of a file, ignoring everything
+//! else, so frames can be decoded and encoded by signal name.
+//!
+//! Only available with the `dbc` feature.
+//!
+//! ```rust
+//! # use packed::dbc::{Database, DbcError};
+//! # fn main() -> Result<(), DbcError> {
+//! let db = Database::parse(
+//!     r#"
+//! BO_ 2364540158 EEC1: 8 Engine
+//!  SG_ EngineSpeed : 24|16@1+ (0.125,0) [0|8031.875] "rpm" Vector__XXX
+//!  SG_ DriverDemand : 8|8@1+ (1,-125) [-125|125] "%" Vector__XXX
+//! "#,
+//! )?;
+//! let eec1 = db.message_by_name("EEC1").unwrap();
+//! assert_eq!(eec1.can_id(), 0x0cf0_04fe);
+//!
+//! let mut frame = [0u8; 8];
+//! eec1.signal("EngineSpeed").unwrap().encode(1500.0, &mut frame);
+//! assert_eq!(frame[3..5], [0xe0, 0x2e]);
+//! let decoded = eec1.decode(&frame)?;
+//! assert_eq!(decoded[0], ("EngineSpeed", 1500.0));
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::UnpackError;
+use crate::cfg::{ByteOrder, Lsb0Le, Msb0Be};
+use crate::error::check_len;
+use crate::narrow::{read_bits, write_bits};
+
+/// Error returned when parsing a DBC file fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbcError {
+    /// Line `line` (counted from 1) couldn't be parsed
+    Syntax { line: usize, message: &'static str },
+    /// A frame couldn't be decoded
+    Unpack(UnpackError),
+}
+
+impl fmt::Display for DbcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            Self::Unpack(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DbcError {}
+
+impl From<UnpackError> for DbcError {
+    fn from(e: UnpackError) -> Self {
+        Self::Unpack(e)
+    }
+}
+
+/// The messages of a DBC file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Database {
+    pub messages: Vec<Message>,
+}
+
+/// A CAN frame and the signals it carries
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    /// The identifier as written in the file, with bit 31 set for extended
+    /// identifiers
+    pub id: u32,
+    pub name: String,
+    /// Length of the frame in bytes
+    pub len: usize,
+    pub signals: Vec<Signal>,
+}
+
+/// How a signal takes part in multiplexing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplex {
+    /// The signal selects which multiplexed signals are present
+    Multiplexor,
+    /// The signal is present when the multiplexor holds this value
+    Multiplexed(u64),
+}
+
+/// A value in a CAN frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub name: String,
+    /// Bit the signal starts at as numbered by DBC files: the least
+    /// significant bit for little-endian (Intel) signals, and the most
+    /// significant for big-endian (Motorola) ones
+    pub start: usize,
+    /// Amount of bits, at most 64
+    pub len: usize,
+    pub byte_order: ByteOrder,
+    pub signed: bool,
+    pub factor: f64,
+    pub offset: f64,
+    pub min: f64,
+    pub max: f64,
+    pub unit: String,
+    pub multiplex: Option<Multiplex>,
+}
+
+impl Database {
+    /// Read the messages and signals of a DBC file
+    pub fn parse(src: &str) -> Result<Self, DbcError> {
+        let mut out = Self::default();
+        for (i, line) in src.lines().enumerate() {
+            let syntax = |message| DbcError::Syntax {
+                line: i + 1,
+                message,
+            };
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("BO_ ") {
+                out.messages
+                    .push(parse_message(rest).ok_or(syntax("invalid message"))?);
+            } else if let Some(rest) = line.strip_prefix("SG_ ") {
+                let message = out
+                    .messages
+                    .last_mut()
+                    .ok_or(syntax("signal outside of a message"))?;
+                let signal = parse_signal(rest).ok_or(syntax("invalid signal"))?;
+                message.signals.push(signal);
+            }
+        }
+        Ok(out)
+    }
+
+    /// The message with the CAN identifier `id`
+    pub fn message(&self, id: u32) -> Option<&Message> {
+        self.messages.iter().find(|m| m.can_id() == id)
+    }
+
+    pub fn message_by_name(&self, name: &str) -> Option<&Message> {
+        self.messages.iter().find(|m| m.name == name)
+    }
+}
+
+/// `<id> <name>: <len> <sender>`
+fn parse_message(rest: &str) -> Option<Message> {
+    let (id, rest) = rest.split_once(' ')?;
+    let (name, rest) = rest.split_once(':')?;
+    let len = rest.split_whitespace().next()?;
+    Some(Message {
+        id: id.parse().ok()?,
+        name: name.trim().to_owned(),
+        len: len.parse().ok()?,
+        signals: Vec::new(),
+    })
+}
+
+/// `<name> [M|m<n>] : <start>|<len>@<order><sign> (<factor>,<offset>)
+/// [<min>|<max>] "<unit>" <receivers>`
+///
+/// Extended multiplexing isn't supported, so the `m<n>M` of a signal which
+/// is both multiplexed and a multiplexor is read as `m<n>`.
+fn parse_signal(rest: &str) -> Option<Signal> {
+    let (head, rest) = rest.split_once(':')?;
+    let mut head = head.split_whitespace();
+    let name = head.next()?.to_owned();
+    let multiplex = match head.next() {
+        None => None,
+        Some("M") => Some(Multiplex::Multiplexor),
+        Some(m) => {
+            let m = m.strip_prefix('m')?;
+            let m = m.strip_suffix('M').unwrap_or(m);
+            Some(Multiplex::Multiplexed(m.parse().ok()?))
+        }
+    };
+    if head.next().is_some() {
+        return None;
+    }
+
+    let (bits, rest) = rest.trim_start().split_once(' ')?;
+    let (start, bits) = bits.split_once('|')?;
+    let (len, kind) = bits.split_once('@')?;
+    let byte_order = match kind.get(..1)? {
+        "1" => ByteOrder::Little,
+        "0" => ByteOrder::Big,
+        _ => return None,
+    };
+    let signed = match kind.get(1..)? {
+        "+" => false,
+        "-" => true,
+        _ => return None,
+    };
+
+    let (scale, rest) = rest.trim_start().strip_prefix('(')?.split_once(')')?;
+    let (factor, offset) = scale.split_once(',')?;
+    let (range, rest) = rest.trim_start().strip_prefix('[')?.split_once(']')?;
+    let (min, max) = range.split_once('|')?;
+    let (unit, _receivers) = rest.trim_start().strip_prefix('"')?.split_once('"')?;
+
+    let len = len.parse().ok()?;
+    if !(1..=64).contains(&len) {
+        return None;
+    }
+    Some(Signal {
+        name,
+        start: start.parse().ok()?,
+        len,
+        byte_order,
+        signed,
+        factor: factor.trim().parse().ok()?,
+        offset: offset.trim().parse().ok()?,
+        min: min.trim().parse().ok()?,
+        max: max.trim().parse().ok()?,
+        unit: unit.to_owned(),
+        multiplex,
+    })
+}
+
+impl Message {
+    /// The CAN identifier, without the flag marking extended identifiers
+    pub fn can_id(&self) -> u32 {
+        self.id & 0x1fff_ffff
+    }
+
+    pub fn is_extended(&self) -> bool {
+        self.id & 0x8000_0000 != 0
+    }
+
+    pub fn signal(&self, name: &str) -> Option<&Signal> {
+        self.signals.iter().find(|s| s.name == name)
+    }
+
+    /// The physical values of the signals present in `data`, skipping
+    /// multiplexed signals not selected by the multiplexor
+    pub fn decode<'a>(&'a self, data: &[u8]) -> Result<Vec<(&'a str, f64)>, UnpackError> {
+        let selector = match self
+            .signals
+            .iter()
+            .find(|s| s.multiplex == Some(Multiplex::Multiplexor))
+        {
+            Some(s) => Some(s.raw(data)?),
+            None => None,
+        };
+        let mut out = Vec::with_capacity(self.signals.len());
+        for s in &self.signals {
+            if let Some(Multiplex::Multiplexed(value)) = s.multiplex
+                && selector != Some(value)
+            {
+                continue;
+            }
+            out.push((s.name.as_str(), s.decode(data)?));
+        }
+        Ok(out)
+    }
+}
+
+impl Signal {
+    /// Offset of the signal's most significant bit when numbered like
+    /// [`Msb0Be`], or of its least significant bit when numbered like
+    /// [`Lsb0Le`]
+    fn bit_offset(&self) -> usize {
+        match self.byte_order {
+            ByteOrder::Little => self.start,
+            // DBC numbers bits from the least significant bit of each byte
+            ByteOrder::Big => self.start / 8 * 8 + 7 - self.start % 8,
+        }
+    }
+
+    /// The raw bits of the signal, before sign extension and scaling
+    pub fn raw(&self, data: &[u8]) -> Result<u64, UnpackError> {
+        let offset = self.bit_offset();
+        check_len(data, offset, self.len)?;
+        let raw = match self.byte_order {
+            ByteOrder::Little => read_bits::<Lsb0Le>(self.len, data, offset),
+            ByteOrder::Big => read_bits::<Msb0Be>(self.len, data, offset),
+        };
+        Ok(raw as u64)
+    }
+
+    /// The physical value of the signal
+    pub fn decode(&self, data: &[u8]) -> Result<f64, UnpackError> {
+        let raw = self.raw(data)?;
+        let raw = if self.signed {
+            // move the sign bit to the top and shift back arithmetically
+            ((raw << (64 - self.len)) as i64 >> (64 - self.len)) as f64
+        } else {
+            raw as f64
+        };
+        Ok(raw * self.factor + self.offset)
+    }
+
+    /// Write the raw bits of the signal, dropping any above its width
+    ///
+    /// # Panics
+    ///
+    /// If `data` ends before the signal does
+    pub fn encode_raw(&self, raw: u64, data: &mut [u8]) {
+        let offset = self.bit_offset();
+        assert!(data.len() * 8 >= offset + self.len, "frame is too short");
+        let raw = u128::from(raw) & ((1 << self.len) - 1);
+        match self.byte_order {
+            ByteOrder::Little => write_bits::<Lsb0Le>(raw, self.len, data, offset),
+            ByteOrder::Big => write_bits::<Msb0Be>(raw, self.len, data, offset),
+        }
+    }
+
+    /// Write the nearest raw value to `value`, saturating at the range of
+    /// the signal's bits
+    ///
+    /// # Panics
+    ///
+    /// If `data` ends before the signal does
+    pub fn encode(&self, value: f64, data: &mut [u8]) {
+        let raw = ((value - self.offset) / self.factor).round();
+        let raw = if self.signed {
+            let half = 1i128 << (self.len - 1);
+            raw.clamp(-half as f64, (half - 1) as f64) as i64 as u64
+        } else {
+            raw.clamp(0.0, (u64::MAX >> (64 - self.len)) as f64) as u64
+        };
+        self.encode_raw(raw, data);
+    }
+}
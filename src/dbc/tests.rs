@@ -0,0 +1,206 @@
+use super::{Database, DbcError, Multiplex};
+use crate::UnpackErrorKind;
+use crate::cfg::ByteOrder;
+
+const DBC: &str = r#"VERSION ""
+
+NS_ :
+    CM_
+
+BU_: Engine Dash
+
+BO_ 100 Intel: 4 Engine
+ SG_ Nibbles : 4|12@1+ (1,0) [0|4095] "" Dash
+ SG_ Small : 0|4@1- (0.5,1) [-3|4.5] "V" Dash
+ SG_ Wide : 16|16@1+ (1,0) [0|65535] "" Dash
+
+BO_ 200 Motorola: 4 Engine
+ SG_ Nibbles : 3|12@0+ (1,0) [0|4095] "" Dash
+ SG_ Speed : 23|16@0+ (0.01,0) [0|655.35] "km/h" Dash
+
+BO_ 300 Muxed: 2 Engine
+ SG_ Page M : 0|8@1+ (1,0) [0|255] "" Dash
+ SG_ Temp m0 : 8|8@1- (1,0) [-128|127] "degC" Dash
+ SG_ Pressure m1 : 8|8@1+ (2,0) [0|510] "kPa" Dash
+
+CM_ SG_ 100 Small "a comment, which is ignored";
+"#;
+
+#[test]
+fn parses() {
+    let db = Database::parse(DBC).unwrap();
+    assert_eq!(db.messages.len(), 3);
+    let intel = db.message(100).unwrap();
+    assert_eq!(intel.name, "Intel");
+    assert_eq!(intel.len, 4);
+    assert!(!intel.is_extended());
+    let small = intel.signal("Small").unwrap();
+    assert_eq!(small.start, 0);
+    assert_eq!(small.len, 4);
+    assert_eq!(small.byte_order, ByteOrder::Little);
+    assert!(small.signed);
+    assert_eq!((small.factor, small.offset), (0.5, 1.0));
+    assert_eq!((small.min, small.max), (-3.0, 4.5));
+    assert_eq!(small.unit, "V");
+    assert_eq!(small.multiplex, None);
+
+    let muxed = db.message_by_name("Muxed").unwrap();
+    assert_eq!(muxed.signals[0].multiplex, Some(Multiplex::Multiplexor));
+    assert_eq!(muxed.signals[2].multiplex, Some(Multiplex::Multiplexed(1)));
+    assert_eq!(
+        db.message(300).unwrap().signal("Speed"),
+        None,
+        "signals belong to the message above them"
+    );
+}
+
+#[test]
+fn extended_ids() {
+    let db = Database::parse("BO_ 2566844926 Ext: 8 X").unwrap();
+    let message = &db.messages[0];
+    assert!(message.is_extended());
+    assert_eq!(message.can_id(), 0x18fe_f1fe);
+    assert_eq!(db.message(0x18fe_f1fe), Some(message));
+}
+
+#[test]
+fn little_endian() {
+    let db = Database::parse(DBC).unwrap();
+    let intel = db.message(100).unwrap();
+    let mut frame = [0u8; 4];
+    intel
+        .signal("Nibbles")
+        .unwrap()
+        .encode_raw(0xabc, &mut frame);
+    assert_eq!(frame, [0xc0, 0xab, 0, 0]);
+    intel.signal("Small").unwrap().encode(0.0, &mut frame);
+    assert_eq!(frame, [0xce, 0xab, 0, 0]);
+    intel
+        .signal("Wide")
+        .unwrap()
+        .encode(0x1234 as f64, &mut frame);
+    assert_eq!(frame, [0xce, 0xab, 0x34, 0x12]);
+    assert_eq!(
+        intel.decode(&frame),
+        Ok(vec![("Nibbles", 2748.0), ("Small", 0.0), ("Wide", 4660.0)])
+    );
+}
+
+#[test]
+fn big_endian() {
+    let db = Database::parse(DBC).unwrap();
+    let motorola = db.message(200).unwrap();
+    let mut frame = [0u8; 4];
+    motorola
+        .signal("Nibbles")
+        .unwrap()
+        .encode_raw(0xabc, &mut frame);
+    assert_eq!(frame, [0x0a, 0xbc, 0, 0]);
+    motorola.signal("Speed").unwrap().encode(46.6, &mut frame);
+    assert_eq!(frame, [0x0a, 0xbc, 0x12, 0x34]);
+    let decoded = motorola.decode(&frame).unwrap();
+    assert_eq!(decoded[0], ("Nibbles", 2748.0));
+    assert_eq!(decoded[1].0, "Speed");
+    assert!((decoded[1].1 - 46.6).abs() < 1e-9);
+}
+
+#[test]
+fn saturates() {
+    let db = Database::parse(DBC).unwrap();
+    let intel = db.message(100).unwrap();
+    let small = intel.signal("Small").unwrap();
+    let mut frame = [0u8; 4];
+    small.encode(100.0, &mut frame);
+    assert_eq!(small.decode(&frame), Ok(4.5));
+    small.encode(-100.0, &mut frame);
+    assert_eq!(small.raw(&frame), Ok(0b1000));
+    assert_eq!(small.decode(&frame), Ok(-3.0));
+    let wide = intel.signal("Wide").unwrap();
+    wide.encode(-1.0, &mut frame);
+    assert_eq!(wide.raw(&frame), Ok(0));
+}
+
+#[test]
+fn one_bit_signed() {
+    let db = Database::parse("BO_ 1 M: 1 X\n SG_ Bit : 0|1@1- (1,0) [-1|0] \"\" X").unwrap();
+    let bit = db.messages[0].signal("Bit").unwrap();
+    let mut frame = [0u8; 1];
+    bit.encode(-1.0, &mut frame);
+    assert_eq!(frame, [0x01]);
+    assert_eq!(bit.decode(&frame), Ok(-1.0));
+    bit.encode(5.0, &mut frame);
+    assert_eq!(bit.decode(&frame), Ok(0.0));
+    bit.encode(-5.0, &mut frame);
+    assert_eq!(bit.decode(&frame), Ok(-1.0));
+}
+
+#[test]
+fn extended_multiplexing() {
+    let db = Database::parse(
+        "BO_ 1 M: 2 X
+ SG_ Page M : 0|4@1+ (1,0) [0|15] \"\" X
+ SG_ SubPage m0M : 4|4@1+ (1,0) [0|15] \"\" X
+ SG_ Value m1 : 8|8@1+ (1,0) [0|255] \"\" X",
+    )
+    .unwrap();
+    let message = &db.messages[0];
+    assert_eq!(
+        message.signals[1].multiplex,
+        Some(Multiplex::Multiplexed(0))
+    );
+    assert_eq!(
+        message.decode(&[0x20, 7]),
+        Ok(vec![("Page", 0.0), ("SubPage", 2.0)])
+    );
+}
+
+#[test]
+fn multiplexed() {
+    let db = Database::parse(DBC).unwrap();
+    let muxed = db.message(300).unwrap();
+    assert_eq!(
+        muxed.decode(&[0, 0xfe]),
+        Ok(vec![("Page", 0.0), ("Temp", -2.0)])
+    );
+    assert_eq!(
+        muxed.decode(&[1, 0xfe]),
+        Ok(vec![("Page", 1.0), ("Pressure", 508.0)])
+    );
+    assert_eq!(muxed.decode(&[2, 0xfe]), Ok(vec![("Page", 2.0)]));
+}
+
+#[test]
+fn short_frame() {
+    let db = Database::parse(DBC).unwrap();
+    assert_eq!(
+        db.message(100).unwrap().decode(&[0; 3]),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 24 }.into())
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        Database::parse("\n SG_ A : 0|8@1+ (1,0) [0|1] \"\" X"),
+        Err(DbcError::Syntax {
+            line: 2,
+            message: "signal outside of a message"
+        })
+    );
+    for signal in [
+        "SG_ A : 0|8@2+ (1,0) [0|1] \"\" X",
+        "SG_ A : 0|8@1+ (1,0 [0|1] \"\" X",
+        "SG_ A : 0|65@1+ (1,0) [0|1] \"\" X",
+        "SG_ A x : 0|8@1+ (1,0) [0|1] \"\" X",
+    ] {
+        let err = Database::parse(&format!("BO_ 1 M: 8 X\n{signal}")).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid signal");
+    }
+    assert_eq!(
+        Database::parse("BO_ one M: 8 X"),
+        Err(DbcError::Syntax {
+            line: 1,
+            message: "invalid message"
+        })
+    );
+}
@@ -0,0 +1,76 @@
+//! `Debug` output showing the packed bits of each field.
+//!
+//! Deriving [`PackedDebug`] instead of `Debug` formats a struct like the
+//! usual derive, but follows each field with the range of bits it is packed
+//! into and those bits as packed with
+//! [`DefaultCfg`](crate::cfg::DefaultCfg), which shows exactly what goes on
+//! the wire while bringing up a protocol.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::debug::PackedDebug;
+//! #[derive(Packed, PackedDebug)]
+//! struct Header {
+//!     flag: bool,
+//!     #[packed(bits = 3)]
+//!     kind: u8,
+//!     #[packed(bits = 4)]
+//!     delta: i8,
+//! }
+//!
+//! let header = Header { flag: true, kind: 5, delta: -3 };
+//! assert_eq!(
+//!     format!("{header:?}"),
+//!     "Header { flag: true @ 0..1 = 0b1, kind: 5 @ 1..4 = 0b101, delta: -3 @ 4..8 = 0b1101 }",
+//! );
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::display;
+use crate::error::bit_len;
+
+#[cfg(feature = "derive")]
+pub use packed_derive::PackedDebug;
+
+/// A value shown along with the `len` bits at `offset` in `bytes` it was
+/// packed into
+pub struct FieldBits<'a, T: ?Sized> {
+    value: &'a T,
+    bytes: &'a [u8],
+    offset: usize,
+    len: usize,
+}
+
+impl<'a, T: ?Sized> FieldBits<'a, T> {
+    pub fn new(value: &'a T, bytes: &'a [u8], offset: usize, len: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= len);
+        Self {
+            value,
+            bytes,
+            offset,
+            len,
+        }
+    }
+}
+
+impl<T> fmt::Debug for FieldBits<'_, T>
+where
+    T: fmt::Debug + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)?;
+        let bits = display::bits(self.bytes, self.offset, self.len);
+        write!(
+            f,
+            " @ {}..{} = {bits:#b}",
+            self.offset,
+            self.offset + self.len
+        )
+    }
+}
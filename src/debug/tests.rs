@@ -0,0 +1,86 @@
+use super::FieldBits;
+#[cfg(feature = "derive")]
+use super::PackedDebug;
+#[cfg(feature = "derive")]
+use crate::{Packed, PackedSize};
+
+#[cfg(feature = "derive")]
+#[derive(Packed, PackedDebug)]
+struct Frame {
+    #[packed(bits = 3)]
+    kind: u8,
+    #[packed(endian = "little")]
+    len: u16,
+    tail: (bool, [u8; 1]),
+}
+
+#[cfg(feature = "derive")]
+#[derive(Packed, PackedDebug)]
+#[packed(c_abi)]
+struct Flags {
+    #[packed(bits = 3)]
+    a: u8,
+    c: u16,
+}
+
+#[test]
+fn field_bits() {
+    let bytes = [0b1010_0110];
+    assert_eq!(
+        format!("{:?}", FieldBits::new(&3u8, &bytes, 2, 4)),
+        "3 @ 2..6 = 0b1001"
+    );
+    assert_eq!(
+        format!("{:?}", FieldBits::new(&(), &bytes, 8, 0)),
+        "() @ 8..8 = 0b"
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive() {
+    let frame = Frame {
+        kind: 5,
+        len: 0x0102,
+        tail: (true, [0x81]),
+    };
+    assert_eq!(
+        format!("{frame:?}"),
+        "Frame { kind: 5 @ 0..3 = 0b101, \
+         len: 258 @ 3..19 = 0b0000001000000001, \
+         tail: (true, [129]) @ 19..28 = 0b110000001 }"
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_alternate() {
+    let frame = Frame {
+        kind: 0,
+        len: 0,
+        tail: (false, [0]),
+    };
+    assert_eq!(
+        format!("{frame:#?}"),
+        "Frame {
+    kind: 0 @ 0..3 = 0b000,
+    len: 0 @ 3..19 = 0b0000000000000000,
+    tail: (
+        false,
+        [
+            0,
+        ],
+    ) @ 19..28 = 0b000000000,
+}"
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_c_abi() {
+    let flags = Flags { a: 6, c: 0xf00f };
+    assert_eq!(
+        format!("{flags:?}"),
+        "Flags { a: 6 @ 0..3 = 0b110, c: 61455 @ 16..32 = 0b1111000000001111 }"
+    );
+}
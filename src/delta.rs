@@ -0,0 +1,204 @@
+//! Exchanging only the changed bits of packed state.
+//!
+//! Two peers holding the same packed blob can keep it in sync by sending a
+//! [`DeltaFrame`] holding the bits which changed, rather than the whole
+//! blob.  [`diff_packed`] compares two versions of a blob, and
+//! [`TrackedBuffer::delta`](crate::tracked::TrackedBuffer::delta) collects
+//! the bits written since the last sync without keeping the old version
+//! around.  Offsets are counted in the bit order of the [`Cfg`], so both
+//! peers need to use the same one.
+//!
+//! ```rust
+//! # use packed::delta::{self, DeltaFrame};
+//! let old = [0u8; 64];
+//! let mut new = old;
+//! new[3] = 0x40;
+//! new[40] = 0x0f;
+//! let frame = delta::diff_packed(&old, &new);
+//! assert_eq!(frame.ranges().collect::<Vec<_>>(), [25..26, 324..328]);
+//!
+//! let wire = frame.to_bytes();
+//! assert_eq!(wire.len(), 8);
+//! let mut replica = old;
+//! delta::apply_delta(&DeltaFrame::from_bytes(&wire).unwrap(), &mut replica).unwrap();
+//! assert_eq!(replica, new);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::ops::Range;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::{bit_len, check_len};
+use crate::narrow::{read_bits, write_bits};
+use crate::{UnpackError, UnpackErrorKind, varint};
+
+/// Changed runs closer together than this are sent as one patch, as each
+/// patch costs at least this much in headers
+pub const MERGE_GAP: usize = 16;
+
+/// The changed bits of a packed blob, as patches in increasing order of
+/// offset
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DeltaFrame {
+    patches: Vec<Patch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Patch {
+    offset: usize,
+    bits: usize,
+    /// The new bits, packed from the start of the first byte
+    data: Vec<u8>,
+}
+
+impl DeltaFrame {
+    /// Frame holding the bits of `bytes` in `ranges`, which must be in
+    /// increasing order and not overlap
+    pub(crate) fn from_ranges<C: Cfg>(
+        bytes: &[u8],
+        ranges: impl IntoIterator<Item = Range<usize>>,
+    ) -> Self {
+        let patches = ranges
+            .into_iter()
+            .map(|r| {
+                let mut data = vec![0; r.len().div_ceil(8)];
+                copy_bits::<C>(bytes, r.start, &mut data, 0, r.len());
+                Patch {
+                    offset: r.start,
+                    bits: r.len(),
+                    data,
+                }
+            })
+            .collect();
+        Self { patches }
+    }
+
+    /// Whether applying the frame changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// The bit ranges the frame overwrites, in order
+    pub fn ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.patches.iter().map(|p| p.offset..p.offset + p.bits)
+    }
+
+    /// Encode the frame for sending.  The encoding is a varint count of
+    /// patches, followed by each patch as a varint gap since the end of the
+    /// last one, a varint length in bits, and its bits padded to whole bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_varint(&mut out, self.patches.len());
+        let mut end = 0;
+        for p in &self.patches {
+            push_varint(&mut out, p.offset - end);
+            push_varint(&mut out, p.bits);
+            out.extend_from_slice(&p.data);
+            end = p.offset + p.bits;
+        }
+        out
+    }
+
+    /// Decode a frame encoded by [`DeltaFrame::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UnpackError> {
+        let mut pos = 0;
+        let count = read_varint(bytes, &mut pos)?;
+        let mut patches = Vec::new();
+        let mut end = 0usize;
+        for _ in 0..count {
+            let at = pos;
+            let offset = end.checked_add(read_varint(bytes, &mut pos)?);
+            let bits = read_varint(bytes, &mut pos)?;
+            let Some(offset) = offset.filter(|o| o.checked_add(bits).is_some()) else {
+                return Err(UnpackErrorKind::Overflow { offset: at }.into());
+            };
+            let len = bits.div_ceil(8);
+            let Some(padded) = len.checked_mul(8) else {
+                return Err(UnpackErrorKind::Overflow { offset: at }.into());
+            };
+            check_len(bytes, pos, padded)?;
+            let data = bytes[pos / 8..][..len].to_vec();
+            pos += len * 8;
+            patches.push(Patch { offset, bits, data });
+            end = offset + bits;
+        }
+        if pos != bit_len(bytes) {
+            return Err(UnpackErrorKind::Framing { offset: pos }.into());
+        }
+        Ok(Self { patches })
+    }
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, UnpackError> {
+    let (value, read) = varint::unpack(bytes, *pos)?;
+    let value = usize::try_from(value)
+        .map_err(|_| UnpackError::from(UnpackErrorKind::Overflow { offset: *pos }))?;
+    *pos += read;
+    Ok(value)
+}
+
+fn push_varint(out: &mut Vec<u8>, value: usize) {
+    let mut buf = [0u8; varint::MAX_SIZE / 8];
+    let bits = varint::pack(value as u64, &mut buf, 0);
+    out.extend_from_slice(&buf[..bits / 8]);
+}
+
+/// Copy `len` bits from `src` at `src_offset` to `dst` at `dst_offset`
+fn copy_bits<C: Cfg>(src: &[u8], src_offset: usize, dst: &mut [u8], dst_offset: usize, len: usize) {
+    let mut done = 0;
+    while done < len {
+        let bits = (len - done).min(128);
+        let value = read_bits::<C>(bits, src, src_offset + done);
+        write_bits::<C>(value, bits, dst, dst_offset + done);
+        done += bits;
+    }
+}
+
+/// The frame turning `old` into `new`
+///
+/// # Panics
+///
+/// If `old` and `new` aren't the same length
+pub fn diff_packed_with<C: Cfg>(old: &[u8], new: &[u8]) -> DeltaFrame {
+    assert_eq!(old.len(), new.len(), "blobs must be the same length");
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (i, (a, b)) in old.iter().zip(new).enumerate() {
+        if a == b {
+            continue;
+        }
+        for bit in i * 8..i * 8 + 8 {
+            if (a ^ b) & crate::cfg::bit_mask::<C>(bit) == 0 {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(last) if bit - last.end < MERGE_GAP => last.end = bit + 1,
+                _ => ranges.push(bit..bit + 1),
+            }
+        }
+    }
+    DeltaFrame::from_ranges::<C>(new, ranges)
+}
+
+/// Overwrite the bits of `bytes` the frame holds.  Nothing is written if any
+/// patch doesn't fit in `bytes`.
+pub fn apply_delta_with<C: Cfg>(frame: &DeltaFrame, bytes: &mut [u8]) -> Result<(), UnpackError> {
+    for p in &frame.patches {
+        check_len(bytes, p.offset, p.bits)?;
+    }
+    for p in &frame.patches {
+        copy_bits::<C>(&p.data, 0, bytes, p.offset, p.bits);
+    }
+    Ok(())
+}
+
+/// Diff using [`DefaultCfg`]
+pub fn diff_packed(old: &[u8], new: &[u8]) -> DeltaFrame {
+    diff_packed_with::<DefaultCfg>(old, new)
+}
+
+/// Apply using [`DefaultCfg`]
+pub fn apply_delta(frame: &DeltaFrame, bytes: &mut [u8]) -> Result<(), UnpackError> {
+    apply_delta_with::<DefaultCfg>(frame, bytes)
+}
@@ -0,0 +1,102 @@
+#![allow(clippy::single_range_in_vec_init)]
+
+use super::{DeltaFrame, apply_delta, apply_delta_with, diff_packed, diff_packed_with};
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+use crate::tracked::TrackedBuffer;
+
+#[test]
+fn merges_close_changes() {
+    let old = [0u8; 8];
+    let mut new = old;
+    new[0] = 0x81;
+    assert_eq!(diff_packed(&old, &new).ranges().collect::<Vec<_>>(), [0..8]);
+    assert_eq!(
+        diff_packed_with::<Lsb0Le>(&old, &new)
+            .ranges()
+            .collect::<Vec<_>>(),
+        [0..8]
+    );
+    // MERGE_GAP unchanged bits apart
+    new[0] = 0x80;
+    new[2] = 0x40;
+    assert_eq!(
+        diff_packed(&old, &new).ranges().collect::<Vec<_>>(),
+        [0..1, 17..18]
+    );
+    new[2] = 0x80;
+    assert_eq!(
+        diff_packed(&old, &new).ranges().collect::<Vec<_>>(),
+        [0..17]
+    );
+    assert!(diff_packed(&old, &old).is_empty());
+}
+
+#[test]
+fn wire_errors() {
+    let old = [0u8; 4];
+    let mut new = old;
+    new[1] = 0xff;
+    let wire = diff_packed(&old, &new).to_bytes();
+    assert_eq!(wire, [1, 8, 8, 0xff]);
+    assert_eq!(
+        DeltaFrame::from_bytes(&wire[..3]).map_err(|e| e.kind().clone()),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 24 })
+    );
+    let mut trailing = wire.clone();
+    trailing.push(0);
+    assert_eq!(
+        DeltaFrame::from_bytes(&trailing).map_err(|e| e.kind().clone()),
+        Err(UnpackErrorKind::Framing { offset: 32 })
+    );
+    assert_eq!(DeltaFrame::from_bytes(&[0]), Ok(DeltaFrame::default()));
+    // a patch of `u64::MAX` bits, whose padded length overflows
+    let huge = [
+        1, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+    ];
+    assert!(matches!(
+        DeltaFrame::from_bytes(&huge).map_err(|e| e.kind().clone()),
+        Err(UnpackErrorKind::Overflow { .. })
+    ));
+
+    let frame = DeltaFrame::from_bytes(&wire).unwrap();
+    let mut short = [0u8; 1];
+    assert!(apply_delta(&frame, &mut short).is_err());
+    assert_eq!(short, [0]);
+}
+
+#[test]
+fn tracked_delta() {
+    let mut primary = TrackedBuffer::<_, Lsb0Le>::with_cfg([0u8; 32]);
+    let mut replica = [0u8; 32];
+    primary.pack(&0x1234u16, 3).unwrap();
+    primary.pack(&-1i64, 150).unwrap();
+    let frame = primary.delta();
+    assert_eq!(frame.ranges().collect::<Vec<_>>(), [3..19, 150..214]);
+    apply_delta_with::<Lsb0Le>(
+        &DeltaFrame::from_bytes(&frame.to_bytes()).unwrap(),
+        &mut replica,
+    )
+    .unwrap();
+    assert_eq!(replica, primary.as_bytes());
+
+    primary.clear();
+    primary.pack(&0u16, 3).unwrap();
+    apply_delta_with::<Lsb0Le>(&primary.delta(), &mut replica).unwrap();
+    assert_eq!(replica, primary.as_bytes());
+}
+
+proptest::proptest! {
+    #[test]
+    fn round_trip(old: [u8; 40], new: [u8; 40]) {
+        let frame = diff_packed(&old, &new);
+        let mut out = old;
+        apply_delta(&DeltaFrame::from_bytes(&frame.to_bytes()).unwrap(), &mut out).unwrap();
+        proptest::prop_assert_eq!(out, new);
+
+        let frame = diff_packed_with::<Lsb0Le>(&old, &new);
+        let mut out = old;
+        apply_delta_with::<Lsb0Le>(&frame, &mut out).unwrap();
+        proptest::prop_assert_eq!(out, new);
+    }
+}
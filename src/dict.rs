@@ -0,0 +1,229 @@
+//! Dictionary encoding of values with few distinct values.
+//!
+//! [`DictEncoder`] gives every distinct value a small code in the order
+//! they are first seen.  It packs as the dictionary, a [`varint`] count
+//! followed by each value, and then a [`varint`] count of codes followed by
+//! the codes themselves, packed at the fewest bits which can hold them all.
+//!
+//! ```rust
+//! # use packed::dict::{self, DictEncoder};
+//! let mut levels = DictEncoder::new();
+//! for level in [3u8, 3, 1, 3, 4, 1, 3] {
+//!     levels.push(level);
+//! }
+//! assert_eq!(levels.dict(), [3, 1, 4]);
+//! assert_eq!(levels.width(), 2);
+//! // a byte for each count, 3 entries, and 7 codes of 2 bits
+//! assert_eq!(levels.size(), 2 * 8 + 3 * 8 + 7 * 2);
+//! let mut buf = [0u8; 7];
+//! let n = levels.pack(&mut buf, 0);
+//! assert_eq!(dict::unpack(&buf, 0), Ok((vec![3u8, 3, 1, 3, 4, 1, 3], n)));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::bit_len;
+use crate::narrow::NarrowInt;
+use crate::parse::Limits;
+use crate::{Pack, Unpack, UnpackError, UnpackErrorKind, varint};
+
+/// Amount of bits needed for the codes of a dictionary of `len` values
+const fn code_width(len: usize) -> usize {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()) as usize
+}
+
+/// Assigns codes to values and packs them as a dictionary and a code stream
+#[derive(Debug, Clone)]
+pub struct DictEncoder<T> {
+    dict: Vec<T>,
+    index: HashMap<T, usize>,
+    codes: Vec<usize>,
+}
+
+impl<T> DictEncoder<T>
+where
+    T: Clone + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            dict: Vec::new(),
+            index: HashMap::new(),
+            codes: Vec::new(),
+        }
+    }
+
+    /// Append `value`, returning its code
+    pub fn push(&mut self, value: T) -> usize {
+        let code = *self.index.entry(value).or_insert_with_key(|value| {
+            self.dict.push(value.clone());
+            self.dict.len() - 1
+        });
+        self.codes.push(code);
+        code
+    }
+}
+
+impl<T> Default for DictEncoder<T>
+where
+    T: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DictEncoder<T> {
+    /// The distinct values, indexed by their codes
+    pub fn dict(&self) -> &[T] {
+        &self.dict
+    }
+
+    /// The code of every value pushed so far
+    pub fn codes(&self) -> &[usize] {
+        &self.codes
+    }
+
+    /// Amount of bits each code is packed into
+    pub fn width(&self) -> usize {
+        code_width(self.dict.len())
+    }
+}
+
+impl<T> DictEncoder<T>
+where
+    T: Pack,
+{
+    /// Amount of bits the dictionary and codes take up when packed
+    pub fn size(&self) -> usize {
+        varint::size(self.dict.len() as u64)
+            + self.dict.len() * T::SIZE
+            + varint::size(self.codes.len() as u64)
+            + self.codes.len() * self.width()
+    }
+
+    /// Pack the dictionary and codes at `offset`, returning the amount of
+    /// bits written
+    pub fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) -> usize {
+        debug_assert!(bit_len(bytes) - offset >= self.size());
+        let width = self.width();
+        let mut written = varint::pack_with::<C>(self.dict.len() as u64, bytes, offset);
+        for x in &self.dict {
+            x.pack_with::<C>(bytes, offset + written);
+            written += T::SIZE;
+        }
+        written += varint::pack_with::<C>(self.codes.len() as u64, bytes, offset + written);
+        for code in &self.codes {
+            code.pack_narrow::<C>(width, bytes, offset + written);
+            written += width;
+        }
+        written
+    }
+
+    /// Pack using [`DefaultCfg`]
+    pub fn pack(&self, bytes: &mut [u8], offset: usize) -> usize {
+        self.pack_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+/// Fail unless `n` values of `size` bits fit between `offset` and the end
+fn check_fits(bytes: &[u8], offset: usize, n: u64, size: usize) -> Result<usize, UnpackError> {
+    usize::try_from(n)
+        .ok()
+        .filter(|&n| {
+            n.checked_mul(size)
+                .and_then(|bits| bits.checked_add(offset))
+                .is_some_and(|end| end <= bit_len(bytes))
+        })
+        .ok_or(
+            UnpackErrorKind::UnexpectedEnd {
+                offset: bit_len(bytes),
+            }
+            .into(),
+        )
+}
+
+/// Unpack a dictionary and codes at `offset`, returning the values the codes
+/// stand for along with the amount of bits read.  Fails with
+/// [`UnpackErrorKind::Corrupt`] if a code isn't in the dictionary.
+///
+/// The codes of a dictionary of one value take no bits, so the amount of
+/// values returned isn't bounded by the length of `bytes`; use
+/// [`unpack_limited_with`] for input which can't be trusted.
+pub fn unpack_with<C, T>(bytes: &[u8], offset: usize) -> Result<(Vec<T>, usize), UnpackError>
+where
+    C: Cfg,
+    T: Unpack + Clone,
+{
+    unpack_limited_with::<C, T>(bytes, offset, Limits::NONE)
+}
+
+/// Fail unless a count of `n` at `offset` is within `limits`
+fn check_count(limits: Limits, offset: usize, n: u64) -> Result<(), UnpackError> {
+    if usize::try_from(n).ok().is_none_or(|n| n > limits.max_count) {
+        return Err(UnpackErrorKind::Limit { offset }.into());
+    }
+    Ok(())
+}
+
+/// Like [`unpack_with`], but fails with [`UnpackErrorKind::Limit`] rather
+/// than unpacking more than `limits.max_count` dictionary entries or values,
+/// or reading more than `limits.max_bits` bits
+pub fn unpack_limited_with<C, T>(
+    bytes: &[u8],
+    offset: usize,
+    limits: Limits,
+) -> Result<(Vec<T>, usize), UnpackError>
+where
+    C: Cfg,
+    T: Unpack + Clone,
+{
+    let (len, mut read) = varint::unpack_with::<C>(bytes, offset)?;
+    check_count(limits, offset, len)?;
+    let len = check_fits(bytes, offset + read, len, T::SIZE)?;
+    let mut dict = Vec::with_capacity(len);
+    for _ in 0..len {
+        dict.push(T::unpack_with::<C>(bytes, offset + read));
+        read += T::SIZE;
+    }
+    let width = code_width(len);
+    let (n, n_read) = varint::unpack_with::<C>(bytes, offset + read)?;
+    check_count(limits, offset + read, n)?;
+    read += n_read;
+    let n = check_fits(bytes, offset + read, n, width)?;
+    let mut values = Vec::with_capacity(if width == 0 { 0 } else { n });
+    for _ in 0..n {
+        let code = usize::unpack_narrow::<C>(width, bytes, offset + read);
+        let x = dict.get(code).ok_or(UnpackErrorKind::Corrupt {
+            offset: offset + read,
+        })?;
+        values.push(x.clone());
+        read += width;
+    }
+    limits.consume(offset + read, read)?;
+    Ok((values, read))
+}
+
+/// Unpack using [`DefaultCfg`]
+pub fn unpack<T>(bytes: &[u8], offset: usize) -> Result<(Vec<T>, usize), UnpackError>
+where
+    T: Unpack + Clone,
+{
+    unpack_with::<DefaultCfg, T>(bytes, offset)
+}
+
+/// Unpack with limits using [`DefaultCfg`]
+pub fn unpack_limited<T>(
+    bytes: &[u8],
+    offset: usize,
+    limits: Limits,
+) -> Result<(Vec<T>, usize), UnpackError>
+where
+    T: Unpack + Clone,
+{
+    unpack_limited_with::<DefaultCfg, T>(bytes, offset, limits)
+}
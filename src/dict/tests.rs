@@ -0,0 +1,87 @@
+use super::{DictEncoder, unpack, unpack_limited, unpack_with};
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+use crate::parse::Limits;
+
+#[test]
+fn single_value() {
+    let mut enc = DictEncoder::new();
+    for _ in 0..100 {
+        assert_eq!(enc.push(0x1234u16), 0);
+    }
+    assert_eq!((enc.width(), enc.size()), (0, 8 + 16 + 8));
+    let mut buf = [0u8; 4];
+    assert_eq!(enc.pack(&mut buf, 0), 32);
+    assert_eq!(buf, [1, 0x12, 0x34, 100]);
+    assert_eq!(unpack(&buf, 0), Ok((vec![0x1234u16; 100], 32)));
+    assert_eq!(unpack::<u16>(&[0, 0], 0), Ok((vec![], 16)));
+}
+
+#[test]
+fn invalid() {
+    // 3 entries, so codes are 2 bits and 3 is unused
+    assert_eq!(
+        unpack::<u8>(&[3, 10, 20, 30, 2, 0b0011_0000], 0),
+        Err(UnpackErrorKind::Corrupt { offset: 42 }.into())
+    );
+    assert_eq!(
+        unpack::<u8>(&[3, 10, 20], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 24 }.into())
+    );
+    assert_eq!(
+        unpack::<u8>(
+            &[
+                2, 10, 20, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f
+            ],
+            0
+        ),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 96 }.into())
+    );
+}
+
+#[test]
+fn limited() {
+    let limits = |max_count| Limits {
+        max_count,
+        ..Limits::NONE
+    };
+    // a single entry and 2^63 codes of no bits each
+    let huge = [
+        1, 7, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01,
+    ];
+    assert_eq!(
+        unpack_limited::<u8>(&huge, 0, limits(1000)),
+        Err(UnpackErrorKind::Limit { offset: 16 }.into())
+    );
+    // 2^63 entries of no bits each
+    let empty = [
+        0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0,
+    ];
+    assert_eq!(
+        unpack_limited::<()>(&empty, 0, limits(1000)),
+        Err(UnpackErrorKind::Limit { offset: 0 }.into())
+    );
+    let buf = [1, 7, 100];
+    assert_eq!(
+        unpack_limited(&buf, 0, limits(100)),
+        Ok((vec![7u8; 100], 24))
+    );
+    assert!(unpack_limited::<u8>(&buf, 0, limits(99)).is_err());
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(values in proptest::collection::vec(0i16..20, 0..50), offset in 0usize..=8) {
+        let mut enc = DictEncoder::new();
+        for x in &values {
+            enc.push(*x);
+        }
+        for (x, code) in values.iter().zip(enc.codes()) {
+            assert_eq!(&enc.dict()[*code], x);
+        }
+        let mut buf = vec![0u8; (offset + enc.size()).div_ceil(8)];
+        let n = enc.pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(n, enc.size());
+        assert_eq!(unpack_with::<Lsb0Le, i16>(&buf, offset), Ok((values, n)));
+    }
+}
@@ -0,0 +1,171 @@
+//! Formatting packed bits as binary strings without allocating.
+//!
+//! [`bits`] shows a range of bits in a buffer, and [`packed()`] shows the bits
+//! a value packs to.  Bits are shown in the order they are packed, grouped
+//! in bytes by `Display` and all together by `Binary`, which adds a `0b`
+//! prefix with `{:#b}` like the integer types do.
+//!
+//! ```rust
+//! # use packed::display;
+//! let buf = [0x12, 0x34, 0x56];
+//! assert_eq!(display::bits(&buf, 4, 16).to_string(), "00100011 01000101");
+//! assert_eq!(format!("{:#b}", display::bits(&buf, 4, 6)), "0b001000");
+//! assert_eq!(display::bits(&buf, 0, 12).group(4, '_').to_string(), "0001_0010_0011");
+//! assert_eq!(display::packed(&(true, 5u8)).to_string(), "10000010 1");
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::Pack;
+use crate::cfg::{Cfg, DefaultCfg, bit_mask};
+use crate::error::bit_len;
+
+/// Largest value [`PackedDisplay`] formats without allocating, in bits
+pub const STACK_BITS: usize = 1024;
+
+/// Formats bits of a buffer, see [`bits`]
+#[derive(Debug, Clone, Copy)]
+pub struct BitsDisplay<'a, C = DefaultCfg> {
+    bytes: &'a [u8],
+    offset: usize,
+    len: usize,
+    group: usize,
+    separator: char,
+    _marker: PhantomData<fn() -> C>,
+}
+
+/// Show the `len` bits at `offset` in the order they are packed with `C`
+pub fn bits_with<C: Cfg>(bytes: &[u8], offset: usize, len: usize) -> BitsDisplay<'_, C> {
+    debug_assert!(bit_len(bytes) - offset >= len);
+    BitsDisplay {
+        bytes,
+        offset,
+        len,
+        group: 8,
+        separator: ' ',
+        _marker: PhantomData,
+    }
+}
+
+/// Show bits using [`DefaultCfg`]
+pub fn bits(bytes: &[u8], offset: usize, len: usize) -> BitsDisplay<'_> {
+    bits_with::<DefaultCfg>(bytes, offset, len)
+}
+
+impl<C: Cfg> BitsDisplay<'_, C> {
+    /// Separate every `n` bits with `separator` when displayed, or never if
+    /// `n` is zero
+    pub fn group(self, n: usize, separator: char) -> Self {
+        Self {
+            group: n,
+            separator,
+            ..self
+        }
+    }
+
+    fn write_bits(&self, f: &mut fmt::Formatter<'_>, group: usize) -> fmt::Result {
+        for i in 0..self.len {
+            if group > 0 && i > 0 && i % group == 0 {
+                fmt::Write::write_char(f, self.separator)?;
+            }
+            let at = self.offset + i;
+            let set = self.bytes[at / 8] & bit_mask::<C>(at) != 0;
+            fmt::Write::write_char(f, if set { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Cfg> fmt::Display for BitsDisplay<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_bits(f, self.group)
+    }
+}
+
+impl<C: Cfg> fmt::Binary for BitsDisplay<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0b")?;
+        }
+        self.write_bits(f, 0)
+    }
+}
+
+/// Formats the bits a value packs to, see [`packed()`]
+#[derive(Debug, Clone, Copy)]
+pub struct PackedDisplay<'a, T: ?Sized, C = DefaultCfg> {
+    value: &'a T,
+    group: usize,
+    separator: char,
+    _marker: PhantomData<fn() -> C>,
+}
+
+/// Show the bits `value` packs to with `C`.  Values of up to
+/// [`STACK_BITS`] bits are packed on the stack, larger ones allocate.
+pub fn packed_with<C: Cfg, T: Pack + ?Sized>(value: &T) -> PackedDisplay<'_, T, C> {
+    PackedDisplay {
+        value,
+        group: 8,
+        separator: ' ',
+        _marker: PhantomData,
+    }
+}
+
+/// Show a packed value using [`DefaultCfg`]
+pub fn packed<T: Pack + ?Sized>(value: &T) -> PackedDisplay<'_, T> {
+    packed_with::<DefaultCfg, T>(value)
+}
+
+impl<T: Pack + ?Sized, C: Cfg> PackedDisplay<'_, T, C> {
+    /// Separate every `n` bits with `separator` when displayed, or never if
+    /// `n` is zero
+    pub fn group(self, n: usize, separator: char) -> Self {
+        Self {
+            group: n,
+            separator,
+            ..self
+        }
+    }
+
+    /// Pack the value and format its bits with `f`
+    fn with_bits(&self, f: impl FnOnce(BitsDisplay<'_, C>) -> fmt::Result) -> fmt::Result {
+        with_packed::<C, T, _>(self.value, |bytes| {
+            f(bits_with::<C>(bytes, 0, T::SIZE).group(self.group, self.separator))
+        })
+    }
+}
+
+/// Call `f` with `value` packed at the start of a zeroed buffer, which is on
+/// the stack unless the value is larger than [`STACK_BITS`]
+pub(crate) fn with_packed<C, T, R>(value: &T, f: impl FnOnce(&[u8]) -> R) -> R
+where
+    C: Cfg,
+    T: Pack + ?Sized,
+{
+    let mut stack = [0u8; STACK_BITS / 8];
+    let mut heap = Vec::new();
+    let bytes = if T::SIZE <= STACK_BITS {
+        &mut stack[..T::SIZE.div_ceil(8)]
+    } else {
+        heap.resize(T::SIZE.div_ceil(8), 0);
+        &mut heap[..]
+    };
+    value.pack_with::<C>(bytes, 0);
+    f(bytes)
+}
+
+impl<T: Pack + ?Sized, C: Cfg> fmt::Display for PackedDisplay<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_bits(|bits| fmt::Display::fmt(&bits, f))
+    }
+}
+
+impl<T: Pack + ?Sized, C: Cfg> fmt::Binary for PackedDisplay<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_bits(|bits| fmt::Binary::fmt(&bits, f))
+    }
+}
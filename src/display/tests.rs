@@ -0,0 +1,64 @@
+use super::{STACK_BITS, bits, bits_with, packed, packed_with};
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn buffer() {
+    let buf = [0b1010_0110, 0b0000_1111];
+    assert_eq!(bits(&buf, 0, 16).to_string(), "10100110 00001111");
+    assert_eq!(bits(&buf, 3, 10).to_string(), "00110000 01");
+    assert_eq!(bits(&buf, 3, 10).group(0, ' ').to_string(), "0011000001");
+    assert_eq!(bits(&buf, 0, 9).group(3, '_').to_string(), "101_001_100");
+    assert_eq!(bits(&buf, 5, 0).to_string(), "");
+    assert_eq!(format!("{:b}", bits(&buf, 0, 12)), "101001100000");
+    assert_eq!(format!("{:#b}", bits(&buf, 0, 4)), "0b1010");
+    assert_eq!(
+        bits_with::<Lsb0Le>(&buf, 0, 16).to_string(),
+        "01100101 11110000"
+    );
+}
+
+#[test]
+fn value() {
+    assert_eq!(packed(&0x1234u16).to_string(), "00010010 00110100");
+    assert_eq!(
+        packed_with::<Lsb0Le, _>(&0x1234u16).to_string(),
+        "00101100 01001000"
+    );
+    assert_eq!(format!("{:#b}", packed(&(true, [false; 2]))), "0b100");
+    assert_eq!(packed(&()).to_string(), "");
+
+    // values too big for the stack buffer are still shown
+    let big = [0xa5u8; STACK_BITS / 8 + 1];
+    let shown = packed(&big).group(0, ' ').to_string();
+    assert_eq!(shown.len(), STACK_BITS + 8);
+    assert!(
+        shown
+            .chars()
+            .eq("10100101".chars().cycle().take(STACK_BITS + 8))
+    );
+}
+
+#[test]
+fn writes_in_place() {
+    use std::fmt::Write;
+
+    // a fixed-size sink, like a logger on a device without an allocator
+    struct Sink([u8; 32], usize);
+
+    impl Write for Sink {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            let end = self.1 + s.len();
+            self.0
+                .get_mut(self.1..end)
+                .ok_or(std::fmt::Error)?
+                .copy_from_slice(s.as_bytes());
+            self.1 = end;
+            Ok(())
+        }
+    }
+
+    let mut sink = Sink([0; 32], 0);
+    write!(sink, "{}", packed(&0xf0u8)).unwrap();
+    assert_eq!(&sink.0[..sink.1], b"11110000");
+    assert!(write!(sink, "{}", packed(&[0u8; 4])).is_err());
+}
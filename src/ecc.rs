@@ -0,0 +1,228 @@
+//! Error-correcting codes.
+//!
+//! [`Ecc`] packs a value followed by the parity bits of an [`EccScheme`].
+//! Unpacking corrects what the scheme can correct and reports it in
+//! [`Ecc::status`].
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::ecc::{Ecc, EccStatus};
+//! let mut buf = [0u8; 3];
+//! Ecc::new(0xabcdu16).pack(&mut buf, 0);
+//! buf[0] ^= 0b0001_0000;
+//!
+//! let ecc = Ecc::<u16>::unpack(&buf, 0);
+//! assert_eq!(ecc.value, 0xabcd);
+//! assert_eq!(ecc.status, EccStatus::Corrected { bit: 3 });
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::{bit_len, check_len};
+use crate::{Pack, PackedSize, Unpack, UnpackError, UnpackErrorKind};
+
+/// Outcome of checking the parity of an [`Ecc`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EccStatus {
+    /// No errors were detected
+    #[default]
+    Clean,
+    /// A single error was corrected
+    Corrected {
+        /// Bit offset of the flipped bit from the start of the [`Ecc`]
+        bit: usize,
+    },
+    /// More errors were detected than the scheme can correct, the value is
+    /// garbage
+    Uncorrectable,
+}
+
+/// A way of computing parity bits for the packed bits of a `T`
+pub trait EccScheme<T: PackedSize> {
+    /// Amount of parity bits appended to the value
+    const PARITY_BITS: usize;
+
+    /// Write the parity bits for the `T::SIZE` data bits at `offset` right
+    /// after them
+    fn encode<C: Cfg>(bytes: &mut [u8], offset: usize);
+
+    /// Check the data and parity bits at `offset`, correcting errors in
+    /// place where possible
+    fn decode<C: Cfg>(bytes: &mut [u8], offset: usize) -> EccStatus;
+}
+
+/// Extended Hamming code: corrects single and detects double bit errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hamming;
+
+/// Amount of Hamming parity bits needed for `data` bits, excluding the
+/// overall parity bit
+const fn hamming_bits(data: usize) -> usize {
+    let mut r = 0;
+    while (1 << r) < data + r + 1 {
+        r += 1;
+    }
+    r
+}
+
+/// Position of data bit `i` in the Hamming codeword, skipping the powers of
+/// two where the parity bits go
+fn hamming_position(i: usize) -> usize {
+    let mut pos = i + 1;
+    // each power of two at or below the position pushes it up by one
+    let mut p = 1;
+    while p <= pos {
+        pos += 1;
+        p <<= 1;
+    }
+    pos
+}
+
+impl Hamming {
+    /// Hamming syndrome and overall parity of the data bits at `offset`
+    fn parity<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> (usize, bool) {
+        (0..bits)
+            .filter(|&i| bool::unpack_with::<C>(bytes, offset + i))
+            .fold((0, false), |(syn, all), i| {
+                (syn ^ hamming_position(i), !all)
+            })
+    }
+}
+
+impl<T: PackedSize> EccScheme<T> for Hamming {
+    const PARITY_BITS: usize = hamming_bits(T::SIZE) + 1;
+
+    fn encode<C: Cfg>(bytes: &mut [u8], offset: usize) {
+        let r = hamming_bits(T::SIZE);
+        let (syndrome, mut all) = Self::parity::<C>(T::SIZE, bytes, offset);
+        for i in 0..r {
+            let bit = syndrome >> i & 1 != 0;
+            all ^= bit;
+            bit.pack_with::<C>(bytes, offset + T::SIZE + i);
+        }
+        all.pack_with::<C>(bytes, offset + T::SIZE + r);
+    }
+
+    fn decode<C: Cfg>(bytes: &mut [u8], offset: usize) -> EccStatus {
+        let r = hamming_bits(T::SIZE);
+        let (mut syndrome, mut all) = Self::parity::<C>(T::SIZE, bytes, offset);
+        for i in 0..r {
+            let bit = bool::unpack_with::<C>(bytes, offset + T::SIZE + i);
+            syndrome ^= usize::from(bit) << i;
+            all ^= bit;
+        }
+        all ^= bool::unpack_with::<C>(bytes, offset + T::SIZE + r);
+
+        let bit = match (syndrome, all) {
+            (0, false) => return EccStatus::Clean,
+            (_, false) => return EccStatus::Uncorrectable,
+            // the overall parity bit itself
+            (0, true) => T::SIZE + r,
+            (s, true) if s.is_power_of_two() => T::SIZE + s.trailing_zeros() as usize,
+            (s, true) => match (0..T::SIZE).find(|&i| hamming_position(i) == s) {
+                Some(i) => i,
+                None => return EccStatus::Uncorrectable,
+            },
+        };
+        let flipped = !bool::unpack_with::<C>(bytes, offset + bit);
+        flipped.pack_with::<C>(bytes, offset + bit);
+        EccStatus::Corrected { bit }
+    }
+}
+
+/// A value followed by the parity bits of the scheme `S`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ecc<T, S = Hamming> {
+    pub value: T,
+    /// What happened when this was unpacked.  Ignored when packing.
+    pub status: EccStatus,
+    _marker: PhantomData<S>,
+}
+
+impl<T> Ecc<T> {
+    pub fn new(value: T) -> Self {
+        Self::with_scheme(value)
+    }
+}
+
+impl<T, S> Ecc<T, S> {
+    /// Protect `value` with the scheme `S`
+    pub fn with_scheme(value: T) -> Self {
+        Self {
+            value,
+            status: EccStatus::Clean,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, S> Ecc<T, S>
+where
+    T: Unpack,
+    S: EccScheme<T>,
+{
+    /// Unpack the value, failing if it couldn't be corrected or `bytes` ends
+    /// before the codeword does
+    pub fn unpack_checked_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<T, UnpackError> {
+        check_len(bytes, offset, Self::SIZE)?;
+        let ecc = Self::unpack_with::<C>(bytes, offset);
+        match ecc.status {
+            EccStatus::Uncorrectable => Err(UnpackErrorKind::Corrupt { offset }.into()),
+            _ => Ok(ecc.value),
+        }
+    }
+
+    /// Checked unpack using [`DefaultCfg`]
+    pub fn unpack_checked(bytes: &[u8], offset: usize) -> Result<T, UnpackError> {
+        Self::unpack_checked_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+impl<T, S> PackedSize for Ecc<T, S>
+where
+    T: PackedSize,
+    S: EccScheme<T>,
+{
+    const SIZE: usize = T::SIZE + S::PARITY_BITS;
+}
+
+impl<T, S> Pack for Ecc<T, S>
+where
+    T: Pack,
+    S: EccScheme<T>,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        self.value.pack_with::<C>(bytes, offset);
+        S::encode::<C>(bytes, offset);
+    }
+}
+
+impl<T, S> Unpack for Ecc<T, S>
+where
+    T: Unpack,
+    S: EccScheme<T>,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        // copy the codeword so it can be corrected in place
+        let mut code = vec![0; Self::SIZE.div_ceil(8)];
+        for i in 0..Self::SIZE {
+            bool::unpack_with::<C>(bytes, offset + i).pack_with::<C>(&mut code, i);
+        }
+        let status = S::decode::<C>(&mut code, 0);
+        Self {
+            value: T::unpack_with::<C>(&code, 0),
+            status,
+            _marker: PhantomData,
+        }
+    }
+}
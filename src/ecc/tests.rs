@@ -0,0 +1,42 @@
+use super::{Ecc, EccStatus, hamming_bits, hamming_position};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, PackedSize, Unpack};
+
+#[test]
+fn parity_sizes() {
+    assert_eq!(hamming_bits(4), 3);
+    assert_eq!(hamming_bits(11), 4);
+    assert_eq!(hamming_bits(26), 5);
+    assert_eq!(hamming_bits(57), 6);
+    assert_eq!(<Ecc<u64>>::SIZE, 64 + 7 + 1);
+    assert_eq!(
+        (0..4).map(hamming_position).collect::<Vec<_>>(),
+        [3, 5, 6, 7]
+    );
+}
+
+#[test]
+fn double_error_detected() {
+    let mut buf = [0u8; 4];
+    Ecc::<u16>::new(0x1234).pack(&mut buf, 0);
+    buf[0] ^= 0b1000_0001;
+    let ecc = Ecc::<u16>::unpack(&buf, 0);
+    assert_eq!(ecc.status, EccStatus::Uncorrectable);
+    assert!(Ecc::<u16>::unpack_checked(&buf, 0).is_err());
+}
+
+proptest::proptest! {
+    #[test]
+    fn corrects_any_single_error(value: (u32, bool, u8), offset in 0usize..=16, flip in 0usize..(41 + 7)) {
+        type E = Ecc<(u32, bool, u8)>;
+        let mut buf = [0u8; 9];
+        E::new(value).pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(E::unpack_with::<Lsb0Le>(&buf, offset).status, EccStatus::Clean);
+
+        let flipped = !bool::unpack_with::<Lsb0Le>(&buf, offset + flip);
+        flipped.pack_with::<Lsb0Le>(&mut buf, offset + flip);
+        let ecc = E::unpack_with::<Lsb0Le>(&buf, offset);
+        assert_eq!(ecc.value, value);
+        assert_eq!(ecc.status, EccStatus::Corrected { bit: flip });
+    }
+}
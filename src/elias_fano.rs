@@ -0,0 +1,243 @@
+//! Elias-Fano encoding of sorted integers, with random access.
+//!
+//! Each value is split into low bits, packed at a fixed width, and high
+//! bits, stored in unary as the gaps between set bits of a bitmap.  A
+//! sorted sequence of `n` values up to `u` takes about `2 + log2(u / n)`
+//! bits per value, and the `i`th value is found by selecting the `i`th set
+//! bit of the bitmap.  This is the usual encoding of the posting lists of
+//! inverted indexes.
+//!
+//! ```rust
+//! # use packed::elias_fano::EliasFano;
+//! let postings = EliasFano::new(&[3, 4, 7, 13, 14, 15, 21, 43]);
+//! assert_eq!(postings.get(3), Some(13));
+//! assert_eq!(postings.get(8), None);
+//! assert_eq!(postings.iter().rev().next(), Some(43));
+//!
+//! let mut buf = vec![0u8; postings.size().div_ceil(8)];
+//! let n = postings.pack(&mut buf, 0);
+//! assert_eq!(EliasFano::unpack(&buf, 0), Ok((postings, n)));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::bit_len;
+use crate::narrow::NarrowInt;
+use crate::{Pack, Unpack, UnpackError, UnpackErrorKind, varint};
+
+/// Amount of set bits between each sample of their positions
+const SAMPLE: usize = 64;
+
+/// A sorted sequence of integers in Elias-Fano encoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliasFano {
+    len: usize,
+    low_bits: usize,
+    /// Low bits of each value, packed MSB-first
+    lower: Vec<u8>,
+    /// Bit `j` is bit `j % 64` of word `j / 64`
+    upper: Vec<u64>,
+    upper_len: usize,
+    /// Position of every `SAMPLE`th set bit of `upper`
+    samples: Vec<usize>,
+}
+
+impl EliasFano {
+    /// Encode `values`
+    ///
+    /// # Panics
+    ///
+    /// If `values` isn't sorted
+    pub fn new(values: &[u64]) -> Self {
+        assert!(values.is_sorted(), "Elias-Fano sequences must be sorted");
+        let max = values.last().copied().unwrap_or(0);
+        let low_bits = match max / values.len().max(1) as u64 {
+            0 => 0,
+            x => x.ilog2() as usize,
+        };
+        let upper_len = values.len() + (max >> low_bits) as usize + 1;
+        let mut ef = Self::empty(values.len(), low_bits, upper_len);
+        for (i, &x) in values.iter().enumerate() {
+            x.pack_narrow::<DefaultCfg>(low_bits, &mut ef.lower, i * low_bits);
+            let j = (x >> low_bits) as usize + i;
+            ef.upper[j / 64] |= 1 << (j % 64);
+        }
+        ef.sample();
+        ef
+    }
+
+    /// A sequence with no bits set
+    fn empty(len: usize, low_bits: usize, upper_len: usize) -> Self {
+        Self {
+            len,
+            low_bits,
+            lower: vec![0; (len * low_bits).div_ceil(8)],
+            upper: vec![0; upper_len.div_ceil(64)],
+            upper_len,
+            samples: Vec::new(),
+        }
+    }
+
+    fn sample(&mut self) {
+        self.samples.clear();
+        let mut ones = 0;
+        for (w, &word) in self.upper.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                if ones % SAMPLE == 0 {
+                    self.samples.push(w * 64 + word.trailing_zeros() as usize);
+                }
+                word &= word - 1;
+                ones += 1;
+            }
+        }
+    }
+
+    /// Position of the `i`th set bit of `upper`
+    fn select(&self, i: usize) -> usize {
+        let start = self.samples[i / SAMPLE];
+        let mut left = i % SAMPLE;
+        let mut w = start / 64;
+        // ignore the bits before the sample
+        let mut word = self.upper[w] & (u64::MAX << (start % 64));
+        loop {
+            let ones = word.count_ones() as usize;
+            if left < ones {
+                for _ in 0..left {
+                    word &= word - 1;
+                }
+                return w * 64 + word.trailing_zeros() as usize;
+            }
+            left -= ones;
+            w += 1;
+            word = self.upper[w];
+        }
+    }
+
+    /// The `i`th value
+    pub fn get(&self, i: usize) -> Option<u64> {
+        if i >= self.len {
+            return None;
+        }
+        let high = (self.select(i) - i) as u64;
+        let low = u64::unpack_narrow::<DefaultCfg>(self.low_bits, &self.lower, i * self.low_bits);
+        Some(high << self.low_bits | low)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = u64> + ExactSizeIterator + '_ {
+        (0..self.len).map(|i| self.get(i).unwrap())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Amount of low bits stored for each value
+    pub fn low_bits(&self) -> usize {
+        self.low_bits
+    }
+
+    /// Amount of bits the sequence takes up when packed
+    pub fn size(&self) -> usize {
+        varint::size(self.len as u64)
+            + 8
+            + varint::size(self.upper_len as u64)
+            + self.len * self.low_bits
+            + self.upper_len
+    }
+
+    /// Pack the sequence at `offset`, returning the amount of bits written.
+    /// The length, low bit width and bitmap length come first, followed by
+    /// the low bits of every value and then the bitmap.
+    pub fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) -> usize {
+        debug_assert!(bit_len(bytes) - offset >= self.size());
+        let mut written = varint::pack_with::<C>(self.len as u64, bytes, offset);
+        (self.low_bits as u8).pack_with::<C>(bytes, offset + written);
+        written += 8;
+        written += varint::pack_with::<C>(self.upper_len as u64, bytes, offset + written);
+        for i in 0..self.len {
+            let low =
+                u64::unpack_narrow::<DefaultCfg>(self.low_bits, &self.lower, i * self.low_bits);
+            low.pack_narrow::<C>(self.low_bits, bytes, offset + written);
+            written += self.low_bits;
+        }
+        for j in 0..self.upper_len {
+            (self.upper[j / 64] >> (j % 64) & 1 != 0).pack_with::<C>(bytes, offset + written);
+            written += 1;
+        }
+        written
+    }
+
+    /// Pack using [`DefaultCfg`]
+    pub fn pack(&self, bytes: &mut [u8], offset: usize) -> usize {
+        self.pack_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Unpack a sequence at `offset`, returning it along with the amount of
+    /// bits read.  Fails with [`UnpackErrorKind::Corrupt`] if the bitmap
+    /// doesn't have a set bit for every value.
+    pub fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(Self, usize), UnpackError> {
+        let end = bit_len(bytes);
+        let (len, mut read) = varint::unpack_with::<C>(bytes, offset)?;
+        if end < offset + read + 8 {
+            return Err(UnpackErrorKind::UnexpectedEnd {
+                offset: offset + read,
+            }
+            .into());
+        }
+        // `new` never splits off more than 63 low bits, which keeps the high
+        // bits shifted into place by `get` from overflowing
+        let low_bits = u8::unpack_with::<C>(bytes, offset + read) as usize;
+        if low_bits > 63 {
+            return Err(UnpackErrorKind::Overflow {
+                offset: offset + read,
+            }
+            .into());
+        }
+        read += 8;
+        let (upper_len, n) = varint::unpack_with::<C>(bytes, offset + read)?;
+        read += n;
+        let body = usize::try_from(len).ok().and_then(|len| {
+            let upper_len = usize::try_from(upper_len).ok()?;
+            let bits = len.checked_mul(low_bits)?.checked_add(upper_len)?;
+            (bits <= end - offset - read).then_some((len, upper_len))
+        });
+        let Some((len, upper_len)) = body else {
+            return Err(UnpackErrorKind::UnexpectedEnd { offset: end }.into());
+        };
+        if upper_len < len {
+            return Err(UnpackErrorKind::Corrupt { offset }.into());
+        }
+
+        let mut ef = Self::empty(len, low_bits, upper_len);
+        for i in 0..len {
+            let low = u64::unpack_narrow::<C>(low_bits, bytes, offset + read);
+            low.pack_narrow::<DefaultCfg>(low_bits, &mut ef.lower, i * low_bits);
+            read += low_bits;
+        }
+        let mut ones = 0;
+        for j in 0..upper_len {
+            if bool::unpack_with::<C>(bytes, offset + read) {
+                ef.upper[j / 64] |= 1 << (j % 64);
+                ones += 1;
+            }
+            read += 1;
+        }
+        if ones != len {
+            return Err(UnpackErrorKind::Corrupt { offset }.into());
+        }
+        ef.sample();
+        Ok((ef, read))
+    }
+
+    /// Unpack using [`DefaultCfg`]
+    pub fn unpack(bytes: &[u8], offset: usize) -> Result<(Self, usize), UnpackError> {
+        Self::unpack_with::<DefaultCfg>(bytes, offset)
+    }
+}
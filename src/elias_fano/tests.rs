@@ -0,0 +1,88 @@
+use super::EliasFano;
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn layout() {
+    let ef = EliasFano::new(&[3, 4, 7, 13, 14, 15, 21, 43]);
+    assert_eq!(ef.low_bits(), 2);
+    let mut buf = [0u8; 8];
+    assert_eq!(ef.pack(&mut buf, 0), 8 + 8 + 8 + 16 + 19);
+    // low bits 11 00 11 01 10 11 01 11, then a bit for each value at its high
+    // bits plus its index: 0, 2, 3, 6, 7, 8, 11, 17
+    assert_eq!(
+        &buf[3..],
+        [
+            0b1100_1101,
+            0b1011_0111,
+            0b1011_0011,
+            0b1001_0000,
+            0b0100_0000
+        ]
+    );
+}
+
+#[test]
+fn edge_values() {
+    for values in [
+        vec![],
+        vec![0],
+        vec![0, 0, 0],
+        vec![u64::MAX],
+        vec![1, u64::MAX, u64::MAX],
+    ] {
+        let ef = EliasFano::new(&values);
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+        let mut buf = vec![0u8; ef.size().div_ceil(8)];
+        let n = ef.pack(&mut buf, 0);
+        assert_eq!(EliasFano::unpack(&buf, 0), Ok((ef, n)));
+    }
+}
+
+#[test]
+#[should_panic = "must be sorted"]
+fn unsorted() {
+    EliasFano::new(&[2, 1]);
+}
+
+#[test]
+fn invalid() {
+    // 2 values but only one set bit
+    assert_eq!(
+        EliasFano::unpack(&[2, 0, 3, 0b0100_0000], 0),
+        Err(UnpackErrorKind::Corrupt { offset: 0 }.into())
+    );
+    assert_eq!(
+        EliasFano::unpack(&[2, 0, 30, 0], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 32 }.into())
+    );
+    // a value of 64 low bits, whose high bits would shift out of a `u64`
+    let mut buf = [0u8; 12];
+    buf[..3].copy_from_slice(&[1, 64, 2]);
+    buf[11] = 0b1000_0000;
+    assert_eq!(
+        EliasFano::unpack(&buf, 0),
+        Err(UnpackErrorKind::Overflow { offset: 8 }.into())
+    );
+    buf[1] = 63;
+    let (ef, _) = EliasFano::unpack(&buf, 0).unwrap();
+    assert_eq!(ef.get(0), Some(1 << 63));
+}
+
+proptest::proptest! {
+    #[test]
+    fn random_access(
+        mut values in proptest::collection::vec(0u64..1 << 20, 0..300),
+        offset in 0usize..8,
+    ) {
+        values.sort();
+        let ef = EliasFano::new(&values);
+        for (i, x) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), Some(*x));
+        }
+        let mut buf = vec![0u8; (offset + ef.size()).div_ceil(8)];
+        let n = ef.pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(n, ef.size());
+        assert_eq!(EliasFano::unpack_with::<Lsb0Le>(&buf, offset), Ok((ef, n)));
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt;
+
+/// Error returned by the fallible unpack methods
+///
+/// Errors from the fields of derived types record which field failed, see
+/// [`path`](Self::path).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'static")))]
+pub struct UnpackError {
+    kind: UnpackErrorKind,
+    path: FieldPath,
+}
+
+impl UnpackError {
+    pub fn kind(&self) -> &UnpackErrorKind {
+        &self.kind
+    }
+
+    /// Path of the field that failed, outermost first
+    pub fn path(&self) -> &FieldPath {
+        &self.path
+    }
+
+    /// Bit offset at which the failure was detected
+    pub fn offset(&self) -> usize {
+        match self.kind {
+            UnpackErrorKind::Reserved { offset, .. }
+            | UnpackErrorKind::UnexpectedEnd { offset }
+            | UnpackErrorKind::Overflow { offset }
+            | UnpackErrorKind::Length { offset, .. }
+            | UnpackErrorKind::Framing { offset }
+            | UnpackErrorKind::Unaligned { offset }
+            | UnpackErrorKind::Corrupt { offset }
+            | UnpackErrorKind::Limit { offset }
+            | UnpackErrorKind::NonCanonical { offset }
+            | UnpackErrorKind::UnknownVersion { offset, .. }
+            | UnpackErrorKind::UnknownVariant { offset, .. } => offset,
+        }
+    }
+
+    /// Record that the error happened inside the field `name`
+    pub fn in_field(mut self, name: &'static str) -> Self {
+        self.path.push_front(name);
+        self
+    }
+}
+
+impl From<UnpackErrorKind> for UnpackError {
+    fn from(kind: UnpackErrorKind) -> Self {
+        Self {
+            kind,
+            path: FieldPath::default(),
+        }
+    }
+}
+
+impl fmt::Display for UnpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "in field `{}`: ", self.path)?;
+        }
+        self.kind.fmt(f)
+    }
+}
+
+impl std::error::Error for UnpackError {}
+
+/// Amount of bits in `bytes`.  Buffers of more than `usize::MAX / 8` bytes,
+/// which 32-bit targets can map, saturate rather than overflow, as bits past
+/// `usize::MAX` can't be addressed anyway.
+pub(crate) const fn bit_len(bytes: &[u8]) -> usize {
+    bytes.len().saturating_mul(8)
+}
+
+/// Fails with [`UnpackErrorKind::UnexpectedEnd`] unless `bytes` holds `bits`
+/// bits at `offset`
+pub(crate) fn check_len(bytes: &[u8], offset: usize, bits: usize) -> Result<(), UnpackError> {
+    if offset
+        .checked_add(bits)
+        .is_none_or(|end| end > bit_len(bytes))
+    {
+        return Err(UnpackErrorKind::UnexpectedEnd {
+            offset: bit_len(bytes),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Fails with [`PackError::OutOfSpace`] unless `bytes` has room for `bits`
+/// bits at `offset`
+pub(crate) fn check_space(bytes: &[u8], offset: usize, bits: usize) -> Result<(), PackError> {
+    if offset
+        .checked_add(bits)
+        .is_none_or(|end| end > bit_len(bytes))
+    {
+        return Err(PackError::OutOfSpace {
+            offset,
+            needed: bits,
+        });
+    }
+    Ok(())
+}
+
+/// What went wrong in an [`UnpackError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnpackErrorKind {
+    /// A reserved bit did not have its required value
+    Reserved {
+        /// Bit offset of the offending bit
+        offset: usize,
+        /// The required value of the bit
+        expected: bool,
+    },
+    /// The buffer ended before the value did
+    UnexpectedEnd {
+        /// Bit offset at which more bits were needed
+        offset: usize,
+    },
+    /// A variable-length value does not fit in its type
+    Overflow {
+        /// Bit offset at which the value starts
+        offset: usize,
+    },
+    /// A length-prefixed value has a different size than expected
+    Length {
+        /// Bit offset at which the value starts
+        offset: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The input violates the framing rules of its format
+    Framing {
+        /// Bit offset at which the violation was detected
+        offset: usize,
+    },
+    /// A borrowed value does not start on a byte boundary
+    Unaligned {
+        /// Bit offset at which the value starts
+        offset: usize,
+    },
+    /// An integrity check failed
+    Corrupt {
+        /// Bit offset at which the checked value starts
+        offset: usize,
+    },
+    /// A parse went past its [`Limits`](crate::parse::Limits)
+    Limit {
+        /// Bit offset at which the limit was hit
+        offset: usize,
+    },
+    /// The value has more than one encoding and this isn't the one it packs
+    /// back to
+    NonCanonical {
+        /// Bit offset of the first bit which differs
+        offset: usize,
+    },
+    /// A version field names a version which isn't known
+    UnknownVersion {
+        /// Bit offset at which the version field starts
+        offset: usize,
+        version: u64,
+    },
+    /// A value doesn't match any variant of its enum
+    UnknownVariant {
+        /// Bit offset at which the value starts
+        offset: usize,
+        value: u64,
+    },
+}
+
+impl fmt::Display for UnpackErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reserved { offset, expected } => write!(
+                f,
+                "reserved bit {offset} is {}, expected {}",
+                u8::from(!expected),
+                u8::from(*expected)
+            ),
+            Self::UnexpectedEnd { offset } => write!(f, "unexpected end of input at bit {offset}"),
+            Self::Overflow { offset } => write!(f, "value at bit {offset} overflows"),
+            Self::Length {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "value at bit {offset} is {found} bits long, expected {expected}"
+            ),
+            Self::Framing { offset } => write!(f, "invalid framing at bit {offset}"),
+            Self::Unaligned { offset } => write!(f, "value at bit {offset} is not byte-aligned"),
+            Self::Corrupt { offset } => write!(f, "value at bit {offset} is corrupt"),
+            Self::Limit { offset } => write!(f, "parse limit exceeded at bit {offset}"),
+            Self::NonCanonical { offset } => {
+                write!(f, "value is not in canonical form at bit {offset}")
+            }
+            Self::UnknownVersion { offset, version } => {
+                write!(f, "unknown version {version} at bit {offset}")
+            }
+            Self::UnknownVariant { offset, value } => {
+                write!(f, "unknown variant {value} at bit {offset}")
+            }
+        }
+    }
+}
+
+/// Names of the nested fields leading to an error, without allocating.
+/// Paths deeper than [`FieldPath::MAX_DEPTH`] keep only their outermost
+/// fields.
+///
+/// With the `serde` feature the names are serialized outermost first.  They
+/// are borrowed when deserializing, so paths can only be deserialized from
+/// `'static` input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "FieldPathRepr",
+        try_from = "FieldPathRepr",
+        bound(deserialize = "'de: 'static")
+    )
+)]
+pub struct FieldPath {
+    /// Innermost field first
+    names: [&'static str; FieldPath::MAX_DEPTH],
+    len: u8,
+    truncated: bool,
+}
+
+impl FieldPath {
+    pub const MAX_DEPTH: usize = 4;
+
+    fn push_front(&mut self, name: &'static str) {
+        if usize::from(self.len) == Self::MAX_DEPTH {
+            self.truncated = true;
+            self.names.copy_within(1.., 0);
+            self.len -= 1;
+        }
+        self.names[usize::from(self.len)] = name;
+        self.len += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Field names, outermost first
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.names[..usize::from(self.len)].iter().rev().copied()
+    }
+}
+
+/// How a [`FieldPath`] is serialized
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(deserialize = "'de: 'static"))]
+struct FieldPathRepr {
+    names: Vec<&'static str>,
+    truncated: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<FieldPath> for FieldPathRepr {
+    fn from(path: FieldPath) -> Self {
+        Self {
+            names: path.iter().collect(),
+            truncated: path.truncated,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FieldPathRepr> for FieldPath {
+    type Error = String;
+
+    fn try_from(repr: FieldPathRepr) -> Result<Self, String> {
+        if repr.names.len() > Self::MAX_DEPTH {
+            return Err(format!(
+                "field path is deeper than {} fields",
+                Self::MAX_DEPTH
+            ));
+        }
+        let mut path = Self::default();
+        for name in repr.names.into_iter().rev() {
+            path.push_front(name);
+        }
+        path.truncated = repr.truncated;
+        Ok(path)
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, name) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            f.write_str(name)?;
+        }
+        if self.truncated {
+            f.write_str(".…")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by the fallible pack methods
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PackError {
+    /// The buffer doesn't have room for the value
+    OutOfSpace {
+        /// Bit offset at which the value would have started
+        offset: usize,
+        /// Size of the value in bits
+        needed: usize,
+    },
+    /// The value is wider than the type it is packed as
+    Overflow {
+        /// Bit offset at which the value would have started
+        offset: usize,
+        /// Width of the value in bits
+        bits: usize,
+    },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfSpace { offset, needed } => {
+                write!(f, "no room for {needed} bits at bit {offset}")
+            }
+            Self::Overflow { offset, bits } => {
+                write!(f, "{bits} bit value at bit {offset} overflows")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
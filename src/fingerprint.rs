@@ -0,0 +1,291 @@
+//! Fingerprints of packed layouts, for processes sharing packed buffers.
+//!
+//! Two builds reading the same shared memory or IPC messages must agree on
+//! the layout of every struct in them.  [`Fingerprint::LAYOUT_HASH`] is a
+//! hash of a type's layout: the widths and order of its fields, their
+//! names, byte order overrides and narrowing, and the layouts of the field
+//! types.  Deriving [`Fingerprint`] alongside `Packed` implements it.
+//!
+//! The hash is a constant, so a layout can be pinned at compile time with
+//! [`assert_layout!`](crate::assert_layout), or two types compared with
+//! [`assert_same_layout!`](crate::assert_same_layout).  At runtime, each
+//! side can send its [`handshake`] when connecting and check the other's
+//! with [`check_handshake`], failing fast rather than misreading data.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::fingerprint::{self, Fingerprint};
+//! #[derive(Packed, Fingerprint)]
+//! struct Shared {
+//!     seq: u32,
+//!     #[packed(bits = 4)]
+//!     state: u8,
+//! }
+//!
+//! #[derive(Packed, Fingerprint)]
+//! struct Widened {
+//!     seq: u32,
+//!     #[packed(bits = 5)]
+//!     state: u8,
+//! }
+//!
+//! assert_ne!(Shared::LAYOUT_HASH, Widened::LAYOUT_HASH);
+//! let hello = fingerprint::handshake::<Shared>();
+//! assert!(fingerprint::check_handshake::<Shared>(&hello).is_ok());
+//! assert!(fingerprint::check_handshake::<Widened>(&hello).is_err());
+//! # }
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{
+    AtomicBool, AtomicI8, AtomicI16, AtomicI32, AtomicU8, AtomicU16, AtomicU32,
+};
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicI64, AtomicU64};
+
+use crate::cfg::{BitOrder, ByteOrder, Cfg, DefaultCfg};
+use crate::{Bits, PackedSize, Reserved};
+
+#[cfg(feature = "derive")]
+pub use packed_derive::Fingerprint;
+
+/// A type with a known layout hash
+pub trait Fingerprint: PackedSize {
+    /// Hash of the layout, which differs between layouts with high
+    /// probability but is not cryptographically secure
+    const LAYOUT_HASH: u64;
+}
+
+/// Starting value for [`mix`] and [`mix_str`], for implementing
+/// [`Fingerprint`] by hand
+pub const SEED: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a over `bytes`, continuing from `hash`
+const fn fnv(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Fold `value` into `hash`
+pub const fn mix(hash: u64, value: u64) -> u64 {
+    fnv(hash, &value.to_le_bytes())
+}
+
+/// Fold `s` into `hash`, prefixed by its length so consecutive strings
+/// can't run into each other
+pub const fn mix_str(hash: u64, s: &str) -> u64 {
+    fnv(mix(hash, s.len() as u64), s.as_bytes())
+}
+
+/// Fail the build unless the layout hash of a type is the given value, e.g.
+/// one printed by the build it has to match
+#[macro_export]
+macro_rules! assert_layout {
+    ($ty: ty, $hash: expr) => {
+        const _: () = ::core::assert!(
+            <$ty as $crate::fingerprint::Fingerprint>::LAYOUT_HASH == $hash,
+            ::core::concat!("the layout of `", ::core::stringify!($ty), "` changed"),
+        );
+    };
+}
+
+/// Fail the build unless two types have the same layout hash
+#[macro_export]
+macro_rules! assert_same_layout {
+    ($a: ty, $b: ty) => {
+        const _: () = ::core::assert!(
+            <$a as $crate::fingerprint::Fingerprint>::LAYOUT_HASH
+                == <$b as $crate::fingerprint::Fingerprint>::LAYOUT_HASH,
+            ::core::concat!(
+                "`",
+                ::core::stringify!($a),
+                "` and `",
+                ::core::stringify!($b),
+                "` have different layouts"
+            ),
+        );
+    };
+}
+
+/// The layout hash of `T` packed with `C`, which differs between
+/// configurations
+pub const fn layout_hash_with<C: Cfg, T: Fingerprint>() -> u64 {
+    let hash = match C::BIT_ORDER {
+        BitOrder::Msb0 => mix_str(T::LAYOUT_HASH, "msb0"),
+        BitOrder::Lsb0 => mix_str(T::LAYOUT_HASH, "lsb0"),
+    };
+    match C::BYTE_ORDER {
+        ByteOrder::Big => mix_str(hash, "big"),
+        ByteOrder::Little => mix_str(hash, "little"),
+    }
+}
+
+/// Length of a handshake in bytes
+pub const HANDSHAKE_LEN: usize = 16;
+
+/// Bytes describing the layout of `T` packed with `C`, to send to a peer
+/// and check with [`check_handshake_with`]: the layout hash and the size
+/// in bits, both as big-endian `u64`s
+pub fn handshake_with<C: Cfg, T: Fingerprint>() -> [u8; HANDSHAKE_LEN] {
+    let mut out = [0; HANDSHAKE_LEN];
+    out[..8].copy_from_slice(&layout_hash_with::<C, T>().to_be_bytes());
+    out[8..].copy_from_slice(&(T::SIZE as u64).to_be_bytes());
+    out
+}
+
+/// Check a handshake received from a peer against the layout of `T`
+/// packed with `C`
+pub fn check_handshake_with<C: Cfg, T: Fingerprint>(remote: &[u8]) -> Result<(), LayoutMismatch> {
+    let local = handshake_with::<C, T>();
+    if remote == local {
+        return Ok(());
+    }
+    let word = |at: usize| {
+        let mut bytes = [0; 8];
+        let part = remote.get(at..).unwrap_or_default();
+        let n = part.len().min(8);
+        bytes[..n].copy_from_slice(&part[..n]);
+        u64::from_be_bytes(bytes)
+    };
+    Err(LayoutMismatch {
+        local_hash: layout_hash_with::<C, T>(),
+        local_size: T::SIZE as u64,
+        remote_hash: word(0),
+        remote_size: word(8),
+    })
+}
+
+/// Handshake using [`DefaultCfg`]
+pub fn handshake<T: Fingerprint>() -> [u8; HANDSHAKE_LEN] {
+    handshake_with::<DefaultCfg, T>()
+}
+
+/// Check a handshake using [`DefaultCfg`]
+pub fn check_handshake<T: Fingerprint>(remote: &[u8]) -> Result<(), LayoutMismatch> {
+    check_handshake_with::<DefaultCfg, T>(remote)
+}
+
+/// Error returned when a peer's handshake doesn't match.  Missing bytes of
+/// a short handshake are read as zeros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutMismatch {
+    pub local_hash: u64,
+    /// Size in bits
+    pub local_size: u64,
+    pub remote_hash: u64,
+    /// Size in bits
+    pub remote_size: u64,
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "layout mismatch: {} bits with hash {:#018x} here, {} bits with hash {:#018x} remotely",
+            self.local_size, self.local_hash, self.remote_size, self.remote_hash
+        )
+    }
+}
+
+impl std::error::Error for LayoutMismatch {}
+
+macro_rules! fingerprint_primitive {
+    ($($ty: ident),+) => {
+        $(impl Fingerprint for $ty {
+            const LAYOUT_HASH: u64 = mix(mix_str(SEED, stringify!($ty)), Self::SIZE as u64);
+        })+
+    };
+}
+
+fingerprint_primitive!(bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+#[cfg(feature = "native-size")]
+fingerprint_primitive!(usize, isize);
+
+/// Atomics pack like the value they hold
+macro_rules! fingerprint_atomic {
+    ($($atomic: ident($ty: ident)),+) => {
+        $(impl Fingerprint for $atomic {
+            const LAYOUT_HASH: u64 = $ty::LAYOUT_HASH;
+        })+
+    };
+}
+
+fingerprint_atomic!(
+    AtomicBool(bool),
+    AtomicU8(u8),
+    AtomicU16(u16),
+    AtomicU32(u32),
+    AtomicI8(i8),
+    AtomicI16(i16),
+    AtomicI32(i32)
+);
+#[cfg(target_has_atomic = "64")]
+fingerprint_atomic!(AtomicU64(u64), AtomicI64(i64));
+
+/// Pointers pack like the value they point to
+macro_rules! fingerprint_ptr {
+    ($($ptr: ident),+) => {
+        $(impl<T: Fingerprint> Fingerprint for $ptr<T> {
+            const LAYOUT_HASH: u64 = T::LAYOUT_HASH;
+        })+
+    };
+}
+
+fingerprint_ptr!(Box, Rc, Arc);
+
+impl<T: Fingerprint + ?Sized> Fingerprint for &T {
+    const LAYOUT_HASH: u64 = T::LAYOUT_HASH;
+}
+
+impl<T: Fingerprint, const N: usize> Fingerprint for [T; N] {
+    const LAYOUT_HASH: u64 = mix(mix(mix_str(SEED, "array"), T::LAYOUT_HASH), N as u64);
+}
+
+impl<T: Fingerprint, E: Fingerprint> Fingerprint for Result<T, E> {
+    const LAYOUT_HASH: u64 = mix(mix(mix_str(SEED, "result"), T::LAYOUT_HASH), E::LAYOUT_HASH);
+}
+
+impl<T: ?Sized> Fingerprint for PhantomData<T> {
+    const LAYOUT_HASH: u64 = mix_str(SEED, "()");
+}
+
+impl<const BITS: usize, const ONES: bool> Fingerprint for Reserved<BITS, ONES> {
+    const LAYOUT_HASH: u64 = mix(mix(mix_str(SEED, "reserved"), BITS as u64), ONES as u64);
+}
+
+impl<const N: usize> Fingerprint for Bits<N> {
+    const LAYOUT_HASH: u64 = mix(mix_str(SEED, "bits"), N as u64);
+}
+
+impl Fingerprint for () {
+    const LAYOUT_HASH: u64 = mix_str(SEED, "()");
+}
+
+macro_rules! fingerprint_tuple {
+    (()) => {};
+    (($head: ident, $($tail: ident,)*)) => {
+        fingerprint_tuple!(($($tail,)*));
+
+        /// The hash of the first element folded into the hash of the rest
+        impl<$head: Fingerprint, $($tail: Fingerprint,)*> Fingerprint for ($head, $($tail,)*) {
+            const LAYOUT_HASH: u64 = mix(
+                mix_str(<($($tail,)*)>::LAYOUT_HASH, "tuple"),
+                $head::LAYOUT_HASH,
+            );
+        }
+    };
+}
+
+fingerprint_tuple!((A, B, C, D, E, F, G, H, I, J, K, L,));
@@ -0,0 +1,143 @@
+use super::{
+    Fingerprint, HANDSHAKE_LEN, LayoutMismatch, check_handshake, check_handshake_with, handshake,
+    handshake_with, layout_hash_with,
+};
+use crate::cfg::{DefaultCfg, Lsb0Le};
+use crate::{Packed, PackedSize, Reserved};
+
+#[derive(Packed, Fingerprint)]
+struct Header {
+    id: u16,
+    #[packed(bits = 3)]
+    level: u8,
+    #[packed(niche)]
+    next: Option<std::num::NonZeroU8>,
+    flags: [bool; 2],
+}
+
+/// Same fields as [`Header`] under a different name
+#[derive(Packed, Fingerprint)]
+struct Copied {
+    id: u16,
+    #[packed(bits = 3)]
+    level: u8,
+    #[packed(niche)]
+    next: Option<std::num::NonZeroU8>,
+    flags: [bool; 2],
+}
+
+#[derive(Packed, Fingerprint)]
+struct Narrower {
+    id: u16,
+    #[packed(bits = 2)]
+    level: u8,
+    #[packed(niche)]
+    next: Option<std::num::NonZeroU8>,
+    flags: [bool; 2],
+}
+
+#[derive(Packed, Fingerprint)]
+struct Reordered {
+    #[packed(bits = 3)]
+    level: u8,
+    id: u16,
+    #[packed(niche)]
+    next: Option<std::num::NonZeroU8>,
+    flags: [bool; 2],
+}
+
+#[derive(Packed, Fingerprint)]
+struct Swapped {
+    #[packed(endian = "little")]
+    id: u16,
+    #[packed(bits = 3)]
+    level: u8,
+    #[packed(niche)]
+    next: Option<std::num::NonZeroU8>,
+    flags: [bool; 2],
+}
+
+#[derive(Packed, Fingerprint)]
+struct Outer {
+    header: Header,
+    pad: Reserved<5>,
+}
+
+#[derive(Packed, Fingerprint)]
+struct OuterNarrower {
+    header: Narrower,
+    pad: Reserved<5>,
+}
+
+crate::assert_same_layout!(Header, Copied);
+crate::assert_same_layout!((u8, (u16, bool)), (u8, (u16, bool)));
+crate::assert_layout!(u8, <u8 as Fingerprint>::LAYOUT_HASH);
+
+#[test]
+fn layouts_differ() {
+    let hashes = [
+        Header::LAYOUT_HASH,
+        Narrower::LAYOUT_HASH,
+        Reordered::LAYOUT_HASH,
+        Swapped::LAYOUT_HASH,
+        Outer::LAYOUT_HASH,
+        OuterNarrower::LAYOUT_HASH,
+        u8::LAYOUT_HASH,
+        i8::LAYOUT_HASH,
+        bool::LAYOUT_HASH,
+        <[bool; 8]>::LAYOUT_HASH,
+        <[u8; 1]>::LAYOUT_HASH,
+        <(u8, u16)>::LAYOUT_HASH,
+        <(u16, u8)>::LAYOUT_HASH,
+        <((u8, u16), u8)>::LAYOUT_HASH,
+        <Reserved<8>>::LAYOUT_HASH,
+        <Reserved<8, true>>::LAYOUT_HASH,
+        <()>::LAYOUT_HASH,
+    ];
+    for (i, a) in hashes.iter().enumerate() {
+        for b in &hashes[i + 1..] {
+            assert_ne!(a, b, "hash {i} collided");
+        }
+    }
+}
+
+#[test]
+fn wrappers() {
+    assert_eq!(<Box<Header>>::LAYOUT_HASH, Header::LAYOUT_HASH);
+    assert_eq!(<&u32>::LAYOUT_HASH, u32::LAYOUT_HASH);
+    assert_eq!(
+        <std::sync::atomic::AtomicU16>::LAYOUT_HASH,
+        u16::LAYOUT_HASH
+    );
+}
+
+#[test]
+fn handshakes() {
+    let hello = handshake::<Header>();
+    assert_eq!(hello.len(), HANDSHAKE_LEN);
+    assert_eq!(&hello[8..], &(Header::SIZE as u64).to_be_bytes());
+    assert_eq!(check_handshake::<Header>(&hello), Ok(()));
+    assert_eq!(check_handshake::<Copied>(&hello), Ok(()));
+    assert_eq!(
+        check_handshake::<Narrower>(&hello),
+        Err(LayoutMismatch {
+            local_hash: layout_hash_with::<DefaultCfg, Narrower>(),
+            local_size: Narrower::SIZE as u64,
+            remote_hash: layout_hash_with::<DefaultCfg, Header>(),
+            remote_size: Header::SIZE as u64,
+        })
+    );
+
+    let other = handshake_with::<Lsb0Le, Header>();
+    assert_ne!(other, hello);
+    assert!(check_handshake_with::<Lsb0Le, Header>(&other).is_ok());
+    assert!(check_handshake_with::<Lsb0Le, Header>(&hello).is_err());
+
+    let short = check_handshake::<Header>(&hello[..10]).unwrap_err();
+    assert_eq!(short.remote_hash, layout_hash_with::<DefaultCfg, Header>());
+    assert_eq!(
+        short.remote_size,
+        u64::from(hello[8]) << 56 | u64::from(hello[9]) << 48
+    );
+    assert!(short.to_string().starts_with("layout mismatch"));
+}
@@ -0,0 +1,171 @@
+//! Ready-made definitions of well-known header formats.
+//!
+//! Alongside common internet headers there are the headers of constrained
+//! IoT protocols such as CoAP and MQTT-SN.
+//!
+//! These show how real formats map onto the derive and can be used as they
+//! are or copied as a starting point.  All multi-byte fields are big-endian
+//! and numbered MSB-first, so they are meant to be used with the default
+//! [`Cfg`](crate::cfg::Cfg).  Checksums are carried but not computed.
+//!
+//! Only available with the `formats` feature.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::formats::Ipv4Header;
+//! let bytes = [
+//!     0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11,
+//!     0xb8, 0x61, 0xc0, 0xa8, 0x00, 0x01, 0xc0, 0xa8, 0x00, 0xc7,
+//! ];
+//! let header = Ipv4Header::unpack(&bytes, 0);
+//! assert_eq!(header.total_length, 115);
+//! assert_eq!(header.protocol, 17);
+//! assert_eq!(header.destination, [192, 168, 0, 199]);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::{Packed, PackedSize, Reserved};
+
+/// IPv4 header without options (RFC 791)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct Ipv4Header {
+    #[packed(bits = 4)]
+    pub version: u8,
+    /// Header length in 32-bit words
+    #[packed(bits = 4)]
+    pub ihl: u8,
+    #[packed(bits = 6)]
+    pub dscp: u8,
+    #[packed(bits = 2)]
+    pub ecn: u8,
+    pub total_length: u16,
+    pub identification: u16,
+    pub _reserved: Reserved<1>,
+    pub dont_fragment: bool,
+    pub more_fragments: bool,
+    /// Offset of the fragment in 8-byte units
+    #[packed(bits = 13)]
+    pub fragment_offset: u16,
+    pub ttl: u8,
+    pub protocol: u8,
+    pub checksum: u16,
+    pub source: [u8; 4],
+    pub destination: [u8; 4],
+}
+
+/// TCP header without options (RFC 9293, with the ECN flags of RFC 3168)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct TcpHeader {
+    pub source_port: u16,
+    pub destination_port: u16,
+    pub sequence: u32,
+    pub acknowledgment: u32,
+    /// Header length in 32-bit words
+    #[packed(bits = 4)]
+    pub data_offset: u8,
+    pub _reserved: Reserved<4>,
+    pub cwr: bool,
+    pub ece: bool,
+    pub urg: bool,
+    pub ack: bool,
+    pub psh: bool,
+    pub rst: bool,
+    pub syn: bool,
+    pub fin: bool,
+    pub window: u16,
+    pub checksum: u16,
+    pub urgent_pointer: u16,
+}
+
+/// DNS message header (RFC 1035, with the DNSSEC bits of RFC 4035)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct DnsHeader {
+    pub id: u16,
+    /// Set for responses
+    pub qr: bool,
+    #[packed(bits = 4)]
+    pub opcode: u8,
+    pub authoritative: bool,
+    pub truncated: bool,
+    pub recursion_desired: bool,
+    pub recursion_available: bool,
+    pub _reserved: Reserved<1>,
+    pub authentic_data: bool,
+    pub checking_disabled: bool,
+    #[packed(bits = 4)]
+    pub rcode: u8,
+    pub question_count: u16,
+    pub answer_count: u16,
+    pub authority_count: u16,
+    pub additional_count: u16,
+}
+
+/// Arbitration and control fields of a classic CAN base frame, from the
+/// start-of-frame bit up to the data field, before bit stuffing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct CanHeader {
+    /// Start of frame, always dominant (0)
+    pub _sof: Reserved<1>,
+    #[packed(bits = 11)]
+    pub id: u16,
+    /// Remote transmission request
+    pub rtr: bool,
+    /// Identifier extension, clear for base frames
+    pub ide: bool,
+    pub _r0: Reserved<1>,
+    /// Data length code
+    #[packed(bits = 4)]
+    pub dlc: u8,
+}
+
+/// CoAP message header (RFC 7252), followed on the wire by `token_length`
+/// bytes of token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct CoapHeader {
+    #[packed(bits = 2)]
+    pub version: u8,
+    /// One of the `CON`, `NON`, `ACK` or `RST` constants
+    #[packed(bits = 2)]
+    pub kind: u8,
+    #[packed(bits = 4)]
+    pub token_length: u8,
+    /// Class of the code, e.g. 2 for "2.05 Content"
+    #[packed(bits = 3)]
+    pub code_class: u8,
+    /// Detail of the code, e.g. 5 for "2.05 Content"
+    #[packed(bits = 5)]
+    pub code_detail: u8,
+    pub message_id: u16,
+}
+
+impl CoapHeader {
+    pub const CON: u8 = 0;
+    pub const NON: u8 = 1;
+    pub const ACK: u8 = 2;
+    pub const RST: u8 = 3;
+}
+
+/// First byte of a CoAP option.  Deltas and lengths of 13 and up are
+/// extended by the bytes that follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct CoapOptionHeader {
+    #[packed(bits = 4)]
+    pub delta: u8,
+    #[packed(bits = 4)]
+    pub length: u8,
+}
+
+/// Flags byte of MQTT-SN messages (MQTT-SN 1.2, section 5.3.4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+pub struct MqttSnFlags {
+    pub dup: bool,
+    #[packed(bits = 2)]
+    pub qos: u8,
+    pub retain: bool,
+    pub will: bool,
+    pub clean_session: bool,
+    #[packed(bits = 2)]
+    pub topic_id_type: u8,
+}
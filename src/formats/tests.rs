@@ -0,0 +1,151 @@
+use super::{
+    CanHeader, CoapHeader, CoapOptionHeader, DnsHeader, Ipv4Header, MqttSnFlags, TcpHeader,
+};
+use crate::{Pack, PackedSize, Reserved, Unpack};
+
+#[test]
+fn sizes() {
+    assert_eq!(Ipv4Header::SIZE, 20 * 8);
+    assert_eq!(TcpHeader::SIZE, 20 * 8);
+    assert_eq!(DnsHeader::SIZE, 12 * 8);
+    assert_eq!(CanHeader::SIZE, 19);
+    assert_eq!(CoapHeader::SIZE, 4 * 8);
+    assert_eq!(CoapOptionHeader::SIZE, 8);
+    assert_eq!(MqttSnFlags::SIZE, 8);
+}
+
+#[test]
+fn ipv4() {
+    let bytes = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8, 0x00,
+        0x01, 0xc0, 0xa8, 0x00, 0xc7,
+    ];
+    let header = Ipv4Header::unpack_strict(&bytes, 0).unwrap();
+    assert_eq!(
+        header,
+        Ipv4Header {
+            version: 4,
+            ihl: 5,
+            dscp: 0,
+            ecn: 0,
+            total_length: 0x73,
+            identification: 0,
+            _reserved: Reserved,
+            dont_fragment: true,
+            more_fragments: false,
+            fragment_offset: 0,
+            ttl: 64,
+            protocol: 17,
+            checksum: 0xb861,
+            source: [192, 168, 0, 1],
+            destination: [192, 168, 0, 199],
+        }
+    );
+    let mut buf = [0u8; 20];
+    header.pack(&mut buf, 0);
+    assert_eq!(buf, bytes);
+}
+
+#[test]
+fn tcp_syn() {
+    let bytes = [
+        0xc3, 0x50, 0x00, 0x50, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00, 0x50, 0x02, 0xfa,
+        0xf0, 0xab, 0xcd, 0x00, 0x00,
+    ];
+    let header = TcpHeader::unpack_strict(&bytes, 0).unwrap();
+    assert_eq!(header.source_port, 50000);
+    assert_eq!(header.destination_port, 80);
+    assert_eq!(header.sequence, 0x1234_5678);
+    assert_eq!(header.data_offset, 5);
+    assert!(header.syn && !header.ack && !header.fin);
+    assert_eq!(header.window, 64240);
+    let mut buf = [0u8; 20];
+    header.pack(&mut buf, 0);
+    assert_eq!(buf, bytes);
+}
+
+#[test]
+fn dns_response() {
+    let bytes = [
+        0xab, 0xcd, 0x81, 0x80, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00,
+    ];
+    let header = DnsHeader::unpack_strict(&bytes, 0).unwrap();
+    assert_eq!(header.id, 0xabcd);
+    assert!(header.qr && header.recursion_desired && header.recursion_available);
+    assert!(!header.authoritative && !header.truncated);
+    assert_eq!((header.opcode, header.rcode), (0, 0));
+    assert_eq!((header.question_count, header.answer_count), (1, 2));
+    let mut buf = [0u8; 12];
+    header.pack(&mut buf, 0);
+    assert_eq!(buf, bytes);
+}
+
+#[test]
+fn can() {
+    let header = CanHeader {
+        _sof: Reserved,
+        id: 0x123,
+        rtr: false,
+        ide: false,
+        _r0: Reserved,
+        dlc: 8,
+    };
+    let mut buf = [0u8; 3];
+    header.pack(&mut buf, 0);
+    // 0 00100100011 0 0 0 1000
+    assert_eq!(buf, [0b0001_0010, 0b0011_0001, 0b0000_0000]);
+    assert_eq!(CanHeader::unpack_strict(&buf, 0), Ok(header));
+}
+
+#[test]
+fn coap_get() {
+    // CON GET with a 2 byte token, then a Uri-Path option of length 4
+    let bytes = [0x42, 0x01, 0x12, 0x34, 0xaa, 0xbb, 0xb4];
+    let header = CoapHeader::unpack(&bytes, 0);
+    assert_eq!(
+        header,
+        CoapHeader {
+            version: 1,
+            kind: CoapHeader::CON,
+            token_length: 2,
+            code_class: 0,
+            code_detail: 1,
+            message_id: 0x1234,
+        }
+    );
+    let option = CoapOptionHeader::unpack(&bytes, (4 + 2) * 8);
+    assert_eq!(
+        option,
+        CoapOptionHeader {
+            delta: 11,
+            length: 4
+        }
+    );
+
+    let mut buf = [0u8; 4];
+    CoapHeader {
+        kind: CoapHeader::ACK,
+        code_class: 2,
+        code_detail: 5,
+        token_length: 0,
+        ..header
+    }
+    .pack(&mut buf, 0);
+    assert_eq!(buf, [0x60, 0x45, 0x12, 0x34]);
+}
+
+#[test]
+fn mqtt_sn_flags() {
+    let flags = MqttSnFlags::unpack(&[0b1010_0110], 0);
+    assert_eq!(
+        flags,
+        MqttSnFlags {
+            dup: true,
+            qos: 1,
+            retain: false,
+            will: false,
+            clean_session: true,
+            topic_id_type: 2,
+        }
+    );
+}
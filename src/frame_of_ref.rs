@@ -0,0 +1,142 @@
+//! Frame-of-reference compression of blocks of integers.
+//!
+//! A block stores the amount of values and their minimum as
+//! [varints](crate::varint), then the width of the largest difference from
+//! the minimum as a byte, followed by every difference packed at that
+//! width.  Values which are close together compress well no matter how
+//! large they are.
+//!
+//! ```rust
+//! # use packed::frame_of_ref;
+//! let timestamps = [1_700_000_000, 1_700_000_003, 1_700_000_001, 1_700_000_007];
+//! // a byte each for the count and width, 5 for the minimum, and 3 bits for
+//! // each difference
+//! assert_eq!(frame_of_ref::size(&timestamps), 7 * 8 + 4 * 3);
+//! let mut buf = [0u8; 10];
+//! let n = frame_of_ref::pack(&timestamps, &mut buf, 0);
+//! assert_eq!(frame_of_ref::unpack(&buf, 0), Ok((timestamps.to_vec(), n)));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::bit_len;
+use crate::narrow::NarrowInt;
+use crate::parse::Limits;
+use crate::{Pack, Unpack, UnpackError, UnpackErrorKind, varint};
+
+/// The minimum of `values` and the amount of bits each difference from it
+/// needs
+fn frame(values: &[u64]) -> (u64, usize) {
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    (min, 64 - (max - min).leading_zeros() as usize)
+}
+
+/// Amount of bits `values` take up when packed
+pub fn size(values: &[u64]) -> usize {
+    let (min, width) = frame(values);
+    varint::size(values.len() as u64) + varint::size(min) + 8 + values.len() * width
+}
+
+/// Pack `values` as a block at `offset`, returning the amount of bits
+/// written
+pub fn pack_with<C: Cfg>(values: &[u64], bytes: &mut [u8], offset: usize) -> usize {
+    debug_assert!(bit_len(bytes) - offset >= size(values));
+    let (min, width) = frame(values);
+    let mut written = varint::pack_with::<C>(values.len() as u64, bytes, offset);
+    written += varint::pack_with::<C>(min, bytes, offset + written);
+    (width as u8).pack_with::<C>(bytes, offset + written);
+    written += 8;
+    for x in values {
+        (x - min).pack_narrow::<C>(width, bytes, offset + written);
+        written += width;
+    }
+    written
+}
+
+/// Unpack a block at `offset`, returning its values along with the amount
+/// of bits read.  Blocks of equal values take no bits per value, so the
+/// amount of values returned isn't bounded by the length of `bytes`; use
+/// [`unpack_limited_with`] for input which can't be trusted.
+pub fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(Vec<u64>, usize), UnpackError> {
+    unpack_limited_with::<C>(bytes, offset, Limits::NONE)
+}
+
+/// Like [`unpack_with`], but fails with [`UnpackErrorKind::Limit`] rather
+/// than returning more than `limits.max_count` values or reading more than
+/// `limits.max_bits` bits
+pub fn unpack_limited_with<C: Cfg>(
+    bytes: &[u8],
+    offset: usize,
+    limits: Limits,
+) -> Result<(Vec<u64>, usize), UnpackError> {
+    let (len, mut read) = varint::unpack_with::<C>(bytes, offset)?;
+    if usize::try_from(len)
+        .ok()
+        .is_none_or(|len| len > limits.max_count)
+    {
+        return Err(UnpackErrorKind::Limit { offset }.into());
+    }
+    let (min, n) = varint::unpack_with::<C>(bytes, offset + read)?;
+    read += n;
+    if (offset + read)
+        .checked_add(8)
+        .is_none_or(|end| end > bit_len(bytes))
+    {
+        return Err(UnpackErrorKind::UnexpectedEnd {
+            offset: offset + read,
+        }
+        .into());
+    }
+    let width = u8::unpack_with::<C>(bytes, offset + read) as usize;
+    if width > 64 {
+        return Err(UnpackErrorKind::Overflow {
+            offset: offset + read,
+        }
+        .into());
+    }
+    read += 8;
+    let available = bit_len(bytes) - offset - read;
+    if usize::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_mul(width))
+        .is_none_or(|bits| bits > available)
+    {
+        return Err(UnpackErrorKind::UnexpectedEnd {
+            offset: bit_len(bytes),
+        }
+        .into());
+    }
+    let mut values = Vec::with_capacity(if width == 0 { 0 } else { len as usize });
+    for _ in 0..len {
+        let x = u64::unpack_narrow::<C>(width, bytes, offset + read);
+        let x = min.checked_add(x).ok_or(UnpackErrorKind::Overflow {
+            offset: offset + read,
+        })?;
+        values.push(x);
+        read += width;
+    }
+    limits.consume(offset + read, read)?;
+    Ok((values, read))
+}
+
+/// Pack using [`DefaultCfg`]
+pub fn pack(values: &[u64], bytes: &mut [u8], offset: usize) -> usize {
+    pack_with::<DefaultCfg>(values, bytes, offset)
+}
+
+/// Unpack using [`DefaultCfg`]
+pub fn unpack(bytes: &[u8], offset: usize) -> Result<(Vec<u64>, usize), UnpackError> {
+    unpack_with::<DefaultCfg>(bytes, offset)
+}
+
+/// Unpack with limits using [`DefaultCfg`]
+pub fn unpack_limited(
+    bytes: &[u8],
+    offset: usize,
+    limits: Limits,
+) -> Result<(Vec<u64>, usize), UnpackError> {
+    unpack_limited_with::<DefaultCfg>(bytes, offset, limits)
+}
@@ -0,0 +1,77 @@
+use crate::cfg::Lsb0Le;
+use crate::parse::Limits;
+use crate::{UnpackErrorKind, frame_of_ref};
+
+#[test]
+fn constant_block() {
+    let values = [u64::MAX; 1000];
+    assert_eq!(frame_of_ref::size(&values), 2 * 8 + 10 * 8 + 8);
+    let mut buf = [0u8; 13];
+    let n = frame_of_ref::pack(&values, &mut buf, 0);
+    assert_eq!(n, 104);
+    assert_eq!(frame_of_ref::unpack(&buf, 0), Ok((values.to_vec(), n)));
+    assert_eq!(frame_of_ref::size(&[]), 3 * 8);
+}
+
+#[test]
+fn limited() {
+    let limits = |max_count| Limits {
+        max_count,
+        ..Limits::NONE
+    };
+    // 2^63 values of zero in 12 bytes
+    let huge = [
+        0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0, 0,
+    ];
+    assert_eq!(
+        frame_of_ref::unpack_limited(&huge, 0, limits(1000)),
+        Err(UnpackErrorKind::Limit { offset: 0 }.into())
+    );
+    let mut buf = [0u8; 13];
+    let n = frame_of_ref::pack(&[7; 1000], &mut buf, 0);
+    assert_eq!(
+        frame_of_ref::unpack_limited(&buf, 0, limits(1000)),
+        Ok((vec![7; 1000], n))
+    );
+    assert!(frame_of_ref::unpack_limited(&buf, 0, limits(999)).is_err());
+}
+
+#[test]
+fn invalid_blocks() {
+    // 3 values from 250 at 65 bits
+    assert_eq!(
+        frame_of_ref::unpack(&[3, 0xfa, 0x01, 65], 0),
+        Err(UnpackErrorKind::Overflow { offset: 24 }.into())
+    );
+    // 3 values at 4 bits need 12 bits
+    assert_eq!(
+        frame_of_ref::unpack(&[3, 0, 4, 0xff], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 32 }.into())
+    );
+    // u64::MAX - 1 plus 2
+    let mut buf = [0xff; 13];
+    buf[0] = 1;
+    buf[1] = 0xfe;
+    buf[10] = 0x01;
+    buf[11] = 2;
+    buf[12] = 0b1000_0000;
+    assert_eq!(
+        frame_of_ref::unpack(&buf, 0),
+        Err(UnpackErrorKind::Overflow { offset: 96 }.into())
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(
+        base: u64,
+        deltas in proptest::collection::vec(0u64..1 << 20, 0..20),
+        offset in 0usize..=16,
+    ) {
+        let values: Vec<u64> = deltas.iter().map(|d| base.saturating_add(*d)).collect();
+        let size = frame_of_ref::size(&values);
+        let mut buf = vec![0u8; (offset + size).div_ceil(8)];
+        assert_eq!(frame_of_ref::pack_with::<Lsb0Le>(&values, &mut buf, offset), size);
+        assert_eq!(frame_of_ref::unpack_with::<Lsb0Le>(&buf, offset), Ok((values, size)));
+    }
+}
@@ -0,0 +1,136 @@
+//! Packed buffers laid out for GPU compute shaders to unpack.
+//!
+//! An [`Export`] packs an array into a buffer padded to a multiple of an
+//! alignment, 16 bytes by default as storage buffers usually need, and
+//! describes it with a [`Descriptor`] to upload alongside as a uniform.
+//! Elements can be spaced out to a multiple of some amount of bits, e.g. so
+//! none straddles two of the 32-bit words shaders read.
+//!
+//! [`Export::export`] packs with [`Lsb0Le`], so on the GPU element `i`
+//! starts at bit `i * stride % 32` of the little-endian word
+//! `i * stride / 32`.
+//!
+//! ```rust
+//! # use packed::gpu::{Descriptor, Export};
+//! let (buf, desc) = Export::new().export(&[1u8, 2, 3]);
+//! assert_eq!(buf.len(), 16);
+//! assert_eq!(&buf[..3], [1, 2, 3]);
+//! assert_eq!(desc, Descriptor { bits: 8, stride: 8, count: 3, len: 16 });
+//!
+//! // 3 bit elements spaced out to 4, so none straddles a word
+//! let (buf, desc) = Export::new()
+//!     .stride_align(4)
+//!     .export(&[[true, false, true], [false, true, true]]);
+//! assert_eq!(buf[0], 0b0110_0101);
+//! assert_eq!((desc.bits, desc.stride), (3, 4));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::Pack;
+use crate::cfg::{Cfg, Lsb0Le};
+
+/// How an array is laid out for the GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Export {
+    align: usize,
+    stride_align: usize,
+}
+
+impl Export {
+    /// Elements back to back, in a buffer padded to 16 bytes
+    pub const fn new() -> Self {
+        Self {
+            align: 16,
+            stride_align: 1,
+        }
+    }
+
+    /// Pad the buffer to a multiple of `bytes` bytes
+    pub const fn align(self, bytes: usize) -> Self {
+        assert!(bytes > 0, "buffers can't be aligned to 0 bytes");
+        Self {
+            align: bytes,
+            ..self
+        }
+    }
+
+    /// Start each element a multiple of `bits` bits after the previous one
+    pub const fn stride_align(self, bits: usize) -> Self {
+        assert!(bits > 0, "elements can't be aligned to 0 bits");
+        Self {
+            stride_align: bits,
+            ..self
+        }
+    }
+
+    /// Pack `values` with the configuration `C`, zeroing the padding
+    ///
+    /// # Panics
+    ///
+    /// If the buffer is longer than `u32::MAX` bytes, which the descriptor
+    /// can't describe
+    pub fn export_with<C, T>(&self, values: &[T]) -> (Vec<u8>, Descriptor)
+    where
+        C: Cfg,
+        T: Pack,
+    {
+        let stride = T::SIZE.next_multiple_of(self.stride_align);
+        let len = (values.len() * stride)
+            .div_ceil(8)
+            .next_multiple_of(self.align);
+        let mut buf = vec![0u8; len];
+        for (i, x) in values.iter().enumerate() {
+            x.pack_with::<C>(&mut buf, i * stride);
+        }
+        let narrow = |n: usize| u32::try_from(n).expect("buffer is too long to describe");
+        let desc = Descriptor {
+            bits: narrow(T::SIZE),
+            stride: narrow(stride),
+            count: narrow(values.len()),
+            len: narrow(len),
+        };
+        (buf, desc)
+    }
+
+    /// Pack `values` using [`Lsb0Le`], see the [module docs](self)
+    pub fn export<T: Pack>(&self, values: &[T]) -> (Vec<u8>, Descriptor) {
+        self.export_with::<Lsb0Le, T>(values)
+    }
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The layout of an exported buffer, laid out like the matching struct of
+/// four `u32`s in a shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Descriptor {
+    /// Width of each element in bits
+    pub bits: u32,
+    /// Amount of bits from the start of one element to the start of the next
+    pub stride: u32,
+    /// Amount of elements
+    pub count: u32,
+    /// Length of the buffer in bytes, including padding
+    pub len: u32,
+}
+
+impl Descriptor {
+    /// The fields as little-endian `u32`s, to upload as a uniform
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut out = [0u8; 16];
+        for (chunk, x) in
+            out.chunks_exact_mut(4)
+                .zip([self.bits, self.stride, self.count, self.len])
+        {
+            chunk.copy_from_slice(&x.to_le_bytes());
+        }
+        out
+    }
+}
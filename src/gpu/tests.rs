@@ -0,0 +1,54 @@
+use super::{Descriptor, Export};
+use crate::cfg::Msb0Be;
+use crate::{Unpack, UnpackError};
+
+/// Unpack element `i` the way a shader would, from little-endian words
+fn shader_read(buf: &[u8], desc: &Descriptor, i: usize) -> u64 {
+    let words = buf
+        .chunks_exact(4)
+        .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    let start = i * desc.stride as usize;
+    (0..desc.bits as usize).fold(0, |acc, k| {
+        let bit = start + k;
+        acc | u64::from(words[bit / 32] >> (bit % 32) & 1) << k
+    })
+}
+
+#[test]
+fn matches_shader_reads() {
+    let values = (0..100u16).map(|i| i * 41 % 4096).collect::<Vec<_>>();
+    for stride_align in [1, 3, 16, 32] {
+        let (buf, desc) = Export::new()
+            .align(64)
+            .stride_align(stride_align)
+            .export(&values);
+        assert_eq!(buf.len() % 64, 0);
+        assert_eq!(desc.len as usize, buf.len());
+        assert_eq!(desc.count, 100);
+        assert!((desc.stride as usize).is_multiple_of(stride_align));
+        for (i, &x) in values.iter().enumerate() {
+            assert_eq!(shader_read(&buf, &desc, i), u64::from(x));
+        }
+    }
+}
+
+#[test]
+fn pads_with_zeros() {
+    let (buf, desc) = Export::new()
+        .stride_align(8)
+        .export_with::<Msb0Be, _>(&[[true; 3]; 2]);
+    assert_eq!(buf, [0xe0, 0xe0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!((desc.bits, desc.stride), (3, 8));
+    assert_eq!(
+        <[bool; 3]>::unpack_strict_with::<Msb0Be>(&buf, desc.stride as usize),
+        Ok::<_, UnpackError>([true; 3])
+    );
+
+    let (buf, desc) = Export::new().export::<u32>(&[]);
+    assert!(buf.is_empty());
+    assert_eq!(
+        desc.to_bytes(),
+        [32, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
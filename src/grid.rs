@@ -0,0 +1,148 @@
+//! Rectangular arrays of narrow values, such as image sensor data.
+//!
+//! A [`Grid`] packs values row by row, each in the same amount of bits.
+//! Rows can be padded so each one starts on a boundary, like the rows of
+//! many RAW image formats which are padded to whole bytes.  Padding is
+//! zeroed when packing and ignored when unpacking.
+//!
+//! ```rust
+//! # use packed::grid::{self, Grid};
+//! // two rows of three 10 bit pixels, each row padded to whole bytes
+//! let layout = Grid::new(3, 10).align_rows(8);
+//! assert_eq!(layout.row_stride(), 32);
+//! let pixels = [1u16, 2, 3, 1021, 1022, 1023];
+//! let mut buf = [0u8; 8];
+//! assert_eq!(grid::pack_2d(layout, &pixels, &mut buf, 0), 64);
+//! assert_eq!(grid::unpack_2d::<u16>(layout, 2, &buf, 0), Ok(pixels.to_vec()));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::{bit_len, check_len};
+use crate::narrow::NarrowInt;
+use crate::{Pack, UnpackError};
+
+/// The shape of the rows of a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Grid {
+    cols: usize,
+    bits: usize,
+    row_align: usize,
+}
+
+impl Grid {
+    /// Rows of `cols` values of `bits` bits each, with no padding
+    pub const fn new(cols: usize, bits: usize) -> Self {
+        Self {
+            cols,
+            bits,
+            row_align: 1,
+        }
+    }
+
+    /// Pad each row so the next one starts a multiple of `bits` bits after
+    /// the start of the grid, e.g. `8` for whole bytes
+    pub const fn align_rows(self, bits: usize) -> Self {
+        assert!(bits > 0, "rows can't be aligned to 0 bits");
+        Self {
+            row_align: bits,
+            ..self
+        }
+    }
+
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Amount of bits each value is packed into
+    pub const fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Amount of bits from the start of one row to the start of the next
+    pub const fn row_stride(&self) -> usize {
+        (self.cols * self.bits).next_multiple_of(self.row_align)
+    }
+
+    /// Amount of bits `rows` rows take up
+    pub const fn size(&self, rows: usize) -> usize {
+        rows * self.row_stride()
+    }
+}
+
+/// Pack `values` row by row at `offset`, returning the amount of bits
+/// written.  `values.len()` must be a multiple of the amount of columns.
+pub fn pack_2d_with<C, T>(grid: Grid, values: &[T], bytes: &mut [u8], offset: usize) -> usize
+where
+    C: Cfg,
+    T: NarrowInt,
+{
+    if grid.cols == 0 {
+        return 0;
+    }
+    let stride = grid.row_stride();
+    let used = grid.cols * grid.bits;
+    debug_assert!(values.len().is_multiple_of(grid.cols));
+    let rows = values.len() / grid.cols;
+    debug_assert!(bit_len(bytes) - offset >= grid.size(rows));
+    for (r, row) in values.chunks(grid.cols).enumerate() {
+        let start = offset + r * stride;
+        for (c, x) in row.iter().enumerate() {
+            x.pack_narrow::<C>(grid.bits, bytes, start + c * grid.bits);
+        }
+        for i in used..stride {
+            false.pack_with::<C>(bytes, start + i);
+        }
+    }
+    grid.size(rows)
+}
+
+/// Unpack `rows` rows at `offset`
+pub fn unpack_2d_with<C, T>(
+    grid: Grid,
+    rows: usize,
+    bytes: &[u8],
+    offset: usize,
+) -> Result<Vec<T>, UnpackError>
+where
+    C: Cfg,
+    T: NarrowInt,
+{
+    let stride = grid.row_stride();
+    check_len(bytes, offset, rows.saturating_mul(stride))?;
+    let mut out = Vec::new();
+    for r in 0..rows {
+        let start = offset + r * stride;
+        for c in 0..grid.cols {
+            out.push(T::unpack_narrow::<C>(
+                grid.bits,
+                bytes,
+                start + c * grid.bits,
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Pack a grid using [`DefaultCfg`]
+pub fn pack_2d<T>(grid: Grid, values: &[T], bytes: &mut [u8], offset: usize) -> usize
+where
+    T: NarrowInt,
+{
+    pack_2d_with::<DefaultCfg, T>(grid, values, bytes, offset)
+}
+
+/// Unpack a grid using [`DefaultCfg`]
+pub fn unpack_2d<T>(
+    grid: Grid,
+    rows: usize,
+    bytes: &[u8],
+    offset: usize,
+) -> Result<Vec<T>, UnpackError>
+where
+    T: NarrowInt,
+{
+    unpack_2d_with::<DefaultCfg, T>(grid, rows, bytes, offset)
+}
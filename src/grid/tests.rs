@@ -0,0 +1,33 @@
+use super::{Grid, pack_2d, pack_2d_with, unpack_2d, unpack_2d_with};
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn padding_is_zeroed() {
+    let layout = Grid::new(2, 3).align_rows(4);
+    assert_eq!((layout.row_stride(), layout.size(3)), (8, 24));
+    let mut buf = [0xffu8; 3];
+    assert_eq!(pack_2d(layout, &[1u8, 2, 3, 4, 5, 6], &mut buf, 0), 24);
+    assert_eq!(buf, [0b0010_1000, 0b0111_0000, 0b1011_1000]);
+    assert_eq!(
+        unpack_2d::<u8>(layout, 4, &buf, 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 24 }.into())
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(
+        values in proptest::collection::vec(-512i16..512, 12),
+        cols in proptest::sample::select(vec![1usize, 2, 3, 4, 6, 12]),
+        align in 1usize..=16,
+        offset in 0usize..=8,
+    ) {
+        let layout = Grid::new(cols, 10).align_rows(align);
+        let rows = 12 / cols;
+        let mut buf = vec![0u8; (offset + layout.size(rows)).div_ceil(8)];
+        let len = pack_2d_with::<Lsb0Le, _>(layout, &values, &mut buf, offset);
+        assert_eq!(len, layout.size(rows));
+        assert_eq!(unpack_2d_with::<Lsb0Le, i16>(layout, rows, &buf, offset), Ok(values));
+    }
+}
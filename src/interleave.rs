@@ -0,0 +1,131 @@
+//! Bit interleaving.
+//!
+//! A [`BitInterleaver`] moves each bit of a packed value to another position
+//! given by a permutation, which spreads burst errors over many codewords in
+//! forward-error-correction pipelines.
+//!
+//! ```rust
+//! # use packed::interleave::BitInterleaver;
+//! // write 2 rows of 4 bits, read them out column by column
+//! let interleaver = BitInterleaver::block(2, 4);
+//! let mut buf = [0u8; 1];
+//! interleaver.pack(&0b1111_0000u8, &mut buf, 0);
+//! assert_eq!(buf, [0b1010_1010]);
+//! assert_eq!(interleaver.unpack::<u8>(&buf, 0), 0b1111_0000);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::{Pack, Unpack};
+
+/// Maps logical bit positions of a value to physical positions in the buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitInterleaver {
+    /// `map[logical] == physical`
+    map: Vec<usize>,
+}
+
+impl BitInterleaver {
+    /// Interleaver moving logical bit `i` to physical bit `map[i]`
+    ///
+    /// # Panics
+    ///
+    /// If `map` is not a permutation of `0..map.len()`
+    pub fn new(map: Vec<usize>) -> Self {
+        let mut seen = vec![false; map.len()];
+        for &p in &map {
+            assert!(p < map.len() && !seen[p], "not a permutation");
+            seen[p] = true;
+        }
+        Self { map }
+    }
+
+    /// Block interleaver: bits are written into `rows` rows of `cols` bits
+    /// and read out column by column
+    pub fn block(rows: usize, cols: usize) -> Self {
+        let map = (0..rows * cols)
+            .map(|i| i % cols * rows + i / cols)
+            .collect();
+        Self { map }
+    }
+
+    /// Amount of bits the interleaver permutes
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Interleaver undoing this one
+    pub fn inverse(&self) -> Self {
+        let mut map = vec![0; self.map.len()];
+        for (logical, &physical) in self.map.iter().enumerate() {
+            map[physical] = logical;
+        }
+        Self { map }
+    }
+
+    /// Copy `len()` bits from `src` to `dst`, interleaving them
+    pub fn interleave_with<C: Cfg>(
+        &self,
+        src: &[u8],
+        src_offset: usize,
+        dst: &mut [u8],
+        dst_offset: usize,
+    ) {
+        debug_assert!(src.len() * 8 - src_offset >= self.len());
+        debug_assert!(dst.len() * 8 - dst_offset >= self.len());
+        for (logical, &physical) in self.map.iter().enumerate() {
+            bool::unpack_with::<C>(src, src_offset + logical)
+                .pack_with::<C>(dst, dst_offset + physical);
+        }
+    }
+
+    /// Copy `len()` bits from `src` to `dst`, deinterleaving them
+    pub fn deinterleave_with<C: Cfg>(
+        &self,
+        src: &[u8],
+        src_offset: usize,
+        dst: &mut [u8],
+        dst_offset: usize,
+    ) {
+        debug_assert!(src.len() * 8 - src_offset >= self.len());
+        debug_assert!(dst.len() * 8 - dst_offset >= self.len());
+        for (logical, &physical) in self.map.iter().enumerate() {
+            bool::unpack_with::<C>(src, src_offset + physical)
+                .pack_with::<C>(dst, dst_offset + logical);
+        }
+    }
+
+    /// Pack `value` with its bits interleaved.  `T::SIZE` must equal
+    /// [`len`](Self::len).
+    pub fn pack_with<C: Cfg, T: Pack>(&self, value: &T, bytes: &mut [u8], offset: usize) {
+        assert_eq!(T::SIZE, self.len(), "interleaver size mismatch");
+        let mut plain = vec![0; T::SIZE.div_ceil(8)];
+        value.pack_with::<C>(&mut plain, 0);
+        self.interleave_with::<C>(&plain, 0, bytes, offset);
+    }
+
+    /// Unpack a value whose bits were interleaved.  `T::SIZE` must equal
+    /// [`len`](Self::len).
+    pub fn unpack_with<C: Cfg, T: Unpack>(&self, bytes: &[u8], offset: usize) -> T {
+        assert_eq!(T::SIZE, self.len(), "interleaver size mismatch");
+        let mut plain = vec![0; T::SIZE.div_ceil(8)];
+        self.deinterleave_with::<C>(bytes, offset, &mut plain, 0);
+        T::unpack_with::<C>(&plain, 0)
+    }
+
+    /// Pack using [`DefaultCfg`]
+    pub fn pack<T: Pack>(&self, value: &T, bytes: &mut [u8], offset: usize) {
+        self.pack_with::<DefaultCfg, T>(value, bytes, offset);
+    }
+
+    /// Unpack using [`DefaultCfg`]
+    pub fn unpack<T: Unpack>(&self, bytes: &[u8], offset: usize) -> T {
+        self.unpack_with::<DefaultCfg, T>(bytes, offset)
+    }
+}
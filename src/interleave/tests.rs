@@ -0,0 +1,38 @@
+use super::BitInterleaver;
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn block_spreads_bursts() {
+    // a burst of 3 adjacent physical errors hits 3 different rows
+    let interleaver = BitInterleaver::block(3, 8);
+    let mut buf = [0u8; 3];
+    interleaver.pack(&[0u8; 3], &mut buf, 0);
+    buf[1] ^= 0b0011_1000;
+    let rows = interleaver.unpack::<[u8; 3]>(&buf, 0);
+    assert!(rows.iter().all(|r| r.count_ones() == 1));
+}
+
+#[test]
+fn inverse() {
+    let interleaver = BitInterleaver::new(vec![2, 0, 3, 1]);
+    assert_eq!(interleaver.inverse().inverse(), interleaver);
+    assert_eq!(interleaver.inverse(), BitInterleaver::new(vec![1, 3, 0, 2]));
+}
+
+#[test]
+#[should_panic = "not a permutation"]
+fn rejects_non_permutation() {
+    BitInterleaver::new(vec![0, 2, 2]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(value: (u32, bool, u16), offset in 0usize..=16) {
+        let interleaver = BitInterleaver::block(7, 7);
+        let mut buf = [0u8; 10];
+        interleaver.pack(&value, &mut buf, offset);
+        assert_eq!(interleaver.unpack::<(u32, bool, u16)>(&buf, offset), value);
+        interleaver.pack_with::<Lsb0Le, _>(&value, &mut buf, offset);
+        assert_eq!(interleaver.unpack_with::<Lsb0Le, (u32, bool, u16)>(&buf, offset), value);
+    }
+}
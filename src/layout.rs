@@ -0,0 +1,221 @@
+//! Reports on how well a struct's fields fit its bits.
+//!
+//! Deriving [`Layout`] alongside `Packed` lists where each field is packed.
+//! A [`Report`] built from that counts the bits spent on padding and
+//! reserved fields, names the fields straddling more bytes than their
+//! width needs, and estimates what unpacking costs.  When reordering the
+//! fields would straddle less, it suggests an order.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::layout::{self, Layout, Report};
+//! #[derive(Packed, Layout)]
+//! struct Telemetry {
+//!     #[packed(bits = 3)]
+//!     mode: u8,
+//!     seq: u16,
+//!     #[packed(bits = 5)]
+//!     channel: u8,
+//!     armed: bool,
+//! }
+//!
+//! let report = Report::of::<Telemetry>();
+//! assert_eq!(report.straddling, ["seq"]);
+//! assert_eq!(report.wasted, 7);
+//! assert_eq!(
+//!     report.suggested.unwrap(),
+//!     ["seq", "channel", "mode", "armed"],
+//! );
+//! # }
+//! ```
+//!
+//! Layouts can also be held to at compile time with
+//! [`assert_no_straddles!`](crate::assert_no_straddles).  Offsets are those
+//! of [`DefaultCfg`](crate::cfg::DefaultCfg), which only matters for
+//! `c_abi` structs.
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::fmt;
+
+use crate::PackedSize;
+
+#[cfg(feature = "derive")]
+pub use packed_derive::Layout;
+
+/// Where a field is packed within its struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    /// The type as written in the struct
+    pub ty: &'static str,
+    /// Bit offset from the start of the struct
+    pub offset: usize,
+    pub bits: usize,
+    /// Whether the field is [`Reserved`](crate::Reserved)
+    pub reserved: bool,
+}
+
+impl FieldInfo {
+    /// Placeholder while filling in an array of fields
+    pub const EMPTY: Self = Self {
+        name: "",
+        ty: "",
+        offset: 0,
+        bits: 0,
+        reserved: false,
+    };
+
+    /// Whether the field touches more bytes than its width needs
+    pub const fn straddles(&self) -> bool {
+        self.bytes_touched() > self.bits.div_ceil(8)
+    }
+
+    /// Amount of bytes holding bits of the field
+    pub const fn bytes_touched(&self) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+        (self.offset + self.bits - 1) / 8 - self.offset / 8 + 1
+    }
+
+    /// Rough cost of unpacking the field: one per byte loaded, and one more
+    /// if it has to be shifted or masked out
+    pub const fn decode_cost(&self) -> usize {
+        let shifted = !self.offset.is_multiple_of(8) || !self.bits.is_multiple_of(8);
+        self.bytes_touched() + shifted as usize
+    }
+}
+
+/// A type whose fields can be listed, usually derived
+pub trait Layout: PackedSize {
+    /// The fields in declaration order
+    const FIELDS: &'static [FieldInfo];
+}
+
+/// Amount of fields straddling more bytes than their width needs
+pub const fn straddles(fields: &[FieldInfo]) -> usize {
+    let mut n = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        n += fields[i].straddles() as usize;
+        i += 1;
+    }
+    n
+}
+
+/// Fail the build if any field of a type straddles more bytes than its width
+/// needs
+#[macro_export]
+macro_rules! assert_no_straddles {
+    ($ty: ty) => {
+        const _: () = ::core::assert!(
+            $crate::layout::straddles(<$ty as $crate::layout::Layout>::FIELDS) == 0,
+            ::core::concat!(
+                "`",
+                ::core::stringify!($ty),
+                "` has fields straddling byte boundaries"
+            ),
+        );
+    };
+}
+
+/// How well the fields of a type fit its bits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Size of the type in bits
+    pub size: usize,
+    /// Bits of padding and reserved fields, including those padding the
+    /// type to whole bytes
+    pub wasted: usize,
+    /// Names of the fields straddling more bytes than their width needs
+    pub straddling: Vec<&'static str>,
+    /// Sum of the [`FieldInfo::decode_cost`] of every field
+    pub cost: usize,
+    /// An order of the fields, packed back to back, which straddles less or
+    /// decodes cheaper, if there is one
+    pub suggested: Option<Vec<&'static str>>,
+}
+
+impl Report {
+    pub fn of<T: Layout>() -> Self {
+        Self::new(T::FIELDS, T::SIZE)
+    }
+
+    /// Report on `fields` making up a type of `size` bits
+    pub fn new(fields: &[FieldInfo], size: usize) -> Self {
+        let used = fields
+            .iter()
+            .filter(|f| !f.reserved)
+            .map(|f| f.bits)
+            .sum::<usize>();
+        let score = |fields: &[FieldInfo]| {
+            (
+                straddles(fields),
+                fields.iter().map(FieldInfo::decode_cost).sum::<usize>(),
+            )
+        };
+        let (straddling, cost) = score(fields);
+        let reordered = reorder(fields);
+        let suggested = (score(&reordered) < (straddling, cost))
+            .then(|| reordered.iter().map(|f| f.name).collect());
+        Self {
+            size,
+            wasted: size.next_multiple_of(8) - used,
+            straddling: fields
+                .iter()
+                .filter(|f| f.straddles())
+                .map(|f| f.name)
+                .collect(),
+            cost,
+            suggested,
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bits, {} wasted, decode cost {}",
+            self.size, self.wasted, self.cost
+        )?;
+        if !self.straddling.is_empty() {
+            write!(f, "\nstraddling: {}", self.straddling.join(", "))?;
+        }
+        if let Some(order) = &self.suggested {
+            write!(f, "\nsuggested order: {}", order.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// `fields` packed back to back in an order which avoids straddling: whole
+/// bytes first, then each field filling the rest of the current byte if
+/// one fits, or the widest left otherwise
+fn reorder(fields: &[FieldInfo]) -> Vec<FieldInfo> {
+    let (mut out, mut rest): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .copied()
+        .partition(|f| f.bits.is_multiple_of(8));
+    let mut offset = out.iter().map(|f| f.bits).sum::<usize>();
+    // widest first, keeping declaration order among equals
+    rest.sort_by_key(|f| std::cmp::Reverse(f.bits));
+    while !rest.is_empty() {
+        let room = 8 - offset % 8;
+        let i = match offset % 8 {
+            0 => 0,
+            _ => rest.iter().position(|f| f.bits <= room).unwrap_or(0),
+        };
+        offset += rest[i].bits;
+        out.push(rest.remove(i));
+    }
+    let mut offset = 0;
+    for f in &mut out {
+        f.offset = offset;
+        offset += f.bits;
+    }
+    out
+}
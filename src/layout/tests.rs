@@ -0,0 +1,101 @@
+use super::{FieldInfo, Layout, Report};
+use crate::prelude::*;
+use crate::{Reserved, assert_no_straddles};
+
+#[derive(Packed, Layout)]
+struct Header {
+    version: u8,
+    #[packed(bits = 4)]
+    kind: u8,
+    _pad: Reserved<2>,
+    #[packed(bits = 6)]
+    len: u16,
+}
+
+#[derive(Packed, Layout)]
+struct Pair(u16, [bool; 8]);
+
+#[derive(Packed, Layout)]
+#[packed(c_abi)]
+struct Flags {
+    #[packed(bits = 3)]
+    a: u8,
+    #[packed(bits = 6)]
+    b: u8,
+    c: u16,
+}
+
+assert_no_straddles!(Pair);
+assert_no_straddles!(Flags);
+
+#[test]
+fn derived_fields() {
+    let field = |name, ty, offset, bits, reserved| FieldInfo {
+        name,
+        ty,
+        offset,
+        bits,
+        reserved,
+    };
+    assert_eq!(
+        Header::FIELDS,
+        [
+            field("version", "u8", 0, 8, false),
+            field("kind", "u8", 8, 4, false),
+            field("_pad", "Reserved<2>", 12, 2, true),
+            field("len", "u16", 14, 6, false),
+        ]
+    );
+    assert_eq!(
+        Pair::FIELDS,
+        [
+            field("0", "u16", 0, 16, false),
+            field("1", "[bool; 8]", 16, 8, false),
+        ]
+    );
+    assert_eq!(
+        Flags::FIELDS.iter().map(|f| f.offset).collect::<Vec<_>>(),
+        [0, 8, 16]
+    );
+}
+
+#[test]
+fn reports() {
+    let report = Report::of::<Header>();
+    assert_eq!(report.size, 20);
+    assert_eq!(report.wasted, 6);
+    assert_eq!(report.straddling, ["len"]);
+    assert_eq!(report.cost, 1 + 2 + 2 + 3);
+    assert_eq!(
+        report.suggested.unwrap(),
+        ["version", "len", "_pad", "kind"]
+    );
+
+    let report = Report::of::<Flags>();
+    assert_eq!(report.wasted, 7);
+    assert!(report.straddling.is_empty());
+    assert_eq!(report.suggested, None);
+    assert_eq!(report.to_string(), "32 bits, 7 wasted, decode cost 6");
+    assert_eq!(
+        Report::of::<Header>().to_string(),
+        "20 bits, 6 wasted, decode cost 8\n\
+         straddling: len\n\
+         suggested order: version, len, _pad, kind"
+    );
+}
+
+#[test]
+fn field_costs() {
+    let field = |offset, bits| FieldInfo {
+        offset,
+        bits,
+        ..FieldInfo::EMPTY
+    };
+    assert!(!field(4, 4).straddles());
+    assert!(field(4, 8).straddles());
+    assert!(!field(4, 12).straddles());
+    assert!(field(7, 10).straddles());
+    assert_eq!(field(8, 16).decode_cost(), 2);
+    assert_eq!(field(9, 16).decode_cost(), 4);
+    assert_eq!(field(0, 0).decode_cost(), 0);
+}
@@ -2,32 +2,184 @@
 //!
 //! A library for tightly packing and unpacking data in structs.
 //!
-//! We offer a single trait, `Packed`.  The type is implemented by default
-//! on all integer types, booleans, arrays, and tuples.
+//! Values are packed with [`Pack`] and unpacked with [`Unpack`], which share
+//! their size through [`PackedSize`].  [`Packed`] is implemented for every
+//! type that is both, and is implemented by default on all integer types,
+//! booleans, atomics, arrays, tuples, `Result`, `Box`/`Rc`/`Arc`, and
+//! `PhantomData`.  References only implement [`Pack`].  The traits can be
+//! imported together from [`prelude`].
+//!
+//! [`Pack::pack_with`] and [`Unpack::unpack_with`] expect the buffer to have
+//! room for the value and may panic if it doesn't.  Functions returning an
+//! [`UnpackError`] or [`PackError`], such as [`Unpack::unpack_strict`] and
+//! the readers in [`cursor`], check their input instead and never panic on
+//! it, only on arguments documented under `# Panics`.
 //!
 //! ## Usage
 //!
 //! ```rust
-//! # use packed::Packed;
+//! # use packed::prelude::*;
 //! let my_number: u16 = 42;
 //! let mut buf = [0u8; 3];
 //! my_number.pack(&mut buf, 3);
 //! assert_eq!(buf, [0b0000_0000, 0b0000_0101, 0b0100_0000]);
 //! ```
+//!
+//! By default bits are numbered MSB-first and integers are big-endian.  Other
+//! wire conventions can be selected with `pack_with`/`unpack_with`, see
+//! [`cfg`](mod@cfg).
+//!
+//! With the `derive` feature (on by default), `Packed` can be derived for
+//! structs, including tuple and unit structs, as can `Pack` or `Unpack` on
+//! their own.  Integer fields can be narrowed with `#[packed(bits = N)]`, see
+//! [`narrow`], and `Option` fields can be packed into the unused bit patterns
+//! of their value with `#[packed(niche)]`, see [`niche`].  The byte order of
+//! a whole struct or of single fields can be fixed with
+//! `#[packed(endian = "little")]` or `"big"`, whatever the [`cfg::Cfg`] it is
+//! packed with.  Fields are packed in declaration order unless every field
+//! is given its place on the wire with `#[packed(order = N)]`.  Deriving
+//! `PackedDebug` in place of `Debug` shows the bits each field is packed
+//! into, see [`debug`], and deriving `PackedEq` compares and hashes structs
+//! by their packed bits, see [`compare`].
+//! Fields added in later versions of a format can be marked
+//! `#[packed(since = N)]`, so one struct reads every older layout, see
+//! [`version`].  Deriving `Fingerprint` hashes the layout of a struct, so
+//! processes sharing buffers can check they agree on it, see
+//! [`fingerprint`], and deriving `Layout` reports wasted bits and fields
+//! straddling byte boundaries, see [`layout`].
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! #[derive(Packed)]
+//! struct Header {
+//!     flag: bool,
+//!     #[packed(bits = 3)]
+//!     kind: u8,
+//!     #[packed(bits = 4)]
+//!     delta: i8,
+//! }
+//!
+//! assert_eq!(Header::SIZE, 8);
+//! let mut buf = [0u8; 1];
+//! Header { flag: true, kind: 5, delta: -1 }.pack(&mut buf, 0);
+//! assert_eq!(buf, [0b1101_1111]);
+//! # }
+//! ```
+//!
+//! The `paranoid` feature replaces the shortcuts taken when packing bytes and
+//! narrow fields, and when reading from contiguous buffers, with bit-by-bit
+//! implementations such as [`reference`](mod@reference), so the two can be
+//! tested against each other.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for the error
+//! types and for wrapper types such as [`Bits`], [`Reserved`], [`BitOffset`],
+//! [`portable::PortableUsize`], [`scaled::Scaled`] and
+//! [`niche::SentinelOption`].
 
 #[cfg(test)]
 mod tests;
 
-pub trait Packed {
+// lets the derive refer to `::packed` from inside this crate
+extern crate self as packed;
+
+#[cfg(feature = "bench")]
+pub mod bench_support;
+mod bit_string;
+pub mod bitfield;
+mod bits;
+pub mod bloom;
+pub mod borrow;
+pub mod bridge;
+pub mod bulk;
+pub mod c_abi;
+pub mod cfg;
+pub mod columnar;
+pub mod compare;
+pub mod crc;
+pub mod cursor;
+#[cfg(feature = "dbc")]
+pub mod dbc;
+pub mod debug;
+pub mod delta;
+pub mod dict;
+pub mod display;
+#[cfg(feature = "ecc")]
+pub mod ecc;
+pub mod elias_fano;
+mod error;
+pub mod fingerprint;
+#[cfg(feature = "formats")]
+pub mod formats;
+pub mod frame_of_ref;
+pub mod gpu;
+pub mod grid;
+pub mod interleave;
+pub mod layout;
+pub mod narrow;
+pub mod niche;
+pub mod parse;
+pub mod pattern;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+pub mod pcm;
+pub mod portable;
+pub mod prelude;
+pub mod prepacked;
+#[cfg(feature = "raw")]
+pub mod raw;
+pub mod record_log;
+pub mod reference;
+pub mod register;
+mod reserved;
+pub mod ring;
+pub mod scaled;
+pub mod scramble;
+pub mod secret;
+pub mod shared;
+pub mod shift;
+pub mod slice;
+pub mod soa;
+pub mod stream;
+pub mod stuffing;
+pub mod symbols;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod tlv;
+pub mod tracked;
+pub mod varint;
+pub mod vectored;
+pub mod version;
+
+pub use bit_string::Bits;
+pub use bits::{BitLen, BitOffset, WideBitOffset};
+pub use error::{FieldPath, PackError, UnpackError, UnpackErrorKind};
+pub use reserved::Reserved;
+
+#[cfg(feature = "derive")]
+pub use packed_derive::{Pack, Packed, Unpack};
+
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{
+    AtomicBool, AtomicI8, AtomicI16, AtomicI32, AtomicU8, AtomicU16, AtomicU32, Ordering,
+};
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicI64, AtomicU64};
+
+use cfg::{BitOrder, Cfg, DefaultCfg, OrderedBytes};
+use error::bit_len;
+
+/// The number of bits a value takes up when packed, shared by [`Pack`] and
+/// [`Unpack`]
+pub trait PackedSize {
     /// Amount of bits that the packed struct takes up
     const SIZE: usize;
-    // bytes: bytes from which to unpack this value
-    // offset: bit offset from the first bit in the slice
-    // TODO: how to force `bytes.len() * 8 - offset >= Self::SIZE`?
-    fn unpack(bytes: &[u8], offset: usize) -> Self;
-    // TODO: how to force `bytes.len() * 8 - offset >= Self::SIZE`?
-    // offset: offset into the first byte: offset < 8
-    fn pack(self, bytes: &mut [u8], offset: usize);
+    /// [`PackedSize::SIZE`] as a [`BitLen`]
+    const LEN: BitLen = BitLen(Self::SIZE);
 
     // TODO: This should be const, but it can't because fuck you
     fn size_of_val(&self) -> usize {
@@ -35,87 +187,304 @@ pub trait Packed {
     }
 }
 
-impl Packed for bool {
-    const SIZE: usize = 1;
+/// A value which can be packed into a buffer
+pub trait Pack: PackedSize {
+    // TODO: how to force `bytes.len() * 8 - offset >= Self::SIZE`?
+    // offset: offset into the first byte: offset < 8
+    // values are packed by reference, so they don't need to be cloned
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize);
+
+    /// Pack using [`DefaultCfg`]
+    fn pack(&self, bytes: &mut [u8], offset: usize) {
+        self.pack_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Like [`Pack::pack_with`], but the offset is known at compile time so
+    /// the shifts and masks can be folded away
+    #[inline]
+    fn pack_at_with<C: Cfg, const OFFSET: usize>(&self, bytes: &mut [u8]) {
+        self.pack_with::<C>(bytes, OFFSET)
+    }
 
+    /// Pack at a constant offset using [`DefaultCfg`]
+    #[inline]
+    fn pack_at<const OFFSET: usize>(&self, bytes: &mut [u8]) {
+        self.pack_at_with::<DefaultCfg, OFFSET>(bytes)
+    }
+
+    /// Like [`Pack::pack_with`], but with a typed offset
+    fn pack_to_with<C: Cfg>(&self, bytes: &mut [u8], offset: BitOffset) {
+        self.pack_with::<C>(bytes, offset.0)
+    }
+
+    /// Pack at a typed offset using [`DefaultCfg`]
+    fn pack_to(&self, bytes: &mut [u8], offset: BitOffset) {
+        self.pack_to_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Like [`Pack::pack_with`], but fails rather than panicking if `bytes`
+    /// ends before the value would, or the value would end past
+    /// `usize::MAX` bits
+    fn pack_checked_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) -> Result<(), PackError> {
+        error::check_space(bytes, offset, Self::SIZE)?;
+        self.pack_with::<C>(bytes, offset);
+        Ok(())
+    }
+
+    /// Checked pack using [`DefaultCfg`]
+    fn pack_checked(&self, bytes: &mut [u8], offset: usize) -> Result<(), PackError> {
+        self.pack_checked_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+/// A value which can be unpacked from a buffer
+pub trait Unpack: PackedSize + Sized {
+    // bytes: bytes from which to unpack this value
+    // offset: bit offset from the first bit in the slice
+    // TODO: how to force `bytes.len() * 8 - offset >= Self::SIZE`?
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self;
+
+    /// Unpack using [`DefaultCfg`]
     fn unpack(bytes: &[u8], offset: usize) -> Self {
-        debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-        let i = offset / 8;
-        bytes[i] & 1 << (7 - offset % 8) != 0
+        Self::unpack_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Like [`Unpack::unpack_with`], but the offset is known at compile time
+    /// so the shifts and masks can be folded away
+    #[inline]
+    fn unpack_at_with<C: Cfg, const OFFSET: usize>(bytes: &[u8]) -> Self {
+        Self::unpack_with::<C>(bytes, OFFSET)
     }
 
-    fn pack(self, bytes: &mut [u8], offset: usize) {
-        debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-        bytes[offset / 8] &= !(1 << (7 - offset % 8));
-        bytes[offset / 8] |= u8::from(self) << (7 - offset % 8);
+    /// Unpack at a constant offset using [`DefaultCfg`]
+    #[inline]
+    fn unpack_at<const OFFSET: usize>(bytes: &[u8]) -> Self {
+        Self::unpack_at_with::<DefaultCfg, OFFSET>(bytes)
+    }
+
+    /// Like [`Unpack::unpack_with`], but with a typed offset
+    fn unpack_from_with<C: Cfg>(bytes: &[u8], offset: BitOffset) -> Self {
+        Self::unpack_with::<C>(bytes, offset.0)
+    }
+
+    /// Unpack at a typed offset using [`DefaultCfg`]
+    fn unpack_from(bytes: &[u8], offset: BitOffset) -> Self {
+        Self::unpack_from_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Unpack into an existing value rather than returning a new one, which
+    /// lets types holding heap data reuse their allocations
+    fn unpack_into_with<C: Cfg>(&mut self, bytes: &[u8], offset: usize) {
+        *self = Self::unpack_with::<C>(bytes, offset);
+    }
+
+    /// Unpack into an existing value using [`DefaultCfg`]
+    fn unpack_into(&mut self, bytes: &[u8], offset: usize) {
+        self.unpack_into_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Check that all reserved bits (see [`Reserved`]) of the value packed at
+    /// `offset` hold their required value.  `bytes` must hold the whole
+    /// value, as with [`Unpack::unpack_with`].
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        let _ = (bytes, offset);
+        Ok(())
+    }
+
+    /// Like [`Unpack::unpack_with`], but fails if any reserved bits are not
+    /// set to their required value, or if `bytes` ends before the value does
+    fn unpack_strict_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<Self, UnpackError> {
+        error::check_len(bytes, offset, Self::SIZE)?;
+        Self::check_reserved_with::<C>(bytes, offset)?;
+        Ok(Self::unpack_with::<C>(bytes, offset))
+    }
+
+    /// Strict unpack using [`DefaultCfg`]
+    fn unpack_strict(bytes: &[u8], offset: usize) -> Result<Self, UnpackError> {
+        Self::unpack_strict_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+/// A value which can be both packed and unpacked.  This is implemented for
+/// every type implementing [`Pack`] and [`Unpack`].
+pub trait Packed: Pack + Unpack {
+    /// Check that the value at `offset` is in canonical form, so it unpacks
+    /// strictly and packs back to exactly the same bits.  Formats where
+    /// every value must have a single encoding, such as those which are
+    /// hashed or signed, can reject anything else with this.  Fails with
+    /// [`UnpackErrorKind::NonCanonical`] at the first bit which would change.
+    fn check_canonical_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        let value = Self::unpack_strict_with::<C>(bytes, offset)?;
+        // repack into a copy of just the bytes the value touches
+        let (start, end) = (offset / 8, offset + Self::SIZE);
+        let mut packed = bytes[start..end.div_ceil(8)].to_vec();
+        value.pack_with::<C>(&mut packed, offset - start * 8);
+        let changed =
+            |i: usize| (packed[i / 8 - start] ^ bytes[i / 8]) & cfg::bit_mask::<C>(i) != 0;
+        match (offset..end).find(|&i| changed(i)) {
+            Some(offset) => Err(UnpackErrorKind::NonCanonical { offset }.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Canonical form check using [`DefaultCfg`]
+    fn check_canonical(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        Self::check_canonical_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+impl<T> Packed for T where T: Pack + Unpack {}
+
+impl<T> PackedSize for &T
+where
+    T: PackedSize + ?Sized,
+{
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T> Pack for &T
+where
+    T: Pack + ?Sized,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        (**self).pack_with::<C>(bytes, offset);
+    }
+}
+
+impl PackedSize for bool {
+    const SIZE: usize = 1;
+}
+
+impl Pack for bool {
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        let mask = cfg::bit_mask::<C>(offset);
+        if *self {
+            bytes[offset / 8] |= mask;
+        } else {
+            bytes[offset / 8] &= !mask;
+        }
     }
 }
 
-impl<T, const N: usize> Packed for [T; N]
+impl Unpack for bool {
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        bytes[offset / 8] & cfg::bit_mask::<C>(offset) != 0
+    }
+}
+
+impl<T, const N: usize> PackedSize for [T; N]
 where
-    T: Packed,
+    T: PackedSize,
 {
     const SIZE: usize = N * T::SIZE;
+}
 
-    fn unpack(bytes: &[u8], offset: usize) -> Self {
-        debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-        std::array::from_fn::<_, N, _>(|i| T::unpack(bytes, offset + i * T::SIZE))
+impl<T, const N: usize> Pack for [T; N]
+where
+    T: Pack,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        for (i, x) in self.iter().enumerate() {
+            x.pack_with::<C>(bytes, offset + i * T::SIZE);
+        }
+    }
+}
+
+impl<T, const N: usize> Unpack for [T; N]
+where
+    T: Unpack,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        std::array::from_fn::<_, N, _>(|i| T::unpack_with::<C>(bytes, offset + i * T::SIZE))
     }
 
-    fn pack(self, bytes: &mut [u8], offset: usize) {
-        debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-        for (i, x) in self.into_iter().enumerate() {
-            x.pack(bytes, offset + i * T::SIZE);
+    fn unpack_into_with<C: Cfg>(&mut self, bytes: &[u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        for (i, x) in self.iter_mut().enumerate() {
+            x.unpack_into_with::<C>(bytes, offset + i * T::SIZE);
         }
     }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        (0..N).try_for_each(|i| T::check_reserved_with::<C>(bytes, offset + i * T::SIZE))
+    }
 }
 
-impl Packed for u8 {
+impl PackedSize for u8 {
     const SIZE: usize = Self::BITS as _;
+}
 
-    fn unpack(bytes: &[u8], offset: usize) -> Self {
-        debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-        let bytes = &bytes[offset / 8..];
+impl Pack for u8 {
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        if cfg!(feature = "paranoid") {
+            return reference::pack_with::<C, _>(*self, bytes, offset);
+        }
+        let bytes = &mut bytes[offset / 8..];
         let offset = offset % 8;
 
-        let mut out = bytes[0] << offset;
-        if offset != 0 {
-            out |= bytes[1] >> (8 - offset)
+        if offset == 0 {
+            bytes[0] = *self;
+            return;
+        }
+        // bits of the first byte which are kept
+        let keep: u8 = match C::BIT_ORDER {
+            BitOrder::Msb0 => !((1 << (8 - offset)) - 1),
+            BitOrder::Lsb0 => (1 << offset) - 1,
+        };
+        match C::BIT_ORDER {
+            BitOrder::Msb0 => {
+                bytes[0] = bytes[0] & keep | *self >> offset;
+                bytes[1] = bytes[1] & !keep | *self << (8 - offset);
+            }
+            BitOrder::Lsb0 => {
+                bytes[0] = bytes[0] & keep | *self << offset;
+                bytes[1] = bytes[1] & !keep | *self >> (8 - offset);
+            }
         }
-        out
     }
+}
 
-    fn pack(self, bytes: &mut [u8], offset: usize) {
-        debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-        let bytes = &mut bytes[offset / 8..];
+impl Unpack for u8 {
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        if cfg!(feature = "paranoid") {
+            return reference::unpack_with::<C, _>(bytes, offset);
+        }
+        let bytes = &bytes[offset / 8..];
         let offset = offset % 8;
 
         if offset == 0 {
-            bytes[0] = self;
-        } else {
-            bytes[0] &= !((1 << (8 - offset)) - 1);
-            bytes[0] |= self >> offset;
-            let mask: u8 = (1 << offset) - 1;
-            let b = bytes[1] & !(mask << (8 - offset));
-            bytes[1] = (self & mask) << (8 - offset) | b;
+            return bytes[0];
+        }
+        match C::BIT_ORDER {
+            BitOrder::Msb0 => bytes[0] << offset | bytes[1] >> (8 - offset),
+            BitOrder::Lsb0 => bytes[0] >> offset | bytes[1] << (8 - offset),
         }
     }
 }
 
 macro_rules! packed_int {
     ($ty: ident) => {
-        impl Packed for $ty {
+        impl PackedSize for $ty {
             const SIZE: usize = Self::BITS as _;
+        }
 
-            fn unpack(bytes: &[u8], offset: usize) -> Self {
-                debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-                let x = Packed::unpack(bytes, offset);
-                Self::from_be_bytes(x)
+        impl Pack for $ty {
+            fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                self.to_bytes(C::BYTE_ORDER).pack_with::<C>(bytes, offset);
             }
+        }
 
-            fn pack(self, bytes: &mut [u8], offset: usize) {
-                debug_assert!(bytes.len() * 8 - offset >= Self::SIZE);
-                self.to_be_bytes().pack(bytes, offset);
+        impl Unpack for $ty {
+            fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                Self::from_bytes(Unpack::unpack_with::<C>(bytes, offset), C::BYTE_ORDER)
             }
         }
     };
@@ -124,15 +493,209 @@ macro_rules! packed_int {
     };
 }
 
-packed_int!(u16, u32, u64, u128, usize);
-packed_int!(i8, i16, i32, i64, i128, isize);
+packed_int!(u16, u32, u64, u128);
+packed_int!(i8, i16, i32, i64, i128);
+// the width of these depends on the target, see `portable`
+#[cfg(feature = "native-size")]
+packed_int!(usize, isize);
+
+macro_rules! packed_ptr {
+    ($ptr: ident) => {
+        impl<T> PackedSize for $ptr<T>
+        where
+            T: PackedSize,
+        {
+            const SIZE: usize = T::SIZE;
+        }
+
+        impl<T> Pack for $ptr<T>
+        where
+            T: Pack,
+        {
+            fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                (**self).pack_with::<C>(bytes, offset);
+            }
+        }
+
+        impl<T> Unpack for $ptr<T>
+        where
+            T: Unpack,
+        {
+            fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+                $ptr::new(T::unpack_with::<C>(bytes, offset))
+            }
+
+            fn unpack_into_with<C: Cfg>(&mut self, bytes: &[u8], offset: usize) {
+                match $ptr::get_mut(self) {
+                    Some(x) => x.unpack_into_with::<C>(bytes, offset),
+                    None => *self = Self::unpack_with::<C>(bytes, offset),
+                }
+            }
+
+            fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+                T::check_reserved_with::<C>(bytes, offset)
+            }
+        }
+    };
+}
+
+impl<T> PackedSize for Box<T>
+where
+    T: PackedSize,
+{
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T> Pack for Box<T>
+where
+    T: Pack,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        (**self).pack_with::<C>(bytes, offset);
+    }
+}
+
+impl<T> Unpack for Box<T>
+where
+    T: Unpack,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Box::new(T::unpack_with::<C>(bytes, offset))
+    }
+
+    fn unpack_into_with<C: Cfg>(&mut self, bytes: &[u8], offset: usize) {
+        (**self).unpack_into_with::<C>(bytes, offset);
+    }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        T::check_reserved_with::<C>(bytes, offset)
+    }
+}
+
+packed_ptr!(Rc);
+packed_ptr!(Arc);
+
+macro_rules! packed_atomic {
+    ($($atomic: ident($ty: ident)),+) => {
+        $(impl PackedSize for $atomic {
+            const SIZE: usize = $ty::SIZE;
+        }
+
+        impl Pack for $atomic {
+            fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                self.load(Ordering::Relaxed).pack_with::<C>(bytes, offset);
+            }
+        }
 
-impl Packed for () {
+        impl Unpack for $atomic {
+            fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+                $atomic::new($ty::unpack_with::<C>(bytes, offset))
+            }
+
+            fn unpack_into_with<C: Cfg>(&mut self, bytes: &[u8], offset: usize) {
+                *self.get_mut() = $ty::unpack_with::<C>(bytes, offset);
+            }
+        })+
+    };
+}
+
+packed_atomic!(
+    AtomicBool(bool),
+    AtomicU8(u8),
+    AtomicU16(u16),
+    AtomicU32(u32)
+);
+packed_atomic!(AtomicI8(i8), AtomicI16(i16), AtomicI32(i32));
+#[cfg(target_has_atomic = "64")]
+packed_atomic!(AtomicU64(u64), AtomicI64(i64));
+
+/// A tag bit, set for `Err`, followed by the payload.  The payload takes up
+/// the size of the larger of `T` and `E`, and the bits the smaller one
+/// doesn't use are zeroed and checked like [`Reserved`] bits.
+impl<T, E> PackedSize for Result<T, E>
+where
+    T: PackedSize,
+    E: PackedSize,
+{
+    const SIZE: usize = 1 + if T::SIZE > E::SIZE { T::SIZE } else { E::SIZE };
+}
+
+impl<T, E> Pack for Result<T, E>
+where
+    T: Pack,
+    E: Pack,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        let used = match self {
+            Ok(x) => {
+                false.pack_with::<C>(bytes, offset);
+                x.pack_with::<C>(bytes, offset + 1);
+                T::SIZE
+            }
+            Err(e) => {
+                true.pack_with::<C>(bytes, offset);
+                e.pack_with::<C>(bytes, offset + 1);
+                E::SIZE
+            }
+        };
+        for i in 1 + used..Self::SIZE {
+            false.pack_with::<C>(bytes, offset + i);
+        }
+    }
+}
+
+impl<T, E> Unpack for Result<T, E>
+where
+    T: Unpack,
+    E: Unpack,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        if bool::unpack_with::<C>(bytes, offset) {
+            Err(E::unpack_with::<C>(bytes, offset + 1))
+        } else {
+            Ok(T::unpack_with::<C>(bytes, offset + 1))
+        }
+    }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        let used = if bool::unpack_with::<C>(bytes, offset) {
+            E::check_reserved_with::<C>(bytes, offset + 1)?;
+            E::SIZE
+        } else {
+            T::check_reserved_with::<C>(bytes, offset + 1)?;
+            T::SIZE
+        };
+        // the bits the smaller payload doesn't use are reserved
+        Reserved::<0>::check_bits::<C>(bytes, offset + 1 + used, Self::SIZE - 1 - used)
+    }
+}
+
+impl<T: ?Sized> PackedSize for PhantomData<T> {
     const SIZE: usize = 0;
+}
 
-    fn unpack(_: &[u8], _: usize) -> Self {}
+impl<T: ?Sized> Pack for PhantomData<T> {
+    fn pack_with<C: Cfg>(&self, _: &mut [u8], _: usize) {}
+}
+
+impl<T: ?Sized> Unpack for PhantomData<T> {
+    fn unpack_with<C: Cfg>(_: &[u8], _: usize) -> Self {
+        PhantomData
+    }
+}
+
+impl PackedSize for () {
+    const SIZE: usize = 0;
+}
+
+impl Pack for () {
+    fn pack_with<C: Cfg>(&self, _: &mut [u8], _: usize) {}
+}
 
-    fn pack(self, _: &mut [u8], _: usize) {}
+impl Unpack for () {
+    fn unpack_with<C: Cfg>(_: &[u8], _: usize) -> Self {}
 }
 
 macro_rules! tuple_impl {
@@ -140,29 +703,55 @@ macro_rules! tuple_impl {
     (($($x: ident,)+)) => {
         tuple_impl!(@tail2 $($x,)+);
 
-        impl<$($x,)+> Packed for ($($x,)+)
+        impl<$($x,)+> PackedSize for ($($x,)+)
         where
-            $($x: Packed,)+
+            $($x: PackedSize,)+
         {
             const SIZE: usize = <tuple_impl!(@head $($x,)+)>::SIZE + <tuple_impl!(@tail $($x,)+)>::SIZE;
+        }
+
+        impl<$($x,)+> Pack for ($($x,)+)
+        where
+            $($x: Pack,)+
+        {
+            #[allow(non_snake_case, unused_assignments)]
+            fn pack_with<CFG: Cfg>(&self, bytes: &mut [u8], mut offset: usize) {
+                let ($($x,)+) = self;
+                $(
+                    $x.pack_with::<CFG>(bytes, offset);
+                    offset += <$x as PackedSize>::SIZE;
+                )+
+            }
+        }
 
+        impl<$($x,)+> Unpack for ($($x,)+)
+        where
+            $($x: Unpack,)+
+        {
             #[allow(unused_assignments)]
-            fn unpack(bytes: &[u8], mut offset: usize) -> Self {
+            fn unpack_with<CFG: Cfg>(bytes: &[u8], mut offset: usize) -> Self {
                 (
                     $({
-                        let x = $x::unpack(bytes, offset);
+                        let x = $x::unpack_with::<CFG>(bytes, offset);
                         offset += $x::SIZE;
                         x
                     },)+
                 )
             }
 
-            fn pack(self, bytes: &mut [u8], offset: usize) {
-                let a_sz = <tuple_impl!(@head $($x,)+)>::SIZE;
-                #[allow(non_snake_case)]
+            #[allow(non_snake_case, unused_assignments)]
+            fn unpack_into_with<CFG: Cfg>(&mut self, bytes: &[u8], mut offset: usize) {
                 let ($($x,)+) = self;
-                tuple_impl!(@head $($x,)+).pack(bytes, offset);
-                tuple_impl!(@tail $($x,)+).pack(bytes, offset + a_sz);
+                $(
+                    $x.unpack_into_with::<CFG>(bytes, offset);
+                    offset += <$x as PackedSize>::SIZE;
+                )+
+            }
+
+            fn check_reserved_with<CFG: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+                let a_sz = <tuple_impl!(@head $($x,)+)>::SIZE;
+                <tuple_impl!(@head $($x,)+)>::check_reserved_with::<CFG>(bytes, offset)?;
+                <tuple_impl!(@tail $($x,)+)>::check_reserved_with::<CFG>(bytes, offset + a_sz)
             }
         }
     };
@@ -0,0 +1,249 @@
+//! Integers packed into fewer bits than their type.
+//!
+//! Used by the derive for `#[packed(bits = N)]` fields.  Unsigned values are
+//! masked to their low `N` bits.  Signed values are stored as `N`-bit two's
+//! complement and sign-extended on unpack, so `-1` packed into 3 bits comes
+//! back as `-1` rather than `7`.
+//!
+//! Narrow fields are always written as a single run of bits in the bit order
+//! of the [`Cfg`]; the byte order only applies to full-width integers.
+//!
+//! ```rust
+//! # use packed::narrow::NarrowInt;
+//! # use packed::cfg::DefaultCfg;
+//! let mut buf = [0u8; 1];
+//! (-3i8).pack_narrow::<DefaultCfg>(4, &mut buf, 0);
+//! assert_eq!(buf, [0b1101_0000]);
+//! assert_eq!(i8::unpack_narrow::<DefaultCfg>(4, &buf, 0), -3);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::bitfield;
+use crate::cfg::{BitOrder, ByteOrder, Cfg, OrderedBytes};
+use crate::error::bit_len;
+use crate::{Pack, Unpack};
+
+/// Mask selecting the low `bits` bits
+const fn mask(bits: usize) -> u128 {
+    if bits >= 128 { !0 } else { (1 << bits) - 1 }
+}
+
+/// Where a value of at most 8 bits sits in the 16-bit window made of the
+/// byte it starts in and the byte after
+#[derive(Clone, Copy)]
+struct Slot {
+    shift: u32,
+    mask: u16,
+    /// Whether the value runs into the second byte
+    spans: bool,
+}
+
+/// Slots indexed by width and then offset within the first byte.  The first
+/// byte is the high half of the window for [`BitOrder::Msb0`] and the low
+/// half for [`BitOrder::Lsb0`].
+const fn slots(order: BitOrder) -> [[Slot; 8]; 9] {
+    let empty = Slot {
+        shift: 0,
+        mask: 0,
+        spans: false,
+    };
+    let mut out = [[empty; 8]; 9];
+    let mut bits = 0;
+    while bits <= 8 {
+        let mut bit = 0;
+        while bit < 8 {
+            let shift = match order {
+                BitOrder::Msb0 => 16 - bit - bits,
+                BitOrder::Lsb0 => bit,
+            };
+            out[bits][bit] = Slot {
+                shift: shift as u32,
+                mask: (((1u32 << bits) - 1) << shift) as u16,
+                spans: bit + bits > 8,
+            };
+            bit += 1;
+        }
+        bits += 1;
+    }
+    out
+}
+
+const MSB0_SLOTS: [[Slot; 8]; 9] = slots(BitOrder::Msb0);
+const LSB0_SLOTS: [[Slot; 8]; 9] = slots(BitOrder::Lsb0);
+
+/// The slot for `bits` bits at `offset`, and the window around it
+fn window<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> (Slot, u16) {
+    let (i, bit) = (offset / 8, offset % 8);
+    let (slot, first, second) = match C::BIT_ORDER {
+        BitOrder::Msb0 => (MSB0_SLOTS[bits][bit], 8, 0),
+        BitOrder::Lsb0 => (LSB0_SLOTS[bits][bit], 0, 8),
+    };
+    let mut window = u16::from(bytes[i]) << first;
+    if slot.spans {
+        window |= u16::from(bytes[i + 1]) << second;
+    }
+    (slot, window)
+}
+
+/// [`write_bits`] for at most 8 bits, in one or two byte accesses
+fn write_small<C: Cfg>(value: u8, bits: usize, bytes: &mut [u8], offset: usize) {
+    let (slot, window) = window::<C>(bits, bytes, offset);
+    let window = window & !slot.mask | (u32::from(value) << slot.shift) as u16 & slot.mask;
+    let [hi, lo] = window.to_be_bytes();
+    let (first, second) = match C::BIT_ORDER {
+        BitOrder::Msb0 => (hi, lo),
+        BitOrder::Lsb0 => (lo, hi),
+    };
+    bytes[offset / 8] = first;
+    if slot.spans {
+        bytes[offset / 8 + 1] = second;
+    }
+}
+
+/// [`read_bits`] for at most 8 bits, in one or two byte accesses
+fn read_small<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> u8 {
+    let (slot, window) = window::<C>(bits, bytes, offset);
+    (u32::from(window & slot.mask) >> slot.shift) as u8
+}
+
+/// Longest field [`write_word`] and [`read_word`] handle at any offset
+const WORD_BITS: usize = 57;
+
+/// Byte order a 64-bit window is loaded in, so that bits run from the most
+/// significant end of the word for [`BitOrder::Msb0`] and from the least
+/// significant end for [`BitOrder::Lsb0`]
+const fn word_order<C: Cfg>() -> ByteOrder {
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => ByteOrder::Big,
+        BitOrder::Lsb0 => ByteOrder::Little,
+    }
+}
+
+/// The 64-bit window of the bytes a field of `bits` bits at `offset`
+/// touches, the mask of the field within it, and how many bytes it spans
+fn word_window<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> (u64, u64, usize) {
+    let (i, bit) = (offset / 8, offset % 8);
+    let len = (bit + bits).div_ceil(8);
+    let mut buf = [0u8; 8];
+    buf[..len].copy_from_slice(&bytes[i..i + len]);
+    let word = u64::from_bytes(buf, word_order::<C>());
+    let field = (1u64 << bits) - 1;
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => (word, field << (64 - bit - bits), len),
+        BitOrder::Lsb0 => (word, field << bit, len),
+    }
+}
+
+/// [`write_bits`] for at most [`WORD_BITS`] bits, through a 64-bit window
+fn write_word<C: Cfg>(value: u64, bits: usize, bytes: &mut [u8], offset: usize) {
+    let (word, mask, len) = word_window::<C>(bits, bytes, offset);
+    let word = bitfield::set_bits(word, mask, value);
+    let buf = word.to_bytes(word_order::<C>());
+    bytes[offset / 8..offset / 8 + len].copy_from_slice(&buf[..len]);
+}
+
+/// [`read_bits`] for at most [`WORD_BITS`] bits, through a 64-bit window
+fn read_word<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> u64 {
+    let (word, mask, _) = word_window::<C>(bits, bytes, offset);
+    bitfield::get_bits(word, mask)
+}
+
+/// Write the low `bits` bits of `value` at `offset`
+pub(crate) fn write_bits<C: Cfg>(value: u128, bits: usize, bytes: &mut [u8], offset: usize) {
+    debug_assert!(bit_len(bytes) - offset >= bits);
+    match bits {
+        _ if cfg!(feature = "paranoid") => write_each::<C>(value, bits, bytes, offset),
+        1..=8 => write_small::<C>(value as u8, bits, bytes, offset),
+        9..=WORD_BITS => write_word::<C>(value as u64, bits, bytes, offset),
+        _ => write_each::<C>(value, bits, bytes, offset),
+    }
+}
+
+/// Read `bits` bits at `offset` into the low bits of the result
+pub(crate) fn read_bits<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> u128 {
+    debug_assert!(bit_len(bytes) - offset >= bits);
+    match bits {
+        _ if cfg!(feature = "paranoid") => read_each::<C>(bits, bytes, offset),
+        1..=8 => read_small::<C>(bits, bytes, offset).into(),
+        9..=WORD_BITS => read_word::<C>(bits, bytes, offset).into(),
+        _ => read_each::<C>(bits, bytes, offset),
+    }
+}
+
+/// [`write_bits`] one bit at a time
+fn write_each<C: Cfg>(value: u128, bits: usize, bytes: &mut [u8], offset: usize) {
+    for i in 0..bits {
+        let bit = match C::BIT_ORDER {
+            BitOrder::Msb0 => bits - 1 - i,
+            BitOrder::Lsb0 => i,
+        };
+        (value >> bit & 1 != 0).pack_with::<C>(bytes, offset + i);
+    }
+}
+
+/// [`read_bits`] one bit at a time
+fn read_each<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> u128 {
+    let mut out = 0;
+    for i in 0..bits {
+        let bit = match C::BIT_ORDER {
+            BitOrder::Msb0 => bits - 1 - i,
+            BitOrder::Lsb0 => i,
+        };
+        out |= u128::from(bool::unpack_with::<C>(bytes, offset + i)) << bit;
+    }
+    out
+}
+
+/// An integer that can be packed into its low `bits` bits
+pub trait NarrowInt: Copy {
+    const BITS: usize;
+
+    fn pack_narrow<C: Cfg>(self, bits: usize, bytes: &mut [u8], offset: usize);
+    fn unpack_narrow<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> Self;
+}
+
+macro_rules! narrow_unsigned {
+    ($($ty: ident),+) => {
+        $(impl NarrowInt for $ty {
+            const BITS: usize = Self::BITS as _;
+
+            fn pack_narrow<C: Cfg>(self, bits: usize, bytes: &mut [u8], offset: usize) {
+                debug_assert!(bits <= Self::BITS as usize);
+                write_bits::<C>(self as u128 & mask(bits), bits, bytes, offset);
+            }
+
+            fn unpack_narrow<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> Self {
+                debug_assert!(bits <= Self::BITS as usize);
+                read_bits::<C>(bits, bytes, offset) as Self
+            }
+        })+
+    };
+}
+
+macro_rules! narrow_signed {
+    ($($ty: ident),+) => {
+        $(impl NarrowInt for $ty {
+            const BITS: usize = Self::BITS as _;
+
+            fn pack_narrow<C: Cfg>(self, bits: usize, bytes: &mut [u8], offset: usize) {
+                debug_assert!(bits <= Self::BITS as usize);
+                write_bits::<C>(self as i128 as u128 & mask(bits), bits, bytes, offset);
+            }
+
+            fn unpack_narrow<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> Self {
+                debug_assert!(bits <= Self::BITS as usize);
+                if bits == 0 {
+                    return 0;
+                }
+                let raw = read_bits::<C>(bits, bytes, offset);
+                // move the sign bit to the top and shift back arithmetically
+                ((raw << (128 - bits)) as i128 >> (128 - bits)) as Self
+            }
+        })+
+    };
+}
+
+narrow_unsigned!(u8, u16, u32, u64, u128, usize);
+narrow_signed!(i8, i16, i32, i64, i128, isize);
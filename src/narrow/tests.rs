@@ -0,0 +1,100 @@
+use super::NarrowInt;
+use crate::cfg::{DefaultCfg, Lsb0Le};
+
+fn roundtrip<T: NarrowInt + Copy>(n: T, bits: usize) -> T {
+    let mut buf = [0u8; 20];
+    n.pack_narrow::<DefaultCfg>(bits, &mut buf, 5);
+    T::unpack_narrow::<DefaultCfg>(bits, &buf, 5)
+}
+
+#[test]
+fn signed_boundaries() {
+    assert_eq!(roundtrip(-1i8, 1), -1);
+    assert_eq!(roundtrip(-1i8, 3), -1);
+    assert_eq!(roundtrip(-4i8, 3), -4);
+    assert_eq!(roundtrip(3i8, 3), 3);
+    assert_eq!(roundtrip(i8::MIN, 8), i8::MIN);
+    assert_eq!(roundtrip(i8::MAX, 8), i8::MAX);
+    assert_eq!(roundtrip(-2048i16, 12), -2048);
+    assert_eq!(roundtrip(2047i16, 12), 2047);
+    assert_eq!(roundtrip(i128::MIN, 128), i128::MIN);
+    assert_eq!(roundtrip(-1i128, 127), -1);
+    assert_eq!(roundtrip(0i32, 0), 0);
+}
+
+#[test]
+fn masking() {
+    // out of range values are truncated to the field width
+    assert_eq!(roundtrip(0xffu8, 4), 0xf);
+    assert_eq!(roundtrip(4i8, 3), -4);
+    assert_eq!(roundtrip(u128::MAX, 128), u128::MAX);
+
+    let mut buf = [0xffu8; 2];
+    0u16.pack_narrow::<DefaultCfg>(4, &mut buf, 6);
+    assert_eq!(buf, [0b1111_1100, 0b0011_1111]);
+}
+
+#[test]
+fn lsb0() {
+    let mut buf = [0u8; 2];
+    (-3i8).pack_narrow::<Lsb0Le>(4, &mut buf, 6);
+    assert_eq!(buf, [0b0100_0000, 0b0000_0011]);
+    assert_eq!(i8::unpack_narrow::<Lsb0Le>(4, &buf, 6), -3);
+}
+
+proptest::proptest! {
+    #[test]
+    fn signed_in_range(n in -(1i32 << 19)..(1i32 << 19)) {
+        assert_eq!(roundtrip(n, 20), n);
+    }
+
+    #[test]
+    fn unsigned_in_range(n in 0u32..(1 << 19)) {
+        assert_eq!(roundtrip(n, 19), n);
+    }
+}
+
+fn small_matches_each<C: crate::cfg::Cfg>() {
+    use super::{read_bits, read_each, write_bits, write_each};
+    for bits in 0..=8 {
+        for offset in 0..16 - bits {
+            for value in 0..1u128 << bits {
+                for background in [0x00, 0xff, 0xa5] {
+                    let mut fast = [background; 2];
+                    let mut slow = [background; 2];
+                    write_bits::<C>(value, bits, &mut fast, offset);
+                    write_each::<C>(value, bits, &mut slow, offset);
+                    assert_eq!(fast, slow, "{value} in {bits} bits at {offset}");
+                    assert_eq!(read_bits::<C>(bits, &fast, offset), value);
+                    assert_eq!(read_each::<C>(bits, &fast, offset), value);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn small_widths() {
+    small_matches_each::<crate::cfg::Msb0Be>();
+    small_matches_each::<crate::cfg::Msb0Le>();
+    small_matches_each::<Lsb0Le>();
+    small_matches_each::<crate::cfg::Lsb0Be>();
+}
+
+proptest::proptest! {
+    #[test]
+    fn word_widths(value: u64, bits in 9usize..=64, offset in 0usize..16, background: u8) {
+        use super::{read_bits, read_each, write_bits, write_each};
+        let value = u128::from(value) & ((1 << bits) - 1);
+        let mut fast = [background; 10];
+        let mut slow = [background; 10];
+        write_bits::<DefaultCfg>(value, bits, &mut fast, offset);
+        write_each::<DefaultCfg>(value, bits, &mut slow, offset);
+        assert_eq!(fast, slow);
+        assert_eq!(read_bits::<DefaultCfg>(bits, &fast, offset), value);
+        write_bits::<Lsb0Le>(value, bits, &mut fast, offset);
+        write_each::<Lsb0Le>(value, bits, &mut slow, offset);
+        assert_eq!(fast, slow);
+        assert_eq!(read_bits::<Lsb0Le>(bits, &fast, offset), read_each::<Lsb0Le>(bits, &slow, offset));
+    }
+}
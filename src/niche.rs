@@ -0,0 +1,202 @@
+//! Packing `Option`s into the unused bit patterns of their value.
+//!
+//! Fields of type `Option<T>` marked `#[packed(niche)]` take up no more bits
+//! than `T` itself, as long as `T` implements [`Niche`].  The encoding of
+//! `None` is part of the wire format and won't change:
+//!
+//! * `NonZero` integers pack `None` as all zeros
+//!
+//! For formats with their own convention for absent values, such as `0xffff`
+//! for a missing `u16`, [`SentinelOption`] packs `None` as a given bit
+//! pattern of any [`Packed`](crate::Packed) type.
+//!
+//! Niche fields can also be narrowed with `#[packed(bits = N, niche)]`.
+//! Like other narrow fields, values which don't fit in `N` bits are
+//! truncated, which may turn them into `None`.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use std::num::NonZeroU8;
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct Entry {
+//!     #[packed(bits = 4, niche)]
+//!     channel: Option<NonZeroU8>,
+//!     #[packed(bits = 4)]
+//!     level: u8,
+//! }
+//!
+//! assert_eq!(Entry::SIZE, 8);
+//! let mut buf = [0u8; 1];
+//! Entry { channel: None, level: 3 }.pack(&mut buf, 0);
+//! assert_eq!(buf, [0x03]);
+//! Entry { channel: NonZeroU8::new(12), level: 3 }.pack(&mut buf, 0);
+//! assert_eq!(buf, [0xc3]);
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::num::{
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroU8, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU128,
+};
+
+use crate::cfg::{BitOrder, ByteOrder, Cfg};
+use crate::error::bit_len;
+use crate::narrow::{NarrowInt, read_bits, write_bits};
+use crate::{Pack, PackedSize, Unpack, UnpackError};
+
+/// A type with a bit pattern no value uses, which can stand in for `None`
+pub trait Niche: Copy {
+    /// Width of the type when it isn't narrowed
+    const BITS: usize;
+
+    /// Pack `value` into `bits` bits, using the niche for `None`
+    fn pack_niche<C: Cfg>(value: Option<Self>, bits: usize, bytes: &mut [u8], offset: usize);
+    /// Unpack `bits` bits, which are `None` if they hold the niche
+    fn unpack_niche<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> Option<Self>;
+}
+
+macro_rules! niche_nonzero {
+    ($($ty: ident($int: ident)),+) => {
+        $(impl Niche for $ty {
+            const BITS: usize = $int::BITS as _;
+
+            fn pack_niche<C: Cfg>(value: Option<Self>, bits: usize, bytes: &mut [u8], offset: usize) {
+                value.map_or(0, $ty::get).pack_narrow::<C>(bits, bytes, offset);
+            }
+
+            fn unpack_niche<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> Option<Self> {
+                $ty::new($int::unpack_narrow::<C>(bits, bytes, offset))
+            }
+        })+
+    };
+}
+
+niche_nonzero!(
+    NonZeroU8(u8),
+    NonZeroU16(u16),
+    NonZeroU32(u32),
+    NonZeroU64(u64),
+    NonZeroU128(u128)
+);
+niche_nonzero!(
+    NonZeroI8(i8),
+    NonZeroI16(i16),
+    NonZeroI32(i32),
+    NonZeroI64(i64),
+    NonZeroI128(i128)
+);
+
+/// An `Option<T>` taking up exactly `T::SIZE` bits, with `None` packed as the
+/// bit pattern `SENTINEL`.
+///
+/// The sentinel is packed like an unsigned integer of `T::SIZE` bits, so
+/// for integers it is their value in any [`Cfg`], and must fit in `T::SIZE`
+/// bits.  A `Some` value whose bits equal the sentinel unpacks as `None`.
+///
+/// ```rust
+/// # use packed::prelude::*;
+/// # use packed::niche::SentinelOption;
+/// type Reading = SentinelOption<u16, 0xffff>;
+/// assert_eq!(Reading::SIZE, 16);
+/// let mut buf = [0u8; 2];
+/// Reading::from(None).pack(&mut buf, 0);
+/// assert_eq!(buf, [0xff, 0xff]);
+/// assert_eq!(Reading::unpack(&[0x01, 0x02], 0).0, Some(0x0102));
+/// ```
+///
+/// ```rust,compile_fail
+/// # use packed::prelude::*;
+/// # use packed::niche::SentinelOption;
+/// # use packed::cfg::Lsb0Le;
+/// // 0x1ff doesn't fit in a `u8`
+/// let mut buf = [0u8; 1];
+/// SentinelOption::<u8, 0x1ff>::from(None).pack_with::<Lsb0Le>(&mut buf, 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SentinelOption<T, const SENTINEL: u128>(pub Option<T>);
+
+impl<T, const SENTINEL: u128> From<Option<T>> for SentinelOption<T, SENTINEL> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T, const SENTINEL: u128> From<SentinelOption<T, SENTINEL>> for Option<T> {
+    fn from(value: SentinelOption<T, SENTINEL>) -> Self {
+        value.0
+    }
+}
+
+impl<T, const SENTINEL: u128> PackedSize for SentinelOption<T, SENTINEL>
+where
+    T: PackedSize,
+{
+    const SIZE: usize = {
+        assert!(T::SIZE <= 128, "sentinel values are at most 128 bits wide");
+        assert!(
+            T::SIZE == 128 || SENTINEL >> T::SIZE == 0,
+            "the sentinel doesn't fit in the value"
+        );
+        T::SIZE
+    };
+}
+
+impl<T, const SENTINEL: u128> SentinelOption<T, SENTINEL>
+where
+    T: PackedSize,
+{
+    /// The run of bits `None` is packed as with `C`.  Whole bytes are
+    /// swapped when the byte order runs against the bit order, as they are
+    /// for integers.
+    fn sentinel<C: Cfg>() -> u128 {
+        let in_order = matches!(
+            (C::BIT_ORDER, C::BYTE_ORDER),
+            (BitOrder::Msb0, ByteOrder::Big) | (BitOrder::Lsb0, ByteOrder::Little)
+        );
+        if in_order || Self::SIZE == 0 || Self::SIZE % 8 != 0 {
+            SENTINEL
+        } else {
+            (SENTINEL << (128 - Self::SIZE)).swap_bytes()
+        }
+    }
+}
+
+impl<T, const SENTINEL: u128> Pack for SentinelOption<T, SENTINEL>
+where
+    T: Pack,
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        match &self.0 {
+            Some(x) => x.pack_with::<C>(bytes, offset),
+            None => write_bits::<C>(Self::sentinel::<C>(), Self::SIZE, bytes, offset),
+        }
+    }
+}
+
+impl<T, const SENTINEL: u128> Unpack for SentinelOption<T, SENTINEL>
+where
+    T: Unpack,
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        if read_bits::<C>(Self::SIZE, bytes, offset) == Self::sentinel::<C>() {
+            Self(None)
+        } else {
+            Self(Some(T::unpack_with::<C>(bytes, offset)))
+        }
+    }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        if read_bits::<C>(Self::SIZE, bytes, offset) == Self::sentinel::<C>() {
+            Ok(())
+        } else {
+            T::check_reserved_with::<C>(bytes, offset)
+        }
+    }
+}
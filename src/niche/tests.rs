@@ -0,0 +1,133 @@
+#[cfg(feature = "derive")]
+use std::num::{NonZeroI16, NonZeroU8, NonZeroU32};
+
+use crate::cfg::Lsb0Le;
+use crate::{Pack, Unpack};
+#[cfg(feature = "derive")]
+use crate::{Packed, PackedSize};
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Packed)]
+struct Sample {
+    #[packed(niche)]
+    id: Option<NonZeroU32>,
+    #[packed(bits = 5, niche)]
+    delta: Option<NonZeroI16>,
+    flag: bool,
+    #[packed(bits = 3, niche)]
+    slot: Option<NonZeroU8>,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn size() {
+    assert_eq!(Sample::SIZE, 32 + 5 + 1 + 3);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn none_is_zero() {
+    let mut buf = [0xffu8; 6];
+    let sample = Sample {
+        id: None,
+        delta: None,
+        flag: true,
+        slot: None,
+    };
+    sample.pack(&mut buf, 0);
+    assert_eq!(buf, [0, 0, 0, 0, 0b0000_0100, 0b0111_1111]);
+    assert_eq!(Sample::unpack(&buf, 0), sample);
+}
+
+#[cfg(feature = "derive")]
+proptest::proptest! {
+    #[test]
+    fn pack_unpack_niche(
+        id: Option<NonZeroU32>,
+        delta in proptest::option::of(-16i16..16),
+        flag: bool,
+        slot in proptest::option::of(1u8..8),
+        offset in 0usize..=16,
+    ) {
+        let sample = Sample {
+            id,
+            delta: delta.and_then(NonZeroI16::new),
+            flag,
+            slot: slot.and_then(NonZeroU8::new),
+        };
+        let mut buf = [0u8; 8];
+        sample.pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(Sample::unpack_with::<Lsb0Le>(&buf, offset), sample);
+    }
+}
+
+#[test]
+fn sentinel_cfgs() {
+    use super::SentinelOption;
+    use crate::cfg::Msb0Be;
+    type Word = SentinelOption<u16, 0x8001>;
+    let mut buf = [0u8; 3];
+    Word::from(None).pack_with::<Msb0Be>(&mut buf, 4);
+    assert_eq!(buf, [0x08, 0x00, 0x10]);
+    assert_eq!(Word::unpack_with::<Msb0Be>(&buf, 4), Word::from(None));
+    Word::from(None).pack_with::<Lsb0Le>(&mut buf, 4);
+    assert_eq!(u16::unpack_with::<Lsb0Le>(&buf, 4), 0x8001);
+    assert_eq!(Word::unpack_with::<Lsb0Le>(&buf, 4).0, None);
+    // the sentinel value itself can't be told apart from `None`
+    Word::from(Some(0x8001)).pack_with::<Lsb0Le>(&mut buf, 4);
+    assert_eq!(Word::unpack_with::<Lsb0Le>(&buf, 4).0, None);
+}
+
+#[test]
+fn sentinel_byte_order() {
+    use super::SentinelOption;
+    use crate::cfg::{Cfg, Lsb0Be, Msb0Be, Msb0Le};
+
+    type Word = SentinelOption<u16, 0x00ff>;
+    fn check<C: Cfg>() {
+        let mut buf = [0u8; 3];
+        Word::from(None).pack_with::<C>(&mut buf, 4);
+        assert_eq!(u16::unpack_with::<C>(&buf, 4), 0x00ff);
+        assert_eq!(Word::unpack_with::<C>(&buf, 4).0, None);
+        // the sentinel with its bytes swapped is an ordinary value
+        Word::from(Some(0xff00)).pack_with::<C>(&mut buf, 4);
+        assert_eq!(Word::unpack_with::<C>(&buf, 4).0, Some(0xff00));
+        assert_eq!(
+            Word::unpack_strict_with::<C>(&buf, 4).unwrap().0,
+            Some(0xff00)
+        );
+    }
+    check::<Msb0Be>();
+    check::<Msb0Le>();
+    check::<Lsb0Be>();
+    check::<Lsb0Le>();
+}
+
+proptest::proptest! {
+    #[test]
+    fn pack_unpack_sentinel(value in proptest::option::of(0u32..u32::MAX), offset in 0usize..=16) {
+        use super::SentinelOption;
+        type Field = SentinelOption<(u16, u16), { u32::MAX as u128 }>;
+        let value = Field::from(value.map(|x| ((x >> 16) as u16, x as u16)));
+        let mut buf = [0u8; 7];
+        value.pack(&mut buf, offset);
+        assert_eq!(Field::unpack(&buf, offset), value);
+        value.pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(Field::unpack_with::<Lsb0Le>(&buf, offset), value);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn sentinel_serde_roundtrip() {
+    use super::SentinelOption;
+
+    for value in [None, Some(0x1234)] {
+        let reading = SentinelOption::<u16, 0xffff>(value);
+        let json = serde_json::to_string(&reading).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SentinelOption<u16, 0xffff>>(&json).unwrap(),
+            reading
+        );
+    }
+}
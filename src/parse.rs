@@ -0,0 +1,351 @@
+//! Parsers for formats whose fields vary in size.
+//!
+//! A [`Parser`] unpacks a value and returns it along with the amount of bits
+//! it read, so parsers can be chained without threading offsets by hand.
+//! [`fixed`] parses any [`Unpack`] type and [`varint`] parses a
+//! [`varint`](crate::varint), and they are combined with [`Parser::then`],
+//! [`Parser::map`], [`Parser::and_then`] and [`Parser::repeat_n`].
+//!
+//! ```rust
+//! # use packed::parse::{self, Parser};
+//! // a varint count followed by that many bytes
+//! let counted = parse::varint().and_then(|n| parse::fixed::<u8>().repeat_n(n as usize));
+//! let buf = [0x02, 0x12, 0x34];
+//! assert_eq!(counted.parse(&buf, 0), Ok((vec![0x12, 0x34], 24)));
+//!
+//! let pair = parse::fixed::<bool>().then(parse::fixed::<u8>()).map(|(a, b)| a as u8 + b);
+//! assert_eq!(pair.parse(&[0x80, 0x80], 0), Ok((2, 9)));
+//! ```
+//!
+//! Counts read from the input are trusted, so parsers facing untrusted
+//! input should be run with [`Parser::parse_limited`], which bounds the
+//! amount of values repeated, how deeply parsers are nested and how many
+//! bits are read in total.
+//!
+//! ```rust
+//! # use packed::parse::{self, Limits, Parser};
+//! # use packed::UnpackErrorKind;
+//! let counted = parse::varint().and_then(|n| parse::fixed::<()>().repeat_n(n as usize));
+//! let limits = Limits {
+//!     max_count: 1024,
+//!     ..Limits::NONE
+//! };
+//! let buf = [0xff, 0xff, 0xff, 0xff, 0x0f];
+//! assert_eq!(
+//!     counted.parse_limited(&buf, 0, limits),
+//!     Err(UnpackErrorKind::Limit { offset: 40 }.into())
+//! );
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::check_len;
+use crate::{Unpack, UnpackError, UnpackErrorKind};
+
+/// Unpacks a value which may vary in size
+pub trait Parser {
+    type Output;
+
+    /// Parse a value at `offset`, returning it along with the amount of bits
+    /// read
+    fn parse_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> Result<(Self::Output, usize), UnpackError>;
+
+    /// Parse using [`DefaultCfg`]
+    fn parse(&self, bytes: &[u8], offset: usize) -> Result<(Self::Output, usize), UnpackError> {
+        self.parse_with::<DefaultCfg>(bytes, offset)
+    }
+
+    /// Like [`Parser::parse_with`], but fails with
+    /// [`UnpackErrorKind::Limit`] rather than going past `limits`.  The
+    /// combinators pass what is left of the limits on to their parts, other
+    /// parsers are only checked against `max_bits` once they are done.
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        let (x, len) = self.parse_with::<C>(bytes, offset)?;
+        limits.consume(offset + len, len)?;
+        Ok((x, len))
+    }
+
+    /// Parse with limits using [`DefaultCfg`]
+    fn parse_limited(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        self.parse_limited_with::<DefaultCfg>(bytes, offset, limits)
+    }
+
+    /// Parse this and then `next` right after it, returning both values
+    fn then<P>(self, next: P) -> Then<Self, P>
+    where
+        Self: Sized,
+        P: Parser,
+    {
+        Then(self, next)
+    }
+
+    /// Transform the parsed value with `f`
+    fn map<F, U>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Output) -> U,
+    {
+        Map(self, f)
+    }
+
+    /// Parse this, then choose the parser for what follows based on its
+    /// value, e.g. a length prefix
+    fn and_then<F, P>(self, f: F) -> AndThen<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Output) -> P,
+        P: Parser,
+    {
+        AndThen(self, f)
+    }
+
+    /// Parse `n` values one after another
+    fn repeat_n(self, n: usize) -> RepeatN<Self>
+    where
+        Self: Sized,
+    {
+        RepeatN(self, n)
+    }
+}
+
+/// Bounds on what a parse may do, for input which can't be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// Most values a single [`Parser::repeat_n`] may parse
+    pub max_count: usize,
+    /// Most [`Parser::and_then`] and [`Parser::repeat_n`] parsers which may
+    /// be nested inside each other
+    pub max_depth: usize,
+    /// Most bits which may be read in total
+    pub max_bits: usize,
+}
+
+impl Limits {
+    /// No limits at all
+    pub const NONE: Self = Self {
+        max_count: usize::MAX,
+        max_depth: usize::MAX,
+        max_bits: usize::MAX,
+    };
+
+    /// What is left after reading `bits` more bits, ending at `offset`
+    pub(crate) fn consume(self, offset: usize, bits: usize) -> Result<Self, UnpackError> {
+        match self.max_bits.checked_sub(bits) {
+            Some(max_bits) => Ok(Self { max_bits, ..self }),
+            None => Err(UnpackErrorKind::Limit { offset }.into()),
+        }
+    }
+
+    /// What is left inside one more level of nesting at `offset`
+    pub(crate) fn enter(self, offset: usize) -> Result<Self, UnpackError> {
+        match self.max_depth.checked_sub(1) {
+            Some(max_depth) => Ok(Self { max_depth, ..self }),
+            None => Err(UnpackErrorKind::Limit { offset }.into()),
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Parser for a fixed-size [`Unpack`] type, see [`fixed`]
+pub struct Fixed<T>(PhantomData<fn() -> T>);
+
+/// Parse a `T`, which always reads `T::SIZE` bits
+pub fn fixed<T>() -> Fixed<T>
+where
+    T: Unpack,
+{
+    Fixed(PhantomData)
+}
+
+impl<T> Parser for Fixed<T>
+where
+    T: Unpack,
+{
+    type Output = T;
+
+    fn parse_with<C: Cfg>(&self, bytes: &[u8], offset: usize) -> Result<(T, usize), UnpackError> {
+        check_len(bytes, offset, T::SIZE)?;
+        Ok((T::unpack_with::<C>(bytes, offset), T::SIZE))
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(T, usize), UnpackError> {
+        limits.consume(offset, T::SIZE)?;
+        self.parse_with::<C>(bytes, offset)
+    }
+}
+
+/// Parser for a [`varint`](crate::varint), see [`varint`]
+pub struct VarInt;
+
+/// Parse a [`varint`](crate::varint)
+pub fn varint() -> VarInt {
+    VarInt
+}
+
+impl Parser for VarInt {
+    type Output = u64;
+
+    fn parse_with<C: Cfg>(&self, bytes: &[u8], offset: usize) -> Result<(u64, usize), UnpackError> {
+        crate::varint::unpack_with::<C>(bytes, offset)
+    }
+}
+
+/// See [`Parser::then`]
+pub struct Then<A, B>(A, B);
+
+impl<A, B> Parser for Then<A, B>
+where
+    A: Parser,
+    B: Parser,
+{
+    type Output = (A::Output, B::Output);
+
+    fn parse_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        self.parse_limited_with::<C>(bytes, offset, Limits::NONE)
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        let (a, a_len) = self.0.parse_limited_with::<C>(bytes, offset, limits)?;
+        let limits = limits.consume(offset + a_len, a_len)?;
+        let (b, b_len) = self
+            .1
+            .parse_limited_with::<C>(bytes, offset + a_len, limits)?;
+        Ok(((a, b), a_len + b_len))
+    }
+}
+
+/// See [`Parser::map`]
+pub struct Map<P, F>(P, F);
+
+impl<P, F, U> Parser for Map<P, F>
+where
+    P: Parser,
+    F: Fn(P::Output) -> U,
+{
+    type Output = U;
+
+    fn parse_with<C: Cfg>(&self, bytes: &[u8], offset: usize) -> Result<(U, usize), UnpackError> {
+        let (x, len) = self.0.parse_with::<C>(bytes, offset)?;
+        Ok(((self.1)(x), len))
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(U, usize), UnpackError> {
+        let (x, len) = self.0.parse_limited_with::<C>(bytes, offset, limits)?;
+        Ok(((self.1)(x), len))
+    }
+}
+
+/// See [`Parser::and_then`]
+pub struct AndThen<P, F>(P, F);
+
+impl<P, F, Q> Parser for AndThen<P, F>
+where
+    P: Parser,
+    F: Fn(P::Output) -> Q,
+    Q: Parser,
+{
+    type Output = Q::Output;
+
+    fn parse_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> Result<(Q::Output, usize), UnpackError> {
+        self.parse_limited_with::<C>(bytes, offset, Limits::NONE)
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(Q::Output, usize), UnpackError> {
+        let (x, len) = self.0.parse_limited_with::<C>(bytes, offset, limits)?;
+        let limits = limits.consume(offset + len, len)?.enter(offset + len)?;
+        let (y, rest) = (self.1)(x).parse_limited_with::<C>(bytes, offset + len, limits)?;
+        Ok((y, len + rest))
+    }
+}
+
+/// See [`Parser::repeat_n`]
+pub struct RepeatN<P>(P, usize);
+
+impl<P> Parser for RepeatN<P>
+where
+    P: Parser,
+{
+    type Output = Vec<P::Output>;
+
+    fn parse_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        self.parse_limited_with::<C>(bytes, offset, Limits::NONE)
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        if self.1 > limits.max_count {
+            return Err(UnpackErrorKind::Limit { offset }.into());
+        }
+        let mut limits = limits.enter(offset)?;
+        let mut out = Vec::new();
+        let mut read = 0;
+        for _ in 0..self.1 {
+            let (x, len) = self
+                .0
+                .parse_limited_with::<C>(bytes, offset + read, limits)?;
+            out.push(x);
+            read += len;
+            limits = limits.consume(offset + read, len)?;
+        }
+        Ok((out, read))
+    }
+}
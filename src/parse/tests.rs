@@ -0,0 +1,86 @@
+use super::{Limits, Parser, fixed, varint};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, UnpackError, UnpackErrorKind};
+
+#[test]
+fn combinators_track_length() {
+    // a tag in the top 3 bits of a byte, a varint count and that many 4 bit
+    // values
+    let parser = fixed::<u8>()
+        .map(|x| x >> 5)
+        .then(varint().and_then(|n| fixed::<[bool; 4]>().repeat_n(n as usize)));
+    let mut buf = [0u8; 4];
+    0b1010_0000u8.pack(&mut buf, 0);
+    2u8.pack(&mut buf, 8);
+    [true, false, false, true].pack(&mut buf, 16);
+    [false, true, true, false].pack(&mut buf, 20);
+    let ((tag, values), len) = parser.parse(&buf, 0).unwrap();
+    assert_eq!(tag, 0b101);
+    assert_eq!(
+        values,
+        [[true, false, false, true], [false, true, true, false]]
+    );
+    assert_eq!(len, 24);
+
+    // errors come from the inner parser
+    assert_eq!(
+        parser.parse(&buf[..2], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+}
+
+#[test]
+fn limits() {
+    // a varint count of nested varint counts of bytes
+    let inner = || varint().and_then(|n| fixed::<u8>().repeat_n(n as usize));
+    let parser = varint().and_then(move |n| inner().repeat_n(n as usize));
+    let buf = [0x02, 0x01, 0xaa, 0x02, 0xbb, 0xcc];
+    let expected = Ok((vec![vec![0xaa], vec![0xbb, 0xcc]], 48));
+    assert_eq!(parser.parse(&buf, 0), expected);
+    assert_eq!(parser.parse_limited(&buf, 0, Limits::NONE), expected);
+
+    let exact = Limits {
+        max_count: 2,
+        max_depth: 4,
+        max_bits: 48,
+    };
+    assert_eq!(parser.parse_limited(&buf, 0, exact), expected);
+
+    fn limit<T>(offset: usize) -> Result<T, UnpackError> {
+        Err(UnpackErrorKind::Limit { offset }.into())
+    }
+    let fewer = Limits {
+        max_count: 1,
+        ..exact
+    };
+    assert_eq!(parser.parse_limited(&buf, 0, fewer), limit(8));
+    let shallower = Limits {
+        max_depth: 3,
+        ..exact
+    };
+    assert_eq!(parser.parse_limited(&buf, 0, shallower), limit(16));
+    let shorter = Limits {
+        max_bits: 47,
+        ..exact
+    };
+    assert_eq!(parser.parse_limited(&buf, 0, shorter), limit(40));
+
+    // huge counts are refused before anything is parsed
+    let buf = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+    let empty = varint().and_then(|n| fixed::<()>().repeat_n(n as usize));
+    let small = Limits {
+        max_count: 1 << 16,
+        ..Limits::NONE
+    };
+    assert_eq!(empty.parse_limited(&buf, 0, small), limit(72));
+}
+
+proptest::proptest! {
+    #[test]
+    fn repeat_matches_unpack(values: [u16; 3], offset in 0usize..=16) {
+        let mut buf = [0u8; 9];
+        values.pack_with::<Lsb0Le>(&mut buf, offset);
+        let parsed = fixed::<u16>().repeat_n(3).parse_with::<Lsb0Le>(&buf, offset);
+        assert_eq!(parsed, Ok((values.to_vec(), 48)));
+    }
+}
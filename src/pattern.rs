@@ -0,0 +1,146 @@
+//! Matching bits against patterns with don't-cares.
+//!
+//! Instruction sets and protocols often dispatch on a prefix of bits before
+//! decoding the rest.  A [`BitPattern`] is written like `"110x_xxx1"`, with
+//! `0` and `1` for bits which must match, `x` for bits which may be either,
+//! and `_` or spaces to group bits for reading.  Patterns are compiled to a
+//! mask and value once, so matching is a single read and compare.
+//! [`bit_pattern!`](crate::bit_pattern) compiles a pattern at compile time,
+//! so a typo fails the build.
+//!
+//! ```rust
+//! # use packed::pattern::{self, BitPattern};
+//! const JUMP: BitPattern = packed::bit_pattern!("110x_xxx1");
+//! assert!(JUMP.matches(&[0b1101_0011], 0));
+//! assert!(!JUMP.matches(&[0b1101_0010], 0));
+//! assert!(pattern::matches_pattern(&[0x0d, 0x30], 4, "1101 0011"));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::cfg::{BitOrder, Cfg, DefaultCfg};
+use crate::error::bit_len;
+use crate::narrow::read_bits;
+
+/// A pattern of bits to match, see the [module docs](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitPattern {
+    len: usize,
+    /// Bits which must match, with the first bit of the pattern highest
+    mask: u128,
+    value: u128,
+}
+
+/// Error returned when a pattern can't be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternError {
+    /// The byte at `index` isn't `0`, `1`, `x`, `_` or a space
+    InvalidChar { index: usize },
+    /// The pattern has more than 128 bits
+    TooLong,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { index } => write!(f, "invalid character at index {index}"),
+            Self::TooLong => f.write_str("pattern is longer than 128 bits"),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// Compile a [`BitPattern`], failing the build if it is invalid
+#[macro_export]
+macro_rules! bit_pattern {
+    ($pattern: expr) => {
+        const {
+            match $crate::pattern::BitPattern::new($pattern) {
+                ::core::result::Result::Ok(pattern) => pattern,
+                ::core::result::Result::Err(_) => ::core::panic!("invalid bit pattern"),
+            }
+        }
+    };
+}
+
+impl BitPattern {
+    pub const fn new(pattern: &str) -> Result<Self, PatternError> {
+        let bytes = pattern.as_bytes();
+        let (mut len, mut mask, mut value) = (0, 0u128, 0u128);
+        let mut i = 0;
+        while i < bytes.len() {
+            let (m, v) = match bytes[i] {
+                b'0' => (1, 0),
+                b'1' => (1, 1),
+                b'x' | b'X' => (0, 0),
+                b'_' | b' ' => {
+                    i += 1;
+                    continue;
+                }
+                _ => return Err(PatternError::InvalidChar { index: i }),
+            };
+            if len == 128 {
+                return Err(PatternError::TooLong);
+            }
+            // the first bit ends up highest once all bits are shifted in
+            mask = mask << 1 | m;
+            value = value << 1 | v;
+            len += 1;
+            i += 1;
+        }
+        Ok(Self { len, mask, value })
+    }
+
+    /// Amount of bits the pattern covers
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the bits at `offset`, in the order they are packed with `C`,
+    /// match.  Never matches if `bytes` ends before the pattern does.
+    pub fn matches_with<C: Cfg>(&self, bytes: &[u8], offset: usize) -> bool {
+        if offset
+            .checked_add(self.len)
+            .is_none_or(|end| end > bit_len(bytes))
+        {
+            return false;
+        }
+        if self.len == 0 {
+            return true;
+        }
+        let bits = read_bits::<C>(self.len, bytes, offset);
+        // Lsb0 reads the first bit into the lowest bit
+        let bits = match C::BIT_ORDER {
+            BitOrder::Msb0 => bits,
+            BitOrder::Lsb0 => bits.reverse_bits() >> (128 - self.len),
+        };
+        bits & self.mask == self.value
+    }
+
+    /// Match using [`DefaultCfg`]
+    pub fn matches(&self, bytes: &[u8], offset: usize) -> bool {
+        self.matches_with::<DefaultCfg>(bytes, offset)
+    }
+}
+
+/// Whether the bits at `offset` match `pattern`, using [`DefaultCfg`].
+/// Compile patterns which are used more than once with [`BitPattern::new`]
+/// or [`bit_pattern!`](crate::bit_pattern) instead.
+///
+/// # Panics
+///
+/// If `pattern` isn't a valid pattern
+pub fn matches_pattern(bytes: &[u8], offset: usize, pattern: &str) -> bool {
+    match BitPattern::new(pattern) {
+        Ok(pattern) => pattern.matches(bytes, offset),
+        Err(e) => panic!("invalid bit pattern {pattern:?}: {e}"),
+    }
+}
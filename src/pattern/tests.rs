@@ -0,0 +1,73 @@
+use super::{BitPattern, PatternError, matches_pattern};
+use crate::Pack;
+use crate::cfg::{Lsb0Be, Lsb0Le, Msb0Le};
+
+#[test]
+fn parse() {
+    assert_eq!(BitPattern::new("1_0x X").unwrap().len(), 4);
+    assert!(BitPattern::new("").unwrap().is_empty());
+    assert!(BitPattern::new("__").unwrap().is_empty());
+    assert_eq!(
+        BitPattern::new("10x2"),
+        Err(PatternError::InvalidChar { index: 3 })
+    );
+    assert_eq!(
+        BitPattern::new("1é"),
+        Err(PatternError::InvalidChar { index: 1 })
+    );
+    assert_eq!(BitPattern::new(&"x".repeat(128)).unwrap().len(), 128);
+    assert_eq!(
+        BitPattern::new(&"x".repeat(129)),
+        Err(PatternError::TooLong)
+    );
+}
+
+#[test]
+fn matches() {
+    let buf = [0b1101_0011, 0b0100_0000];
+    assert!(matches_pattern(&buf, 0, "1101_0011"));
+    assert!(matches_pattern(&buf, 0, "1x0x"));
+    assert!(matches_pattern(&buf, 6, "1101"));
+    assert!(!matches_pattern(&buf, 6, "1100"));
+    assert!(matches_pattern(&buf, 16, ""));
+    // past the end never matches, even with only don't-cares
+    assert!(!matches_pattern(&buf, 10, "xxxxxxx"));
+    assert!(!matches_pattern(&buf, usize::MAX, "x"));
+
+    let wide = BitPattern::new(&format!("1{}1", "x".repeat(126))).unwrap();
+    let mut bytes = [0u8; 17];
+    bytes[0] = 0x01;
+    bytes[16] = 0x02;
+    assert!(wide.matches(&bytes, 7));
+    assert!(!wide.matches(&bytes, 6));
+}
+
+#[test]
+fn bit_order() {
+    // patterns follow the order bits are packed in, whatever the byte order
+    let pattern = BitPattern::new("1100_0x").unwrap();
+    for offset in 0..10 {
+        let mut buf = [0u8; 3];
+        [true, true, false, false, false, true].pack_with::<Lsb0Le>(&mut buf, offset);
+        assert!(pattern.matches_with::<Lsb0Le>(&buf, offset));
+        assert!(pattern.matches_with::<Lsb0Be>(&buf, offset));
+
+        let mut buf = [0u8; 3];
+        0b11_0000u8.pack_with::<Msb0Le>(&mut buf, offset);
+        assert!(!pattern.matches_with::<Msb0Le>(&buf, offset));
+        assert!(pattern.matches_with::<Msb0Le>(&buf, offset + 2));
+    }
+}
+
+#[test]
+fn macro_form() {
+    const NOP: BitPattern = crate::bit_pattern!("0000 0000");
+    assert!(NOP.matches(&[0], 0));
+    assert_eq!(NOP, BitPattern::new("00000000").unwrap());
+}
+
+#[test]
+#[should_panic = "invalid bit pattern"]
+fn invalid_pattern_panics() {
+    matches_pattern(&[0], 0, "10y");
+}
@@ -0,0 +1,204 @@
+//! Reading packets from pcap capture files.
+//!
+//! [`PcapReader`] walks the records of a capture in the classic pcap format
+//! written by tcpdump and Wireshark, in either byte order and with micro- or
+//! nanosecond timestamps.  Each [`Packet`] can then be decoded into a packed
+//! struct, leaving the payload after it for the next layer.  The pcapng
+//! format is not supported.
+//!
+//! Only available with the `pcap` feature.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::pcap::{PcapError, PcapReader};
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct Header {
+//!     version: u8,
+//!     len: u16,
+//! }
+//!
+//! # fn main() -> Result<(), PcapError> {
+//! # let capture = [
+//! #     0xa1, 0xb2, 0xc3, 0xd4, 0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0,
+//! #     0, 0, 0xff, 0xff, 0, 0, 0, 147,
+//! #     0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 5, 0, 0, 0, 5,
+//! #     1, 0, 2, 0xab, 0xcd,
+//! # ];
+//! // let capture = std::fs::read("capture.pcap")?;
+//! for packet in PcapReader::new(&capture)? {
+//!     let (header, payload) = packet?.decode::<Header>()?;
+//!     assert_eq!(header, Header { version: 1, len: 2 });
+//!     assert_eq!(payload, [0xab, 0xcd]);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::{Unpack, UnpackError};
+
+const MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const HEADER_LEN: usize = 24;
+const RECORD_LEN: usize = 16;
+
+/// Error returned when reading a capture fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PcapError {
+    /// The file doesn't start with a pcap magic number
+    BadMagic(u32),
+    /// The file ends partway through the header or record at byte `offset`
+    Truncated { offset: usize },
+    /// A packet couldn't be decoded
+    Unpack(UnpackError),
+}
+
+impl fmt::Display for PcapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic(magic) => write!(f, "not a pcap file (magic {magic:#010x})"),
+            Self::Truncated { offset } => write!(f, "capture truncated at byte {offset}"),
+            Self::Unpack(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PcapError {}
+
+impl From<UnpackError> for PcapError {
+    fn from(e: UnpackError) -> Self {
+        Self::Unpack(e)
+    }
+}
+
+/// Iterates over the packets of a capture held in memory
+#[derive(Debug, Clone)]
+pub struct PcapReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little: bool,
+    nanos: bool,
+    link_type: u32,
+}
+
+/// A packet read from a capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packet<'a> {
+    /// Time since the Unix epoch at which the packet was captured
+    pub timestamp: Duration,
+    /// Length of the packet on the wire, which can be more than was captured
+    pub orig_len: u32,
+    /// The captured bytes
+    pub data: &'a [u8],
+}
+
+impl<'a> PcapReader<'a> {
+    /// Read the file header of `bytes`
+    pub fn new(bytes: &'a [u8]) -> Result<Self, PcapError> {
+        let Some(header) = bytes.get(..HEADER_LEN) else {
+            return Err(PcapError::Truncated { offset: 0 });
+        };
+        let magic = [header[0], header[1], header[2], header[3]];
+        let (little, nanos) = match (u32::from_be_bytes(magic), u32::from_le_bytes(magic)) {
+            (MAGIC_MICROS, _) => (false, false),
+            (MAGIC_NANOS, _) => (false, true),
+            (_, MAGIC_MICROS) => (true, false),
+            (_, MAGIC_NANOS) => (true, true),
+            (magic, _) => return Err(PcapError::BadMagic(magic)),
+        };
+        let mut reader = Self {
+            bytes,
+            pos: HEADER_LEN,
+            little,
+            nanos,
+            link_type: 0,
+        };
+        reader.link_type = reader.u32_at(20);
+        Ok(reader)
+    }
+
+    /// The link-layer header type of the packets, e.g. `1` for Ethernet
+    pub fn link_type(&self) -> u32 {
+        self.link_type
+    }
+
+    fn u32_at(&self, at: usize) -> u32 {
+        let b = [
+            self.bytes[at],
+            self.bytes[at + 1],
+            self.bytes[at + 2],
+            self.bytes[at + 3],
+        ];
+        if self.little {
+            u32::from_le_bytes(b)
+        } else {
+            u32::from_be_bytes(b)
+        }
+    }
+
+    fn read_packet(&self) -> Option<Packet<'a>> {
+        let start = self.pos.checked_add(RECORD_LEN)?;
+        let len = self
+            .bytes
+            .get(self.pos..start)
+            .map(|_| self.u32_at(self.pos + 8))?;
+        let data = self.bytes.get(start..start.checked_add(len as usize)?)?;
+        let frac = self.u32_at(self.pos + 4);
+        let frac = if self.nanos {
+            frac
+        } else {
+            frac.saturating_mul(1000)
+        };
+        Some(Packet {
+            timestamp: Duration::new(self.u32_at(self.pos).into(), 0)
+                + Duration::from_nanos(frac.into()),
+            orig_len: self.u32_at(self.pos + 12),
+            data,
+        })
+    }
+}
+
+impl<'a> Iterator for PcapReader<'a> {
+    type Item = Result<Packet<'a>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == self.bytes.len() {
+            return None;
+        }
+        let Some(packet) = self.read_packet() else {
+            let offset = self.pos;
+            self.pos = self.bytes.len();
+            return Some(Err(PcapError::Truncated { offset }));
+        };
+        self.pos += RECORD_LEN + packet.data.len();
+        Some(Ok(packet))
+    }
+}
+
+impl<'a> Packet<'a> {
+    /// Strictly unpack a `T` at bit `offset` of the packet, returning it with
+    /// the bytes after it.  A value ending partway through a byte takes the
+    /// rest of that byte with it.
+    pub fn decode_with<C, T>(&self, offset: usize) -> Result<(T, &'a [u8]), UnpackError>
+    where
+        C: Cfg,
+        T: Unpack,
+    {
+        let value = T::unpack_strict_with::<C>(self.data, offset)?;
+        Ok((value, &self.data[(offset + T::SIZE).div_ceil(8)..]))
+    }
+
+    /// Decode the start of the packet using [`DefaultCfg`]
+    pub fn decode<T>(&self) -> Result<(T, &'a [u8]), UnpackError>
+    where
+        T: Unpack,
+    {
+        self.decode_with::<DefaultCfg, T>(0)
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use super::{Packet, PcapError, PcapReader};
+use crate::cfg::DefaultCfg;
+use crate::cfg::Lsb0Le;
+use crate::{Reserved, UnpackErrorKind};
+
+/// A capture in the byte order given by `to_bytes`, with the given magic
+fn capture(magic: u32, to_bytes: fn(u32) -> [u8; 4], packets: &[(u32, u32, &[u8])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    // the version is left as zero, since it isn't read
+    for word in [magic, 0, 0, 0, 0xffff, 1] {
+        out.extend(to_bytes(word));
+    }
+    for &(secs, frac, data) in packets {
+        for word in [secs, frac, data.len() as u32, data.len() as u32 + 10] {
+            out.extend(to_bytes(word));
+        }
+        out.extend_from_slice(data);
+    }
+    out
+}
+
+#[test]
+fn read() {
+    let packets: [(u32, u32, &[u8]); 3] = [(1, 2, &[0x12, 0x34]), (3, 999_999, &[]), (5, 0, &[7])];
+    for to_bytes in [u32::to_be_bytes, u32::to_le_bytes] {
+        let bytes = capture(0xa1b2_c3d4, to_bytes, &packets);
+        let reader = PcapReader::new(&bytes).unwrap();
+        assert_eq!(reader.link_type(), 1);
+        let read: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            read,
+            [
+                Packet {
+                    timestamp: Duration::new(1, 2000),
+                    orig_len: 12,
+                    data: &[0x12, 0x34],
+                },
+                Packet {
+                    timestamp: Duration::new(3, 999_999_000),
+                    orig_len: 10,
+                    data: &[],
+                },
+                Packet {
+                    timestamp: Duration::new(5, 0),
+                    orig_len: 11,
+                    data: &[7],
+                },
+            ]
+        );
+    }
+
+    let bytes = capture(0xa1b2_3c4d, u32::to_le_bytes, &[(1, 2, &[])]);
+    let packet = PcapReader::new(&bytes).unwrap().next().unwrap().unwrap();
+    assert_eq!(packet.timestamp, Duration::new(1, 2));
+}
+
+#[test]
+fn invalid() {
+    let bytes = capture(0xa1b2_c3d4, u32::to_be_bytes, &[(1, 2, &[1, 2, 3])]);
+    assert_eq!(
+        PcapReader::new(&bytes[..23]).unwrap_err(),
+        PcapError::Truncated { offset: 0 }
+    );
+    assert_eq!(
+        PcapReader::new(&[0; 24]).unwrap_err(),
+        PcapError::BadMagic(0)
+    );
+    assert_eq!(PcapReader::new(&bytes[..24]).unwrap().count(), 0);
+    for end in 25..bytes.len() {
+        let mut reader = PcapReader::new(&bytes[..end]).unwrap();
+        assert_eq!(
+            reader.next(),
+            Some(Err(PcapError::Truncated { offset: 24 }))
+        );
+        assert_eq!(reader.next(), None);
+    }
+}
+
+#[test]
+fn decode() {
+    let packet = Packet {
+        timestamp: Duration::ZERO,
+        orig_len: 3,
+        data: &[0x12, 0x34, 0x56],
+    };
+    assert_eq!(packet.decode::<u8>(), Ok((0x12, &[0x34, 0x56][..])));
+    assert_eq!(packet.decode_with::<Lsb0Le, u16>(4), Ok((0x6341, &[][..])));
+    // the rest of a partly used byte is skipped
+    assert_eq!(
+        packet.decode_with::<DefaultCfg, [bool; 3]>(8),
+        Ok(([false, false, true], &[0x56][..]))
+    );
+    assert_eq!(packet.decode::<[u8; 3]>().unwrap().1, [0u8; 0]);
+    assert_eq!(
+        packet.decode::<u32>().unwrap_err().kind(),
+        &UnpackErrorKind::UnexpectedEnd { offset: 24 }
+    );
+    assert!(packet.decode::<(u8, Reserved<8, true>)>().is_err());
+}
+
+proptest::proptest! {
+    #[test]
+    fn never_panics(bytes: Vec<u8>) {
+        let mut header = capture(0xa1b2_c3d4, u32::to_be_bytes, &[]);
+        header.extend(bytes);
+        for packet in PcapReader::new(&header).unwrap().flatten() {
+            let _ = packet.decode::<(u32, u8)>();
+        }
+    }
+}
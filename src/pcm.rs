@@ -0,0 +1,111 @@
+//! Packed 20- and 24-bit PCM audio samples.
+//!
+//! Samples are `i32`s holding a 20- or 24-bit value, sign-extended, and are
+//! packed back to back with no padding between them.  Big-endian streams
+//! are packed MSB-first and little-endian streams LSB-first, so each sample
+//! reads as a normal integer of that byte order.  A 20-bit stream with an
+//! odd amount of samples ends with 4 bits of zero padding.
+//!
+//! Whole samples, or pairs of 20-bit samples, are moved as one word rather
+//! than bit by bit.
+//!
+//! ```rust
+//! # use packed::pcm;
+//! # use packed::cfg::ByteOrder;
+//! let samples = [-2, 0x12345];
+//! let bytes = pcm::pack_pcm20(&samples, ByteOrder::Big);
+//! assert_eq!(bytes, [0xff, 0xff, 0xe1, 0x23, 0x45]);
+//! assert_eq!(pcm::unpack_pcm20(&bytes, ByteOrder::Big), Ok(samples.to_vec()));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::ByteOrder;
+use crate::error::bit_len;
+use crate::{UnpackError, UnpackErrorKind};
+
+/// Sign-extend the low `bits` bits of `x`
+const fn sign_extend(x: u64, bits: u32) -> i32 {
+    ((x << (64 - bits)) as i64 >> (64 - bits)) as i32
+}
+
+fn unexpected_end(bytes: &[u8]) -> UnpackError {
+    UnpackErrorKind::UnexpectedEnd {
+        offset: bit_len(bytes),
+    }
+    .into()
+}
+
+/// Pack 24-bit samples, three bytes each.  Bits above the lowest 24 are
+/// ignored.
+pub fn pack_pcm24(samples: &[i32], order: ByteOrder) -> Vec<u8> {
+    let mut out = Vec::with_capacity(samples.len() * 3);
+    for &x in samples {
+        match order {
+            ByteOrder::Big => out.extend_from_slice(&x.to_be_bytes()[1..]),
+            ByteOrder::Little => out.extend_from_slice(&x.to_le_bytes()[..3]),
+        }
+    }
+    out
+}
+
+/// Unpack 24-bit samples, failing if the last sample is incomplete
+pub fn unpack_pcm24(bytes: &[u8], order: ByteOrder) -> Result<Vec<i32>, UnpackError> {
+    if !bytes.len().is_multiple_of(3) {
+        return Err(unexpected_end(bytes));
+    }
+    Ok(bytes
+        .chunks(3)
+        .map(|b| {
+            let word = match order {
+                ByteOrder::Big => u32::from_be_bytes([0, b[0], b[1], b[2]]),
+                ByteOrder::Little => u32::from_le_bytes([b[0], b[1], b[2], 0]),
+            };
+            sign_extend(word.into(), 24)
+        })
+        .collect())
+}
+
+/// Pack 20-bit samples, two to every five bytes.  Bits above the lowest 20
+/// are ignored.
+pub fn pack_pcm20(samples: &[i32], order: ByteOrder) -> Vec<u8> {
+    let mut out = Vec::with_capacity((samples.len() * 20).div_ceil(8));
+    for pair in samples.chunks(2) {
+        let a = pair[0] as u64 & 0xfffff;
+        let b = pair.get(1).map_or(0, |&b| b as u64 & 0xfffff);
+        let len = if pair.len() == 2 { 5 } else { 3 };
+        match order {
+            ByteOrder::Big => out.extend_from_slice(&(a << 20 | b).to_be_bytes()[3..3 + len]),
+            ByteOrder::Little => out.extend_from_slice(&(a | b << 20).to_le_bytes()[..len]),
+        }
+    }
+    out
+}
+
+/// Unpack 20-bit samples, failing if the last sample is incomplete.  Fewer
+/// than 8 bits left over at the end are taken to be padding.
+pub fn unpack_pcm20(bytes: &[u8], order: ByteOrder) -> Result<Vec<i32>, UnpackError> {
+    if bit_len(bytes) % 20 >= 8 {
+        return Err(unexpected_end(bytes));
+    }
+    let mut out = Vec::with_capacity(bit_len(bytes) / 20);
+    for chunk in bytes.chunks(5) {
+        let mut word = [0u8; 8];
+        let word = match order {
+            ByteOrder::Big => {
+                word[3..3 + chunk.len()].copy_from_slice(chunk);
+                let word = u64::from_be_bytes(word);
+                [word >> 20, word]
+            }
+            ByteOrder::Little => {
+                word[..chunk.len()].copy_from_slice(chunk);
+                let word = u64::from_le_bytes(word);
+                [word, word >> 20]
+            }
+        };
+        let n = if chunk.len() == 5 { 2 } else { 1 };
+        out.extend(word[..n].iter().map(|&x| sign_extend(x & 0xfffff, 20)));
+    }
+    Ok(out)
+}
@@ -0,0 +1,59 @@
+use super::{pack_pcm20, pack_pcm24, unpack_pcm20, unpack_pcm24};
+use crate::UnpackErrorKind;
+use crate::cfg::{ByteOrder, Cfg, Lsb0Le, Msb0Be};
+use crate::narrow::NarrowInt;
+
+/// Samples packed one bit at a time as narrow integers
+fn narrow<C: Cfg>(samples: &[i32], bits: usize) -> Vec<u8> {
+    let mut out = vec![0u8; (samples.len() * bits).div_ceil(8)];
+    for (i, x) in samples.iter().enumerate() {
+        x.pack_narrow::<C>(bits, &mut out, i * bits);
+    }
+    out
+}
+
+#[test]
+fn little_endian() {
+    assert_eq!(pack_pcm24(&[-2], ByteOrder::Little), [0xfe, 0xff, 0xff]);
+    assert_eq!(
+        pack_pcm20(&[0x12345, -1, 0x6789a], ByteOrder::Little),
+        [0x45, 0x23, 0xf1, 0xff, 0xff, 0x9a, 0x78, 0x06]
+    );
+}
+
+#[test]
+fn incomplete_sample() {
+    for len in [1, 2, 4] {
+        let bytes = vec![0; len];
+        let err = UnpackErrorKind::UnexpectedEnd { offset: len * 8 }.into();
+        assert_eq!(unpack_pcm24(&bytes, ByteOrder::Big), Err(err));
+    }
+    for len in [1, 2, 4, 6, 7, 9] {
+        let bytes = vec![0; len];
+        let err = UnpackErrorKind::UnexpectedEnd { offset: len * 8 }.into();
+        assert_eq!(unpack_pcm20(&bytes, ByteOrder::Little), Err(err));
+    }
+    assert_eq!(unpack_pcm20(&[0; 8], ByteOrder::Big), Ok(vec![0; 3]));
+}
+
+proptest::proptest! {
+    #[test]
+    fn pcm24(samples in proptest::collection::vec(-1 << 23..1i32 << 23, 0..8)) {
+        let big = pack_pcm24(&samples, ByteOrder::Big);
+        let little = pack_pcm24(&samples, ByteOrder::Little);
+        assert_eq!(big, narrow::<Msb0Be>(&samples, 24));
+        assert_eq!(little, narrow::<Lsb0Le>(&samples, 24));
+        assert_eq!(unpack_pcm24(&big, ByteOrder::Big).as_ref(), Ok(&samples));
+        assert_eq!(unpack_pcm24(&little, ByteOrder::Little), Ok(samples));
+    }
+
+    #[test]
+    fn pcm20(samples in proptest::collection::vec(-1 << 19..1i32 << 19, 0..8)) {
+        let big = pack_pcm20(&samples, ByteOrder::Big);
+        let little = pack_pcm20(&samples, ByteOrder::Little);
+        assert_eq!(big, narrow::<Msb0Be>(&samples, 20));
+        assert_eq!(little, narrow::<Lsb0Le>(&samples, 20));
+        assert_eq!(unpack_pcm20(&big, ByteOrder::Big).as_ref(), Ok(&samples));
+        assert_eq!(unpack_pcm20(&little, ByteOrder::Little), Ok(samples));
+    }
+}
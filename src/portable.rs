@@ -0,0 +1,287 @@
+//! Platform independent `usize` and `isize`.
+//!
+//! Packing `usize` or `isize` directly uses the width of the target, so the
+//! wire format changes between platforms.  [`PortableUsize`] and
+//! [`PortableIsize`] are always packed into `BITS` bits instead.  Values
+//! which don't fit in `BITS` bits can't be constructed, and the strict unpack
+//! methods report [`UnpackErrorKind::Overflow`] for packed values which don't fit
+//! in the native type; the plain unpack truncates them.
+//!
+//! Both types convert to and from their native type with `From`/`TryFrom`,
+//! from every other primitive integer with `TryFrom`, and losslessly into the
+//! 64 and 128 bit primitives of the same signedness with `From`.  They have
+//! checked, wrapping and saturating arithmetic which stays within `BITS`
+//! bits.
+//!
+//! The native impls are only available with the `native-size` feature, which
+//! is on by default.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::portable::PortableUsize;
+//! let len = PortableUsize::<12>::new(300).unwrap();
+//! let mut buf = [0u8; 2];
+//! len.pack(&mut buf, 0);
+//! assert_eq!(buf, [0x12, 0xc0]);
+//! assert_eq!(PortableUsize::<12>::unpack(&buf, 0).get(), 300);
+//! assert!(PortableUsize::<12>::new(4096).is_none());
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use crate::cfg::Cfg;
+use crate::error::bit_len;
+use crate::narrow::NarrowInt;
+use crate::{Pack, PackedSize, Unpack, UnpackError, UnpackErrorKind};
+
+/// A `usize` packed into `BITS` bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u64", into = "u64"))]
+pub struct PortableUsize<const BITS: usize>(usize);
+
+/// An `isize` packed into `BITS` bits as two's complement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "i64", into = "i64"))]
+pub struct PortableIsize<const BITS: usize>(isize);
+
+impl<const BITS: usize> PortableUsize<BITS> {
+    pub const MIN: Self = Self(0);
+    pub const MAX: Self = Self(if BITS >= usize::BITS as usize {
+        usize::MAX
+    } else {
+        (1 << BITS) - 1
+    });
+
+    /// Wrap `value` around to `BITS` bits
+    const fn wrap(value: usize) -> Self {
+        Self(value & Self::MAX.0)
+    }
+}
+
+impl<const BITS: usize> PortableIsize<BITS> {
+    pub const MIN: Self = Self(-Self::MAX.0 - (BITS > 0) as isize);
+    pub const MAX: Self = Self(match BITS {
+        0 => 0,
+        _ if BITS >= isize::BITS as usize => isize::MAX,
+        _ => (1 << (BITS - 1)) - 1,
+    });
+
+    /// Wrap `value` around to `BITS` bits
+    const fn wrap(value: isize) -> Self {
+        if BITS == 0 {
+            return Self(0);
+        }
+        // move the sign bit to the top and shift back arithmetically
+        let shift = isize::BITS.saturating_sub(BITS as u32);
+        Self(value << shift >> shift)
+    }
+}
+
+/// Error returned when a value doesn't fit in a portable integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfRange;
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value out of range")
+    }
+}
+
+impl std::error::Error for OutOfRange {}
+
+/// Error returned when parsing a portable integer fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string isn't an integer
+    Invalid(ParseIntError),
+    /// The integer doesn't fit in `BITS` bits
+    OutOfRange,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => e.fmt(f),
+            Self::OutOfRange => OutOfRange.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+macro_rules! portable {
+    ($name: ident, $native: ident, $wide: ident, [$($from: ident),+], [$($into: ident),+]) => {
+        impl<const BITS: usize> $name<BITS> {
+            /// `None` if `value` doesn't fit in `BITS` bits
+            pub const fn new(value: $native) -> Option<Self> {
+                if value >= Self::MIN.0 && value <= Self::MAX.0 {
+                    Some(Self(value))
+                } else {
+                    None
+                }
+            }
+
+            pub const fn get(self) -> $native {
+                self.0
+            }
+
+            pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+                match self.0.checked_add(rhs.0) {
+                    Some(x) => Self::new(x),
+                    None => None,
+                }
+            }
+
+            pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+                match self.0.checked_sub(rhs.0) {
+                    Some(x) => Self::new(x),
+                    None => None,
+                }
+            }
+
+            pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+                match self.0.checked_mul(rhs.0) {
+                    Some(x) => Self::new(x),
+                    None => None,
+                }
+            }
+
+            pub const fn wrapping_add(self, rhs: Self) -> Self {
+                Self::wrap(self.0.wrapping_add(rhs.0))
+            }
+
+            pub const fn wrapping_sub(self, rhs: Self) -> Self {
+                Self::wrap(self.0.wrapping_sub(rhs.0))
+            }
+
+            pub const fn wrapping_mul(self, rhs: Self) -> Self {
+                Self::wrap(self.0.wrapping_mul(rhs.0))
+            }
+
+            pub const fn saturating_add(self, rhs: Self) -> Self {
+                Self::saturate(self.0.saturating_add(rhs.0))
+            }
+
+            pub const fn saturating_sub(self, rhs: Self) -> Self {
+                Self::saturate(self.0.saturating_sub(rhs.0))
+            }
+
+            pub const fn saturating_mul(self, rhs: Self) -> Self {
+                Self::saturate(self.0.saturating_mul(rhs.0))
+            }
+
+            /// Clamp `value` to the range of `BITS` bits
+            const fn saturate(value: $native) -> Self {
+                Self(if value < Self::MIN.0 {
+                    Self::MIN.0
+                } else if value > Self::MAX.0 {
+                    Self::MAX.0
+                } else {
+                    value
+                })
+            }
+        }
+
+        impl<const BITS: usize> TryFrom<$native> for $name<BITS> {
+            type Error = OutOfRange;
+
+            fn try_from(value: $native) -> Result<Self, OutOfRange> {
+                Self::new(value).ok_or(OutOfRange)
+            }
+        }
+
+        $(
+            impl<const BITS: usize> TryFrom<$from> for $name<BITS> {
+                type Error = OutOfRange;
+
+                fn try_from(value: $from) -> Result<Self, OutOfRange> {
+                    $native::try_from(value)
+                        .ok()
+                        .and_then(Self::new)
+                        .ok_or(OutOfRange)
+                }
+            }
+        )+
+
+        impl<const BITS: usize> fmt::Display for $name<BITS> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl<const BITS: usize> FromStr for $name<BITS> {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, ParseError> {
+                let value = s.parse().map_err(ParseError::Invalid)?;
+                Self::new(value).ok_or(ParseError::OutOfRange)
+            }
+        }
+
+        impl<const BITS: usize> PackedSize for $name<BITS> {
+            const SIZE: usize = {
+                assert!(BITS <= 128, "`BITS` is wider than 128");
+                BITS
+            };
+        }
+
+        impl<const BITS: usize> Pack for $name<BITS> {
+            fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                (self.0 as $wide).pack_narrow::<C>(BITS, bytes, offset);
+            }
+        }
+
+        impl<const BITS: usize> Unpack for $name<BITS> {
+            fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                Self($wide::unpack_narrow::<C>(BITS, bytes, offset) as $native)
+            }
+
+            fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                match $native::try_from($wide::unpack_narrow::<C>(BITS, bytes, offset)) {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(UnpackErrorKind::Overflow { offset }.into()),
+                }
+            }
+        }
+
+        impl<const BITS: usize> From<$name<BITS>> for $native {
+            fn from(value: $name<BITS>) -> Self {
+                value.0
+            }
+        }
+
+        $(
+            impl<const BITS: usize> From<$name<BITS>> for $into {
+                fn from(value: $name<BITS>) -> Self {
+                    // no target has pointers wider than 64 bits
+                    value.0 as $into
+                }
+            }
+        )+
+    };
+}
+
+portable!(
+    PortableUsize,
+    usize,
+    u128,
+    [u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, isize],
+    [u64, u128]
+);
+portable!(
+    PortableIsize,
+    isize,
+    i128,
+    [i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize],
+    [i64, i128]
+);
@@ -0,0 +1,142 @@
+use super::{PortableIsize, PortableUsize};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, PackedSize, Unpack, UnpackErrorKind};
+
+#[test]
+fn new_checks_width() {
+    assert!(PortableUsize::<8>::new(255).is_some());
+    assert!(PortableUsize::<8>::new(256).is_none());
+    assert!(PortableUsize::<0>::new(0).is_some());
+    assert!(PortableUsize::<64>::new(usize::MAX).is_some());
+    assert!(PortableIsize::<4>::new(-8).is_some());
+    assert!(PortableIsize::<4>::new(7).is_some());
+    assert!(PortableIsize::<4>::new(-9).is_none());
+    assert!(PortableIsize::<4>::new(8).is_none());
+    assert!(PortableIsize::<0>::new(-1).is_none());
+}
+
+#[test]
+fn strict_rejects_overflow() {
+    let buf = [0xffu8; 17];
+    assert_eq!(
+        PortableUsize::<128>::unpack_strict(&buf, 3),
+        Err(UnpackErrorKind::Overflow { offset: 3 }.into())
+    );
+    assert_eq!(
+        PortableIsize::<128>::unpack_strict(&buf, 0).map(PortableIsize::get),
+        Ok(-1)
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn pack_unpack_portable(a in 0usize..1 << 20, b in -(1isize << 9)..1 << 9, offset in 0usize..=16) {
+        let value = (PortableUsize::<20>::new(a).unwrap(), PortableIsize::<10>::new(b).unwrap());
+        assert_eq!(<(PortableUsize<20>, PortableIsize<10>)>::SIZE, 30);
+        let mut buf = [0u8; 6];
+        value.pack(&mut buf, offset);
+        assert_eq!(Unpack::unpack_strict(&buf, offset), Ok(value));
+        value.pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(<(PortableUsize<20>, PortableIsize<10>)>::unpack_with::<Lsb0Le>(&buf, offset), value);
+    }
+}
+
+#[test]
+fn limits() {
+    assert_eq!(PortableUsize::<12>::MAX.get(), 4095);
+    assert_eq!(PortableUsize::<0>::MAX.get(), 0);
+    assert_eq!(PortableUsize::<128>::MAX.get(), usize::MAX);
+    assert_eq!(PortableIsize::<4>::MIN.get(), -8);
+    assert_eq!(PortableIsize::<4>::MAX.get(), 7);
+    assert_eq!(PortableIsize::<0>::MIN.get(), 0);
+    assert_eq!(PortableIsize::<128>::MIN.get(), isize::MIN);
+    const ONE: PortableUsize<3> = PortableUsize::new(1).unwrap();
+    assert_eq!(ONE.get(), 1);
+}
+
+#[test]
+fn arithmetic() {
+    let u = |x| PortableUsize::<4>::new(x).unwrap();
+    assert_eq!(u(9).checked_add(u(6)), Some(u(15)));
+    assert_eq!(u(9).checked_add(u(7)), None);
+    assert_eq!(u(3).checked_sub(u(4)), None);
+    assert_eq!(u(4).checked_mul(u(4)), None);
+    assert_eq!(u(9).wrapping_add(u(7)), u(0));
+    assert_eq!(u(3).wrapping_sub(u(4)), u(15));
+    assert_eq!(u(5).wrapping_mul(u(5)), u(9));
+    assert_eq!(u(9).saturating_add(u(9)), u(15));
+    assert_eq!(u(3).saturating_sub(u(4)), u(0));
+    assert_eq!(u(5).saturating_mul(u(5)), u(15));
+
+    let i = |x| PortableIsize::<4>::new(x).unwrap();
+    assert_eq!(i(7).checked_add(i(1)), None);
+    assert_eq!(i(-8).checked_sub(i(1)), None);
+    assert_eq!(i(-4).checked_mul(i(2)), Some(i(-8)));
+    assert_eq!(i(7).wrapping_add(i(1)), i(-8));
+    assert_eq!(i(-8).wrapping_sub(i(1)), i(7));
+    assert_eq!(i(-3).wrapping_mul(i(3)), i(7));
+    assert_eq!(i(7).saturating_add(i(7)), i(7));
+    assert_eq!(i(-8).saturating_sub(i(7)), i(-8));
+    assert_eq!(i(-4).saturating_mul(i(4)), i(-8));
+}
+
+#[test]
+fn conversions() {
+    use super::{OutOfRange, ParseError};
+    assert_eq!(
+        PortableUsize::<8>::try_from(255),
+        Ok(PortableUsize::<8>::MAX)
+    );
+    assert_eq!(PortableUsize::<8>::try_from(256), Err(OutOfRange));
+    assert_eq!(isize::from(PortableIsize::<8>::MIN), -128);
+    assert_eq!(
+        PortableUsize::<8>::try_from(200u8),
+        Ok(PortableUsize::<8>::new(200).unwrap())
+    );
+    assert_eq!(PortableUsize::<8>::try_from(256u16), Err(OutOfRange));
+    assert_eq!(PortableUsize::<8>::try_from(-1i32), Err(OutOfRange));
+    assert_eq!(PortableUsize::<70>::try_from(u128::MAX), Err(OutOfRange));
+    assert_eq!(
+        PortableIsize::<4>::try_from(-8i64).map(PortableIsize::get),
+        Ok(-8)
+    );
+    assert_eq!(PortableIsize::<4>::try_from(8u8), Err(OutOfRange));
+    assert_eq!(PortableIsize::<4>::try_from(usize::MAX), Err(OutOfRange));
+    assert_eq!(u64::from(PortableUsize::<12>::MAX), 4095);
+    assert_eq!(u128::from(PortableUsize::<12>::MAX), 4095);
+    assert_eq!(i64::from(PortableIsize::<12>::MIN), -2048);
+    assert_eq!(i128::from(PortableIsize::<12>::MIN), -2048);
+    assert_eq!(
+        "-12".parse::<PortableIsize<5>>().map(PortableIsize::get),
+        Ok(-12)
+    );
+    assert_eq!(
+        "-17".parse::<PortableIsize<5>>(),
+        Err(ParseError::OutOfRange)
+    );
+    assert!(matches!(
+        "x".parse::<PortableUsize<5>>(),
+        Err(ParseError::Invalid(_))
+    ));
+    assert_eq!(PortableUsize::<10>::new(1000).unwrap().to_string(), "1000");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_roundtrip() {
+    let len = PortableUsize::<12>::new(300).unwrap();
+    let json = serde_json::to_string(&len).unwrap();
+    assert_eq!(json, "300");
+    assert_eq!(
+        serde_json::from_str::<PortableUsize<12>>(&json).unwrap(),
+        len
+    );
+    assert!(serde_json::from_str::<PortableUsize<12>>("4096").is_err());
+    let delta = PortableIsize::<4>::new(-8).unwrap();
+    let json = serde_json::to_string(&delta).unwrap();
+    assert_eq!(
+        serde_json::from_str::<PortableIsize<4>>(&json).unwrap(),
+        delta
+    );
+    assert!(serde_json::from_str::<PortableIsize<4>>("8").is_err());
+}
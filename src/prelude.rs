@@ -0,0 +1,12 @@
+//! The packing traits, and their derives with the `derive` feature.
+//!
+//! ```rust
+//! use packed::prelude::*;
+//!
+//! let mut buf = [0u8; 2];
+//! 0x1234u16.pack(&mut buf, 0);
+//! assert_eq!(u16::unpack(&buf, 0), 0x1234);
+//! assert_eq!(u16::SIZE, 16);
+//! ```
+
+pub use crate::{Pack, Packed, PackedSize, Unpack};
@@ -0,0 +1,129 @@
+//! Constants packed once and copied from then on.
+//!
+//! Preambles and fixed headers are packed with the same value every time.
+//! A [`PrepackedConst`] packs its value the first time it is needed and
+//! keeps the bytes, so packing it again copies them rather than packing each
+//! field.  It can be declared as a `static` and packed like its value, or a
+//! field of a derived struct can be marked `#[packed(prepacked = "STATIC")]`
+//! to use the static's bytes whenever the field holds its value.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::prepacked::PrepackedConst;
+//! #[derive(Debug, Clone, Copy, PartialEq, Packed)]
+//! struct Preamble {
+//!     sync: u16,
+//!     #[packed(bits = 4)]
+//!     version: u8,
+//! }
+//!
+//! static PREAMBLE: PrepackedConst<Preamble> = PrepackedConst::new(Preamble {
+//!     sync: 0xaa55,
+//!     version: 3,
+//! });
+//!
+//! #[derive(Packed)]
+//! struct Frame {
+//!     #[packed(prepacked = "PREAMBLE")]
+//!     preamble: Preamble,
+//!     payload: u8,
+//! }
+//!
+//! let mut buf = [0u8; 4];
+//! Frame { preamble: *PREAMBLE, payload: 0x12 }.pack(&mut buf, 0);
+//! assert_eq!(buf, [0xaa, 0x55, 0x31, 0x20]);
+//! # }
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use crate::cfg::{BitOrder, Cfg, DefaultCfg};
+use crate::error::bit_len;
+use crate::narrow::write_bits;
+use crate::{Pack, PackedSize};
+
+/// A value which is packed with `C` once, the first time it is needed
+pub struct PrepackedConst<T, C = DefaultCfg> {
+    value: T,
+    bytes: OnceLock<Box<[u8]>>,
+    _cfg: PhantomData<fn() -> C>,
+}
+
+impl<T, C> PrepackedConst<T, C> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            bytes: OnceLock::new(),
+            _cfg: PhantomData,
+        }
+    }
+
+    pub const fn get(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Pack, C: Cfg> PrepackedConst<T, C> {
+    /// The value packed at the start of a zeroed buffer
+    pub fn bytes(&self) -> &[u8] {
+        self.bytes.get_or_init(|| {
+            let mut bytes = vec![0u8; T::SIZE.div_ceil(8)];
+            self.value.pack_with::<C>(&mut bytes, 0);
+            bytes.into()
+        })
+    }
+}
+
+impl<T, C> Deref for PrepackedConst<T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for PrepackedConst<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PrepackedConst").field(&self.value).finish()
+    }
+}
+
+impl<T: PackedSize, C> PackedSize for PrepackedConst<T, C> {
+    const SIZE: usize = T::SIZE;
+}
+
+/// Copies the cached bytes when packed with the same bit and byte order as
+/// `C`, and packs the value as usual otherwise
+impl<T: Pack, C: Cfg> Pack for PrepackedConst<T, C> {
+    fn pack_with<D: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        if D::BIT_ORDER != C::BIT_ORDER || D::BYTE_ORDER != C::BYTE_ORDER {
+            return self.value.pack_with::<D>(bytes, offset);
+        }
+        let cached = self.bytes();
+        let whole = T::SIZE / 8;
+        if offset.is_multiple_of(8) {
+            bytes[offset / 8..][..whole].copy_from_slice(&cached[..whole]);
+        } else {
+            for (i, byte) in cached[..whole].iter().enumerate() {
+                byte.pack_with::<D>(bytes, offset + i * 8);
+            }
+        }
+        let rest = T::SIZE % 8;
+        if rest > 0 {
+            let last = cached[whole];
+            let last = match C::BIT_ORDER {
+                BitOrder::Msb0 => last >> (8 - rest),
+                BitOrder::Lsb0 => last,
+            };
+            write_bits::<D>(last.into(), rest, bytes, offset + whole * 8);
+        }
+    }
+}
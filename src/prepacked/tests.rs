@@ -0,0 +1,93 @@
+use super::PrepackedConst;
+use crate::cfg::{Cfg, Lsb0Be, Lsb0Le, Msb0Be, Msb0Le};
+use crate::{Pack, Packed, PackedSize, Reserved, Unpack};
+
+#[derive(Debug, Clone, Copy, PartialEq, Packed)]
+struct Preamble {
+    sync: u16,
+    #[packed(bits = 5)]
+    version: u8,
+    flags: [bool; 2],
+    pad: Reserved<2, true>,
+}
+
+const PREAMBLE: Preamble = Preamble {
+    sync: 0xa55a,
+    version: 0b10110,
+    flags: [true, false],
+    pad: Reserved,
+};
+
+static MSB: PrepackedConst<Preamble> = PrepackedConst::new(PREAMBLE);
+static LSB: PrepackedConst<Preamble, Lsb0Le> = PrepackedConst::new(PREAMBLE);
+
+#[derive(Debug, PartialEq, Packed)]
+struct Frame {
+    kind: bool,
+    #[packed(prepacked = "MSB")]
+    preamble: Preamble,
+    #[packed(endian = "little", prepacked = "LSB")]
+    trailer: Preamble,
+}
+
+#[test]
+fn caches_bytes() {
+    assert_eq!(Preamble::SIZE, 25);
+    assert_eq!(MSB.bytes(), [0xa5, 0x5a, 0b1011_0101, 0b1000_0000]);
+    assert!(std::ptr::eq(MSB.bytes(), MSB.bytes()));
+    assert_eq!(MSB.get(), &PREAMBLE);
+    assert_eq!(LSB.version, 0b10110);
+}
+
+/// Packing the constant must match packing its value, whichever
+/// configuration it was cached with
+fn matches_value<C: Cfg>(prepacked: &PrepackedConst<Preamble, impl Cfg>) {
+    for offset in 0..16 {
+        for background in [0x00, 0xff] {
+            let mut expected = [background; 6];
+            PREAMBLE.pack_with::<C>(&mut expected, offset);
+            let mut found = [background; 6];
+            prepacked.pack_with::<C>(&mut found, offset);
+            assert_eq!(found, expected, "offset {offset}");
+        }
+    }
+}
+
+#[test]
+fn packs_like_value() {
+    matches_value::<Msb0Be>(&MSB);
+    matches_value::<Msb0Le>(&MSB);
+    matches_value::<Lsb0Be>(&MSB);
+    matches_value::<Lsb0Le>(&MSB);
+    matches_value::<Msb0Be>(&LSB);
+    matches_value::<Lsb0Le>(&LSB);
+}
+
+#[test]
+fn derive_field() {
+    let frame = Frame {
+        kind: true,
+        preamble: PREAMBLE,
+        trailer: PREAMBLE,
+    };
+    let mut buf = [0u8; 7];
+    frame.pack(&mut buf, 0);
+    let mut expected = [0u8; 7];
+    (true, PREAMBLE, PREAMBLE).pack(&mut expected, 0);
+    PREAMBLE.pack_with::<crate::cfg::LittleEndian<Msb0Be>>(&mut expected, 26);
+    assert_eq!(buf, expected);
+    assert_eq!(Frame::unpack(&buf, 0), frame);
+
+    // other values are packed as usual
+    let other = Preamble {
+        version: 1,
+        ..PREAMBLE
+    };
+    let frame = Frame {
+        kind: false,
+        preamble: other,
+        trailer: other,
+    };
+    frame.pack(&mut buf, 0);
+    assert_eq!(Frame::unpack(&buf, 0), frame);
+}
@@ -0,0 +1,112 @@
+//! MIPI CSI-2 packed RAW pixel formats.
+//!
+//! RAW10, RAW12 and RAW14 pixels are packed in groups, with the top 8 bits
+//! of each pixel in its own byte, followed by the remaining low bits of the
+//! group packed LSB-first.  This keeps the top bits readable as 8-bit
+//! pixels, but means the layout can't be described with a plain
+//! [`Grid`](crate::grid::Grid).
+//!
+//! Only available with the `raw` feature.
+//!
+//! ```rust
+//! # use packed::raw;
+//! let pixels = [0x3ff, 0x000, 0x155, 0x2aa];
+//! let bytes = raw::encode_raw10(&pixels);
+//! assert_eq!(bytes, [0xff, 0x00, 0x55, 0xaa, 0b10_01_00_11]);
+//! assert_eq!(raw::decode_raw10(&bytes), Ok(pixels.to_vec()));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::Lsb0Le;
+use crate::error::bit_len;
+use crate::narrow::NarrowInt;
+use crate::{UnpackError, UnpackErrorKind};
+
+/// Pack `pixels` of `bits` bits in groups of `group`
+fn encode(pixels: &[u16], bits: usize, group: usize) -> Vec<u8> {
+    assert!(
+        pixels.len().is_multiple_of(group),
+        "{} pixels don't fill whole groups of {group}",
+        pixels.len()
+    );
+    let low = bits - 8;
+    let group_len = group * bits / 8;
+    let mut out = vec![0u8; pixels.len() / group * group_len];
+    for (chunk, bytes) in pixels.chunks(group).zip(out.chunks_mut(group_len)) {
+        for (i, &x) in chunk.iter().enumerate() {
+            bytes[i] = (x >> low) as u8;
+            x.pack_narrow::<Lsb0Le>(low, bytes, group * 8 + i * low);
+        }
+    }
+    out
+}
+
+/// Unpack pixels of `bits` bits in groups of `group`
+fn decode(bytes: &[u8], bits: usize, group: usize) -> Result<Vec<u16>, UnpackError> {
+    let low = bits - 8;
+    let group_len = group * bits / 8;
+    if !bytes.len().is_multiple_of(group_len) {
+        return Err(UnpackErrorKind::UnexpectedEnd {
+            offset: bit_len(bytes),
+        }
+        .into());
+    }
+    let mut out = Vec::with_capacity(bytes.len() / group_len * group);
+    for bytes in bytes.chunks(group_len) {
+        for i in 0..group {
+            let x = u16::unpack_narrow::<Lsb0Le>(low, bytes, group * 8 + i * low);
+            out.push((bytes[i] as u16) << low | x);
+        }
+    }
+    Ok(out)
+}
+
+/// Pack 10-bit pixels as RAW10, four pixels to five bytes.  Bits above the
+/// lowest 10 are ignored.
+///
+/// # Panics
+///
+/// If the amount of pixels isn't a multiple of four
+pub fn encode_raw10(pixels: &[u16]) -> Vec<u8> {
+    encode(pixels, 10, 4)
+}
+
+/// Unpack RAW10 pixels, failing if the last group of five bytes is
+/// incomplete
+pub fn decode_raw10(bytes: &[u8]) -> Result<Vec<u16>, UnpackError> {
+    decode(bytes, 10, 4)
+}
+
+/// Pack 12-bit pixels as RAW12, two pixels to three bytes.  Bits above the
+/// lowest 12 are ignored.
+///
+/// # Panics
+///
+/// If the amount of pixels isn't a multiple of two
+pub fn encode_raw12(pixels: &[u16]) -> Vec<u8> {
+    encode(pixels, 12, 2)
+}
+
+/// Unpack RAW12 pixels, failing if the last group of three bytes is
+/// incomplete
+pub fn decode_raw12(bytes: &[u8]) -> Result<Vec<u16>, UnpackError> {
+    decode(bytes, 12, 2)
+}
+
+/// Pack 14-bit pixels as RAW14, four pixels to seven bytes.  Bits above the
+/// lowest 14 are ignored.
+///
+/// # Panics
+///
+/// If the amount of pixels isn't a multiple of four
+pub fn encode_raw14(pixels: &[u16]) -> Vec<u8> {
+    encode(pixels, 14, 4)
+}
+
+/// Unpack RAW14 pixels, failing if the last group of seven bytes is
+/// incomplete
+pub fn decode_raw14(bytes: &[u8]) -> Result<Vec<u16>, UnpackError> {
+    decode(bytes, 14, 4)
+}
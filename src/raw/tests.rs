@@ -0,0 +1,41 @@
+use super::{decode_raw10, decode_raw12, decode_raw14, encode_raw10, encode_raw12, encode_raw14};
+use crate::UnpackErrorKind;
+
+#[test]
+fn raw12_layout() {
+    let bytes = encode_raw12(&[0xabc, 0x123]);
+    assert_eq!(bytes, [0xab, 0x12, 0x3c]);
+}
+
+#[test]
+fn raw14_layout() {
+    let bytes = encode_raw14(&[0x3fff, 0x0001, 0x2002, 0x0003 | 0x3fc0]);
+    assert_eq!(bytes, [0xff, 0x00, 0x80, 0xff, 0x7f, 0x20, 0x0c]);
+}
+
+#[test]
+fn incomplete_group() {
+    assert_eq!(
+        decode_raw10(&[0; 6]),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 48 }.into())
+    );
+    assert_eq!(decode_raw12(&[]), Ok(vec![]));
+}
+
+#[test]
+#[should_panic = "whole groups"]
+fn partial_group() {
+    encode_raw10(&[1, 2, 3]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(groups in proptest::collection::vec(proptest::array::uniform4(0u16..1 << 14), 0..4)) {
+        let pixels = groups.concat();
+        let raw10: Vec<_> = pixels.iter().map(|x| x & 0x3ff).collect();
+        let raw12: Vec<_> = pixels.iter().map(|x| x & 0xfff).collect();
+        assert_eq!(decode_raw10(&encode_raw10(&pixels)), Ok(raw10));
+        assert_eq!(decode_raw12(&encode_raw12(&pixels)), Ok(raw12));
+        assert_eq!(decode_raw14(&encode_raw14(&pixels)), Ok(pixels));
+    }
+}
@@ -0,0 +1,252 @@
+//! An append-only file of fixed-size packed records.
+//!
+//! A [`RecordLog`] starts with a 16 byte header holding a magic number, the
+//! size of a record in bits and the amount of records, followed by the
+//! records themselves.  Each record is padded to whole bytes, so record `i`
+//! can be read with a single seek.  Appending writes the record before
+//! bumping the count in the header, so a crash part way through leaves the
+//! log as it was before.
+//!
+//! Logs are read with `Read + Seek` and written with `Write + Seek`, so they
+//! can live in anything from a [`File`] to a `Cursor<Vec<u8>>`.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::record_log::RecordLog;
+//! # use std::io::Cursor;
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct Sample {
+//!     sensor: u8,
+//!     #[packed(bits = 12)]
+//!     value: u16,
+//! }
+//!
+//! # #[cfg(feature = "derive")]
+//! # fn main() -> std::io::Result<()> {
+//! let mut log = RecordLog::<Sample, _>::init(Cursor::new(Vec::new()))?;
+//! log.append(&Sample { sensor: 1, value: 100 })?;
+//! log.append(&Sample { sensor: 2, value: 200 })?;
+//!
+//! // reopen the same bytes, as if from a file written earlier
+//! let mut log = RecordLog::<Sample, _>::load(log.into_inner())?;
+//! assert_eq!(log.len(), 2);
+//! assert_eq!(log.get(1)?, Some(Sample { sensor: 2, value: 200 }));
+//! assert_eq!(log.iter().map(|s| s.unwrap().value).collect::<Vec<_>>(), [100, 200]);
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "derive"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::{Pack, Unpack};
+
+/// Magic number at the start of every log
+pub const MAGIC: [u8; 4] = *b"PKRL";
+/// Size of the header in bytes
+pub const HEADER_LEN: u64 = 16;
+
+/// A log of `T` records stored in `F`, packed with `C`
+#[derive(Debug)]
+pub struct RecordLog<T, F = File, C = DefaultCfg> {
+    inner: F,
+    len: u64,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<T> RecordLog<T>
+where
+    T: Pack + Unpack,
+{
+    /// Create an empty log at `path`, replacing any file already there
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Self::init(file)
+    }
+
+    /// Open the existing log at `path` for reading and appending
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::load(File::options().read(true).write(true).open(path)?)
+    }
+}
+
+impl<T, F, C> RecordLog<T, F, C>
+where
+    T: Pack + Unpack,
+    C: Cfg,
+{
+    /// Bytes each record takes up
+    const STRIDE: u64 = T::SIZE.div_ceil(8) as u64;
+
+    /// Start an empty log at the start of `inner`
+    pub fn init(mut inner: F) -> io::Result<Self>
+    where
+        F: Write + Seek,
+    {
+        let bits = u32::try_from(T::SIZE).map_err(|_| invalid("record too large"))?;
+        let mut header = [0u8; HEADER_LEN as usize];
+        header[..4].copy_from_slice(&MAGIC);
+        header[4..8].copy_from_slice(&bits.to_be_bytes());
+        inner.seek(SeekFrom::Start(0))?;
+        inner.write_all(&header)?;
+        Ok(Self {
+            inner,
+            len: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Read the header of a log written earlier at the start of `inner`
+    ///
+    /// # Errors
+    ///
+    /// With [`io::ErrorKind::InvalidData`] if the header is missing or its
+    /// record size isn't `T::SIZE`
+    pub fn load(mut inner: F) -> io::Result<Self>
+    where
+        F: Read + Seek,
+    {
+        let mut header = [0u8; HEADER_LEN as usize];
+        inner.seek(SeekFrom::Start(0))?;
+        inner.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => invalid("missing record log header"),
+            _ => e,
+        })?;
+        if header[..4] != MAGIC {
+            return Err(invalid("not a record log"));
+        }
+        let bits = u32::from_be_bytes(header[4..8].try_into().unwrap());
+        if usize::try_from(bits).ok() != Some(T::SIZE) {
+            return Err(invalid("record size doesn't match the record type"));
+        }
+        let len = u64::from_be_bytes(header[8..].try_into().unwrap());
+        Ok(Self {
+            inner,
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Amount of records in the log
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Byte position of record `i`
+    fn position(i: u64) -> io::Result<u64> {
+        i.checked_mul(Self::STRIDE)
+            .and_then(|at| at.checked_add(HEADER_LEN))
+            .ok_or_else(|| invalid("record index out of range"))
+    }
+
+    /// Append `record`, returning its index
+    pub fn append(&mut self, record: &T) -> io::Result<u64>
+    where
+        F: Write + Seek,
+    {
+        let mut buf = vec![0u8; Self::STRIDE as usize];
+        record.pack_with::<C>(&mut buf, 0);
+        self.inner
+            .seek(SeekFrom::Start(Self::position(self.len)?))?;
+        self.inner.write_all(&buf)?;
+        // the record only counts once it is fully written
+        self.inner.seek(SeekFrom::Start(8))?;
+        self.inner.write_all(&(self.len + 1).to_be_bytes())?;
+        self.len += 1;
+        Ok(self.len - 1)
+    }
+
+    /// Record `i`, or `None` past the end of the log
+    ///
+    /// # Errors
+    ///
+    /// With [`io::ErrorKind::InvalidData`] if the record fails
+    /// [`Unpack::unpack_strict_with`], or if the file ends before it
+    pub fn get(&mut self, i: u64) -> io::Result<Option<T>>
+    where
+        F: Read + Seek,
+    {
+        if i >= self.len {
+            return Ok(None);
+        }
+        let mut buf = vec![0u8; Self::STRIDE as usize];
+        self.inner.seek(SeekFrom::Start(Self::position(i)?))?;
+        self.inner
+            .read_exact(&mut buf)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => invalid("record log is truncated"),
+                _ => e,
+            })?;
+        T::unpack_strict_with::<C>(&buf, 0)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Iterate over the records from the first
+    pub fn iter(&mut self) -> Iter<'_, T, F, C>
+    where
+        F: Read + Seek,
+    {
+        Iter { log: self, next: 0 }
+    }
+
+    /// Flush buffered writes to `F`
+    pub fn flush(&mut self) -> io::Result<()>
+    where
+        F: Write,
+    {
+        self.inner.flush()
+    }
+
+    pub fn get_ref(&self) -> &F {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+}
+
+/// Iterator over the records of a [`RecordLog`], see [`RecordLog::iter`]
+#[derive(Debug)]
+pub struct Iter<'a, T, F, C> {
+    log: &'a mut RecordLog<T, F, C>,
+    next: u64,
+}
+
+impl<T, F, C> Iterator for Iter<'_, T, F, C>
+where
+    T: Pack + Unpack,
+    F: Read + Seek,
+    C: Cfg,
+{
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.log.get(self.next).transpose()?;
+        self.next += 1;
+        Some(record)
+    }
+}
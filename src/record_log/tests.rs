@@ -0,0 +1,99 @@
+use std::io::{self, Cursor};
+
+use super::{HEADER_LEN, RecordLog};
+use crate::cfg::Lsb0Le;
+use crate::{Packed, PackedSize, Reserved};
+
+#[derive(Debug, Clone, PartialEq, Packed)]
+struct Entry {
+    id: u16,
+    ok: bool,
+    _pad: Reserved<3>,
+}
+
+fn entry(id: u16) -> Entry {
+    Entry {
+        id,
+        ok: id.is_multiple_of(2),
+        _pad: Reserved,
+    }
+}
+
+#[test]
+fn append_and_read() {
+    let mut log = RecordLog::<Entry, _, Lsb0Le>::init(Cursor::new(Vec::new())).unwrap();
+    assert!(log.is_empty());
+    for id in 0..5 {
+        assert_eq!(log.append(&entry(id)).unwrap(), u64::from(id));
+    }
+    assert_eq!(log.get(3).unwrap(), Some(entry(3)));
+    assert_eq!(log.get(5).unwrap(), None);
+    let bytes = log.into_inner().into_inner();
+    // 20 bit records are padded to 3 bytes
+    assert_eq!(bytes.len() as u64, HEADER_LEN + 5 * 3);
+    assert_eq!(bytes[..16], *b"PKRL\0\0\0\x14\0\0\0\0\0\0\0\x05");
+
+    let mut log = RecordLog::<Entry, _, Lsb0Le>::load(Cursor::new(bytes)).unwrap();
+    assert_eq!(log.len(), 5);
+    log.append(&entry(5)).unwrap();
+    let all = log.iter().collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(all, (0..6).map(entry).collect::<Vec<_>>());
+}
+
+#[test]
+fn invalid_logs() {
+    let kind = |bytes: Vec<u8>| {
+        RecordLog::<Entry, _>::load(Cursor::new(bytes))
+            .unwrap_err()
+            .kind()
+    };
+    assert_eq!(kind(vec![]), io::ErrorKind::InvalidData);
+    assert_eq!(kind(vec![0; 16]), io::ErrorKind::InvalidData);
+
+    let mut log = RecordLog::<Entry, _>::init(Cursor::new(Vec::new())).unwrap();
+    log.append(&entry(1)).unwrap();
+    let bytes = log.into_inner().into_inner();
+    // a log of different records is rejected
+    let err = RecordLog::<u16, _>::load(Cursor::new(bytes.clone())).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // so are truncated records and records with bad reserved bits
+    let mut log =
+        RecordLog::<Entry, _>::load(Cursor::new(bytes[..bytes.len() - 1].to_vec())).unwrap();
+    assert_eq!(log.get(0).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    let mut corrupt = bytes;
+    corrupt[18] |= 0x20;
+    let mut log = RecordLog::<Entry, _>::load(Cursor::new(corrupt)).unwrap();
+    let err = log.iter().next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("_pad"), "{err}");
+}
+
+#[test]
+fn torn_append_is_ignored() {
+    let mut log = RecordLog::<Entry, _>::init(Cursor::new(Vec::new())).unwrap();
+    log.append(&entry(1)).unwrap();
+    let mut bytes = log.into_inner().into_inner();
+    // a record written without its count being bumped
+    bytes.extend([0xab; 3]);
+    let mut log = RecordLog::<Entry, _>::load(Cursor::new(bytes)).unwrap();
+    assert_eq!(log.len(), 1);
+    log.append(&entry(2)).unwrap();
+    assert_eq!(log.get(1).unwrap(), Some(entry(2)));
+}
+
+#[test]
+fn file() {
+    let dir = std::env::temp_dir().join(format!("packed-log-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("entries.log");
+    let mut log = RecordLog::<Entry>::create(&path).unwrap();
+    log.append(&entry(7)).unwrap();
+    log.flush().unwrap();
+    drop(log);
+    let mut log = RecordLog::<Entry>::open(&path).unwrap();
+    assert_eq!(log.get(0).unwrap(), Some(entry(7)));
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), HEADER_LEN + 3);
+    assert!(RecordLog::<Entry>::open(dir.join("missing.log")).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,129 @@
+//! Naive bit-by-bit packing of primitives.
+//!
+//! Every bit is packed on its own, in the order given by the [`Cfg`], so
+//! this is slow but easy to check by eye.  It is the implementation used
+//! with the `paranoid` feature, and can be used to check [`Pack`] and
+//! [`Unpack`] against in fuzzers.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::reference;
+//! let mut expected = [0u8; 3];
+//! let mut buf = [0u8; 3];
+//! reference::pack(0x1234u16, &mut expected, 5);
+//! 0x1234u16.pack(&mut buf, 5);
+//! assert_eq!(buf, expected);
+//! assert_eq!(reference::unpack::<u16>(&buf, 5), 0x1234);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{BitOrder, Cfg, DefaultCfg, OrderedBytes};
+use crate::error::bit_len;
+use crate::{Pack, Unpack};
+
+/// A primitive with a reference implementation
+pub trait Primitive: Pack + Unpack + Copy {
+    /// The bits of the value in the order they are packed
+    fn to_bits<C: Cfg>(self) -> Vec<bool>;
+    /// The value from its bits in the order they are packed
+    fn from_bits<C: Cfg>(bits: &[bool]) -> Self;
+}
+
+impl Primitive for bool {
+    fn to_bits<C: Cfg>(self) -> Vec<bool> {
+        vec![self]
+    }
+
+    fn from_bits<C: Cfg>(bits: &[bool]) -> Self {
+        bits[0]
+    }
+}
+
+/// The bits of `byte` in packing order
+fn byte_bits<C: Cfg>(byte: u8) -> [bool; 8] {
+    std::array::from_fn(|i| match C::BIT_ORDER {
+        BitOrder::Msb0 => byte & 0x80 >> i != 0,
+        BitOrder::Lsb0 => byte & 1 << i != 0,
+    })
+}
+
+/// The byte from its bits in packing order
+fn bits_byte<C: Cfg>(bits: &[bool]) -> u8 {
+    let mut byte = 0;
+    for (i, &bit) in bits.iter().enumerate() {
+        if bit {
+            byte |= match C::BIT_ORDER {
+                BitOrder::Msb0 => 0x80 >> i,
+                BitOrder::Lsb0 => 1 << i,
+            };
+        }
+    }
+    byte
+}
+
+macro_rules! primitive_int {
+    ($($ty: ident),+) => {
+        $(impl Primitive for $ty {
+            fn to_bits<C: Cfg>(self) -> Vec<bool> {
+                self.to_bytes(C::BYTE_ORDER).into_iter().flat_map(byte_bits::<C>).collect()
+            }
+
+            fn from_bits<C: Cfg>(bits: &[bool]) -> Self {
+                let mut bytes = [0u8; $ty::BITS as usize / 8];
+                for (byte, bits) in bytes.iter_mut().zip(bits.chunks(8)) {
+                    *byte = bits_byte::<C>(bits);
+                }
+                Self::from_bytes(bytes, C::BYTE_ORDER)
+            }
+        })+
+    };
+}
+
+primitive_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+#[cfg(feature = "native-size")]
+primitive_int!(usize, isize);
+
+/// Pack `value` at `offset` one bit at a time
+pub fn pack_with<C, T>(value: T, bytes: &mut [u8], offset: usize)
+where
+    C: Cfg,
+    T: Primitive,
+{
+    debug_assert!(bit_len(bytes) - offset >= T::SIZE);
+    for (i, bit) in value.to_bits::<C>().into_iter().enumerate() {
+        let mask = crate::cfg::bit_mask::<C>(offset + i);
+        let byte = &mut bytes[(offset + i) / 8];
+        *byte = if bit { *byte | mask } else { *byte & !mask };
+    }
+}
+
+/// Unpack a value at `offset` one bit at a time
+pub fn unpack_with<C, T>(bytes: &[u8], offset: usize) -> T
+where
+    C: Cfg,
+    T: Primitive,
+{
+    debug_assert!(bit_len(bytes) - offset >= T::SIZE);
+    let bits = (offset..offset + T::SIZE)
+        .map(|i| bytes[i / 8] & crate::cfg::bit_mask::<C>(i) != 0)
+        .collect::<Vec<_>>();
+    T::from_bits::<C>(&bits)
+}
+
+/// Pack using [`DefaultCfg`]
+pub fn pack<T>(value: T, bytes: &mut [u8], offset: usize)
+where
+    T: Primitive,
+{
+    pack_with::<DefaultCfg, T>(value, bytes, offset)
+}
+
+/// Unpack using [`DefaultCfg`]
+pub fn unpack<T>(bytes: &[u8], offset: usize) -> T
+where
+    T: Primitive,
+{
+    unpack_with::<DefaultCfg, T>(bytes, offset)
+}
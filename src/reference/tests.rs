@@ -0,0 +1,53 @@
+use crate::cfg::{Cfg, Lsb0Be, Lsb0Le, Msb0Be, Msb0Le};
+use crate::reference::{self, Primitive};
+
+fn check<T>(value: T, offset: usize)
+where
+    T: Primitive + PartialEq + std::fmt::Debug,
+{
+    fn with<C: Cfg, T>(value: T, offset: usize)
+    where
+        T: Primitive + PartialEq + std::fmt::Debug,
+    {
+        let mut expected = [0xa5u8; 18];
+        let mut buf = [0xa5u8; 18];
+        reference::pack_with::<C, T>(value, &mut expected, offset);
+        value.pack_with::<C>(&mut buf, offset);
+        assert_eq!(buf, expected);
+        assert_eq!(reference::unpack_with::<C, T>(&buf, offset), value);
+        assert_eq!(T::unpack_with::<C>(&expected, offset), value);
+    }
+    with::<Msb0Be, T>(value, offset);
+    with::<Msb0Le, T>(value, offset);
+    with::<Lsb0Be, T>(value, offset);
+    with::<Lsb0Le, T>(value, offset);
+}
+
+#[test]
+fn reference_layout() {
+    let mut buf = [0u8; 3];
+    reference::pack_with::<Lsb0Le, u16>(0x1234, &mut buf, 4);
+    assert_eq!(buf, [0x40, 0x23, 0x01]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_primitives(
+        ints: (u8, u16, u32, u64, u128),
+        signed: (i8, i16, i32, i64, i128),
+        flag: bool,
+        offset in 0usize..=16,
+    ) {
+        check(flag, offset);
+        check(ints.0, offset);
+        check(ints.1, offset);
+        check(ints.2, offset);
+        check(ints.3, offset);
+        check(ints.4, offset);
+        check(signed.0, offset);
+        check(signed.1, offset);
+        check(signed.2, offset);
+        check(signed.3, offset);
+        check(signed.4, offset);
+    }
+}
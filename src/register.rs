@@ -0,0 +1,172 @@
+//! Volatile access to memory-mapped registers.
+//!
+//! A [`Register`] wraps a pointer to a hardware word and reads an [`Unpack`]
+//! value from it or writes a [`Pack`] value through it.  Every access is a
+//! single volatile load or store of the full word, so this is safe to use on
+//! MMIO regions where the access width matters.
+//!
+//! The packed value is aligned to the least significant end of the word, so
+//! the first field of `T` lands on the most significant bits, which matches
+//! how datasheets usually draw registers (bit 31 on the left).
+//!
+//! ```rust
+//! # use packed::register::Register;
+//! let mut mmio: u16 = 0;
+//! // SAFETY: `mmio` outlives `reg` and is not accessed elsewhere meanwhile
+//! let reg = unsafe { Register::<(bool, u8), u16>::new(&mut mmio) };
+//! reg.write((true, 0x42));
+//! assert_eq!(mmio, 0b0000_0001_0100_0010);
+//! reg.write_field(0, false);
+//! assert_eq!(reg.read(), (false, 0x42));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use core::marker::PhantomData;
+
+use crate::{Pack, PackedSize, Unpack};
+
+/// A hardware word that a register can be backed by
+pub trait Word: Copy {
+    /// Big-endian byte representation of the word
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default;
+    const BITS: usize;
+
+    fn to_bytes(self) -> Self::Bytes;
+    fn from_bytes(bytes: Self::Bytes) -> Self;
+}
+
+macro_rules! word {
+    ($ty: ident) => {
+        impl Word for $ty {
+            type Bytes = [u8; core::mem::size_of::<$ty>()];
+            const BITS: usize = Self::BITS as _;
+
+            fn to_bytes(self) -> Self::Bytes {
+                self.to_be_bytes()
+            }
+
+            fn from_bytes(bytes: Self::Bytes) -> Self {
+                Self::from_be_bytes(bytes)
+            }
+        }
+    };
+    ($($ty: ident),+) => {
+        $(word!($ty);)+
+    };
+}
+
+word!(u8, u16, u32, u64);
+
+/// A memory-mapped register of width `W` holding a packed `T`
+pub struct Register<T, W> {
+    ptr: *mut W,
+    _marker: PhantomData<T>,
+}
+
+impl<T, W> Register<T, W>
+where
+    T: PackedSize,
+    W: Word,
+{
+    /// Offset of `T` inside the big-endian bytes of the word
+    const OFFSET: usize = {
+        assert!(T::SIZE <= W::BITS, "packed type does not fit in register");
+        W::BITS - T::SIZE
+    };
+
+    /// # Safety
+    ///
+    /// `ptr` must be valid for volatile reads and writes and properly aligned
+    /// for as long as the register is used.
+    pub const unsafe fn new(ptr: *mut W) -> Self {
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut W {
+        self.ptr
+    }
+
+    /// Read the raw word from the register
+    pub fn read_word(&self) -> W {
+        // SAFETY: guaranteed by the caller of `new`
+        unsafe { self.ptr.read_volatile() }
+    }
+
+    /// Write a raw word to the register
+    pub fn write_word(&self, word: W) {
+        // SAFETY: guaranteed by the caller of `new`
+        unsafe { self.ptr.write_volatile(word) }
+    }
+
+    pub fn read(&self) -> T
+    where
+        T: Unpack,
+    {
+        T::unpack(self.read_word().to_bytes().as_ref(), Self::OFFSET)
+    }
+
+    /// Write `value` to the register.  Bits of the word not covered by `T`
+    /// are written as zero.
+    pub fn write(&self, value: T)
+    where
+        T: Pack,
+    {
+        let mut bytes = W::Bytes::default();
+        value.pack(bytes.as_mut(), Self::OFFSET);
+        self.write_word(W::from_bytes(bytes));
+    }
+
+    /// Read the register, let `f` change the value, and write it back
+    pub fn modify(&self, f: impl FnOnce(&mut T))
+    where
+        T: Pack + Unpack,
+    {
+        let mut value = self.read();
+        f(&mut value);
+        self.write(value);
+    }
+
+    /// Read a single field at bit `offset` into `T`
+    ///
+    /// # Panics
+    ///
+    /// If the field runs past the end of `T`
+    pub fn read_field<F>(&self, offset: usize) -> F
+    where
+        F: Unpack,
+    {
+        assert!(
+            offset
+                .checked_add(F::SIZE)
+                .is_some_and(|end| end <= T::SIZE),
+            "field out of range of the register"
+        );
+        F::unpack(self.read_word().to_bytes().as_ref(), Self::OFFSET + offset)
+    }
+
+    /// Read-modify-write a single field at bit `offset` into `T`, leaving
+    /// every other bit of the word untouched
+    ///
+    /// # Panics
+    ///
+    /// If the field runs past the end of `T`
+    pub fn write_field<F>(&self, offset: usize, value: F)
+    where
+        F: Pack,
+    {
+        assert!(
+            offset
+                .checked_add(F::SIZE)
+                .is_some_and(|end| end <= T::SIZE),
+            "field out of range of the register"
+        );
+        let mut bytes = self.read_word().to_bytes();
+        value.pack(bytes.as_mut(), Self::OFFSET + offset);
+        self.write_word(W::from_bytes(bytes));
+    }
+}
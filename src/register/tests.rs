@@ -0,0 +1,51 @@
+use super::Register;
+
+#[test]
+fn read_write() {
+    let mut mmio: u32 = 0;
+    let reg = unsafe { Register::<(u8, bool, u16), u32>::new(&mut mmio) };
+    reg.write((0xab, true, 0x1234));
+    assert_eq!(mmio, 0xab << 17 | 1 << 16 | 0x1234);
+    assert_eq!(reg.read(), (0xab, true, 0x1234));
+}
+
+#[test]
+fn write_clears_unused_bits() {
+    let mut mmio: u16 = 0xffff;
+    let reg = unsafe { Register::<u8, u16>::new(&mut mmio) };
+    reg.write(0x0f);
+    assert_eq!(mmio, 0x000f);
+}
+
+#[test]
+fn modify() {
+    let mut mmio: u16 = 0;
+    let reg = unsafe { Register::<(bool, u8), u16>::new(&mut mmio) };
+    reg.write((false, 7));
+    reg.modify(|(flag, n)| {
+        *flag = true;
+        *n += 1;
+    });
+    assert_eq!(reg.read(), (true, 8));
+}
+
+#[test]
+#[should_panic = "out of range"]
+fn field_past_the_end() {
+    let mut mmio: u32 = 0;
+    let reg = unsafe { Register::<(u8, u16), u32>::new(&mut mmio) };
+    reg.write_field(16, 0u16);
+}
+
+proptest::proptest! {
+    #[test]
+    fn field_rmw(word: u32, value: u8, offset in 0usize..=24) {
+        let mut mmio = word;
+        let reg = unsafe { Register::<u32, u32>::new(&mut mmio) };
+        reg.write_field(offset, value);
+        let shift = 24 - offset;
+        let expected = word & !(0xff << shift) | u32::from(value) << shift;
+        assert_eq!(reg.read_word(), expected);
+        assert_eq!(reg.read_field::<u8>(offset), value);
+    }
+}
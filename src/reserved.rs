@@ -0,0 +1,70 @@
+//! Reserved and padding bits.
+//!
+//! [`Reserved`] takes up `BITS` bits which are always written as zero (or
+//! one, if `ONES` is set) and ignored by the plain `unpack`.  The strict
+//! unpack methods on [`Packed`] check that they hold their required value,
+//! which catches senders that scribble into reserved regions.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::{Reserved, UnpackErrorKind};
+//! type Frame = (u8, Reserved<4>, [bool; 4]);
+//! assert!(Frame::unpack_strict(&[0x12, 0x05], 0).is_ok());
+//! assert_eq!(
+//!     Frame::unpack_strict(&[0x12, 0x25], 0),
+//!     Err(UnpackErrorKind::Reserved { offset: 10, expected: false }.into()),
+//! );
+//! ```
+
+use crate::cfg::Cfg;
+use crate::error::bit_len;
+use crate::{Pack, PackedSize, Unpack, UnpackError, UnpackErrorKind};
+
+/// `BITS` reserved bits which must all be zero, or all one if `ONES` is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reserved<const BITS: usize, const ONES: bool = false>;
+
+impl<const BITS: usize, const ONES: bool> PackedSize for Reserved<BITS, ONES> {
+    const SIZE: usize = BITS;
+}
+
+impl<const BITS: usize, const ONES: bool> Pack for Reserved<BITS, ONES> {
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        for i in 0..BITS {
+            ONES.pack_with::<C>(bytes, offset + i);
+        }
+    }
+}
+
+impl<const BITS: usize, const ONES: bool> Unpack for Reserved<BITS, ONES> {
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        Self
+    }
+
+    fn check_reserved_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(), UnpackError> {
+        Self::check_bits::<C>(bytes, offset, BITS)
+    }
+}
+
+impl<const BITS: usize, const ONES: bool> Reserved<BITS, ONES> {
+    /// Check that `n` bits at `offset` are all set to `ONES`, for reserved
+    /// bits whose amount isn't known at compile time
+    pub(crate) fn check_bits<C: Cfg>(
+        bytes: &[u8],
+        offset: usize,
+        n: usize,
+    ) -> Result<(), UnpackError> {
+        debug_assert!(bit_len(bytes) - offset >= n);
+        match (0..n).find(|i| bool::unpack_with::<C>(bytes, offset + i) != ONES) {
+            Some(i) => Err(UnpackErrorKind::Reserved {
+                offset: offset + i,
+                expected: ONES,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
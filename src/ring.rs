@@ -0,0 +1,163 @@
+//! A fixed-capacity ring buffer of records packed bit by bit.
+//!
+//! [`PackedRingBuffer`] stores its records back to back with no padding in
+//! an array it owns, so it needs no allocator and can be built in a
+//! `static`.  Once full, pushing overwrites the oldest record, which suits
+//! telemetry buffered between radio windows where the newest samples matter
+//! most.  The array size can't be computed from the record type on stable
+//! Rust, so it is given as a second parameter, which [`bytes_for`]
+//! calculates.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::ring::{self, PackedRingBuffer};
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct Sample {
+//!     #[packed(bits = 10)]
+//!     level: u16,
+//!     alarm: bool,
+//! }
+//!
+//! // three 11 bit samples fit in 5 bytes
+//! type Samples = PackedRingBuffer<Sample, 3, { ring::bytes_for::<Sample>(3) }>;
+//! let mut samples = Samples::new();
+//! for level in 1..=4 {
+//!     samples.push(&Sample { level, alarm: false });
+//! }
+//! // the oldest sample was overwritten
+//! assert_eq!(samples.len(), 3);
+//! assert_eq!(samples.pop(), Some(Sample { level: 2, alarm: false }));
+//! assert_eq!(samples.iter().map(|s| s.level).collect::<Vec<_>>(), [3, 4]);
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::{Pack, PackedSize, Unpack};
+
+/// Bytes needed to hold `capacity` records of `T`
+pub const fn bytes_for<T: PackedSize>(capacity: usize) -> usize {
+    (capacity * T::SIZE).div_ceil(8)
+}
+
+/// A ring buffer of up to `CAPACITY` records of `T`, packed with `C` into
+/// `BYTES` bytes, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct PackedRingBuffer<T, const CAPACITY: usize, const BYTES: usize, C = DefaultCfg> {
+    bytes: [u8; BYTES],
+    /// Slot of the oldest record
+    head: usize,
+    len: usize,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T, const CAPACITY: usize, const BYTES: usize, C> PackedRingBuffer<T, CAPACITY, BYTES, C>
+where
+    T: Pack + Unpack,
+    C: Cfg,
+{
+    /// An empty buffer
+    ///
+    /// # Panics
+    ///
+    /// At compile time, if `CAPACITY` is zero or `BYTES` can't hold
+    /// `CAPACITY` records
+    pub const fn new() -> Self {
+        const {
+            assert!(CAPACITY > 0, "ring buffers can't be empty");
+            assert!(
+                BYTES >= bytes_for::<T>(CAPACITY),
+                "`BYTES` is too small for `CAPACITY` records"
+            );
+        }
+        Self {
+            bytes: [0; BYTES],
+            head: 0,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == CAPACITY
+    }
+
+    pub const fn capacity(&self) -> usize {
+        CAPACITY
+    }
+
+    /// Bit offset of the `i`th record from the oldest
+    fn offset(&self, i: usize) -> usize {
+        (self.head + i) % CAPACITY * T::SIZE
+    }
+
+    /// Add a record after the newest, returning the oldest if it had to be
+    /// overwritten to make room
+    pub fn push(&mut self, value: &T) -> Option<T> {
+        let evicted = if self.is_full() { self.pop() } else { None };
+        let offset = self.offset(self.len);
+        value.pack_with::<C>(&mut self.bytes, offset);
+        self.len += 1;
+        evicted
+    }
+
+    /// Remove and return the oldest record
+    pub fn pop(&mut self) -> Option<T> {
+        let value = self.get(0)?;
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// The `i`th record from the oldest
+    pub fn get(&self, i: usize) -> Option<T> {
+        if i >= self.len {
+            return None;
+        }
+        Some(T::unpack_with::<C>(&self.bytes, self.offset(i)))
+    }
+
+    /// The newest record
+    pub fn last(&self) -> Option<T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Iterate over the records from the oldest, leaving them in the buffer
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = T> + '_ {
+        (0..self.len).map(|i| T::unpack_with::<C>(&self.bytes, self.offset(i)))
+    }
+
+    /// Remove the records from the oldest as they are iterated over
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.pop())
+    }
+}
+
+impl<T, const CAPACITY: usize, const BYTES: usize, C> Default
+    for PackedRingBuffer<T, CAPACITY, BYTES, C>
+where
+    T: Pack + Unpack,
+    C: Cfg,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
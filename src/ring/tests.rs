@@ -0,0 +1,68 @@
+use super::{PackedRingBuffer, bytes_for};
+use crate::cfg::Lsb0Le;
+
+type Small = PackedRingBuffer<(bool, [bool; 2]), 5, { bytes_for::<(bool, [bool; 2])>(5) }>;
+
+#[test]
+fn size() {
+    assert_eq!(bytes_for::<(bool, [bool; 2])>(5), 2);
+    assert_eq!(bytes_for::<u8>(0), 0);
+    assert_eq!(std::mem::size_of::<[u8; bytes_for::<u16>(3)]>(), 6);
+}
+
+#[test]
+fn push_pop() {
+    let mut ring = Small::new();
+    assert_eq!(ring.capacity(), 5);
+    assert_eq!(ring.pop(), None);
+    assert_eq!(ring.last(), None);
+    for i in 0..5u8 {
+        let value = (i % 2 == 0, [i & 2 != 0, i & 4 != 0]);
+        assert_eq!(ring.push(&value), None);
+        assert_eq!(ring.last(), Some(value));
+    }
+    assert!(ring.is_full());
+    assert_eq!(ring.get(4), Some((true, [false, true])));
+    assert_eq!(ring.get(5), None);
+
+    // full, so the oldest is overwritten and returned
+    assert_eq!(
+        ring.push(&(false, [true, true])),
+        Some((true, [false, false]))
+    );
+    assert_eq!(ring.len(), 5);
+    assert_eq!(ring.get(0), Some((false, [false, false])));
+    assert_eq!(ring.last(), Some((false, [true, true])));
+
+    let drained: Vec<_> = ring.drain().take(2).collect();
+    assert_eq!(drained.len(), 2);
+    assert_eq!(ring.len(), 3);
+    ring.clear();
+    assert!(ring.is_empty());
+    assert_eq!(ring.iter().len(), 0);
+}
+
+#[test]
+fn wraps_around() {
+    // 12 bit records straddle bytes, and the buffer has a spare byte
+    let record = |r: u8| (r.wrapping_mul(37), [r & 1 != 0, r & 2 != 0, true, false]);
+    let mut ring = PackedRingBuffer::<(u8, [bool; 4]), 3, 6, Lsb0Le>::default();
+    for round in 0..10 {
+        ring.push(&record(round));
+        let oldest = round.saturating_sub(2);
+        let expected: Vec<_> = (oldest..=round).map(record).collect();
+        assert_eq!(ring.iter().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+fn in_a_static() {
+    use std::sync::Mutex;
+
+    static SAMPLES: Mutex<PackedRingBuffer<u8, 4, 4>> = Mutex::new(PackedRingBuffer::new());
+    let mut samples = SAMPLES.lock().unwrap();
+    for x in 0..6 {
+        samples.push(&x);
+    }
+    assert_eq!(samples.iter().collect::<Vec<_>>(), [2, 3, 4, 5]);
+}
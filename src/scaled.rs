@@ -0,0 +1,190 @@
+//! Physical quantities packed as scaled integers.
+//!
+//! Signal definitions such as those of CAN databases and J1939 give a
+//! quantity as a raw integer with a scale and an offset: the physical value
+//! is `raw * NUM / DEN + OFFSET`.  [`Scaled`] holds the raw integer, so it
+//! packs exactly as it was unpacked, and converts to and from the physical
+//! value.  Like any integer it can be narrowed with `#[packed(bits = N)]`.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::scaled::Scaled;
+//! /// 0.25 °C steps from -40 °C
+//! type Celsius = Scaled<u16, 1, 4, -40>;
+//!
+//! #[derive(Packed)]
+//! struct Reading {
+//!     #[packed(bits = 10)]
+//!     temperature: Celsius,
+//!     valid: bool,
+//! }
+//!
+//! let reading = Reading { temperature: Celsius::new(21.75), valid: true };
+//! assert_eq!(reading.temperature.raw(), 247);
+//! let mut buf = [0u8; 2];
+//! reading.pack(&mut buf, 0);
+//! assert_eq!(Reading::unpack(&buf, 0).temperature.value(), 21.75);
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::cfg::Cfg;
+use crate::narrow::NarrowInt;
+use crate::{Pack, PackedSize, Unpack};
+
+/// An integer a [`Scaled`] value can be packed as
+pub trait RawInt: NarrowInt + Pack + Unpack {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn to_f64(self) -> f64;
+    /// Convert, saturating at the bounds of the type
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! raw_int {
+    ($($ty: ident),+) => {
+        $(impl RawInt for $ty {
+            const MIN: Self = $ty::MIN;
+            const MAX: Self = $ty::MAX;
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(value: f64) -> Self {
+                value as Self
+            }
+        })+
+    };
+}
+
+raw_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// A physical value of `raw * NUM / DEN + OFFSET`, packed as its raw
+/// integer `T`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Scaled<T, const NUM: i64, const DEN: i64 = 1, const OFFSET: i64 = 0> {
+    raw: T,
+}
+
+impl<T: RawInt, const NUM: i64, const DEN: i64, const OFFSET: i64> Scaled<T, NUM, DEN, OFFSET> {
+    const VALID: () = assert!(NUM != 0 && DEN != 0, "the scale can't be zero");
+
+    pub const fn from_raw(raw: T) -> Self {
+        Self { raw }
+    }
+
+    /// The nearest value to `value`, saturating at the bounds of `T`
+    pub fn new(value: f64) -> Self {
+        Self::from_raw(T::from_f64(Self::raw_for(value)))
+    }
+
+    /// The nearest value to `value`, or `None` if it is out of the range of
+    /// `T`
+    pub fn try_new(value: f64) -> Option<Self> {
+        let raw = Self::raw_for(value);
+        (T::MIN.to_f64() <= raw && raw <= T::MAX.to_f64()).then(|| Self::from_raw(T::from_f64(raw)))
+    }
+
+    fn raw_for(value: f64) -> f64 {
+        let () = Self::VALID;
+        ((value - OFFSET as f64) * DEN as f64 / NUM as f64).round()
+    }
+
+    pub const fn raw(self) -> T {
+        self.raw
+    }
+
+    /// The physical value
+    pub fn value(self) -> f64 {
+        let () = Self::VALID;
+        self.raw.to_f64() * NUM as f64 / DEN as f64 + OFFSET as f64
+    }
+
+    /// The smallest representable value
+    pub fn min() -> f64 {
+        Self::from_raw(T::MIN)
+            .value()
+            .min(Self::from_raw(T::MAX).value())
+    }
+
+    /// The largest representable value
+    pub fn max() -> f64 {
+        Self::from_raw(T::MIN)
+            .value()
+            .max(Self::from_raw(T::MAX).value())
+    }
+}
+
+impl<T, const NUM: i64, const DEN: i64, const OFFSET: i64> fmt::Debug
+    for Scaled<T, NUM, DEN, OFFSET>
+where
+    T: RawInt + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scaled")
+            .field("raw", &self.raw)
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+impl<T, const NUM: i64, const DEN: i64, const OFFSET: i64> fmt::Display
+    for Scaled<T, NUM, DEN, OFFSET>
+where
+    T: RawInt,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value(), f)
+    }
+}
+
+impl<T: PackedSize, const NUM: i64, const DEN: i64, const OFFSET: i64> PackedSize
+    for Scaled<T, NUM, DEN, OFFSET>
+{
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T: Pack, const NUM: i64, const DEN: i64, const OFFSET: i64> Pack
+    for Scaled<T, NUM, DEN, OFFSET>
+{
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        self.raw.pack_with::<C>(bytes, offset);
+    }
+}
+
+impl<T: Unpack, const NUM: i64, const DEN: i64, const OFFSET: i64> Unpack
+    for Scaled<T, NUM, DEN, OFFSET>
+{
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Self {
+            raw: T::unpack_with::<C>(bytes, offset),
+        }
+    }
+}
+
+/// Narrowing narrows the raw integer, whose value has to fit in the bits
+/// it is narrowed to
+impl<T: NarrowInt, const NUM: i64, const DEN: i64, const OFFSET: i64> NarrowInt
+    for Scaled<T, NUM, DEN, OFFSET>
+{
+    const BITS: usize = T::BITS;
+
+    fn pack_narrow<C: Cfg>(self, bits: usize, bytes: &mut [u8], offset: usize) {
+        self.raw.pack_narrow::<C>(bits, bytes, offset);
+    }
+
+    fn unpack_narrow<C: Cfg>(bits: usize, bytes: &[u8], offset: usize) -> Self {
+        Self {
+            raw: T::unpack_narrow::<C>(bits, bytes, offset),
+        }
+    }
+}
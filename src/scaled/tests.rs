@@ -0,0 +1,89 @@
+use super::Scaled;
+#[cfg(feature = "derive")]
+use crate::cfg::Lsb0Le;
+#[cfg(feature = "derive")]
+use crate::{Pack, Packed, PackedSize, Unpack};
+
+/// J1939 engine coolant temperature: 1 °C per bit from -40 °C
+type Coolant = Scaled<u8, 1, 1, -40>;
+/// J1939 engine speed: 0.125 rpm per bit
+type Rpm = Scaled<u16, 1, 8>;
+/// 0.5 steps below zero
+type Negative = Scaled<i16, -1, 2>;
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Packed)]
+struct Signals {
+    coolant: Coolant,
+    rpm: Rpm,
+    #[packed(bits = 6)]
+    level: Scaled<u8, 5, 1, 0>,
+    #[packed(bits = 5)]
+    trim: Scaled<i8, 1, 10, 0>,
+}
+
+#[test]
+fn converts() {
+    assert_eq!(Coolant::new(90.0).raw(), 130);
+    assert_eq!(Coolant::from_raw(130).value(), 90.0);
+    assert_eq!(Rpm::new(1500.0).raw(), 12000);
+    assert_eq!(Rpm::new(1500.06).raw(), 12000);
+    assert_eq!(Rpm::new(1500.07).raw(), 12001);
+    assert_eq!(Negative::new(-3.0).raw(), 6);
+    assert_eq!(Negative::from_raw(-1).value(), 0.5);
+    assert_eq!(Coolant::new(90.0).to_string(), "90");
+}
+
+#[test]
+fn range() {
+    assert_eq!(Coolant::min(), -40.0);
+    assert_eq!(Coolant::max(), 215.0);
+    assert_eq!(Negative::min(), -16383.5);
+    assert_eq!(Negative::max(), 16384.0);
+    assert_eq!(Coolant::new(-100.0).raw(), 0);
+    assert_eq!(Coolant::new(1000.0).raw(), 255);
+    assert_eq!(Coolant::new(f64::NAN).raw(), 0);
+    assert_eq!(Coolant::try_new(-41.0), None);
+    assert_eq!(Coolant::try_new(215.4), Some(Coolant::from_raw(255)));
+    assert_eq!(Coolant::try_new(215.6), None);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn packs_raw() {
+    assert_eq!(Signals::SIZE, 35);
+    let signals = Signals {
+        coolant: Coolant::new(25.0),
+        rpm: Rpm::new(800.0),
+        level: Scaled::new(150.0),
+        trim: Scaled::new(-0.5),
+    };
+    let mut buf = [0u8; 5];
+    signals.pack(&mut buf, 0);
+    assert_eq!(buf, [65, 0x19, 0x00, 0b0111_1011, 0b0110_0000]);
+    assert_eq!(Signals::unpack(&buf, 0), signals);
+    assert_eq!(Signals::unpack(&buf, 0).trim.value(), -0.5);
+
+    signals.pack_with::<Lsb0Le>(&mut buf, 0);
+    assert_eq!(Signals::unpack_with::<Lsb0Le>(&buf, 0), signals);
+    assert_eq!(buf[1..3], [0x00, 0x19]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn round_trip(raw: u16) {
+        let rpm = Rpm::from_raw(raw);
+        proptest::prop_assert_eq!(Rpm::new(rpm.value()), rpm);
+        proptest::prop_assert_eq!(Rpm::try_new(rpm.value()), Some(rpm));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_roundtrip() {
+    // serialized as the raw value, so nothing is lost to rounding
+    let rpm = Rpm::from_raw(0x1234);
+    let json = serde_json::to_string(&rpm).unwrap();
+    assert_eq!(json, "4660");
+    assert_eq!(serde_json::from_str::<Rpm>(&json).unwrap(), rpm);
+}
@@ -0,0 +1,112 @@
+//! Scrambling bits as they are written and read by the cursors.
+//!
+//! Many radio and line codes whiten their payload by XORing it with a
+//! pseudo-random sequence, so long runs of equal bits don't occur on the
+//! wire.  A [`Scrambler`] given to
+//! [`BitWriter::with_scrambler`](crate::cursor::BitWriter::with_scrambler)
+//! or [`BitReader::with_scrambler`](crate::cursor::BitReader::with_scrambler)
+//! is applied to every bit in the order they are written, so values can be
+//! packed and unpacked as usual.  [`Lfsr`] is the additive scrambler most
+//! such formats use.
+//!
+//! ```rust
+//! # use packed::cursor::{BitReader, BitWriter};
+//! # use packed::scramble::Lfsr;
+//! // the IEEE 802.11 scrambler, x^7 + x^4 + 1
+//! let lfsr = Lfsr::new(0x91, 0x7f);
+//! let mut buf = [0u8; 2];
+//! let mut writer = BitWriter::new(&mut buf).with_scrambler(lfsr);
+//! writer.write(0u16).unwrap();
+//! assert_eq!(buf, [0b0000_1110, 0b1111_0010]);
+//!
+//! let mut reader = BitReader::new(&buf).with_scrambler(lfsr);
+//! assert_eq!(reader.read::<u16>(), Ok(0));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+/// Transforms a stream of bits one at a time
+pub trait Scrambler: Clone {
+    /// Whether the scrambler leaves every bit unchanged, which lets the
+    /// cursors skip it
+    const IDENTITY: bool = false;
+
+    /// Scramble the next bit written
+    fn scramble(&mut self, bit: bool) -> bool;
+
+    /// Undo [`Scrambler::scramble`] for the next bit read
+    fn descramble(&mut self, bit: bool) -> bool;
+}
+
+/// Leaves bits unchanged, the default for the cursors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Plain;
+
+impl Scrambler for Plain {
+    const IDENTITY: bool = true;
+
+    fn scramble(&mut self, bit: bool) -> bool {
+        bit
+    }
+
+    fn descramble(&mut self, bit: bool) -> bool {
+        bit
+    }
+}
+
+/// An additive scrambler XORing bits with the output of a Fibonacci linear
+/// feedback shift register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lfsr {
+    /// The polynomial without its `x^0` term, shifted down to line up with
+    /// the state
+    taps: u64,
+    state: u64,
+    mask: u64,
+}
+
+impl Lfsr {
+    /// A register for the feedback polynomial `poly`, with bit `k` set for
+    /// each term `x^k`, e.g. `0x91` for `x^7 + x^4 + 1`.  Bit `k - 1` of
+    /// `seed` is the initial value of the stage fed back by `x^k`.
+    ///
+    /// # Panics
+    ///
+    /// If `poly` isn't of degree 1 to 64
+    pub const fn new(poly: u128, seed: u64) -> Self {
+        assert!(
+            poly > 1 && poly < 1 << 65,
+            "LFSR polynomials are of degree 1 to 64"
+        );
+        let degree = 127 - poly.leading_zeros();
+        let mask = u64::MAX >> (64 - degree);
+        Self {
+            taps: (poly >> 1) as u64,
+            state: seed & mask,
+            mask,
+        }
+    }
+
+    /// The next bit of the sequence
+    pub fn next_bit(&mut self) -> bool {
+        let bit = (self.state & self.taps).count_ones() % 2 == 1;
+        self.state = (self.state << 1 | u64::from(bit)) & self.mask;
+        bit
+    }
+
+    /// Current contents of the register
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl Scrambler for Lfsr {
+    fn scramble(&mut self, bit: bool) -> bool {
+        bit ^ self.next_bit()
+    }
+
+    fn descramble(&mut self, bit: bool) -> bool {
+        bit ^ self.next_bit()
+    }
+}
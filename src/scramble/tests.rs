@@ -0,0 +1,85 @@
+use super::{Lfsr, Scrambler};
+use crate::cfg::Lsb0Le;
+use crate::crc::Crc;
+use crate::cursor::{BitReader, BitWriter};
+use crate::{Pack, Unpack};
+
+#[test]
+fn ieee_802_11_sequence() {
+    // the sequence repeated by the 802.11 scrambler seeded with all ones
+    let expected = "00001110 11110010 11001001 00000010 00100110 00101110 10110110 \
+                    00001100 11010100 11100111 10110100 00101010 11111010 01010001 \
+                    10111000 1111111";
+    let mut lfsr = Lfsr::new(0x91, 0x7f);
+    let found: String = (0..127)
+        .map(|_| if lfsr.next_bit() { '1' } else { '0' })
+        .collect();
+    assert_eq!(found, expected.replace(char::is_whitespace, ""));
+    assert_eq!(lfsr.state(), 0x7f);
+}
+
+#[test]
+fn degree_64() {
+    let mut lfsr = Lfsr::new(1 << 64 | 1 << 63 | 1, 1 << 62);
+    assert!(lfsr.next_bit());
+    assert_eq!(lfsr.state(), 1 << 63 | 1);
+    assert!(lfsr.next_bit());
+    assert_eq!(lfsr.state(), 0b11);
+}
+
+#[test]
+fn cursors() {
+    // PN9, as used for whitening by many sub-GHz radios
+    let pn9 = Lfsr::new(0x221, 0x1ff);
+    let mut buf = [0u8; 8];
+    let mut writer = BitWriter::<Lsb0Le>::with_cfg(&mut buf).with_scrambler(pn9);
+    writer.write(true).unwrap();
+    writer.attach_crc(Crc::CRC16_XMODEM);
+    writer.write(0x1234u16).unwrap();
+    writer.skip(3).unwrap();
+
+    let checkpoint = writer.checkpoint();
+    writer.write(0xffu8).unwrap();
+    writer.rollback(checkpoint);
+
+    writer.write([1u8, 2]).unwrap();
+    writer.finalize_crc::<u16>().unwrap();
+    assert_eq!(writer.finish(), 52);
+
+    let mut whitened = buf;
+    let mut lfsr = pn9;
+    for i in 0..52 {
+        let bit = bool::unpack_with::<Lsb0Le>(&buf, i);
+        lfsr.descramble(bit).pack_with::<Lsb0Le>(&mut whitened, i);
+    }
+    // the CRC covers the bits before they are scrambled
+    let mut crc = Crc::CRC16_XMODEM;
+    crc.update_bits::<Lsb0Le>(&whitened, 1, 35);
+    assert_eq!(
+        u16::unpack_with::<Lsb0Le>(&whitened, 36),
+        crc.value() as u16
+    );
+
+    let mut reader = BitReader::<Lsb0Le>::with_cfg(&buf).with_scrambler(pn9);
+    assert_eq!(reader.read::<bool>(), Ok(true));
+    assert_eq!(reader.peek::<u16>(), Ok(0x1234));
+    assert_eq!(reader.read::<u16>(), Ok(0x1234));
+    reader.skip(3).unwrap();
+    assert_eq!(reader.read::<[u8; 2]>(), Ok([1, 2]));
+    assert_eq!(reader.read::<u16>(), Ok(crc.value() as u16));
+}
+
+proptest::proptest! {
+    #[test]
+    fn round_trip(values: (u8, bool, u32, [bool; 3]), seed in 1u64..0x7f, lead in 0usize..16) {
+        let lfsr = Lfsr::new(0x91, seed);
+        let mut buf = [0u8; 8];
+        let mut writer = BitWriter::new(&mut buf).with_scrambler(lfsr);
+        writer.skip(lead).unwrap();
+        writer.write(values).unwrap();
+
+        let mut reader = BitReader::new(&buf).with_scrambler(lfsr);
+        reader.skip(lead).unwrap();
+        assert_eq!(reader.read(), Ok(values));
+    }
+}
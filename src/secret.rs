@@ -0,0 +1,158 @@
+//! Constant-time packing for secret values.
+//!
+//! The usual packing code takes shortcuts which depend on the values being
+//! packed, such as branching on each bit of a `bool`.  [`ConstantTime`]
+//! packs every bit with the same shifts and masks whatever its value, so the
+//! time taken depends only on the type and the offset, which are public.
+//! Wrapping a field in [`Secret`] packs it this way, so key material and
+//! authentication flags can sit in ordinary derived structs.
+//!
+//! This is only as strong as the compiler allows: the code is written
+//! without data-dependent branches or table lookups, but no compiler
+//! promises to keep it that way, so check the generated code on targets
+//! where it matters.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::secret::Secret;
+//! #[derive(Packed)]
+//! struct KeyExchange {
+//!     id: u8,
+//!     key: Secret<[u8; 4]>,
+//!     authenticated: Secret<bool>,
+//! }
+//!
+//! let mut buf = [0u8; 6];
+//! KeyExchange {
+//!     id: 1,
+//!     key: Secret([0xde, 0xad, 0xbe, 0xef]),
+//!     authenticated: Secret(true),
+//! }
+//! .pack(&mut buf, 0);
+//! assert_eq!(buf, [0x01, 0xde, 0xad, 0xbe, 0xef, 0x80]);
+//! assert_eq!(KeyExchange::unpack(&buf, 0).key.0, [0xde, 0xad, 0xbe, 0xef]);
+//! # }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::cfg::{BitOrder, Cfg, OrderedBytes};
+use crate::error::bit_len;
+use crate::{Pack, PackedSize, Unpack};
+
+/// A value which can be packed in constant time
+pub trait ConstantTime: PackedSize + Sized {
+    /// Pack the value at `offset`, taking the same time for every value
+    fn pack_ct<C: Cfg>(&self, bytes: &mut [u8], offset: usize);
+    /// Unpack a value at `offset`, taking the same time for every value
+    fn unpack_ct<C: Cfg>(bytes: &[u8], offset: usize) -> Self;
+}
+
+/// Position of the bit at `offset` within its byte, counted from the least
+/// significant bit
+const fn bit_shift<C: Cfg>(offset: usize) -> usize {
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => 7 - offset % 8,
+        BitOrder::Lsb0 => offset % 8,
+    }
+}
+
+/// Write the lowest bit of `bit` at `offset`
+fn write_bit<C: Cfg>(bit: u8, bytes: &mut [u8], offset: usize) {
+    let shift = bit_shift::<C>(offset);
+    let byte = &mut bytes[offset / 8];
+    *byte = *byte & !(1 << shift) | (bit & 1) << shift;
+}
+
+/// The bit at `offset`, as `0` or `1`
+fn read_bit<C: Cfg>(bytes: &[u8], offset: usize) -> u8 {
+    bytes[offset / 8] >> bit_shift::<C>(offset) & 1
+}
+
+impl ConstantTime for bool {
+    fn pack_ct<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        write_bit::<C>(u8::from(*self), bytes, offset);
+    }
+
+    fn unpack_ct<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+        read_bit::<C>(bytes, offset) == 1
+    }
+}
+
+macro_rules! constant_time_int {
+    ($($ty: ident),+) => {
+        $(impl ConstantTime for $ty {
+            fn pack_ct<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                for (i, byte) in self.to_bytes(C::BYTE_ORDER).into_iter().enumerate() {
+                    for j in 0..8 {
+                        let at = offset + i * 8 + j;
+                        write_bit::<C>(byte >> bit_shift::<C>(j), bytes, at);
+                    }
+                }
+            }
+
+            fn unpack_ct<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+                debug_assert!(bit_len(bytes) - offset >= Self::SIZE);
+                let mut wire = [0u8; $ty::BITS as usize / 8];
+                for (i, byte) in wire.iter_mut().enumerate() {
+                    for j in 0..8 {
+                        let at = offset + i * 8 + j;
+                        *byte |= read_bit::<C>(bytes, at) << bit_shift::<C>(j);
+                    }
+                }
+                Self::from_bytes(wire, C::BYTE_ORDER)
+            }
+        })+
+    };
+}
+
+constant_time_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<T, const N: usize> ConstantTime for [T; N]
+where
+    T: ConstantTime,
+{
+    fn pack_ct<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        for (i, x) in self.iter().enumerate() {
+            x.pack_ct::<C>(bytes, offset + i * T::SIZE);
+        }
+    }
+
+    fn unpack_ct<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        std::array::from_fn(|i| T::unpack_ct::<C>(bytes, offset + i * T::SIZE))
+    }
+}
+
+/// A value packed and unpacked in constant time, see [`ConstantTime`].  Its
+/// `Debug` output doesn't show the value.
+#[derive(Clone, Copy, Default)]
+pub struct Secret<T>(pub T);
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+impl<T: ConstantTime> PackedSize for Secret<T> {
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T: ConstantTime> Pack for Secret<T> {
+    fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        self.0.pack_ct::<C>(bytes, offset);
+    }
+}
+
+impl<T: ConstantTime> Unpack for Secret<T> {
+    fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Self(T::unpack_ct::<C>(bytes, offset))
+    }
+}
@@ -0,0 +1,98 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+use super::{ConstantTime, Secret};
+use crate::cfg::{Lsb0Be, Lsb0Le, Msb0Be, Msb0Le};
+use crate::{Pack, Unpack};
+
+/// Pack with both implementations into backgrounds of zeros and ones and
+/// check they agree
+fn agrees<T>(value: T, offset: usize)
+where
+    T: ConstantTime + Pack + Unpack + PartialEq + std::fmt::Debug,
+{
+    macro_rules! check {
+        ($($cfg: ty),+) => {
+            $(for background in [0x00, 0xff] {
+                let mut expected = vec![background; (offset + T::SIZE).div_ceil(8) + 1];
+                let mut found = expected.clone();
+                value.pack_with::<$cfg>(&mut expected, offset);
+                value.pack_ct::<$cfg>(&mut found, offset);
+                assert_eq!(found, expected, "{value:?} at {offset}");
+                assert_eq!(T::unpack_ct::<$cfg>(&found, offset), value);
+            })+
+        };
+    }
+    check!(Msb0Be, Msb0Le, Lsb0Be, Lsb0Le);
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_pack(a: u8, b: i16, c: u32, d: i64, e: u128, f: [bool; 3], offset in 0..16usize) {
+        agrees(a, offset);
+        agrees(b, offset);
+        agrees(c, offset);
+        agrees(d, offset);
+        agrees(e, offset);
+        agrees(f, offset);
+        agrees([b, b.wrapping_neg()], offset);
+    }
+}
+
+#[test]
+fn secret() {
+    let mut buf = [0u8; 3];
+    Secret(0x1234u16).pack(&mut buf, 4);
+    assert_eq!(buf, [0x01, 0x23, 0x40]);
+    assert_eq!(Secret::<u16>::unpack(&buf, 4).0, 0x1234);
+    assert_eq!(format!("{:?}", Secret(42)), "Secret(..)");
+}
+
+/// Welch's t statistic between two sets of timings
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    let stats = |xs: &[f64]| {
+        let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (xs.len() - 1) as f64;
+        (mean, var / xs.len() as f64)
+    };
+    let ((ma, va), (mb, vb)) = (stats(a), stats(b));
+    (ma - mb) / (va + vb).sqrt()
+}
+
+/// Time packing and unpacking a fixed value against random ones, like
+/// dudect, and fail if the two classes are told apart.  Timings are too
+/// noisy on shared machines to run this by default.
+#[test]
+#[ignore = "timing test, run with `cargo test --release -- --ignored`"]
+fn timing() {
+    const SAMPLES: usize = 20_000;
+    const BATCH: usize = 64;
+    // a fixed value of all zeros against random ones is the classic pair
+    // for exposing data-dependent shortcuts
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut random = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let mut times = [Vec::new(), Vec::new()];
+    let mut buf = [0u8; 10];
+    for _ in 0..SAMPLES {
+        let class = (random() & 1) as usize;
+        let values: [u64; BATCH] = std::array::from_fn(|_| if class == 0 { 0 } else { random() });
+        let start = Instant::now();
+        for value in values {
+            Secret(black_box(value)).pack(black_box(&mut buf), 3);
+            black_box(Secret::<u64>::unpack(black_box(&buf), 3));
+        }
+        times[class].push(start.elapsed().as_nanos() as f64);
+    }
+    // drop the slowest samples, which are mostly interrupts
+    for t in &mut times {
+        t.sort_by(f64::total_cmp);
+        t.truncate(t.len() * 9 / 10);
+    }
+    let t = welch_t(&times[0], &times[1]);
+    assert!(t.abs() < 4.5, "timings differ by class, t = {t:.2}");
+}
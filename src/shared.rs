@@ -0,0 +1,164 @@
+//! Packed arrays shared between threads.
+//!
+//! A [`PackedArc`] is a frozen array of values packed back to back, kept
+//! behind an [`Arc`].  Cloning and slicing it only bumps a reference count,
+//! and it is `Send` and `Sync` whatever the element type, as values are
+//! only unpacked on demand.  This suits serving packed columns to a thread
+//! pool, handing each thread its own slice without copying.
+//!
+//! ```rust
+//! # use packed::shared::PackedArc;
+//! let levels: PackedArc<u16> = (0..1000).collect();
+//! let total = std::thread::scope(|s| {
+//!     let workers = levels
+//!         .chunks(250)
+//!         .map(|chunk| s.spawn(move || chunk.iter().map(u64::from).sum::<u64>()))
+//!         .collect::<Vec<_>>();
+//!     workers.into_iter().map(|w| w.join().unwrap()).sum::<u64>()
+//! });
+//! assert_eq!(total, 999 * 1000 / 2);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::check_len;
+use crate::{Pack, PackedSize, Unpack, UnpackError};
+
+/// A read-only array of `T` packed with `C`, cheap to clone and share, see
+/// the [module docs](self)
+#[derive(Debug)]
+pub struct PackedArc<T, C = DefaultCfg> {
+    bytes: Arc<[u8]>,
+    /// Index of the first value in `bytes`
+    start: usize,
+    len: usize,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T: Pack> PackedArc<T> {
+    /// Pack `values` into a new array
+    pub fn new(values: &[T]) -> Self {
+        Self::with_cfg(values)
+    }
+}
+
+impl<T, C> PackedArc<T, C>
+where
+    C: Cfg,
+{
+    /// Pack `values` with the configuration `C` into a new array
+    pub fn with_cfg(values: &[T]) -> Self
+    where
+        T: Pack,
+    {
+        let mut bytes = vec![0u8; (values.len() * T::SIZE).div_ceil(8)];
+        crate::slice::pack_slice_with::<C, T>(values, &mut bytes, 0);
+        Self {
+            bytes: bytes.into(),
+            start: 0,
+            len: values.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// An array of the first `len` values packed in `bytes`, e.g. a column
+    /// read from disk
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>, len: usize) -> Result<Self, UnpackError>
+    where
+        T: PackedSize,
+    {
+        let bytes = bytes.into();
+        check_len(&bytes, 0, len.saturating_mul(T::SIZE))?;
+        Ok(Self {
+            bytes,
+            start: 0,
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<T>
+    where
+        T: Unpack,
+    {
+        (i < self.len).then(|| T::unpack_with::<C>(&self.bytes, (self.start + i) * T::SIZE))
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = T> + '_
+    where
+        T: Unpack,
+    {
+        (self.start..self.start + self.len).map(|i| T::unpack_with::<C>(&self.bytes, i * T::SIZE))
+    }
+
+    /// The values in `range`, sharing this array's buffer
+    ///
+    /// # Panics
+    ///
+    /// If `range` is out of bounds
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "range {range:?} out of bounds for a length of {}",
+            self.len
+        );
+        Self {
+            bytes: Arc::clone(&self.bytes),
+            start: self.start + range.start,
+            len: range.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Slices of `size` values each, except the last which may be shorter
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = Self> + '_ {
+        assert!(size > 0, "chunks can't be empty");
+        (0..self.len)
+            .step_by(size)
+            .map(move |i| self.slice(i..(i + size).min(self.len)))
+    }
+
+    /// The whole shared buffer, which slices start partway into
+    pub fn bytes(&self) -> &Arc<[u8]> {
+        &self.bytes
+    }
+}
+
+impl<T, C> Clone for PackedArc<T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: Arc::clone(&self.bytes),
+            start: self.start,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, C> FromIterator<T> for PackedArc<T, C>
+where
+    T: Pack,
+    C: Cfg,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::with_cfg(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
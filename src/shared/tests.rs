@@ -0,0 +1,55 @@
+use std::cell::Cell;
+
+use super::PackedArc;
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn shares_buffer() {
+    let values = PackedArc::new(&[1u16, 2, 3, 4, 5]);
+    let middle = values.slice(1..4);
+    assert_eq!(middle.iter().collect::<Vec<_>>(), [2, 3, 4]);
+    assert_eq!(middle.slice(1..3).get(1), Some(4));
+    assert_eq!(middle.get(3), None);
+    assert!(std::sync::Arc::ptr_eq(
+        values.bytes(),
+        middle.clone().bytes()
+    ));
+    assert_eq!(
+        values
+            .chunks(2)
+            .map(|c| c.iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        [vec![1, 2], vec![3, 4], vec![5]]
+    );
+    assert!(values.slice(5..5).is_empty());
+}
+
+#[test]
+fn from_bytes() {
+    let values = PackedArc::<[bool; 3], Lsb0Le>::from_bytes(vec![0b1100_0101], 2).unwrap();
+    assert_eq!(
+        values.iter().collect::<Vec<_>>(),
+        [[true, false, true], [false, false, false]]
+    );
+    assert_eq!(
+        PackedArc::<u16>::from_bytes([0u8; 3], 2)
+            .err()
+            .map(|e| e.kind().clone()),
+        Some(UnpackErrorKind::UnexpectedEnd { offset: 24 })
+    );
+}
+
+#[test]
+#[should_panic = "out of bounds"]
+fn slice_out_of_bounds() {
+    PackedArc::new(&[0u8; 4]).slice(2..5);
+}
+
+#[test]
+fn send_sync() {
+    fn shareable<T: Send + Sync + Clone>() {}
+    shareable::<PackedArc<u8>>();
+    // the element type is only ever unpacked, never shared
+    shareable::<PackedArc<Cell<u8>>>();
+}
@@ -0,0 +1,114 @@
+//! Shifting and rotating whole buffers by a number of bits.
+//!
+//! "Left" always means towards offset 0, so shifting left by `k` moves the
+//! bit at offset `k` to offset 0 whatever the bit order of the [`Cfg`].  This
+//! realigns a frame found at an unaligned offset onto a byte boundary:
+//!
+//! ```rust
+//! # use packed::shift;
+//! let mut buf = [0x0a, 0xbc, 0xd0];
+//! shift::shift_left(&mut buf, 4);
+//! assert_eq!(buf, [0xab, 0xcd, 0x00]);
+//! shift::rotate_right(&mut buf, 8);
+//! assert_eq!(buf, [0x00, 0xab, 0xcd]);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{BitOrder, Cfg, DefaultCfg};
+use crate::error::bit_len;
+
+/// The byte at bit offset `r` of the 16 bits `hi` followed by `lo`
+fn window<C: Cfg>(hi: u8, lo: u8, r: usize) -> u8 {
+    debug_assert!(r < 8);
+    if r == 0 {
+        return hi;
+    }
+    match C::BIT_ORDER {
+        BitOrder::Msb0 => hi << r | lo >> (8 - r),
+        BitOrder::Lsb0 => hi >> r | lo << (8 - r),
+    }
+}
+
+/// Move every bit `k` offsets towards offset 0, filling with zeros
+pub fn shift_left_with<C: Cfg>(bytes: &mut [u8], k: usize) {
+    let (q, r) = (k / 8, k % 8);
+    if q >= bytes.len() {
+        bytes.fill(0);
+        return;
+    }
+    bytes.copy_within(q.., 0);
+    let end = bytes.len() - q;
+    bytes[end..].fill(0);
+    for i in 0..end {
+        let next = bytes.get(i + 1).copied().unwrap_or(0);
+        bytes[i] = window::<C>(bytes[i], next, r);
+    }
+}
+
+/// Move every bit `k` offsets away from offset 0, filling with zeros
+pub fn shift_right_with<C: Cfg>(bytes: &mut [u8], k: usize) {
+    let (q, r) = (k / 8, k % 8);
+    if q >= bytes.len() {
+        bytes.fill(0);
+        return;
+    }
+    let end = bytes.len() - q;
+    bytes.copy_within(..end, q);
+    bytes[..q].fill(0);
+    if r == 0 {
+        return;
+    }
+    for i in (q..bytes.len()).rev() {
+        let prev = if i > q { bytes[i - 1] } else { 0 };
+        bytes[i] = window::<C>(prev, bytes[i], 8 - r);
+    }
+}
+
+/// Rotate every bit `k` offsets towards offset 0, wrapping around the end of
+/// the buffer
+pub fn rotate_left_with<C: Cfg>(bytes: &mut [u8], k: usize) {
+    if bytes.is_empty() {
+        return;
+    }
+    let k = k % bit_len(bytes);
+    bytes.rotate_left(k / 8);
+    let r = k % 8;
+    if r != 0 {
+        // the bits shifted out of the first byte come back in at the end
+        let first = bytes[0];
+        shift_left_with::<C>(bytes, r);
+        let last = bytes.len() - 1;
+        bytes[last] |= window::<C>(0, first, r);
+    }
+}
+
+/// Rotate every bit `k` offsets away from offset 0, wrapping around the end
+/// of the buffer
+pub fn rotate_right_with<C: Cfg>(bytes: &mut [u8], k: usize) {
+    let bits = bit_len(bytes);
+    if bits != 0 {
+        rotate_left_with::<C>(bytes, bits - k % bits);
+    }
+}
+
+/// Shift left using [`DefaultCfg`]
+pub fn shift_left(bytes: &mut [u8], k: usize) {
+    shift_left_with::<DefaultCfg>(bytes, k);
+}
+
+/// Shift right using [`DefaultCfg`]
+pub fn shift_right(bytes: &mut [u8], k: usize) {
+    shift_right_with::<DefaultCfg>(bytes, k);
+}
+
+/// Rotate left using [`DefaultCfg`]
+pub fn rotate_left(bytes: &mut [u8], k: usize) {
+    rotate_left_with::<DefaultCfg>(bytes, k);
+}
+
+/// Rotate right using [`DefaultCfg`]
+pub fn rotate_right(bytes: &mut [u8], k: usize) {
+    rotate_right_with::<DefaultCfg>(bytes, k);
+}
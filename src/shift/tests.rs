@@ -0,0 +1,57 @@
+use super::*;
+use crate::cfg::{Lsb0Le, Msb0Be};
+use crate::{Pack, Unpack};
+
+/// Bit `i` of the result is bit `f(i)` of `bytes`, or zero if that is `None`
+fn naive<C: Cfg>(bytes: &[u8], f: impl Fn(usize) -> Option<usize>) -> Vec<u8> {
+    let mut out = vec![0; bytes.len()];
+    for i in 0..bytes.len() * 8 {
+        if let Some(j) = f(i) {
+            bool::unpack_with::<C>(bytes, j).pack_with::<C>(&mut out, i);
+        }
+    }
+    out
+}
+
+fn check<C: Cfg>(bytes: &[u8], k: usize) {
+    let n = bytes.len() * 8;
+
+    let mut buf = bytes.to_vec();
+    shift_left_with::<C>(&mut buf, k);
+    assert_eq!(buf, naive::<C>(bytes, |i| Some(i + k).filter(|&j| j < n)));
+
+    let mut buf = bytes.to_vec();
+    shift_right_with::<C>(&mut buf, k);
+    assert_eq!(buf, naive::<C>(bytes, |i| i.checked_sub(k)));
+
+    let mut buf = bytes.to_vec();
+    rotate_left_with::<C>(&mut buf, k);
+    assert_eq!(buf, naive::<C>(bytes, |i| Some((i + k) % n)));
+
+    let mut buf = bytes.to_vec();
+    rotate_right_with::<C>(&mut buf, k);
+    assert_eq!(buf, naive::<C>(bytes, |i| Some((i + n - k % n) % n)));
+}
+
+#[test]
+fn empty() {
+    let mut buf = [];
+    shift_left(&mut buf, 3);
+    rotate_right(&mut buf, 3);
+}
+
+#[test]
+fn realigns_lsb0() {
+    let mut buf = [0xb0, 0xda, 0x0c];
+    shift_left_with::<Lsb0Le>(&mut buf, 4);
+    assert_eq!(buf, [0xab, 0xcd, 0x00]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_naive(bytes: Vec<u8>, k in 0usize..80) {
+        proptest::prop_assume!(!bytes.is_empty());
+        check::<Msb0Be>(&bytes, k);
+        check::<Lsb0Le>(&bytes, k);
+    }
+}
@@ -0,0 +1,82 @@
+//! Packing runs of values whose count is only known at runtime.
+//!
+//! Arrays need their length at compile time, but formats often give the
+//! count in an earlier header field.  These pack and unpack `n` values back
+//! to back, just like an array of `n` values would be.
+//!
+//! ```rust
+//! # use packed::slice;
+//! let mut buf = [0u8; 3];
+//! assert_eq!(slice::pack_slice(&[0x12u8, 0x34], &mut buf, 4), 16);
+//! let mut out = Vec::<u8>::new();
+//! assert_eq!(slice::unpack_slice_into(&buf, 4, &mut out, 2), Ok(16));
+//! assert_eq!(out, [0x12, 0x34]);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::{bit_len, check_len};
+use crate::{Pack, Unpack, UnpackError};
+
+/// Pack `items` one after another at `offset`, returning the amount of bits
+/// written
+pub fn pack_slice_with<C, T>(items: &[T], bytes: &mut [u8], offset: usize) -> usize
+where
+    C: Cfg,
+    T: Pack,
+{
+    debug_assert!(bit_len(bytes) - offset >= items.len() * T::SIZE);
+    for (i, x) in items.iter().enumerate() {
+        x.pack_with::<C>(bytes, offset + i * T::SIZE);
+    }
+    items.len() * T::SIZE
+}
+
+/// Unpack `n` values at `offset` into `out`, replacing its contents and
+/// returning the amount of bits read.  Values already in `out` are unpacked
+/// into, so their allocations are reused.  `out` is left unchanged if there
+/// aren't enough bits.
+pub fn unpack_slice_into_with<C, T>(
+    bytes: &[u8],
+    offset: usize,
+    out: &mut Vec<T>,
+    n: usize,
+) -> Result<usize, UnpackError>
+where
+    C: Cfg,
+    T: Unpack,
+{
+    let len = n.saturating_mul(T::SIZE);
+    check_len(bytes, offset, len)?;
+    out.truncate(n);
+    for (i, x) in out.iter_mut().enumerate() {
+        x.unpack_into_with::<C>(bytes, offset + i * T::SIZE);
+    }
+    for i in out.len()..n {
+        out.push(T::unpack_with::<C>(bytes, offset + i * T::SIZE));
+    }
+    Ok(len)
+}
+
+/// Pack a slice using [`DefaultCfg`]
+pub fn pack_slice<T>(items: &[T], bytes: &mut [u8], offset: usize) -> usize
+where
+    T: Pack,
+{
+    pack_slice_with::<DefaultCfg, T>(items, bytes, offset)
+}
+
+/// Unpack a slice using [`DefaultCfg`]
+pub fn unpack_slice_into<T>(
+    bytes: &[u8],
+    offset: usize,
+    out: &mut Vec<T>,
+    n: usize,
+) -> Result<usize, UnpackError>
+where
+    T: Unpack,
+{
+    unpack_slice_into_with::<DefaultCfg, T>(bytes, offset, out, n)
+}
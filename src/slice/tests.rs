@@ -0,0 +1,42 @@
+use super::{pack_slice_with, unpack_slice_into, unpack_slice_into_with};
+use crate::UnpackErrorKind;
+use crate::cfg::Lsb0Le;
+
+#[test]
+fn reuses_and_replaces() {
+    let buf = [1, 2, 3, 4, 5, 6];
+    let mut out = vec![Box::new([9u8; 2]); 5];
+    let first: *const [u8; 2] = &*out[0];
+    assert_eq!(unpack_slice_into(&buf, 0, &mut out, 3), Ok(48));
+    assert_eq!(
+        out.iter().map(|x| **x).collect::<Vec<_>>(),
+        [[1, 2], [3, 4], [5, 6]]
+    );
+    assert!(std::ptr::eq(first, &*out[0]));
+
+    assert_eq!(
+        unpack_slice_into(&buf, 4, &mut out, 3),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 48 }.into())
+    );
+    assert_eq!(
+        unpack_slice_into(&buf, 0, &mut out, usize::MAX),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 48 }.into())
+    );
+    assert_eq!(out.len(), 3);
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_array(values: [i16; 4], n in 0usize..=4, offset in 0usize..=16) {
+        let mut buf = [0u8; 11];
+        let mut expected = [0u8; 11];
+        assert_eq!(pack_slice_with::<Lsb0Le, _>(&values[..n], &mut buf, offset), n * 16);
+        for (i, x) in values[..n].iter().enumerate() {
+            crate::Pack::pack_with::<Lsb0Le>(x, &mut expected, offset + i * 16);
+        }
+        assert_eq!(buf, expected);
+        let mut out = Vec::<i16>::new();
+        assert_eq!(unpack_slice_into_with::<Lsb0Le, _>(&buf, offset, &mut out, n), Ok(n * 16));
+        assert_eq!(out, &values[..n]);
+    }
+}
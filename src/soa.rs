@@ -0,0 +1,151 @@
+//! Packing many values field by field rather than value by value.
+//!
+//! Values are split into their fields, given as the widths of each field in
+//! packing order, and all the first fields are packed, then all the second
+//! fields, and so on.  Grouping a field's values like this lines up fields
+//! of the same width, which suits telemetry batches and compresses better.
+//! Values are handled in blocks, so the transpose stays within the cache.
+//!
+//! ```rust
+//! # use packed::soa;
+//! let samples = [(1u8, true), (2, false), (3, true)];
+//! let mut buf = [0u8; 4];
+//! assert_eq!(soa::pack_columns(&samples, &[8, 1], &mut buf, 0), 27);
+//! assert_eq!(buf, [1, 2, 3, 0b1010_0000]);
+//! assert_eq!(soa::unpack_columns(&[8, 1], 3, &buf, 0), Ok(samples.to_vec()));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::{bit_len, check_len};
+use crate::narrow::{read_bits, write_bits};
+use crate::{Pack, Unpack, UnpackError};
+
+/// Amount of values transposed at once
+const BLOCK: usize = 64;
+
+/// Copy `len` bits from `src` at `from` to `dst` at `to`
+fn copy_bits<C: Cfg>(src: &[u8], from: usize, dst: &mut [u8], to: usize, len: usize) {
+    let mut done = 0;
+    while done < len {
+        let n = (len - done).min(56);
+        let bits = read_bits::<C>(n, src, from + done);
+        write_bits::<C>(bits, n, dst, to + done);
+        done += n;
+    }
+}
+
+/// Offset of each field within a value, checking the widths add up to
+/// `size`
+fn field_starts(fields: &[usize], size: usize) -> Vec<usize> {
+    let starts: Vec<usize> = fields
+        .iter()
+        .scan(0, |start, w| {
+            *start += w;
+            Some(*start - w)
+        })
+        .collect();
+    assert_eq!(
+        fields.iter().sum::<usize>(),
+        size,
+        "field widths must add up to the size of the value"
+    );
+    starts
+}
+
+/// Pack `items` field by field at `offset`, returning the amount of bits
+/// written
+///
+/// # Panics
+///
+/// If the widths of `fields` don't add up to `T::SIZE`
+pub fn pack_columns_with<C, T>(
+    items: &[T],
+    fields: &[usize],
+    bytes: &mut [u8],
+    offset: usize,
+) -> usize
+where
+    C: Cfg,
+    T: Pack,
+{
+    let starts = field_starts(fields, T::SIZE);
+    let n = items.len();
+    debug_assert!(bit_len(bytes) - offset >= n * T::SIZE);
+    let mut rows = vec![0u8; (BLOCK * T::SIZE).div_ceil(8)];
+    for (b, block) in items.chunks(BLOCK).enumerate() {
+        for (r, x) in block.iter().enumerate() {
+            x.pack_with::<C>(&mut rows, r * T::SIZE);
+        }
+        for (&w, &start) in fields.iter().zip(&starts) {
+            // every field before this one takes up `start` bits per value
+            let column = offset + n * start;
+            for r in 0..block.len() {
+                let i = b * BLOCK + r;
+                copy_bits::<C>(&rows, r * T::SIZE + start, bytes, column + i * w, w);
+            }
+        }
+    }
+    n * T::SIZE
+}
+
+/// Unpack `n` values packed field by field at `offset`
+///
+/// # Panics
+///
+/// If the widths of `fields` don't add up to `T::SIZE`
+pub fn unpack_columns_with<C, T>(
+    fields: &[usize],
+    n: usize,
+    bytes: &[u8],
+    offset: usize,
+) -> Result<Vec<T>, UnpackError>
+where
+    C: Cfg,
+    T: Unpack,
+{
+    let starts = field_starts(fields, T::SIZE);
+    check_len(bytes, offset, n.saturating_mul(T::SIZE))?;
+    let mut out = Vec::with_capacity(n);
+    let mut rows = vec![0u8; (BLOCK * T::SIZE).div_ceil(8)];
+    for b in (0..n).step_by(BLOCK) {
+        let len = (n - b).min(BLOCK);
+        for (&w, &start) in fields.iter().zip(&starts) {
+            let column = offset + n * start;
+            for r in 0..len {
+                copy_bits::<C>(
+                    bytes,
+                    column + (b + r) * w,
+                    &mut rows,
+                    r * T::SIZE + start,
+                    w,
+                );
+            }
+        }
+        out.extend((0..len).map(|r| T::unpack_with::<C>(&rows, r * T::SIZE)));
+    }
+    Ok(out)
+}
+
+/// Pack field by field using [`DefaultCfg`]
+pub fn pack_columns<T>(items: &[T], fields: &[usize], bytes: &mut [u8], offset: usize) -> usize
+where
+    T: Pack,
+{
+    pack_columns_with::<DefaultCfg, T>(items, fields, bytes, offset)
+}
+
+/// Unpack field by field using [`DefaultCfg`]
+pub fn unpack_columns<T>(
+    fields: &[usize],
+    n: usize,
+    bytes: &[u8],
+    offset: usize,
+) -> Result<Vec<T>, UnpackError>
+where
+    T: Unpack,
+{
+    unpack_columns_with::<DefaultCfg, T>(fields, n, bytes, offset)
+}
@@ -0,0 +1,50 @@
+use super::{pack_columns, pack_columns_with, unpack_columns, unpack_columns_with};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, UnpackErrorKind};
+
+type Telemetry = (u16, [bool; 3], i32, u8);
+const FIELDS: [usize; 4] = [16, 3, 32, 8];
+
+#[test]
+fn columns_follow_each_other() {
+    let items: Vec<(u8, u16)> = (0..200).map(|i| (i as u8, 1000 + i)).collect();
+    let mut buf = vec![0u8; 200 * 3];
+    assert_eq!(pack_columns(&items, &[8, 16], &mut buf, 0), 200 * 24);
+    for (i, (a, b)) in items.iter().enumerate() {
+        assert_eq!(buf[i], *a);
+        assert_eq!(buf[200 + 2 * i..][..2], b.to_be_bytes());
+    }
+    assert_eq!(unpack_columns(&[8, 16], 200, &buf, 0), Ok(items));
+    assert_eq!(
+        unpack_columns::<(u8, u16)>(&[8, 16], 201, &buf, 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 200 * 24 }.into())
+    );
+}
+
+#[test]
+#[should_panic = "add up to the size"]
+fn wrong_widths() {
+    pack_columns(&[(1u8, 2u8)], &[8, 7], &mut [0; 2], 0);
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(
+        items in proptest::collection::vec(proptest::arbitrary::any::<Telemetry>(), 0..150),
+        offset in 0usize..8,
+    ) {
+        let size = items.len() * <Telemetry as crate::PackedSize>::SIZE;
+        let mut buf = vec![0u8; (offset + size).div_ceil(8)];
+        assert_eq!(pack_columns_with::<Lsb0Le, _>(&items, &FIELDS, &mut buf, offset), size);
+        assert_eq!(unpack_columns_with::<Lsb0Le, Telemetry>(&FIELDS, items.len(), &buf, offset), Ok(items.clone()));
+
+        // a single field packs the same as packing every value in a row
+        let mut rows = vec![0u8; buf.len()];
+        for (i, x) in items.iter().enumerate() {
+            x.pack_with::<Lsb0Le>(&mut rows, offset + i * 59);
+        }
+        let mut whole = vec![0u8; buf.len()];
+        pack_columns_with::<Lsb0Le, _>(&items, &[59], &mut whole, offset);
+        assert_eq!(whole, rows);
+    }
+}
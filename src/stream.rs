@@ -0,0 +1,207 @@
+//! Helpers for decoding packed values from a stream of bytes.
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+
+use crate::Unpack;
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::bit_len;
+
+/// Reassembles back-to-back packed values from arbitrarily sized chunks.
+///
+/// Values follow each other with no padding, so they don't need to start on
+/// a byte boundary and may straddle chunks.
+///
+/// ```rust
+/// # use packed::stream::FrameAccumulator;
+/// let mut acc = FrameAccumulator::<(u8, [bool; 4])>::new();
+/// assert_eq!(acc.feed(&[0x12]).next(), None);
+/// let frames = acc.feed(&[0xa3, 0x4f]).collect::<Vec<_>>();
+/// assert_eq!(frames, [(0x12, [true, false, true, false]), (0x34, [true; 4])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameAccumulator<T, C = DefaultCfg> {
+    buf: Vec<u8>,
+    /// Bit offset of the next frame in `buf`
+    offset: usize,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T> FrameAccumulator<T>
+where
+    T: Unpack,
+{
+    pub fn new() -> Self {
+        Self::with_cfg()
+    }
+}
+
+impl<T> Default for FrameAccumulator<T>
+where
+    T: Unpack,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C> FrameAccumulator<T, C>
+where
+    T: Unpack,
+    C: Cfg,
+{
+    /// Accumulator unpacking frames with the configuration `C`
+    pub fn with_cfg() -> Self {
+        const { assert!(T::SIZE > 0, "frames must not be empty") };
+        Self {
+            buf: Vec::new(),
+            offset: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Append a chunk of the stream
+    pub fn extend(&mut self, chunk: &[u8]) {
+        // drop the bytes that only held already returned frames
+        self.buf.drain(..self.offset / 8);
+        self.offset %= 8;
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Append a chunk and iterate over every frame completed so far
+    pub fn feed(&mut self, chunk: &[u8]) -> &mut Self {
+        self.extend(chunk);
+        self
+    }
+
+    /// Amount of bits received but not yet returned as a frame
+    pub fn buffered_bits(&self) -> usize {
+        self.buf.len() * 8 - self.offset
+    }
+
+    /// Return the next complete frame, if any
+    pub fn next_frame(&mut self) -> Option<T> {
+        if self.buffered_bits() < T::SIZE {
+            return None;
+        }
+        let frame = T::unpack_with::<C>(&self.buf, self.offset);
+        self.offset += T::SIZE;
+        Some(frame)
+    }
+
+    /// Discard all buffered bits
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.offset = 0;
+    }
+}
+
+impl<T, C> Iterator for FrameAccumulator<T, C>
+where
+    T: Unpack,
+    C: Cfg,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame()
+    }
+}
+
+/// Searches a bit stream for a sync pattern which needn't be byte aligned.
+///
+/// The pattern is given as its `len` bits in the order they appear in the
+/// stream, first bit in the most significant position.  Match positions are
+/// the bit offset right after the pattern, i.e. where the frame begins.
+///
+/// ```rust
+/// # use packed::stream::SyncScanner;
+/// // 0b1011 starting at bit 3
+/// let scanner = SyncScanner::new(0b1011, 4);
+/// assert_eq!(scanner.find(&[0b0001_0110, 0x00], 0), Some(7));
+/// assert_eq!(scanner.find(&[0b0001_0110, 0x00], 4), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SyncScanner<C = DefaultCfg> {
+    pattern: u64,
+    mask: u64,
+    len: usize,
+    /// The most recently seen bits of the stream
+    window: u64,
+    /// Amount of bits fed so far
+    position: usize,
+    _marker: PhantomData<C>,
+}
+
+impl SyncScanner {
+    pub fn new(pattern: u64, len: usize) -> Self {
+        Self::with_cfg(pattern, len)
+    }
+}
+
+impl<C> SyncScanner<C>
+where
+    C: Cfg,
+{
+    /// Scanner reading bits in the order of the configuration `C`
+    ///
+    /// # Panics
+    ///
+    /// If `len` is not in `1..=64`
+    pub fn with_cfg(pattern: u64, len: usize) -> Self {
+        assert!((1..=64).contains(&len), "sync pattern must be 1 to 64 bits");
+        let mask = u64::MAX >> (64 - len);
+        Self {
+            pattern: pattern & mask,
+            mask,
+            len,
+            window: 0,
+            position: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Length of the pattern in bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Find the first frame in `bytes` whose sync pattern starts at or after
+    /// `offset`
+    pub fn find(&self, bytes: &[u8], offset: usize) -> Option<usize> {
+        let mut window = 0;
+        (offset..bit_len(bytes)).find_map(|i| {
+            window = window << 1 | u64::from(bool::unpack_with::<C>(bytes, i));
+            let seen = i + 1 - offset;
+            (seen >= self.len && window & self.mask == self.pattern).then_some(i + 1)
+        })
+    }
+
+    /// Feed a chunk of the stream, returning the stream offsets of every
+    /// frame whose sync pattern ends in it.  Patterns may straddle chunks.
+    pub fn feed<'a>(&'a mut self, chunk: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        (0..chunk.len() * 8).filter_map(move |i| {
+            self.window = self.window << 1 | u64::from(bool::unpack_with::<C>(chunk, i));
+            self.position += 1;
+            (self.position >= self.len && self.window & self.mask == self.pattern)
+                .then_some(self.position)
+        })
+    }
+
+    /// Amount of bits fed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Forget everything fed so far
+    pub fn reset(&mut self) {
+        self.window = 0;
+        self.position = 0;
+    }
+}
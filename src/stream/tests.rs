@@ -0,0 +1,95 @@
+use super::{FrameAccumulator, SyncScanner};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, Unpack};
+
+#[test]
+fn byte_at_a_time() {
+    let values = [(0x123u16, true), (0xfffe, false), (0, true), (0xa5a5, true)];
+    let mut buf = [0u8; 9];
+    values.pack(&mut buf, 0);
+
+    let mut acc = FrameAccumulator::<(u16, bool)>::new();
+    let mut out = Vec::new();
+    for b in buf {
+        out.extend(acc.feed(&[b]));
+    }
+    assert_eq!(out, values);
+    // 4 bits of trailing padding
+    assert_eq!(acc.buffered_bits(), 4);
+}
+
+#[test]
+fn buffer_is_trimmed() {
+    let mut acc = FrameAccumulator::<u8>::new();
+    for _ in 0..100 {
+        assert_eq!(acc.feed(&[1, 2, 3]).count(), 3);
+    }
+    acc.extend(&[]);
+    assert_eq!(acc.buf.len(), 0);
+}
+
+#[test]
+fn lsb0() {
+    let mut buf = [0u8; 2];
+    [0x5u8, 0xa].pack_with::<Lsb0Le>(&mut buf, 0);
+    let mut acc = FrameAccumulator::<u8, Lsb0Le>::with_cfg();
+    assert_eq!(acc.feed(&buf).collect::<Vec<_>>(), [0x5, 0xa]);
+}
+
+#[test]
+fn sync_overlapping() {
+    let scanner = SyncScanner::new(0b101, 3);
+    let bytes = [0b1010_1000];
+    assert_eq!(scanner.find(&bytes, 0), Some(3));
+    assert_eq!(scanner.find(&bytes, 1), Some(5));
+    assert_eq!(scanner.find(&bytes, 3), None);
+}
+
+#[test]
+fn sync_across_chunks() {
+    // 0x7e flag starting at bit 6
+    let mut scanner = SyncScanner::new(0x7e, 8);
+    assert_eq!(scanner.feed(&[0b0000_0001]).count(), 0);
+    assert_eq!(scanner.feed(&[0b1111_1000]).collect::<Vec<_>>(), [14]);
+    assert_eq!(scanner.position(), 16);
+    scanner.reset();
+    assert_eq!(scanner.feed(&[0x7e, 0x7e]).collect::<Vec<_>>(), [8, 16]);
+}
+
+#[test]
+fn sync_lsb0() {
+    let scanner = SyncScanner::<Lsb0Le>::with_cfg(0b110, 3);
+    assert_eq!(scanner.find(&[0b0001_1000], 0), Some(6));
+}
+
+proptest::proptest! {
+    #[test]
+    fn sync_finds_frame(frame: u32, offset in 0usize..=40) {
+        // the sync word starts with a one, so it can't match any earlier in
+        // the zeroed buffer
+        let mut buf = [0u8; 12];
+        (0xd391u16, frame).pack(&mut buf, offset);
+        let scanner = SyncScanner::new(0xd391, 16);
+        let start = scanner.find(&buf, 0).unwrap();
+        assert_eq!(start, offset + 16);
+        assert_eq!(u32::unpack(&buf, start), frame);
+    }
+
+    #[test]
+    fn arbitrary_chunks(values: [(u32, [bool; 3]); 8], cuts: [usize; 4]) {
+        let mut buf = [0u8; 35];
+        values.pack(&mut buf, 0);
+
+        let mut cuts = cuts.map(|c| c % (buf.len() + 1));
+        cuts.sort();
+        let mut acc = FrameAccumulator::<(u32, [bool; 3])>::new();
+        let mut out = Vec::new();
+        let mut last = 0;
+        for cut in cuts.into_iter().chain([buf.len()]) {
+            out.extend(acc.feed(&buf[last..cut]));
+            last = cut;
+        }
+        assert_eq!(out, values);
+        assert_eq!(acc.buffered_bits(), 0);
+    }
+}
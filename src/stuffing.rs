@@ -0,0 +1,161 @@
+//! HDLC-style bit stuffing.
+//!
+//! After [`RUN`] consecutive one bits the [`Stuffer`] inserts a zero, so the
+//! payload can never contain the [`FLAG`] sequence `0111_1110` which
+//! delimits frames.  The [`Unstuffer`] removes the inserted zeros again.
+//!
+//! ```rust
+//! # use packed::cursor::{BitReader, BitWriter};
+//! # use packed::stuffing;
+//! let mut buf = [0u8; 8];
+//! let mut writer = BitWriter::new(&mut buf);
+//! stuffing::encode_frame(&mut writer, 0xffffu16).unwrap();
+//! let len = writer.finish();
+//! // two flags, 16 payload bits and 3 stuffed zeros
+//! assert_eq!(len, 8 + 16 + 3 + 8);
+//!
+//! let mut reader = BitReader::new(&buf);
+//! assert_eq!(stuffing::decode_frame::<u16, _>(&mut reader), Ok(0xffff));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::Cfg;
+use crate::cursor::{BitReader, BitWriter};
+use crate::{Pack, PackError, Unpack, UnpackError, UnpackErrorKind};
+
+/// Flag sequence delimiting frames
+pub const FLAG: u8 = 0b0111_1110;
+
+/// Amount of consecutive ones after which a zero is stuffed
+pub const RUN: usize = 5;
+
+/// Writes bits through a [`BitWriter`], stuffing a zero after every run of
+/// [`RUN`] ones
+#[derive(Debug)]
+pub struct Stuffer<'w, 'a, C> {
+    writer: &'w mut BitWriter<'a, C>,
+    ones: usize,
+}
+
+impl<'w, 'a, C> Stuffer<'w, 'a, C>
+where
+    C: Cfg,
+{
+    pub fn new(writer: &'w mut BitWriter<'a, C>) -> Self {
+        Self { writer, ones: 0 }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), PackError> {
+        self.writer.write(bit)?;
+        if !bit {
+            self.ones = 0;
+        } else if self.ones + 1 == RUN {
+            self.writer.write(false)?;
+            self.ones = 0;
+        } else {
+            self.ones += 1;
+        }
+        Ok(())
+    }
+
+    /// Write every bit of the packed `value`
+    pub fn write<T>(&mut self, value: T) -> Result<(), PackError>
+    where
+        T: Pack,
+    {
+        let mut bits = vec![0u8; T::SIZE.div_ceil(8)];
+        value.pack_with::<C>(&mut bits, 0);
+        (0..T::SIZE).try_for_each(|i| self.write_bit(bool::unpack_with::<C>(&bits, i)))
+    }
+
+    /// Write an unstuffed [`FLAG`]
+    pub fn write_flag(&mut self) -> Result<(), PackError> {
+        self.ones = 0;
+        self.writer.write(FLAG)
+    }
+}
+
+/// Reads bits through a [`BitReader`], dropping the zeros inserted by a
+/// [`Stuffer`]
+#[derive(Debug)]
+pub struct Unstuffer<'r, 'a, C> {
+    reader: &'r mut BitReader<'a, C>,
+    ones: usize,
+}
+
+impl<'r, 'a, C> Unstuffer<'r, 'a, C>
+where
+    C: Cfg,
+{
+    pub fn new(reader: &'r mut BitReader<'a, C>) -> Self {
+        Self { reader, ones: 0 }
+    }
+
+    /// Read a payload bit.  Fails with [`UnpackErrorKind::Framing`] if a flag or
+    /// an abort sequence is found instead.
+    pub fn read_bit(&mut self) -> Result<bool, UnpackError> {
+        let bit = self.reader.read_bit()?;
+        if !bit {
+            self.ones = 0;
+            return Ok(false);
+        }
+        self.ones += 1;
+        if self.ones == RUN {
+            let offset = self.reader.position();
+            if self.reader.read_bit()? {
+                return Err(UnpackErrorKind::Framing { offset }.into());
+            }
+            self.ones = 0;
+        }
+        Ok(true)
+    }
+
+    /// Read a packed value from the payload bits
+    pub fn read<T>(&mut self) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        let mut bits = vec![0u8; T::SIZE.div_ceil(8)];
+        for i in 0..T::SIZE {
+            self.read_bit()?.pack_with::<C>(&mut bits, i);
+        }
+        Ok(T::unpack_with::<C>(&bits, 0))
+    }
+
+    /// Consume a [`FLAG`]
+    pub fn read_flag(&mut self) -> Result<(), UnpackError> {
+        let offset = self.reader.position();
+        if self.reader.read::<u8>()? != FLAG {
+            return Err(UnpackErrorKind::Framing { offset }.into());
+        }
+        self.ones = 0;
+        Ok(())
+    }
+}
+
+/// Write `value` as a frame: a flag, the stuffed value, and another flag
+pub fn encode_frame<C, T>(writer: &mut BitWriter<'_, C>, value: T) -> Result<(), PackError>
+where
+    C: Cfg,
+    T: Pack,
+{
+    let mut stuffer = Stuffer::new(writer);
+    stuffer.write_flag()?;
+    stuffer.write(value)?;
+    stuffer.write_flag()
+}
+
+/// Read a frame written by [`encode_frame`]
+pub fn decode_frame<T, C>(reader: &mut BitReader<'_, C>) -> Result<T, UnpackError>
+where
+    C: Cfg,
+    T: Unpack,
+{
+    let mut unstuffer = Unstuffer::new(reader);
+    unstuffer.read_flag()?;
+    let value = unstuffer.read()?;
+    unstuffer.read_flag()?;
+    Ok(value)
+}
@@ -0,0 +1,67 @@
+use super::{FLAG, Stuffer, Unstuffer, decode_frame, encode_frame};
+use crate::UnpackErrorKind;
+use crate::cursor::{BitReader, BitWriter};
+
+#[test]
+fn stuffs_after_five_ones() {
+    let mut buf = [0u8; 2];
+    let mut writer = BitWriter::new(&mut buf);
+    let mut stuffer = Stuffer::new(&mut writer);
+    stuffer.write(0b1111_1011u8).unwrap();
+    assert_eq!(writer.finish(), 9);
+    assert_eq!(buf, [0b1111_1001, 0b1000_0000]);
+}
+
+#[test]
+fn flag_in_payload() {
+    let mut buf = [0u8; 4];
+    let mut writer = BitWriter::new(&mut buf);
+    encode_frame(&mut writer, FLAG).unwrap();
+    let len = writer.finish();
+    assert_eq!(len, 8 + 9 + 8);
+
+    let mut reader = BitReader::new(&buf);
+    assert_eq!(decode_frame::<u8, _>(&mut reader), Ok(FLAG));
+    assert_eq!(reader.position(), len);
+}
+
+#[test]
+fn abort_sequence() {
+    let buf = [FLAG, 0xff, 0xff];
+    let mut reader = BitReader::new(&buf);
+    let mut unstuffer = Unstuffer::new(&mut reader);
+    unstuffer.read_flag().unwrap();
+    assert_eq!(
+        unstuffer.read::<u16>(),
+        Err(UnpackErrorKind::Framing { offset: 13 }.into())
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn frame_roundtrip(value: (u32, [bool; 7], u8)) {
+        let mut buf = [0u8; 16];
+        let mut writer = BitWriter::new(&mut buf);
+        encode_frame(&mut writer, value).unwrap();
+        encode_frame(&mut writer, value.0).unwrap();
+
+        let mut reader = BitReader::new(&buf);
+        assert_eq!(decode_frame(&mut reader), Ok(value));
+        assert_eq!(decode_frame(&mut reader), Ok(value.0));
+    }
+
+    #[test]
+    fn no_flag_in_stuffed_payload(value: u64) {
+        let mut buf = [0u8; 10];
+        let mut writer = BitWriter::new(&mut buf);
+        let mut stuffer = Stuffer::new(&mut writer);
+        stuffer.write(value).unwrap();
+        let len = writer.finish();
+        let mut reader = BitReader::new(&buf);
+        let mut run = 0;
+        for _ in 0..len {
+            run = if reader.read_bit().unwrap() { run + 1 } else { 0 };
+            assert!(run <= 5);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Streams of small symbols, such as 2-bit DNA bases or base64 digits.
+//!
+//! Symbols of 1 to 8 bits are packed MSB-first with no gaps between them,
+//! and the final byte is padded with zeros.  [`Tail`] decides how strictly
+//! those padding bits are checked when unpacking.  [`Packer`] and
+//! [`Unpacker`] do the same lazily over iterators.
+//!
+//! ```rust
+//! # use packed::symbols::{self, Tail, Unpacker};
+//! // ACGT as 2-bit codes
+//! let bases = [0, 1, 2, 3, 3];
+//! let bytes = symbols::pack_symbols(&bases, 2, Tail::Zeros);
+//! assert_eq!(bytes, [0b0001_1011, 0b1100_0000]);
+//! // the padding reads as three more `A`s, so the count is kept separately
+//! let unpacked: Vec<u8> = Unpacker::new(bytes, 2).take(bases.len()).collect();
+//! assert_eq!(unpacked, bases);
+//!
+//! let digits = symbols::pack_symbols(&[19, 22, 5, 46], 6, Tail::Exact);
+//! assert_eq!(digits, b"Man".to_vec());
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::bit_len;
+use crate::{UnpackError, UnpackErrorKind};
+
+/// How the bits after the last whole symbol in the final byte are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tail {
+    /// Padding bits are ignored when unpacking
+    Ignore,
+    /// Padding bits must be zero when unpacking
+    Zeros,
+    /// The symbols must fill whole bytes, leaving no padding
+    Exact,
+}
+
+/// Packs an iterator of symbols into bytes
+#[derive(Debug, Clone)]
+pub struct Packer<I> {
+    symbols: I,
+    bits: u32,
+    acc: u32,
+    len: u32,
+}
+
+impl<I: Iterator<Item = u8>> Packer<I> {
+    /// Pack `symbols` of `bits` bits each.  Bits of a symbol above the
+    /// lowest `bits` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` isn't in `1..=8`
+    pub fn new(symbols: impl IntoIterator<IntoIter = I>, bits: u32) -> Self {
+        assert!((1..=8).contains(&bits), "symbols can't be {bits} bits");
+        Self {
+            symbols: symbols.into_iter(),
+            bits,
+            acc: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Packer<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.len < 8 {
+            let Some(symbol) = self.symbols.next() else {
+                if self.len == 0 {
+                    return None;
+                }
+                let byte = (self.acc << (8 - self.len)) as u8;
+                self.len = 0;
+                return Some(byte);
+            };
+            self.acc = self.acc << self.bits | u32::from(symbol) & ((1 << self.bits) - 1);
+            self.len += self.bits;
+        }
+        self.len -= 8;
+        let byte = (self.acc >> self.len) as u8;
+        self.acc &= (1 << self.len) - 1;
+        Some(byte)
+    }
+}
+
+/// Unpacks symbols from an iterator of bytes, until no whole symbol is left
+#[derive(Debug, Clone)]
+pub struct Unpacker<I> {
+    bytes: I,
+    bits: u32,
+    acc: u32,
+    len: u32,
+}
+
+impl<I: Iterator<Item = u8>> Unpacker<I> {
+    /// Unpack symbols of `bits` bits each from `bytes`
+    ///
+    /// # Panics
+    ///
+    /// If `bits` isn't in `1..=8`
+    pub fn new(bytes: impl IntoIterator<IntoIter = I>, bits: u32) -> Self {
+        assert!((1..=8).contains(&bits), "symbols can't be {bits} bits");
+        Self {
+            bytes: bytes.into_iter(),
+            bits,
+            acc: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Unpacker<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.len < self.bits {
+            self.acc = self.acc << 8 | u32::from(self.bytes.next()?);
+            self.len += 8;
+        }
+        self.len -= self.bits;
+        let symbol = (self.acc >> self.len) as u8;
+        self.acc &= (1 << self.len) - 1;
+        Some(symbol)
+    }
+}
+
+/// Pack `symbols` of `bits` bits each, padding the final byte with zeros
+///
+/// # Panics
+///
+/// If `bits` isn't in `1..=8`, or with [`Tail::Exact`] if the symbols don't
+/// fill whole bytes
+pub fn pack_symbols(symbols: &[u8], bits: u32, tail: Tail) -> Vec<u8> {
+    let len = symbols.len() * bits as usize;
+    assert!(
+        tail != Tail::Exact || len.is_multiple_of(8),
+        "{} symbols of {bits} bits don't fill whole bytes",
+        symbols.len()
+    );
+    Packer::new(symbols.iter().copied(), bits).collect()
+}
+
+/// Unpack every whole symbol of `bits` bits in `bytes`, checking the
+/// padding after them according to `tail`
+///
+/// # Panics
+///
+/// If `bits` isn't in `1..=8`
+pub fn unpack_symbols(bytes: &[u8], bits: u32, tail: Tail) -> Result<Vec<u8>, UnpackError> {
+    let end = bit_len(bytes);
+    let padding = end % bits as usize;
+    match tail {
+        Tail::Ignore => {}
+        Tail::Zeros => {
+            let mask = (1u16 << padding) - 1;
+            let last = bytes.last().map_or(0, |&b| u16::from(b));
+            if last & mask != 0 {
+                let bit = mask.ilog2() - (last & mask).ilog2();
+                return Err(UnpackErrorKind::Reserved {
+                    offset: end - padding + bit as usize,
+                    expected: false,
+                }
+                .into());
+            }
+        }
+        Tail::Exact if padding != 0 => {
+            return Err(UnpackErrorKind::Framing {
+                offset: end - padding,
+            }
+            .into());
+        }
+        Tail::Exact => {}
+    }
+    Ok(Unpacker::new(bytes.iter().copied(), bits).collect())
+}
@@ -0,0 +1,62 @@
+use super::{Packer, Tail, Unpacker, pack_symbols, unpack_symbols};
+use crate::UnpackErrorKind;
+use crate::narrow::NarrowInt;
+
+#[test]
+fn tail_policies() {
+    // three 5-bit symbols leave a bit of padding
+    let bytes = [0b1000_1000, 0b1000_0100];
+    assert_eq!(unpack_symbols(&bytes, 5, Tail::Ignore), Ok(vec![17, 2, 2]));
+    assert_eq!(unpack_symbols(&bytes, 5, Tail::Zeros), Ok(vec![17, 2, 2]));
+    assert_eq!(
+        unpack_symbols(&[0, 1], 5, Tail::Zeros),
+        Err(UnpackErrorKind::Reserved {
+            offset: 15,
+            expected: false
+        }
+        .into())
+    );
+    assert_eq!(
+        unpack_symbols(&[0, 0x0c], 6, Tail::Zeros),
+        Err(UnpackErrorKind::Reserved {
+            offset: 12,
+            expected: false
+        }
+        .into())
+    );
+    assert_eq!(
+        unpack_symbols(&[0, 0], 5, Tail::Exact),
+        Err(UnpackErrorKind::Framing { offset: 15 }.into())
+    );
+    assert_eq!(unpack_symbols(&[0; 5], 5, Tail::Exact), Ok(vec![0; 8]));
+}
+
+#[test]
+#[should_panic = "don't fill whole bytes"]
+fn exact_pack() {
+    pack_symbols(&[1, 2, 3], 6, Tail::Exact);
+}
+
+#[test]
+fn high_bits_ignored() {
+    assert_eq!(pack_symbols(&[0xff, 0xfc], 2, Tail::Zeros), [0b1100_0000]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn roundtrip(bits in 1u32..=8, symbols in proptest::collection::vec(0u8..=255, 0..20)) {
+        let symbols: Vec<u8> = symbols.iter().map(|x| x & ((1u16 << bits) - 1) as u8).collect();
+        let bytes = pack_symbols(&symbols, bits, Tail::Zeros);
+        let mut expected = vec![0u8; (symbols.len() * bits as usize).div_ceil(8)];
+        for (i, x) in symbols.iter().enumerate() {
+            x.pack_narrow::<crate::cfg::Msb0Be>(bits as usize, &mut expected, i * bits as usize);
+        }
+        assert_eq!(&bytes, &expected);
+
+        let unpacked = unpack_symbols(&bytes, bits, Tail::Zeros).unwrap();
+        assert_eq!(&unpacked[..symbols.len()], &symbols);
+        assert!(unpacked[symbols.len()..].iter().all(|&x| x == 0));
+        let lazy: Vec<u8> = Unpacker::new(Packer::new(symbols.clone(), bits), bits).collect();
+        assert_eq!(lazy, unpacked);
+    }
+}
@@ -0,0 +1,486 @@
+//! Helpers for testing wire formats from downstream test suites.
+//!
+//! [`golden`] checks a packed value against a binary snapshot checked in
+//! next to the tests, so a change to the wire format fails CI rather than
+//! going unnoticed.  Missing snapshots are written on the first run, and
+//! existing ones are rewritten when the `PACKED_BLESS` environment variable
+//! is set.
+//!
+//! ```rust,no_run
+//! # use packed::testing;
+//! testing::golden("tests/golden/header.bin", &(0x12u8, true, 0x3456u16));
+//! ```
+//!
+//! [`exhaustive`] round-trips every bit pattern of a small type at every
+//! offset within a byte, which catches corner cases random inputs miss.
+//!
+//! ```rust
+//! # use packed::testing;
+//! testing::exhaustive::<(bool, u8, packed::Reserved<3>)>();
+//! ```
+//!
+//! [`conformance`] checks packing against a fixed set of vectors covering
+//! every primitive at every offset within a byte, for ports to new targets
+//! and changes to the fast paths to prove they pack bit for bit the same.
+//!
+//! ```rust
+//! # use packed::testing;
+//! assert!(testing::conformance().is_ok());
+//! ```
+//!
+//! [`oracle`] checks packing against another encoder, such as the C code a
+//! format is being migrated from, over random values at random offsets.
+//!
+//! ```rust
+//! # use packed::testing;
+//! // a hand-written big-endian encoder for byte-aligned `u16`s
+//! testing::oracle(|value: &u16, bytes: &mut [u8], offset: usize| {
+//!     bytes[offset / 8..][..2].copy_from_slice(&value.to_be_bytes());
+//! })
+//! .offsets(1)
+//! .check();
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Debug, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::cfg::{Cfg, DefaultCfg, Lsb0Be, Lsb0Le, Msb0Be, Msb0Le, bit_mask};
+use crate::narrow::{read_bits, write_bits};
+use crate::{Pack, Unpack};
+
+/// Environment variable which makes [`golden`] rewrite its snapshots
+pub const BLESS_VAR: &str = "PACKED_BLESS";
+
+/// Pack `value` and compare it against the snapshot at `path`
+///
+/// # Panics
+///
+/// If the packed value differs from the snapshot, with a diff of the bits
+/// which changed
+#[track_caller]
+pub fn golden_with<C, T>(path: impl AsRef<Path>, value: &T)
+where
+    C: Cfg,
+    T: Pack,
+{
+    let path = path.as_ref();
+    let mut found = vec![0u8; T::SIZE.div_ceil(8)];
+    value.pack_with::<C>(&mut found, 0);
+
+    let bless = std::env::var_os(BLESS_VAR).is_some_and(|v| !v.is_empty());
+    if bless || !path.exists() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).expect("failed to create snapshot directory");
+        }
+        std::fs::write(path, &found).expect("failed to write snapshot");
+        return;
+    }
+    let expected = std::fs::read(path).expect("failed to read snapshot");
+    if let Some(diff) = bit_diff(&expected, &found) {
+        panic!(
+            "packed value differs from snapshot {}\n{diff}(set {BLESS_VAR}=1 to update it)",
+            path.display()
+        );
+    }
+}
+
+/// Compare against a snapshot using [`DefaultCfg`]
+#[track_caller]
+pub fn golden<T>(path: impl AsRef<Path>, value: &T)
+where
+    T: Pack,
+{
+    golden_with::<DefaultCfg, T>(path, value)
+}
+
+/// A line for each byte which differs, labelled with the bit offsets it
+/// covers, or `None` if the buffers match
+pub fn bit_diff(expected: &[u8], found: &[u8]) -> Option<String> {
+    if expected == found {
+        return None;
+    }
+    let mut out = String::new();
+    if expected.len() != found.len() {
+        let _ = writeln!(
+            out,
+            "length: expected {} bytes, found {}",
+            expected.len(),
+            found.len()
+        );
+    }
+    for i in 0..expected.len().max(found.len()) {
+        let (a, b) = (expected.get(i), found.get(i));
+        if a == b {
+            continue;
+        }
+        let show = |x: Option<&u8>| x.map_or("--------".to_owned(), |x| format!("{x:08b}"));
+        let marks: String = (0..8)
+            .map(|bit| match (a, b) {
+                (Some(a), Some(b)) if (a ^ b) & 0x80 >> bit == 0 => ' ',
+                _ => '^',
+            })
+            .collect();
+        let label = format!("bits {}..{}", i * 8, i * 8 + 8);
+        let _ = writeln!(out, "{label:<16}expected {}", show(a));
+        let _ = writeln!(out, "{:<16}found    {}", "", show(b));
+        let _ = writeln!(out, "{:<16}         {marks}", "");
+    }
+    Some(out)
+}
+
+/// Check every bit pattern of `T` at offsets `0..8`, with the bits around
+/// the value set to ones and to zeros.  Each pattern which passes
+/// [`Unpack::check_reserved_with`] must pack back to the same bits, and
+/// every unpacked value must unpack again after being packed.  Packing must
+/// leave the bits around the value untouched.
+///
+/// # Panics
+///
+/// If `T::SIZE > 24`, or with the failing pattern and offset if a check
+/// fails
+#[track_caller]
+pub fn exhaustive_with<C, T>()
+where
+    C: Cfg,
+    T: Pack + Unpack + PartialEq + Debug,
+{
+    assert!(
+        T::SIZE <= 24,
+        "{} bits are too many to check exhaustively",
+        T::SIZE
+    );
+    let len = (T::SIZE + 7).div_ceil(8);
+    for pattern in 0..1u128 << T::SIZE {
+        for offset in 0..8 {
+            for background in [0x00, 0xff] {
+                let mut bytes = vec![background; len];
+                write_bits::<C>(pattern, T::SIZE, &mut bytes, offset);
+                let value = T::unpack_with::<C>(&bytes, offset);
+
+                let mut packed = vec![background; len];
+                value.pack_with::<C>(&mut packed, offset);
+                let written = read_bits::<C>(T::SIZE, &packed, offset);
+                write_bits::<C>(pattern, T::SIZE, &mut packed, offset);
+                if packed != bytes {
+                    panic!("packing {value:?} at offset {offset} changed the bits around it");
+                }
+                write_bits::<C>(written, T::SIZE, &mut packed, offset);
+                let canonical = T::check_reserved_with::<C>(&bytes, offset).is_ok();
+                if canonical && written != pattern {
+                    panic!(
+                        "pattern {pattern:#b} at offset {offset} unpacked as {value:?}, \
+                         which packed to\n{}",
+                        bit_diff(&bytes, &packed).unwrap()
+                    );
+                }
+                let again = T::unpack_with::<C>(&packed, offset);
+                if again != value {
+                    panic!("{value:?} at offset {offset} unpacked again as {again:?}");
+                }
+            }
+        }
+    }
+}
+
+/// Exhaustive check using [`DefaultCfg`]
+#[track_caller]
+pub fn exhaustive<T>()
+where
+    T: Pack + Unpack + PartialEq + Debug,
+{
+    exhaustive_with::<DefaultCfg, T>()
+}
+
+/// Packing checked against a reference encoder, built by [`oracle`]
+pub struct Oracle<T, F> {
+    encode: F,
+    cases: usize,
+    offsets: usize,
+    seed: u64,
+    _value: PhantomData<fn(&T)>,
+}
+
+/// Check packing against `encode`, which is given a value, a zeroed buffer
+/// and the offset in bits to write the value at.  Only the bits of the
+/// value are compared, so `encode` may write whole bytes.
+///
+/// By default 1000 random values are checked at offsets `0..8`.
+pub fn oracle<T, F>(encode: F) -> Oracle<T, F>
+where
+    T: Pack + Unpack + Debug,
+    F: FnMut(&T, &mut [u8], usize),
+{
+    Oracle {
+        encode,
+        cases: 1000,
+        offsets: 8,
+        seed: 0x2545_f491_4f6c_dd1d,
+        _value: PhantomData,
+    }
+}
+
+impl<T, F> Oracle<T, F>
+where
+    T: Pack + Unpack + Debug,
+    F: FnMut(&T, &mut [u8], usize),
+{
+    /// Amount of random values to check
+    pub fn cases(self, cases: usize) -> Self {
+        Self { cases, ..self }
+    }
+
+    /// Check offsets `0..offsets`, e.g. `1` for an encoder which only
+    /// handles whole bytes
+    pub fn offsets(self, offsets: usize) -> Self {
+        assert!(offsets > 0, "at least one offset has to be checked");
+        Self { offsets, ..self }
+    }
+
+    /// Seed of the random values, which are the same for every run with
+    /// the same seed
+    pub fn seed(self, seed: u64) -> Self {
+        // xorshift never leaves zero
+        Self {
+            seed: seed.max(1),
+            ..self
+        }
+    }
+
+    /// Compare the encoders, returning the first value they disagree on.
+    /// Values are unpacked from random bits.
+    pub fn run_with<C: Cfg>(mut self) -> Result<(), OracleMismatch<T>> {
+        let mut state = self.seed;
+        let mut next = move || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let len = (self.offsets - 1 + T::SIZE).div_ceil(8);
+        let mut random = vec![0u8; len];
+        for _ in 0..self.cases {
+            random.fill_with(|| next() as u8);
+            let offset = (next() % self.offsets as u64) as usize;
+            let value = T::unpack_with::<C>(&random, offset);
+
+            let mut expected = vec![0u8; len];
+            (self.encode)(&value, &mut expected, offset);
+            let mut found = vec![0u8; len];
+            value.pack_with::<C>(&mut found, offset);
+
+            let differs = |i: &usize| {
+                let mask = bit_mask::<C>(*i);
+                expected[i / 8] & mask != found[i / 8] & mask
+            };
+            if let Some(i) = (offset..offset + T::SIZE).find(differs) {
+                return Err(OracleMismatch {
+                    value,
+                    offset,
+                    bit: i - offset,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare the encoders using [`DefaultCfg`]
+    pub fn run(self) -> Result<(), OracleMismatch<T>> {
+        self.run_with::<DefaultCfg>()
+    }
+
+    /// Compare the encoders
+    ///
+    /// # Panics
+    ///
+    /// With the first value they disagree on
+    #[track_caller]
+    pub fn check_with<C: Cfg>(self) {
+        if let Err(e) = self.run_with::<C>() {
+            panic!("{e}");
+        }
+    }
+
+    /// Compare the encoders using [`DefaultCfg`]
+    ///
+    /// # Panics
+    ///
+    /// With the first value they disagree on
+    #[track_caller]
+    pub fn check(self) {
+        self.check_with::<DefaultCfg>()
+    }
+}
+
+/// A value packed differently by [`Oracle`]'s reference encoder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleMismatch<T> {
+    pub value: T,
+    /// Offset the value was packed at
+    pub offset: usize,
+    /// The first differing bit, counted from the start of the value
+    pub bit: usize,
+    /// Bytes written by the reference encoder
+    pub expected: Vec<u8>,
+    /// Bytes written by [`Pack`]
+    pub found: Vec<u8>,
+}
+
+impl<T: Debug> fmt::Display for OracleMismatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at offset {} differs from the reference from bit {} of the value\n{}",
+            self.value,
+            self.offset,
+            self.bit,
+            bit_diff(&self.expected, &self.found).unwrap_or_default()
+        )
+    }
+}
+
+impl<T: Debug> std::error::Error for OracleMismatch<T> {}
+
+/// Conformance vectors, one per line: the type, the configuration, the
+/// offset, the value as big-endian hex, and the bytes it packs to in hex.
+/// Values are packed into buffers filled with [`CONFORMANCE_BACKGROUND`],
+/// just long enough to hold them.
+pub const CONFORMANCE_VECTORS: &str = include_str!("testing/conformance.txt");
+
+/// The byte conformance buffers are filled with before packing
+pub const CONFORMANCE_BACKGROUND: u8 = 0x5a;
+
+/// A conformance vector which didn't pack or unpack as expected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    /// Line of the vector in [`CONFORMANCE_VECTORS`], counted from 1
+    pub line: usize,
+    pub vector: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ConformanceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "vector {} `{}`: {}", self.line, self.vector, self.reason)
+    }
+}
+
+impl std::error::Error for ConformanceFailure {}
+
+/// Check every vector in [`CONFORMANCE_VECTORS`], returning the amount
+/// checked
+pub fn conformance() -> Result<usize, ConformanceFailure> {
+    let mut checked = 0;
+    for (i, vector) in CONFORMANCE_VECTORS.lines().enumerate() {
+        if vector.is_empty() || vector.starts_with('#') {
+            continue;
+        }
+        check_vector(vector).map_err(|reason| ConformanceFailure {
+            line: i + 1,
+            vector: vector.to_owned(),
+            reason,
+        })?;
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("odd length hex `{hex}`"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// A primitive with conformance vectors
+trait Conforming: Pack + Unpack + PartialEq + Debug + Sized {
+    fn from_be(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Conforming for bool {
+    fn from_be(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! conforming {
+    ($($ty: ident),+) => {
+        $(impl Conforming for $ty {
+            fn from_be(bytes: &[u8]) -> Option<Self> {
+                Some(Self::from_be_bytes(bytes.try_into().ok()?))
+            }
+        })+
+    };
+}
+
+conforming!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+fn check_vector(vector: &str) -> Result<(), String> {
+    let [ty, cfg, offset, value, expected] = vector
+        .split(' ')
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| "expected 5 fields".to_owned())?;
+    let offset = offset.parse().map_err(|_| "invalid offset".to_owned())?;
+    let value = from_hex(value)?;
+    let expected = from_hex(expected)?;
+
+    macro_rules! by_type {
+        ($($ty: ident),+) => {
+            match ty {
+                $(stringify!($ty) => by_cfg::<$ty>(cfg, offset, &value, &expected),)+
+                _ => Err(format!("unknown type `{ty}`")),
+            }
+        };
+    }
+    by_type!(bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128)
+}
+
+fn by_cfg<T: Conforming>(
+    cfg: &str,
+    offset: usize,
+    value: &[u8],
+    expected: &[u8],
+) -> Result<(), String> {
+    let value = T::from_be(value).ok_or("invalid value")?;
+    match cfg {
+        "Msb0Be" => check_packs::<Msb0Be, T>(&value, offset, expected),
+        "Msb0Le" => check_packs::<Msb0Le, T>(&value, offset, expected),
+        "Lsb0Be" => check_packs::<Lsb0Be, T>(&value, offset, expected),
+        "Lsb0Le" => check_packs::<Lsb0Le, T>(&value, offset, expected),
+        _ => Err(format!("unknown configuration `{cfg}`")),
+    }
+}
+
+fn check_packs<C: Cfg, T: Conforming>(
+    value: &T,
+    offset: usize,
+    expected: &[u8],
+) -> Result<(), String> {
+    if expected.len() != (offset + T::SIZE).div_ceil(8) {
+        return Err("wrong amount of expected bytes".to_owned());
+    }
+    let mut found = vec![CONFORMANCE_BACKGROUND; expected.len()];
+    value.pack_with::<C>(&mut found, offset);
+    if let Some(diff) = bit_diff(expected, &found) {
+        return Err(format!("packed differently\n{diff}"));
+    }
+    let unpacked = T::unpack_with::<C>(expected, offset);
+    if unpacked != *value {
+        return Err(format!("unpacked as {unpacked:?}"));
+    }
+    Ok(())
+}
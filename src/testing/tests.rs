@@ -0,0 +1,210 @@
+use super::{bit_diff, exhaustive, golden, oracle};
+use crate::cfg::{Lsb0Le, Msb0Be};
+use crate::reference;
+
+#[test]
+fn diff_marks_changed_bits() {
+    assert_eq!(bit_diff(&[1, 2], &[1, 2]), None);
+    let diff = bit_diff(&[0x12, 0x34], &[0x12, 0x36]).unwrap();
+    assert_eq!(
+        diff,
+        "bits 8..16      expected 00110100\n\
+         \x20               found    00110110\n\
+         \x20                              ^ \n"
+    );
+    let diff = bit_diff(&[0x12], &[0x12, 0xff]).unwrap();
+    assert!(diff.starts_with("length: expected 1 bytes, found 2\nbits 8..16"));
+    assert!(diff.contains("expected --------"));
+}
+
+#[test]
+fn golden_roundtrip() {
+    let dir = std::env::temp_dir().join(format!("packed-golden-{}", std::process::id()));
+    let path = dir.join("value.bin");
+    // the first run writes the snapshot
+    golden(&path, &(0x12u8, true));
+    assert_eq!(std::fs::read(&path).unwrap(), [0x12, 0x80]);
+    golden(&path, &(0x12u8, true));
+    let changed = std::panic::catch_unwind(|| golden(&path, &(0x12u8, false)));
+    assert!(changed.is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn exhaustive_small_types() {
+    use super::exhaustive_with;
+    use crate::Reserved;
+    use crate::niche::SentinelOption;
+    use crate::portable::PortableIsize;
+
+    macro_rules! check {
+        ($($ty: ty),+) => {$(
+            exhaustive_with::<Msb0Be, $ty>();
+            exhaustive_with::<Lsb0Le, $ty>();
+        )+};
+    }
+    check!(
+        bool,
+        i16,
+        [bool; 5],
+        (u8, Reserved<2, true>),
+        Result<u8, bool>,
+        SentinelOption<u8, 0xff>,
+        PortableIsize<12>
+    );
+}
+
+#[test]
+#[should_panic(expected = "changed the bits around it")]
+fn exhaustive_catches_overwrite() {
+    use crate::cfg::Cfg;
+    use crate::{Pack, PackedSize, Unpack};
+
+    /// Packs a bit too many
+    #[derive(Debug, PartialEq)]
+    struct Sloppy(u8);
+
+    impl PackedSize for Sloppy {
+        const SIZE: usize = 7;
+    }
+
+    impl Pack for Sloppy {
+        fn pack_with<C: Cfg>(&self, bytes: &mut [u8], offset: usize) {
+            (self.0 << 1 | 1).pack_with::<C>(bytes, offset);
+        }
+    }
+
+    impl Unpack for Sloppy {
+        fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Self {
+            Self(u8::unpack_with::<C>(bytes, offset) >> 1)
+        }
+    }
+
+    exhaustive::<Sloppy>();
+}
+
+#[test]
+fn oracle_agrees_with_reference() {
+    oracle(|x: &u32, bytes: &mut [u8], offset| reference::pack(*x, bytes, offset)).check();
+    oracle(|x: &i16, bytes: &mut [u8], offset| {
+        reference::pack_with::<Lsb0Le, _>(*x, bytes, offset)
+    })
+    .cases(200)
+    .offsets(16)
+    .check_with::<Lsb0Le>();
+}
+
+#[test]
+fn oracle_reports_first_differing_bit() {
+    // packs the low byte first, which only agrees on values with equal bytes
+    let err = oracle(|x: &u16, bytes: &mut [u8], offset| {
+        reference::pack_with::<Msb0Be, _>(x.swap_bytes(), bytes, offset)
+    })
+    .seed(7)
+    .run()
+    .unwrap_err();
+    let mut expected = vec![0u8; err.expected.len()];
+    reference::pack(err.value.swap_bytes(), &mut expected, err.offset);
+    assert_eq!(err.expected, expected);
+    let diff = err.value ^ err.value.swap_bytes();
+    assert_eq!(err.bit, diff.leading_zeros() as usize);
+    assert!(err.to_string().contains("differs from the reference"));
+}
+
+#[test]
+fn oracle_ignores_bits_around_value() {
+    oracle(|x: &bool, bytes: &mut [u8], offset| {
+        bytes.fill(0xff);
+        reference::pack(*x, bytes, offset)
+    })
+    .check();
+}
+
+/// The conformance vectors, generated with the bit by bit packing of
+/// [`reference`]
+fn reference_vectors() -> String {
+    use super::CONFORMANCE_BACKGROUND;
+    use crate::cfg::{Cfg, Lsb0Be, Msb0Le};
+    use crate::reference::Primitive;
+
+    const PATTERN: u128 = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+
+    fn vectors<C: Cfg, T: Primitive>(out: &mut String, ty: &str, cfg: &str, values: &[(T, &[u8])]) {
+        for (value, be) in values {
+            for offset in 0..8 {
+                let mut bytes = vec![CONFORMANCE_BACKGROUND; (offset + T::SIZE).div_ceil(8)];
+                reference::pack_with::<C, T>(*value, &mut bytes, offset);
+                let hex =
+                    |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+                out.push_str(&format!(
+                    "{ty} {cfg} {offset} {} {}\n",
+                    hex(be),
+                    hex(&bytes)
+                ));
+            }
+        }
+    }
+
+    let mut out = String::from(
+        "# generated by `reference_vectors` in src/testing/tests.rs\n\
+         # type cfg offset value packed\n",
+    );
+    macro_rules! all_cfgs {
+        ($ty: ident, $values: expr) => {
+            vectors::<Msb0Be, $ty>(&mut out, stringify!($ty), "Msb0Be", $values);
+            vectors::<Msb0Le, $ty>(&mut out, stringify!($ty), "Msb0Le", $values);
+            vectors::<Lsb0Be, $ty>(&mut out, stringify!($ty), "Lsb0Be", $values);
+            vectors::<Lsb0Le, $ty>(&mut out, stringify!($ty), "Lsb0Le", $values);
+        };
+    }
+    all_cfgs!(bool, &[(false, &[0][..]), (true, &[1][..])]);
+    macro_rules! ints {
+        ($($ty: ident),+) => {$(
+            let pattern = PATTERN as $ty;
+            all_cfgs!(
+                $ty,
+                &[
+                    (pattern, &pattern.to_be_bytes()[..]),
+                    (!pattern, &(!pattern).to_be_bytes()[..]),
+                ]
+            );
+        )+};
+    }
+    ints!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+    out
+}
+
+#[test]
+fn conformance_vectors() {
+    use super::{CONFORMANCE_VECTORS, conformance};
+
+    if std::env::var_os(super::BLESS_VAR).is_some_and(|v| !v.is_empty()) {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/testing/conformance.txt");
+        std::fs::write(path, reference_vectors()).unwrap();
+        return;
+    }
+    assert_eq!(CONFORMANCE_VECTORS, reference_vectors());
+    assert_eq!(conformance(), Ok(11 * 4 * 2 * 8));
+}
+
+#[test]
+fn conformance_reports_failures() {
+    use super::check_vector;
+
+    assert_eq!(check_vector("u8 Msb0Be 0 12 12"), Ok(()));
+    assert_eq!(check_vector("u8 Msb0Be 4 12 512a"), Ok(()));
+    let err = check_vector("u8 Msb0Be 4 12 512b").unwrap_err();
+    assert!(err.starts_with("packed differently\nbits 8..16"), "{err}");
+    assert_eq!(
+        check_vector("f32 Msb0Be 0 00 00"),
+        Err("unknown type `f32`".to_owned())
+    );
+    assert_eq!(
+        check_vector("u8 Msb0Be 0 12"),
+        Err("expected 5 fields".to_owned())
+    );
+    assert_eq!(
+        check_vector("u8 Msb0Be 0 12 1234"),
+        Err("wrong amount of expected bytes".to_owned())
+    );
+}
@@ -1,4 +1,5 @@
-use crate::Packed;
+use crate::cfg::{Lsb0Be, Lsb0Le, Msb0Be, Msb0Le};
+use crate::{Pack, PackError, Packed, PackedSize, Unpack};
 
 fn unpack_from_val<T>(_: &T, bytes: &[u8], offset: usize) -> T
 where
@@ -47,17 +48,61 @@ simple!(pack_unpack_u16(u16));
 simple!(pack_unpack_u32(u32));
 simple!(pack_unpack_u64(u64));
 simple!(pack_unpack_u128(u128));
+#[cfg(feature = "native-size")]
 simple!(pack_unpack_usize(usize));
 simple!(pack_unpack_i8(i8));
 simple!(pack_unpack_i16(i16));
 simple!(pack_unpack_i32(i32));
 simple!(pack_unpack_i64(i64));
 simple!(pack_unpack_i128(i128));
+#[cfg(feature = "native-size")]
 simple!(pack_unpack_isize(isize));
 simple!(pack_unpack_int_tuple1((u8, u16, u32)));
 simple!(pack_unpack_int_tuple2((i8, u16, i128)));
+#[cfg(feature = "native-size")]
 simple!(pack_unpack_int_tuple3((u8, u16, u32, u64, u128, usize)));
+#[cfg(feature = "native-size")]
 simple!(pack_unpack_int_tuple4((i8, i16, i32, i64, i128, isize)));
+simple!(pack_unpack_box(Box<u16>));
+simple!(pack_unpack_rc(std::rc::Rc<(bool, i32)>));
+simple!(pack_unpack_arc(std::sync::Arc<[u8; 3]>));
+simple!(pack_unpack_result(Result<u16, (bool, u8)>));
+simple!(pack_unpack_result_array([Result<bool, i32>; 3]));
+simple!(pack_unpack_ptr_tuple((Box<u8>, std::sync::Arc<bool>, u16)));
+
+proptest::proptest! {
+    #[test]
+    fn pack_unpack_atomics(a: bool, b: u16, c: i64, offset in 0usize..=16) {
+        use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU16, Ordering::Relaxed};
+        type Atomics = (AtomicBool, AtomicU16, AtomicI64);
+        assert_eq!(Atomics::SIZE, 81);
+        let mut buf = [0u8; 14];
+        (AtomicBool::new(a), AtomicU16::new(b), AtomicI64::new(c)).pack(&mut buf, offset);
+        assert_eq!(<(bool, u16, i64)>::unpack(&buf, offset), (a, b, c));
+        let (x, y, z) = Atomics::unpack(&buf, offset);
+        assert_eq!((x.load(Relaxed), y.load(Relaxed), z.load(Relaxed)), (a, b, c));
+        let mut out = Atomics::default();
+        out.unpack_into(&buf, offset);
+        assert_eq!((out.0.into_inner(), out.1.into_inner(), out.2.into_inner()), (a, b, c));
+    }
+}
+
+#[test]
+fn result_layout() {
+    assert_eq!(<Result<u8, [bool; 3]>>::SIZE, 9);
+    let mut buf = [0xffu8; 2];
+    Err::<u8, _>([true, false, true]).pack(&mut buf, 0);
+    assert_eq!(buf, [0b1101_0000, 0b0111_1111]);
+    Ok::<_, [bool; 3]>(0x5au8).pack(&mut buf, 0);
+    assert_eq!(buf, [0b0010_1101, 0b0111_1111]);
+}
+
+#[test]
+fn phantom_data() {
+    use std::marker::PhantomData;
+    assert_eq!(<PhantomData<u64>>::SIZE, 0);
+    assert_eq!(<(u8, PhantomData<String>, bool)>::SIZE, 9);
+}
 
 proptest::proptest! {
     #[test]
@@ -87,3 +132,689 @@ proptest::proptest! {
         }
     }
 }
+
+macro_rules! with_cfg {
+    ($name: ident ($type: ty)) => {
+        #[test]
+        fn $name() {
+            fn check<C: crate::cfg::Cfg>(n: $type) {
+                let mut buf = [0u8; <$type>::SIZE / 8 + 3];
+                for offset in 0..=16 {
+                    n.pack_with::<C>(&mut buf, offset);
+                    let m = <$type>::unpack_with::<C>(&buf, offset);
+                    assert_eq!(n, m);
+                }
+            }
+            proptest::proptest!(|(n: $type)| {
+                check::<Msb0Be>(n);
+                check::<Msb0Le>(n);
+                check::<Lsb0Be>(n);
+                check::<Lsb0Le>(n);
+            });
+        }
+    };
+}
+
+with_cfg!(pack_unpack_cfg_bools([bool; 14]));
+with_cfg!(pack_unpack_cfg_u8(u8));
+#[cfg(feature = "native-size")]
+with_cfg!(pack_unpack_cfg_int_tuple((u8, u16, u32, u64, u128, usize)));
+with_cfg!(pack_unpack_cfg_mixed_tuple((bool, i16, [bool; 3], i64)));
+
+#[test]
+fn cfg_layout() {
+    let mut buf = [0u8; 3];
+    0x1234u16.pack_with::<Msb0Le>(&mut buf, 4);
+    assert_eq!(buf, [0x03, 0x41, 0x20]);
+
+    let mut buf = [0u8; 3];
+    0x1234u16.pack_with::<Lsb0Be>(&mut buf, 4);
+    assert_eq!(buf, [0x20, 0x41, 0x03]);
+
+    let mut buf = [0u8; 3];
+    0x1234u16.pack_with::<Lsb0Le>(&mut buf, 4);
+    assert_eq!(buf, [0x40, 0x23, 0x01]);
+
+    let mut buf = [0u8; 1];
+    true.pack_with::<Lsb0Le>(&mut buf, 1);
+    assert_eq!(buf, [0b0000_0010]);
+    true.pack_with::<Msb0Be>(&mut buf, 1);
+    assert_eq!(buf, [0b0100_0010]);
+}
+
+#[test]
+fn cfg_preserves_neighbours() {
+    let mut buf = [0xffu8; 3];
+    0u16.pack_with::<Lsb0Le>(&mut buf, 4);
+    assert_eq!(buf, [0x0f, 0x00, 0xf0]);
+
+    let mut buf = [0xffu8; 3];
+    0u16.pack_with::<Msb0Be>(&mut buf, 4);
+    assert_eq!(buf, [0xf0, 0x00, 0x0f]);
+}
+
+/// Fixed expectations which would fail if packing went through the target's
+/// own byte order, for running on big-endian targets
+#[test]
+fn host_byte_order_independent() {
+    use crate::cfg::{ByteOrder, OrderedBytes};
+
+    assert_eq!(
+        ByteOrder::NATIVE == ByteOrder::Big,
+        cfg!(target_endian = "big")
+    );
+    assert_eq!(0x0102_0304u32.to_bytes(ByteOrder::Big), [1, 2, 3, 4]);
+    assert_eq!(0x0102_0304u32.to_bytes(ByteOrder::Little), [4, 3, 2, 1]);
+    assert_eq!(i16::from_bytes([0xff, 0xfe], ByteOrder::Big), -2);
+
+    let value = 0x0123_4567_89ab_cdefu64;
+    let mut buf = [0u8; 9];
+    value.pack_with::<Msb0Be>(&mut buf, 4);
+    assert_eq!(buf, [0x00, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0]);
+    let mut buf = [0u8; 9];
+    value.pack_with::<Lsb0Le>(&mut buf, 4);
+    assert_eq!(buf, [0xf0, 0xde, 0xbc, 0x9a, 0x78, 0x56, 0x34, 0x12, 0x00]);
+    assert_eq!(u64::unpack_with::<Lsb0Le>(&buf, 4), value);
+
+    // through the narrow word path
+    let mut buf = [0u8; 6];
+    crate::narrow::NarrowInt::pack_narrow::<Msb0Be>(0x1_2345_6789u64, 36, &mut buf, 4);
+    assert_eq!(buf, [0x01, 0x23, 0x45, 0x67, 0x89, 0x00]);
+    let mut buf = [0u8; 6];
+    crate::narrow::NarrowInt::pack_narrow::<Lsb0Le>(0x1_2345_6789u64, 36, &mut buf, 4);
+    assert_eq!(buf, [0x90, 0x78, 0x56, 0x34, 0x12, 0x00]);
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Packed)]
+struct Header {
+    version: u8,
+    #[packed(bits = 3)]
+    kind: u8,
+    flag: bool,
+    #[packed(bits = 12)]
+    delta: i16,
+    #[packed(bits = 1)]
+    sign: i8,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_size() {
+    assert_eq!(Header::SIZE, 8 + 3 + 1 + 12 + 1);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_layout() {
+    let header = Header {
+        version: 0xa5,
+        kind: 0b101,
+        flag: true,
+        delta: -1,
+        sign: -1,
+    };
+    let mut buf = [0u8; 4];
+    header.pack(&mut buf, 0);
+    assert_eq!(buf, [0xa5, 0b1011_1111, 0xff, 0b1000_0000]);
+    assert_eq!(Header::unpack(&buf, 0), header);
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Packed)]
+struct Marker;
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Packed)]
+struct Meters(u16);
+
+#[cfg(feature = "derive")]
+#[derive(Clone, Copy, PartialEq, Eq, crate::Packed, crate::debug::PackedDebug)]
+struct Sample(
+    #[packed(bits = 4)] u8,
+    Meters,
+    Marker,
+    #[packed(niche)] Option<std::num::NonZeroU8>,
+);
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_shapes() {
+    assert_eq!(Marker::SIZE, 0);
+    Marker.pack(&mut [], 0);
+    assert_eq!(Marker::unpack_strict(&[], 0), Ok(Marker));
+
+    assert_eq!(Meters::SIZE, u16::SIZE);
+    let mut buf = [0u8; 2];
+    Meters(0x1234).pack_with::<Lsb0Le>(&mut buf, 0);
+    assert_eq!(buf, [0x34, 0x12]);
+    assert_eq!(Meters::unpack_with::<Lsb0Le>(&buf, 0), Meters(0x1234));
+
+    assert_eq!(Sample::SIZE, 28);
+    let sample = Sample(0xa, Meters(0x1234), Marker, std::num::NonZeroU8::new(0x56));
+    let mut buf = [0u8; 4];
+    sample.pack(&mut buf, 0);
+    assert_eq!(buf, [0xa1, 0x23, 0x45, 0x60]);
+    assert_eq!(Sample::unpack(&buf, 0), sample);
+    assert!(format!("{sample:?}").starts_with("Sample { 0: "));
+}
+
+/// Declared with related fields together, packed in the order of a spec
+#[cfg(feature = "derive")]
+#[derive(Clone, Copy, PartialEq, Eq, crate::Packed, crate::debug::PackedDebug)]
+struct Reordered {
+    #[packed(order = 2)]
+    src: u8,
+    #[packed(order = 0)]
+    dst: u8,
+    #[packed(order = 1, bits = 4)]
+    flags: u8,
+    #[packed(order = 3, bits = 4)]
+    hops: u8,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_wire_order() {
+    let value = Reordered {
+        src: 0x12,
+        dst: 0x34,
+        flags: 0x5,
+        hops: 0x6,
+    };
+    let mut buf = [0u8; 3];
+    value.pack(&mut buf, 0);
+    assert_eq!(buf, [0x34, 0x51, 0x26]);
+    assert_eq!(Reordered::unpack(&buf, 0), value);
+    assert!(format!("{value:?}").starts_with("Reordered { dst: 52 @ 0..8"));
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Packed)]
+#[packed(endian = "little")]
+struct MixedEndian {
+    counter: u16,
+    #[packed(endian = "big")]
+    register: u32,
+    #[packed(bits = 12)]
+    narrow: u16,
+    nested: (u16, [i16; 2]),
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_endian() {
+    let value = MixedEndian {
+        counter: 0x1234,
+        register: 0x89ab_cdef,
+        narrow: 0xabc,
+        nested: (0x5678, [1, -2]),
+    };
+    let mut buf = [0u8; 14];
+    value.pack(&mut buf, 4);
+    assert_eq!(
+        buf,
+        [
+            0x03, 0x41, 0x28, 0x9a, 0xbc, 0xde, 0xfa, 0xbc, 0x78, 0x56, 0x01, 0x00, 0xfe, 0xff
+        ]
+    );
+    assert_eq!(MixedEndian::unpack(&buf, 4), value);
+
+    // the struct's own byte order is overridden, but not its bit order
+    let mut buf = [0u8; 14];
+    value.pack_with::<Lsb0Be>(&mut buf, 0);
+    assert_eq!(buf[..6], [0x34, 0x12, 0x89, 0xab, 0xcd, 0xef]);
+    assert_eq!(MixedEndian::unpack_with::<Lsb0Be>(&buf, 0), value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_signed_boundaries() {
+    for delta in [-1, 0, 1, -2048, 2047] {
+        let header = Header {
+            version: 0,
+            kind: 0,
+            flag: false,
+            delta,
+            sign: 0,
+        };
+        let mut buf = [0u8; 5];
+        header.pack(&mut buf, 3);
+        assert_eq!(Header::unpack(&buf, 3).delta, delta);
+    }
+}
+
+#[cfg(feature = "derive")]
+proptest::proptest! {
+    #[test]
+    fn derive_pack_unpack(
+        version: u8,
+        kind in 0u8..8,
+        flag: bool,
+        delta in -2048i16..2048,
+        sign in -1i8..=0,
+    ) {
+        let header = Header { version, kind, flag, delta, sign };
+        let mut buf = [0u8; 7];
+        for offset in 0..=16 {
+            header.pack(&mut buf, offset);
+            assert_eq!(Header::unpack(&buf, offset), header);
+            header.pack_with::<Lsb0Le>(&mut buf, offset);
+            assert_eq!(Header::unpack_with::<Lsb0Le>(&buf, offset), header);
+        }
+    }
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Eq, crate::Packed)]
+struct Nested {
+    flag: bool,
+    inner: Padded,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Eq, crate::Packed)]
+struct Padded {
+    a: u8,
+    _pad: crate::Reserved<3>,
+    b: bool,
+    _fill: crate::Reserved<4, true>,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn reserved_roundtrip() {
+    use crate::Reserved;
+    let value = Padded {
+        a: 0xab,
+        _pad: Reserved,
+        b: true,
+        _fill: Reserved,
+    };
+    let mut buf = [0xffu8; 2];
+    value.pack(&mut buf, 0);
+    assert_eq!(buf, [0xab, 0b0001_1111]);
+    assert_eq!(Padded::unpack_strict(&buf, 0), Ok(Padded::unpack(&buf, 0)));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn reserved_rejects() {
+    use crate::UnpackErrorKind;
+    let err = Padded::unpack_strict(&[0xab, 0b0011_1111], 0).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &UnpackErrorKind::Reserved {
+            offset: 10,
+            expected: false
+        }
+    );
+    assert_eq!(err.path().iter().collect::<Vec<_>>(), ["_pad"]);
+    assert_eq!(
+        err.to_string(),
+        "in field `_pad`: reserved bit 10 is 1, expected 0"
+    );
+    let err = Padded::unpack_strict(&[0x00, 0b0001_1011, 0], 0).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &UnpackErrorKind::Reserved {
+            offset: 13,
+            expected: true
+        }
+    );
+    assert_eq!(err.path().to_string(), "_fill");
+    assert_eq!(
+        <[Padded; 2]>::unpack_strict(&[0, 0x0f, 0, 0x2f], 0).map_err(|e| e.offset()),
+        Err(26)
+    );
+    // the plain unpack doesn't look at reserved bits
+    assert_eq!(Padded::unpack(&[0xab, 0b1110_0000], 0).a, 0xab);
+}
+
+/// A flag which reads any non-zero pattern as set, but only packs 0b01
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq)]
+struct LooseFlag(bool);
+
+#[cfg(feature = "derive")]
+impl PackedSize for LooseFlag {
+    const SIZE: usize = 2;
+}
+
+#[cfg(feature = "derive")]
+impl Pack for LooseFlag {
+    fn pack_with<C: crate::cfg::Cfg>(&self, bytes: &mut [u8], offset: usize) {
+        false.pack_with::<C>(bytes, offset);
+        self.0.pack_with::<C>(bytes, offset + 1);
+    }
+}
+
+#[cfg(feature = "derive")]
+impl Unpack for LooseFlag {
+    fn unpack_with<C: crate::cfg::Cfg>(bytes: &[u8], offset: usize) -> Self {
+        Self(<[bool; 2]>::unpack_with::<C>(bytes, offset) != [false; 2])
+    }
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq, Packed)]
+struct Canonical {
+    a: u8,
+    flag: LooseFlag,
+    b: Result<bool, [bool; 3]>,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn canonical() {
+    use crate::UnpackErrorKind;
+    let value = Canonical {
+        a: 0x12,
+        flag: LooseFlag(true),
+        b: Ok(true),
+    };
+    let mut buf = [0xffu8; 3];
+    value.pack(&mut buf, 3);
+    assert_eq!(Canonical::check_canonical(&buf, 3), Ok(()));
+    assert_eq!(
+        Canonical::check_canonical_with::<Lsb0Le>(&[0x12, 0b1010], 0),
+        Ok(())
+    );
+
+    // both flag bits set still reads as true, but packs differently
+    buf[1] |= 0b0001_0000;
+    assert_eq!(Canonical::unpack(&buf, 3), value);
+    let err = Canonical::check_canonical(&buf, 3).unwrap_err();
+    assert_eq!(err.kind(), &UnpackErrorKind::NonCanonical { offset: 11 });
+    assert_eq!(err.to_string(), "value is not in canonical form at bit 11");
+
+    // only the bytes under the value are repacked, wherever it starts
+    let mut far = vec![0xffu8; 1000];
+    far[997..].copy_from_slice(&buf);
+    let err = Canonical::check_canonical(&far, 997 * 8 + 3).unwrap_err();
+    assert_eq!(err.offset(), 997 * 8 + 11);
+    value.pack(&mut far, 997 * 8 + 3);
+    assert_eq!(Canonical::check_canonical(&far, 997 * 8 + 3), Ok(()));
+
+    // reserved bits are reported as such
+    assert_eq!(
+        Canonical::check_canonical(&[0x12, 0b0100_0100], 0).map_err(|e| e.offset()),
+        Err(13)
+    );
+    assert_eq!(
+        Canonical::check_canonical(&[0x12], 0).map_err(|e| e.kind().clone()),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 8 })
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn error_path_nests() {
+    let err = Nested::unpack_strict(&[0x80, 0x1f, 0xff], 0).unwrap_err();
+    assert_eq!(err.offset(), 11);
+    assert_eq!(err.path().to_string(), "inner._pad");
+    assert_eq!(
+        err.to_string(),
+        "in field `inner._pad`: reserved bit 11 is 1, expected 0"
+    );
+}
+
+#[test]
+fn error_path_truncates() {
+    use crate::{UnpackError, UnpackErrorKind};
+    let err = ["e", "d", "c", "b", "a"].into_iter().fold(
+        UnpackError::from(UnpackErrorKind::Overflow { offset: 3 }),
+        UnpackError::in_field,
+    );
+    assert_eq!(err.path().to_string(), "a.b.c.d.…");
+    assert_eq!(err.offset(), 3);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn errors_serde_roundtrip() {
+    use crate::{Reserved, UnpackError, UnpackErrorKind};
+
+    let err = ["c", "b", "a"].into_iter().fold(
+        UnpackError::from(UnpackErrorKind::Reserved {
+            offset: 11,
+            expected: false,
+        }),
+        UnpackError::in_field,
+    );
+    // field names are borrowed from the input, so it has to outlive them
+    let json = serde_json::to_string(&err).unwrap().leak();
+    assert_eq!(
+        json,
+        r#"{"kind":{"Reserved":{"offset":11,"expected":false}},"path":{"names":["a","b","c"],"truncated":false}}"#
+    );
+    assert_eq!(serde_json::from_str::<UnpackError>(json).unwrap(), err);
+
+    let deep = r#"{"kind":{"Limit":{"offset":0}},"path":{"names":["a","b","c","d","e"],"truncated":true}}"#;
+    assert!(serde_json::from_str::<UnpackError>(deep).is_err());
+
+    let err = PackError::OutOfSpace {
+        offset: 3,
+        needed: 16,
+    };
+    let json = serde_json::to_string(&err).unwrap();
+    assert_eq!(serde_json::from_str::<PackError>(&json).unwrap(), err);
+
+    let json = serde_json::to_string(&Reserved::<3, true>).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Reserved<3, true>>(&json).unwrap(),
+        Reserved
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn reserved_strict_accepts_packed(a: u8, b: bool, offset in 0usize..=16) {
+        use crate::Reserved;
+        let mut buf = [0x55u8; 5];
+        let value = (a, Reserved::<5>, b, Reserved::<7, true>);
+        value.pack_with::<Lsb0Le>(&mut buf, offset);
+        assert_eq!(<(u8, Reserved<5>, bool, Reserved<7, true>)>::unpack_strict_with::<Lsb0Le>(&buf, offset), Ok(value));
+    }
+}
+
+#[cfg(feature = "derive")]
+proptest::proptest! {
+    #[test]
+    fn unpack_into_matches_unpack(
+        value: (u16, [bool; 5], Box<[u8; 3]>),
+        version: u8,
+        delta in -2048i16..2048,
+        offset in 0usize..=16,
+    ) {
+        let mut buf = [0u8; 8];
+        value.pack(&mut buf, offset);
+        let mut out = Default::default();
+        <(u16, [bool; 5], Box<[u8; 3]>)>::unpack_into(&mut out, &buf, offset);
+        assert_eq!(out, value);
+
+        let header = Header { version, kind: 3, flag: true, delta, sign: -1 };
+        header.pack_with::<Lsb0Le>(&mut buf, offset);
+        let mut out = Header { version: 0, kind: 0, flag: false, delta: 0, sign: 0 };
+        out.unpack_into_with::<Lsb0Le>(&buf, offset);
+        assert_eq!(out, header);
+    }
+}
+
+#[test]
+fn unpack_into_reuses_box() {
+    let mut out = Box::new([0u8; 4]);
+    let before: *const [u8; 4] = &*out;
+    out.unpack_into(&[1, 2, 3, 4], 0);
+    assert_eq!(*out, [1, 2, 3, 4]);
+    assert!(std::ptr::eq(before, &*out));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn pack_only() {
+    // a borrowed view can be packed, but not unpacked
+    #[derive(crate::Pack)]
+    struct View<'a> {
+        kind: u8,
+        payload: &'a [u8; 2],
+    }
+
+    #[derive(Debug, PartialEq, crate::Unpack)]
+    struct Owned {
+        kind: u8,
+        payload: [u8; 2],
+    }
+
+    let payload = [2, 3];
+    let mut buf = [0u8; 3];
+    View {
+        kind: 1,
+        payload: &payload,
+    }
+    .pack(&mut buf, 0);
+    assert_eq!(View::SIZE, Owned::SIZE);
+    assert_eq!(
+        Owned::unpack(&buf, 0),
+        Owned {
+            kind: 1,
+            payload: [2, 3]
+        }
+    );
+}
+
+#[test]
+fn offsets_near_usize_max() {
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        0u8.pack_checked(&mut buf, usize::MAX - 3),
+        Err(PackError::OutOfSpace {
+            offset: usize::MAX - 3,
+            needed: 8
+        })
+    );
+    assert_eq!(0x5au8.pack_checked(&mut buf, 20), Ok(()));
+    assert!(0u16.pack_checked(&mut buf, 20).is_err());
+    assert_eq!(buf, [0, 0, 0x05, 0xa0]);
+    assert!(u32::unpack_strict(&buf, usize::MAX - 3).is_err());
+    assert!(crate::varint::unpack(&buf, usize::MAX - 3).is_err());
+    let fixed = crate::parse::fixed::<u8>();
+    assert!(crate::parse::Parser::parse(&fixed, &buf, usize::MAX).is_err());
+}
+
+#[test]
+fn pack_by_reference() {
+    // neither `Rc` nor `AtomicU8` needs to be cloned or consumed to be packed
+    let shared = std::rc::Rc::new([1u8, 2, 3]);
+    let value = (shared.clone(), std::sync::atomic::AtomicU8::new(4));
+    let mut buf = [0u8; 4];
+    value.pack(&mut buf, 0);
+    assert_eq!(buf, [1, 2, 3, 4]);
+    assert_eq!(std::rc::Rc::strong_count(&shared), 2);
+}
+
+proptest::proptest! {
+    #[test]
+    fn pack_at_matches_pack(value: (u8, bool, i32)) {
+        macro_rules! check {
+            ($($offset: literal),+) => {$({
+                let mut expected = [0x5au8; 8];
+                let mut buf = [0x5au8; 8];
+                value.pack(&mut expected, $offset);
+                value.pack_at::<$offset>(&mut buf);
+                assert_eq!(buf, expected);
+                assert_eq!(<(u8, bool, i32)>::unpack_at::<$offset>(&buf), value);
+                value.pack_at_with::<Lsb0Le, $offset>(&mut buf);
+                assert_eq!(<(u8, bool, i32)>::unpack_at_with::<Lsb0Le, $offset>(&buf), value);
+            })+};
+        }
+        check!(0, 1, 7, 8, 13, 16, 23);
+    }
+}
+
+/// Offsets near the end of a short buffer, or anywhere at all
+fn any_offset() -> impl proptest::strategy::Strategy<Value = usize> {
+    proptest::prop_oneof![0usize..256, proptest::num::usize::ANY]
+}
+
+proptest::proptest! {
+    // the fallible API must return an error for any input rather than panic
+    #[test]
+    fn fallible_never_panics(
+        bytes in proptest::collection::vec(proptest::num::u8::ANY, 0..24),
+        offset in any_offset(),
+        n in any_offset(),
+    ) {
+        use crate::borrow::PackedRef;
+        use crate::cursor::{BitReader, VectoredReader};
+        use crate::parse::{self, Limits, Parser};
+        use crate::symbols::Tail;
+        use crate::{Reserved, cfg::ByteOrder};
+
+        let _ = <(bool, Reserved<3>, u16)>::unpack_strict(&bytes, offset);
+        let _ = <[u8; 3]>::unpack_strict_with::<Lsb0Le>(&bytes, offset);
+        let _ = <&[u8; 2]>::unpack_ref(&bytes, offset);
+        let _ = <Result<u8, bool>>::check_canonical(&bytes, offset);
+        let _ = crate::bit_pattern!("1x_0x01").matches(&bytes, offset);
+        let _ = crate::varint::unpack(&bytes, offset);
+        // a count of values taking no bits each can ask for any amount of
+        // memory without limits
+        let limits = Limits { max_count: 1 << 16, ..Limits::NONE };
+        let _ = crate::frame_of_ref::unpack_limited(&bytes, offset, limits);
+        let _ = crate::dict::unpack_limited::<u8>(&bytes, offset, limits);
+        let _ = crate::dict::unpack_limited::<()>(&bytes, offset, limits);
+        let _ = crate::elias_fano::EliasFano::unpack(&bytes, offset);
+        let _ = crate::delta::DeltaFrame::from_bytes(&bytes);
+        for bits in 1..=8 {
+            for tail in [Tail::Ignore, Tail::Zeros, Tail::Exact] {
+                let _ = crate::symbols::unpack_symbols(&bytes, bits, tail);
+            }
+        }
+        for order in [ByteOrder::Big, ByteOrder::Little] {
+            let _ = crate::pcm::unpack_pcm24(&bytes, order);
+            let _ = crate::pcm::unpack_pcm20(&bytes, order);
+        }
+        let _ = crate::grid::unpack_2d::<u16>(crate::grid::Grid::new(3, 10), n, &bytes, offset);
+        let _ = crate::slice::unpack_slice_into(&bytes, offset, &mut Vec::<u16>::new(), n);
+        let _ = crate::soa::unpack_columns::<(u8, bool)>(&[8, 1], n, &bytes, offset);
+        let _ = crate::columnar::Table::<Msb0Be>::from_parts(&[3, 64], vec![bytes.clone(); 2], n);
+
+        let counted = parse::varint().and_then(|n| parse::fixed::<u8>().repeat_n(n as usize));
+        let _ = counted.parse(&bytes, offset);
+        let _ = counted.parse_limited(&bytes, offset, Limits::NONE);
+
+        let mut reader = BitReader::new(&bytes);
+        let _ = reader.seek(offset);
+        let _ = reader.skip(n);
+        let _ = reader.read::<u16>();
+        let _ = reader.peek::<(bool, u32)>();
+        let _ = reader.align_to(n.max(1));
+        let _ = reader.pad_to_byte();
+        let (a, b) = bytes.split_at(bytes.len() / 2);
+        let bufs = [a, b];
+        let mut reader = VectoredReader::new(&bufs);
+        let _ = reader.skip(offset);
+        let _ = reader.read::<u16>();
+        let _ = reader.peek::<[u8; 3]>();
+
+        if offset <= bytes.len() * 8 {
+            let reader = crate::tlv::TlvReader::new(&bytes, offset, bytes.len() * 8 - offset);
+            let _ = reader.get::<u16>(1);
+            for field in reader.take(64).flatten() {
+                let _ = field.get::<u32>();
+            }
+        }
+        let reader = crate::tlv::TlvReader::new(&bytes, offset, n);
+        let _ = reader.get::<u16>(1);
+        let _ = reader.take(64).count();
+
+        #[cfg(feature = "ecc")]
+        let _ = crate::ecc::Ecc::<u16>::unpack_checked(&bytes, offset);
+        #[cfg(feature = "raw")]
+        {
+            let _ = crate::raw::decode_raw10(&bytes);
+            let _ = crate::raw::decode_raw12(&bytes);
+            let _ = crate::raw::decode_raw14(&bytes);
+        }
+    }
+}
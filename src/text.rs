@@ -0,0 +1,131 @@
+//! Hex and base64 encodings of packed buffers.
+//!
+//! Packed data usually ends up in logs, command lines or JSON at some point,
+//! which need text.  Hex is lowercase and accepted in either case, and
+//! base64 uses the standard alphabet with padding, which may be left out
+//! when decoding.
+//!
+//! Only available with the `text` feature.
+//!
+//! ```rust
+//! # use packed::prelude::*;
+//! # use packed::text;
+//! let mut buf = [0u8; 3];
+//! (0x12u8, 0x3456u16).pack(&mut buf, 0);
+//! assert_eq!(text::to_hex(&buf), "123456");
+//! assert_eq!(text::to_base64(&buf), "EjRW");
+//! assert_eq!(text::from_hex("123456"), Ok(buf.to_vec()));
+//! assert_eq!(text::from_base64("EjRW"), Ok(buf.to_vec()));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Error returned when decoding text fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A character outside the encoding, at byte `index` of the input
+    InvalidChar { index: usize, char: char },
+    /// The input ends partway through a byte
+    InvalidLength { len: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { index, char } => {
+                write!(f, "invalid character {char:?} at index {index}")
+            }
+            Self::InvalidLength { len } => write!(f, "invalid input length {len}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// The character at `index`, for errors
+fn char_at(s: &str, index: usize) -> DecodeError {
+    let char = s[index..].chars().next().unwrap_or_default();
+    DecodeError::InvalidChar { index, char }
+}
+
+/// `bytes` as two lowercase hex digits each
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        out.push(HEX[usize::from(b >> 4)] as char);
+        out.push(HEX[usize::from(b & 0xf)] as char);
+    }
+    out
+}
+
+/// The bytes of a string of hex digit pairs
+pub fn from_hex(s: &str) -> Result<Vec<u8>, DecodeError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(DecodeError::InvalidLength { len: s.len() });
+    }
+    let digit = |i: usize| match s.as_bytes()[i] {
+        b @ b'0'..=b'9' => Ok(b - b'0'),
+        b @ b'a'..=b'f' => Ok(b - b'a' + 10),
+        b @ b'A'..=b'F' => Ok(b - b'A' + 10),
+        _ => Err(char_at(s, i)),
+    };
+    (0..s.len())
+        .step_by(2)
+        .map(|i| Ok(digit(i)? << 4 | digit(i + 1)?))
+        .collect()
+}
+
+/// `bytes` as padded base64
+pub fn to_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let n = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// The bytes of a base64 string, with or without padding
+pub fn from_base64(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let data = s.trim_end_matches('=');
+    let padding = s.len() - data.len();
+    if data.len() % 4 == 1 || padding > 2 || (padding > 0 && !s.len().is_multiple_of(4)) {
+        return Err(DecodeError::InvalidLength { len: s.len() });
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut n = 0u32;
+    for (i, b) in data.bytes().enumerate() {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(char_at(s, i)),
+        };
+        n = n << 6 | u32::from(value);
+        if i % 4 == 3 {
+            out.extend_from_slice(&n.to_be_bytes()[1..]);
+        }
+    }
+    match data.len() % 4 {
+        2 => out.push((n >> 4) as u8),
+        3 => out.extend_from_slice(&((n >> 2) as u16).to_be_bytes()),
+        _ => {}
+    }
+    Ok(out)
+}
@@ -0,0 +1,84 @@
+use super::{DecodeError, from_base64, from_hex, to_base64, to_hex};
+
+#[test]
+fn hex() {
+    assert_eq!(to_hex(&[]), "");
+    assert_eq!(to_hex(&[0x00, 0x9f, 0xa0, 0xff]), "009fa0ff");
+    assert_eq!(from_hex("009FA0ff"), Ok(vec![0x00, 0x9f, 0xa0, 0xff]));
+    assert_eq!(from_hex("abc"), Err(DecodeError::InvalidLength { len: 3 }));
+    assert_eq!(
+        from_hex("12g4"),
+        Err(DecodeError::InvalidChar {
+            index: 2,
+            char: 'g'
+        })
+    );
+    assert_eq!(
+        from_hex("1é0"),
+        Err(DecodeError::InvalidChar {
+            index: 1,
+            char: 'é'
+        })
+    );
+}
+
+#[test]
+fn base64() {
+    // the test vectors of RFC 4648
+    let vectors = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+    for (plain, encoded) in vectors {
+        assert_eq!(to_base64(plain.as_bytes()), encoded);
+        assert_eq!(from_base64(encoded), Ok(plain.as_bytes().to_vec()));
+        let unpadded = encoded.trim_end_matches('=');
+        assert_eq!(from_base64(unpadded), Ok(plain.as_bytes().to_vec()));
+    }
+    assert_eq!(to_base64(&[0xfb, 0xff]), "+/8=");
+    assert_eq!(from_base64("+/8="), Ok(vec![0xfb, 0xff]));
+}
+
+#[test]
+fn base64_invalid() {
+    for s in ["Z", "Zg=", "Zg===", "Zm9vY", "Zm9vY===="] {
+        assert_eq!(
+            from_base64(s),
+            Err(DecodeError::InvalidLength { len: s.len() }),
+            "{s}"
+        );
+    }
+    assert_eq!(
+        from_base64("Zm-v"),
+        Err(DecodeError::InvalidChar {
+            index: 2,
+            char: '-'
+        })
+    );
+    assert_eq!(
+        from_base64("Zm=v"),
+        Err(DecodeError::InvalidChar {
+            index: 2,
+            char: '='
+        })
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn round_trip(bytes: Vec<u8>) {
+        proptest::prop_assert_eq!(from_hex(&to_hex(&bytes)), Ok(bytes.clone()));
+        proptest::prop_assert_eq!(from_base64(&to_base64(&bytes)), Ok(bytes));
+    }
+
+    #[test]
+    fn never_panics(s: String) {
+        let _ = from_hex(&s);
+        let _ = from_base64(&s);
+    }
+}
@@ -0,0 +1,195 @@
+//! Self-describing tag-length-value containers.
+//!
+//! Each field is written as a [`varint`] tag, a [`varint`] length in bits,
+//! and then the packed value itself.  Since every field carries its length,
+//! readers can skip tags they don't know about, which lets a format grow new
+//! fields without breaking old decoders.
+//!
+//! ```rust
+//! # use packed::tlv::{TlvReader, TlvWriter};
+//! let mut buf = [0u8; 16];
+//! let mut writer = TlvWriter::new(&mut buf, 0);
+//! writer.write(1, 0x1234u16).write(7, [true, false, true]).write(2, -5i8);
+//! let len = writer.len();
+//!
+//! let reader = TlvReader::new(&buf, 0, len);
+//! assert_eq!(reader.get::<i8>(2), Ok(Some(-5)));
+//! assert_eq!(reader.get::<u16>(1), Ok(Some(0x1234)));
+//! assert_eq!(reader.get::<u16>(3), Ok(None));
+//! ```
+//!
+//! The size of a field depends on its tag, so buffers can be sized for the
+//! worst case with [`max_field_size`] and budgeted exactly with
+//! [`field_size`].
+//!
+//! ```rust
+//! # use packed::tlv;
+//! assert_eq!(tlv::max_field_size::<u16>(), 80 + 8 + 16);
+//! assert_eq!(tlv::field_size::<u16>(1), 8 + 8 + 16);
+//! assert_eq!(tlv::field_size::<u16>(1000), 16 + 8 + 16);
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{bit_len, check_len};
+use crate::{Pack, PackedSize, Unpack, UnpackError, UnpackErrorKind, varint};
+
+/// Amount of bits a field holding a `T` takes up with the largest possible
+/// tag
+pub const fn max_field_size<T: PackedSize>() -> usize {
+    varint::MAX_SIZE + varint::size(T::SIZE as u64) + T::SIZE
+}
+
+/// Amount of bits a field holding a `T` takes up with tag `tag`
+pub const fn field_size<T: PackedSize>(tag: u64) -> usize {
+    varint::size(tag) + varint::size(T::SIZE as u64) + T::SIZE
+}
+
+/// Writes tagged fields one after another
+pub struct TlvWriter<'a> {
+    bytes: &'a mut [u8],
+    start: usize,
+    offset: usize,
+}
+
+impl<'a> TlvWriter<'a> {
+    pub fn new(bytes: &'a mut [u8], offset: usize) -> Self {
+        Self {
+            bytes,
+            start: offset,
+            offset,
+        }
+    }
+
+    /// Append a field
+    ///
+    /// # Panics
+    ///
+    /// If the field doesn't fit in the buffer
+    pub fn write<T>(&mut self, tag: u64, value: T) -> &mut Self
+    where
+        T: Pack,
+    {
+        assert!(
+            bit_len(self.bytes) - self.offset >= field_size::<T>(tag),
+            "tlv field does not fit in buffer"
+        );
+        self.offset += varint::pack(tag, self.bytes, self.offset);
+        self.offset += varint::pack(T::SIZE as u64, self.bytes, self.offset);
+        value.pack(self.bytes, self.offset);
+        self.offset += T::SIZE;
+        self
+    }
+
+    /// Amount of bits written so far
+    pub fn len(&self) -> usize {
+        self.offset - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A single field read from a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlvField<'a> {
+    pub tag: u64,
+    /// Bit offset of the value in `bytes`
+    pub offset: usize,
+    /// Length of the value in bits
+    pub len: usize,
+    pub bytes: &'a [u8],
+}
+
+impl TlvField<'_> {
+    /// Unpack the value, failing if it doesn't have the size of `T`
+    pub fn get<T>(&self) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        if self.len != T::SIZE {
+            return Err(UnpackErrorKind::Length {
+                offset: self.offset,
+                expected: T::SIZE,
+                found: self.len,
+            }
+            .into());
+        }
+        Ok(T::unpack(self.bytes, self.offset))
+    }
+}
+
+/// Iterates over the fields of a container
+#[derive(Debug, Clone)]
+pub struct TlvReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    end: usize,
+}
+
+impl<'a> TlvReader<'a> {
+    /// Read the `len` bits starting at `offset`.  If `bytes` doesn't hold
+    /// them, reading fails with [`UnpackErrorKind::UnexpectedEnd`].
+    pub fn new(bytes: &'a [u8], offset: usize, len: usize) -> Self {
+        Self {
+            bytes,
+            offset,
+            end: offset.saturating_add(len),
+        }
+    }
+
+    /// Unpack the first field tagged `tag`, skipping all others
+    pub fn get<T>(&self, tag: u64) -> Result<Option<T>, UnpackError>
+    where
+        T: Unpack,
+    {
+        for field in self.clone() {
+            let field = field?;
+            if field.tag == tag {
+                return field.get().map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_field(&mut self) -> Result<TlvField<'a>, UnpackError> {
+        check_len(self.bytes, self.offset, self.end - self.offset)?;
+        // don't let varints read past the end of the container
+        let bytes = &self.bytes[..self.end.div_ceil(8)];
+        let (tag, n) = varint::unpack(bytes, self.offset)?;
+        let (len, m) = varint::unpack(bytes, self.offset + n)?;
+        let offset = self.offset + n + m;
+        if offset > self.end {
+            return Err(UnpackErrorKind::UnexpectedEnd { offset: self.end }.into());
+        }
+        let len = usize::try_from(len).map_err(|_| UnpackErrorKind::Overflow { offset })?;
+        if self.end - offset < len {
+            return Err(UnpackErrorKind::UnexpectedEnd { offset: self.end }.into());
+        }
+        self.offset = offset + len;
+        Ok(TlvField {
+            tag,
+            offset,
+            len,
+            bytes: self.bytes,
+        })
+    }
+}
+
+impl<'a> Iterator for TlvReader<'a> {
+    type Item = Result<TlvField<'a>, UnpackError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.end {
+            return None;
+        }
+        let field = self.read_field();
+        if field.is_err() {
+            // stop after the first error
+            self.offset = self.end;
+        }
+        Some(field)
+    }
+}
@@ -0,0 +1,89 @@
+use super::{TlvReader, TlvWriter, field_size, max_field_size};
+use crate::UnpackErrorKind;
+
+#[test]
+fn skips_unknown_tags() {
+    let mut buf = [0u8; 32];
+    let mut writer = TlvWriter::new(&mut buf, 5);
+    writer
+        .write(1, 42u8)
+        .write(1000, [0xffu8; 8])
+        .write(2, (true, 0x1234u16));
+    let len = writer.len();
+
+    let reader = TlvReader::new(&buf, 5, len);
+    let tags = reader.clone().map(|f| f.unwrap().tag).collect::<Vec<_>>();
+    assert_eq!(tags, [1, 1000, 2]);
+    assert_eq!(reader.get::<(bool, u16)>(2), Ok(Some((true, 0x1234))));
+    assert_eq!(reader.get::<u8>(1), Ok(Some(42)));
+}
+
+#[test]
+fn wrong_length() {
+    let mut buf = [0u8; 4];
+    let mut writer = TlvWriter::new(&mut buf, 0);
+    writer.write(3, 7u8);
+    let len = writer.len();
+    assert_eq!(
+        TlvReader::new(&buf, 0, len).get::<u16>(3),
+        Err(UnpackErrorKind::Length {
+            offset: 16,
+            expected: 16,
+            found: 8
+        }
+        .into())
+    );
+}
+
+#[test]
+fn truncated() {
+    let mut buf = [0u8; 4];
+    let mut writer = TlvWriter::new(&mut buf, 0);
+    writer.write(3, 0xabcdu16);
+    let mut reader = TlvReader::new(&buf, 0, 24);
+    assert_eq!(
+        reader.next(),
+        Some(Err(UnpackErrorKind::UnexpectedEnd { offset: 24 }.into()))
+    );
+    assert_eq!(reader.next(), None);
+}
+
+#[test]
+fn longer_than_buffer() {
+    let reader = TlvReader::new(&[0x01, 0x08], 0, 1000);
+    assert_eq!(
+        reader.get::<u8>(1),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+    let reader = TlvReader::new(&[0x01, 0x08], 12, usize::MAX);
+    assert_eq!(reader.count(), 1);
+}
+
+proptest::proptest! {
+    #[test]
+    fn tlv_roundtrip(a: u32, b: [bool; 5], tag_a: u64, offset in 0usize..=16) {
+        let mut buf = [0u8; 32];
+        let mut writer = TlvWriter::new(&mut buf, offset);
+        writer.write(tag_a, a).write(tag_a.wrapping_add(1), b);
+        let len = writer.len();
+        let mut reader = TlvReader::new(&buf, offset, len);
+        assert_eq!(reader.next().unwrap().unwrap().get::<u32>(), Ok(a));
+        assert_eq!(reader.next().unwrap().unwrap().get::<[bool; 5]>(), Ok(b));
+        assert_eq!(reader.next(), None);
+    }
+}
+
+#[test]
+fn field_sizes() {
+    let mut buf = [0u8; 48];
+    let mut writer = TlvWriter::new(&mut buf, 3);
+    writer.write(1, 42u8);
+    assert_eq!(writer.len(), field_size::<u8>(1));
+    writer.write(u64::MAX, [true; 200]);
+    assert_eq!(
+        writer.len(),
+        field_size::<u8>(1) + max_field_size::<[bool; 200]>()
+    );
+    assert_eq!(field_size::<[bool; 200]>(u64::MAX), 80 + 16 + 200);
+    assert_eq!(field_size::<()>(0), 16);
+}
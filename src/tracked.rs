@@ -0,0 +1,165 @@
+//! Buffers which remember which of their bits were written.
+//!
+//! A [`TrackedBuffer`] packs values into a buffer like
+//! [`Pack::pack_with`], and records the bit range each write covered.
+//! Protocols which send incremental updates of a packed blob can then
+//! resend only the dirty regions as a [`DeltaFrame`], and blobs checksummed
+//! in blocks only need the checksums of the dirty blocks recomputed.
+//!
+//! ```rust
+//! # use packed::tracked::TrackedBuffer;
+//! let mut state = TrackedBuffer::new(vec![0u8; 16]);
+//! state.pack(&0x1234u16, 20).unwrap();
+//! state.pack(&true, 36).unwrap();
+//! state.pack(&0xffu8, 100).unwrap();
+//! assert_eq!(state.dirty(), [20..37, 100..108]);
+//! assert_eq!(state.dirty_blocks(32).collect::<Vec<_>>(), [0, 1, 3]);
+//! state.clear();
+//! assert!(state.dirty().is_empty());
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::delta::DeltaFrame;
+use crate::error::{check_len, check_space};
+use crate::{Pack, PackError, Unpack, UnpackError};
+
+/// A buffer recording the bit ranges written since it was last cleared
+#[derive(Debug, Clone)]
+pub struct TrackedBuffer<B = Vec<u8>, C = DefaultCfg> {
+    buf: B,
+    /// Sorted, with no two ranges overlapping or touching
+    dirty: Vec<Range<usize>>,
+    _cfg: PhantomData<fn() -> C>,
+}
+
+impl<B> TrackedBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    pub fn new(buf: B) -> Self {
+        Self::with_cfg(buf)
+    }
+}
+
+impl<B, C> TrackedBuffer<B, C>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+    C: Cfg,
+{
+    /// Buffer packing values with the configuration `C`
+    pub fn with_cfg(buf: B) -> Self {
+        Self {
+            buf,
+            dirty: Vec::new(),
+            _cfg: PhantomData,
+        }
+    }
+
+    /// Pack `value` at `offset` and mark its bits dirty
+    pub fn pack<T>(&mut self, value: &T, offset: usize) -> Result<(), PackError>
+    where
+        T: Pack + ?Sized,
+    {
+        let bytes = self.buf.as_mut();
+        check_space(bytes, offset, T::SIZE)?;
+        value.pack_with::<C>(bytes, offset);
+        self.mark_dirty(offset..offset + T::SIZE);
+        Ok(())
+    }
+
+    pub fn unpack<T>(&self, offset: usize) -> Result<T, UnpackError>
+    where
+        T: Unpack,
+    {
+        let bytes = self.buf.as_ref();
+        check_len(bytes, offset, T::SIZE)?;
+        Ok(T::unpack_with::<C>(bytes, offset))
+    }
+
+    /// Mark bits dirty which were changed some other way, e.g. through
+    /// [`TrackedBuffer::as_mut_untracked`]
+    pub fn mark_dirty(&mut self, bits: Range<usize>) {
+        if bits.is_empty() {
+            return;
+        }
+        // ranges which overlap or touch `bits` are merged into it
+        let first = self.dirty.partition_point(|r| r.end < bits.start);
+        let last = self.dirty.partition_point(|r| r.start <= bits.end);
+        let merged = match self.dirty[first..last] {
+            [] => bits,
+            [ref head, .., ref tail] | [ref head @ ref tail] => {
+                head.start.min(bits.start)..tail.end.max(bits.end)
+            }
+        };
+        self.dirty.splice(first..last, [merged]);
+    }
+
+    /// The bit ranges written since the last [`TrackedBuffer::clear`], in
+    /// order, with overlapping and adjacent writes merged
+    pub fn dirty(&self) -> &[Range<usize>] {
+        &self.dirty
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// The byte ranges holding dirty bits, merged where they share bytes
+    pub fn dirty_bytes(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let mut ranges = self.dirty.iter().map(|r| r.start / 8..r.end.div_ceil(8));
+        let mut pending = ranges.next();
+        std::iter::from_fn(move || {
+            let mut current = pending.take()?;
+            for next in ranges.by_ref() {
+                if next.start > current.end {
+                    pending = Some(next);
+                    break;
+                }
+                current.end = next.end;
+            }
+            Some(current)
+        })
+    }
+
+    /// Indices of the blocks of `block_bits` bits holding dirty bits, in
+    /// order
+    pub fn dirty_blocks(&self, block_bits: usize) -> impl Iterator<Item = usize> + '_ {
+        assert!(block_bits > 0, "blocks can't be empty");
+        let mut next = 0;
+        self.dirty.iter().flat_map(move |r| {
+            let blocks = (r.start / block_bits).max(next)..r.end.div_ceil(block_bits);
+            next = next.max(blocks.end);
+            blocks
+        })
+    }
+
+    /// The dirty bits, to bring a peer which saw the buffer when it was last
+    /// cleared up to date
+    pub fn delta(&self) -> DeltaFrame {
+        DeltaFrame::from_ranges::<C>(self.buf.as_ref(), self.dirty.iter().cloned())
+    }
+
+    /// Forget every dirty range
+    pub fn clear(&mut self) {
+        self.dirty.clear();
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.buf.as_ref()
+    }
+
+    /// The buffer, without tracking changes made through it
+    pub fn as_mut_untracked(&mut self) -> &mut [u8] {
+        self.buf.as_mut()
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
@@ -0,0 +1,86 @@
+#![allow(clippy::single_range_in_vec_init)]
+
+use super::TrackedBuffer;
+use crate::PackError;
+use crate::cfg::Lsb0Le;
+use crate::crc::Crc;
+
+#[test]
+fn merges_ranges() {
+    let mut buf = TrackedBuffer::new([0u8; 32]);
+    buf.mark_dirty(40..48);
+    buf.mark_dirty(10..12);
+    buf.mark_dirty(12..14);
+    assert_eq!(buf.dirty(), [10..14, 40..48]);
+    buf.mark_dirty(20..20);
+    buf.mark_dirty(100..110);
+    buf.mark_dirty(44..101);
+    assert_eq!(buf.dirty(), [10..14, 40..110]);
+    buf.mark_dirty(0..200);
+    assert_eq!(buf.dirty(), [0..200]);
+    assert!(buf.is_dirty());
+    buf.clear();
+    assert!(!buf.is_dirty());
+}
+
+#[test]
+fn dirty_bytes_and_blocks() {
+    let mut buf = TrackedBuffer::new(vec![0u8; 32]);
+    buf.pack(&[true; 3], 3).unwrap();
+    buf.pack(&0u8, 7).unwrap();
+    buf.pack(&false, 9).unwrap();
+    buf.pack(&0u16, 30).unwrap();
+    buf.pack(&0u8, 100).unwrap();
+    assert_eq!(buf.dirty(), [3..6, 7..15, 30..46, 100..108]);
+    assert_eq!(buf.dirty_bytes().collect::<Vec<_>>(), [0..2, 3..6, 12..14]);
+    assert_eq!(buf.dirty_blocks(16).collect::<Vec<_>>(), [0, 1, 2, 6]);
+    assert_eq!(buf.dirty_blocks(256).collect::<Vec<_>>(), [0]);
+}
+
+#[test]
+fn packs_with_cfg() {
+    let mut buf = TrackedBuffer::<_, Lsb0Le>::with_cfg([0u8; 3]);
+    buf.pack(&0x1234u16, 4).unwrap();
+    assert_eq!(buf.as_bytes(), [0x40, 0x23, 0x01]);
+    assert_eq!(buf.unpack::<u16>(4), Ok(0x1234));
+    assert!(buf.unpack::<u16>(9).is_err());
+    assert_eq!(
+        buf.pack(&0u16, 9),
+        Err(PackError::OutOfSpace {
+            offset: 9,
+            needed: 16
+        })
+    );
+    assert_eq!(buf.dirty(), [4..20]);
+    buf.as_mut_untracked()[0] = 0;
+    assert_eq!(buf.dirty(), [4..20]);
+    assert_eq!(buf.into_inner(), [0x00, 0x23, 0x01]);
+}
+
+#[test]
+fn recomputes_dirty_crcs() {
+    const BLOCK: usize = 64;
+    let mut state = TrackedBuffer::new(vec![0u8; 64]);
+    let checksums = |bytes: &[u8]| {
+        bytes
+            .chunks(BLOCK / 8)
+            .map(|block| {
+                let mut crc = Crc::CRC16_IBM_3740;
+                crc.update_bytes(block);
+                crc.value()
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut sums = checksums(state.as_bytes());
+    state.clear();
+
+    state.pack(&0xdead_beefu32, 60).unwrap();
+    state.pack(&true, 300).unwrap();
+    for block in state.dirty_blocks(BLOCK).collect::<Vec<_>>() {
+        let bytes = &state.as_bytes()[block * BLOCK / 8..][..BLOCK / 8];
+        let mut crc = Crc::CRC16_IBM_3740;
+        crc.update_bytes(bytes);
+        sums[block] = crc.value();
+    }
+    assert_eq!(sums, checksums(state.as_bytes()));
+}
@@ -0,0 +1,84 @@
+//! Variable-length integers.
+//!
+//! Values are split into groups of 7 bits, least significant group first,
+//! and each group is packed as a byte whose top bit says whether another
+//! group follows.  At byte-aligned offsets this is plain LEB128, but like the
+//! rest of the crate a varint can start at any bit offset.
+//!
+//! ```rust
+//! # use packed::varint;
+//! let mut buf = [0u8; 3];
+//! assert_eq!(varint::pack(300, &mut buf, 4), 16);
+//! assert_eq!(buf, [0x0a, 0xc0, 0x20]);
+//! assert_eq!(varint::unpack(&buf, 4), Ok((300, 16)));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::error::bit_len;
+use crate::{Pack, Unpack, UnpackError, UnpackErrorKind};
+
+/// Longest encoding of a `u64`, in bits
+pub const MAX_SIZE: usize = 10 * 8;
+
+/// Amount of bits `value` takes up when packed
+pub const fn size(value: u64) -> usize {
+    let groups = (64 - value.leading_zeros() as usize).div_ceil(7);
+    if groups == 0 { 8 } else { groups * 8 }
+}
+
+/// Pack `value` at `offset`, returning the amount of bits written
+pub fn pack_with<C: Cfg>(mut value: u64, bytes: &mut [u8], offset: usize) -> usize {
+    debug_assert!(bit_len(bytes) - offset >= size(value));
+    let mut written = 0;
+    loop {
+        let group = (value & 0x7f) as u8;
+        value >>= 7;
+        let more = value != 0;
+        (group | u8::from(more) << 7).pack_with::<C>(bytes, offset + written);
+        written += 8;
+        if !more {
+            return written;
+        }
+    }
+}
+
+/// Unpack a value at `offset`, returning it along with the amount of bits
+/// read
+pub fn unpack_with<C: Cfg>(bytes: &[u8], offset: usize) -> Result<(u64, usize), UnpackError> {
+    let mut value = 0;
+    let mut read = 0;
+    loop {
+        if offset
+            .checked_add(read + 8)
+            .is_none_or(|end| end > bit_len(bytes))
+        {
+            return Err(UnpackErrorKind::UnexpectedEnd {
+                offset: offset.saturating_add(read),
+            }
+            .into());
+        }
+        let byte = u8::unpack_with::<C>(bytes, offset + read);
+        let group = u64::from(byte & 0x7f);
+        if read == 9 * 8 && group > 1 || read == MAX_SIZE {
+            return Err(UnpackErrorKind::Overflow { offset }.into());
+        }
+        value |= group << (read / 8 * 7);
+        read += 8;
+        if byte & 0x80 == 0 {
+            return Ok((value, read));
+        }
+    }
+}
+
+/// Pack using [`DefaultCfg`]
+pub fn pack(value: u64, bytes: &mut [u8], offset: usize) -> usize {
+    pack_with::<DefaultCfg>(value, bytes, offset)
+}
+
+/// Unpack using [`DefaultCfg`]
+pub fn unpack(bytes: &[u8], offset: usize) -> Result<(u64, usize), UnpackError> {
+    unpack_with::<DefaultCfg>(bytes, offset)
+}
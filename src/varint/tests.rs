@@ -0,0 +1,31 @@
+use crate::{UnpackErrorKind, varint};
+
+#[test]
+fn varint_limits() {
+    let mut buf = [0u8; 12];
+    for value in [0, 1, 127, 128, u32::MAX as u64, u64::MAX] {
+        let n = varint::pack(value, &mut buf, 3);
+        assert_eq!(n, varint::size(value));
+        assert_eq!(varint::unpack(&buf, 3), Ok((value, n)));
+    }
+    assert_eq!(varint::size(u64::MAX), varint::MAX_SIZE);
+
+    let too_long = [0xff; 10];
+    assert_eq!(
+        varint::unpack(&[too_long.as_slice(), &[0]].concat(), 0),
+        Err(UnpackErrorKind::Overflow { offset: 0 }.into())
+    );
+    assert_eq!(
+        varint::unpack(&[0x80, 0x80], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn varint_roundtrip(value: u64, offset in 0usize..=16) {
+        let mut buf = [0u8; 13];
+        let n = varint::pack(value, &mut buf, offset);
+        assert_eq!(varint::unpack(&buf, offset), Ok((value, n)));
+    }
+}
@@ -0,0 +1,165 @@
+//! Packing across several non-contiguous buffers.
+//!
+//! Network stacks often hand out frames as a list of fixed-size buffers
+//! from a pool, which vectored I/O then sends as one.  These functions
+//! treat such a list as one long buffer, so a value can be packed straight
+//! into it even where its fields straddle the seam between two buffers.
+//! Bits around the value are left untouched, as with
+//! [`Pack::pack_with`], and reserved bits aren't checked when unpacking, as
+//! with [`Unpack::unpack_with`].  [`VectoredReader`] reads values back to
+//! back from such a list.
+//!
+//! [`VectoredReader`]: crate::cursor::VectoredReader
+//!
+//! ```rust
+//! # use std::io::IoSliceMut;
+//! # use packed::vectored;
+//! let (mut a, mut b) = ([0u8; 3], [0u8; 2]);
+//! let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+//! assert_eq!(vectored::pack_vectored_out(&0x123456u32, &mut bufs, 4), Ok(36));
+//! assert_eq!(vectored::unpack_vectored::<u32, _>(&bufs, 4), Ok(0x123456));
+//! assert_eq!((a, b), ([0x00, 0x01, 0x23], [0x45, 0x60]));
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::ops::{Deref, DerefMut};
+
+use crate::cfg::{Cfg, DefaultCfg};
+use crate::{Pack, PackError, Unpack, UnpackError, UnpackErrorKind};
+
+/// Values up to this many bytes are staged on the stack
+const STACK_BYTES: usize = 128;
+
+/// Copy the bytes of `bufs` from byte `start` on into `out`
+fn gather<B: Deref<Target = [u8]>>(bufs: &[B], mut start: usize, out: &mut [u8]) {
+    let mut done = 0;
+    for buf in bufs {
+        if done == out.len() {
+            break;
+        }
+        if start >= buf.len() {
+            start -= buf.len();
+            continue;
+        }
+        let n = (buf.len() - start).min(out.len() - done);
+        out[done..done + n].copy_from_slice(&buf[start..start + n]);
+        done += n;
+        start = 0;
+    }
+}
+
+/// Copy `bytes` into `bufs` from byte `start` on
+fn scatter<B: DerefMut<Target = [u8]>>(bufs: &mut [B], mut start: usize, bytes: &[u8]) {
+    let mut done = 0;
+    for buf in bufs {
+        if done == bytes.len() {
+            break;
+        }
+        if start >= buf.len() {
+            start -= buf.len();
+            continue;
+        }
+        let n = (buf.len() - start).min(bytes.len() - done);
+        buf[start..start + n].copy_from_slice(&bytes[done..done + n]);
+        done += n;
+        start = 0;
+    }
+}
+
+/// Call `f` with a buffer of `len` bytes, on the stack if it is short
+fn with_stage<R>(len: usize, f: impl FnOnce(&mut [u8]) -> R) -> R {
+    let mut stack = [0u8; STACK_BYTES];
+    let mut heap = Vec::new();
+    let stage = if len <= STACK_BYTES {
+        &mut stack[..len]
+    } else {
+        heap.resize(len, 0);
+        &mut heap[..]
+    };
+    f(stage)
+}
+
+/// Pack `value` at bit `offset` of `bufs` taken as one buffer, returning
+/// the offset just after it
+pub fn pack_vectored_out_with<C, T, B>(
+    value: &T,
+    bufs: &mut [B],
+    offset: usize,
+) -> Result<usize, PackError>
+where
+    C: Cfg,
+    T: Pack + ?Sized,
+    B: DerefMut<Target = [u8]>,
+{
+    let total = bufs.iter().map(|b| b.len()).sum::<usize>();
+    let end = offset
+        .checked_add(T::SIZE)
+        .filter(|&end| end <= total.saturating_mul(8))
+        .ok_or(PackError::OutOfSpace {
+            offset,
+            needed: T::SIZE,
+        })?;
+    let first = offset / 8;
+    with_stage(end.div_ceil(8) - first, |stage| {
+        // keep the bits sharing the first and last bytes with the value
+        gather(bufs, first, stage);
+        value.pack_with::<C>(stage, offset % 8);
+        scatter(bufs, first, stage);
+    });
+    Ok(end)
+}
+
+/// Unpack a value at bit `offset` of `bufs` taken as one buffer
+pub fn unpack_vectored_with<C, T, B>(bufs: &[B], offset: usize) -> Result<T, UnpackError>
+where
+    C: Cfg,
+    T: Unpack,
+    B: Deref<Target = [u8]>,
+{
+    let total = bufs.iter().map(|b| b.len()).sum::<usize>();
+    let end = offset
+        .checked_add(T::SIZE)
+        .filter(|&end| end <= total.saturating_mul(8))
+        .ok_or(UnpackErrorKind::UnexpectedEnd {
+            offset: total.saturating_mul(8),
+        })?;
+    let first = offset / 8;
+    let len = end.div_ceil(8) - first;
+    if !cfg!(feature = "paranoid") {
+        // values within a single buffer are unpacked in place
+        let mut start = first;
+        for buf in bufs {
+            if start < buf.len() {
+                if len <= buf.len() - start {
+                    return Ok(T::unpack_with::<C>(&buf[start..], offset % 8));
+                }
+                break;
+            }
+            start -= buf.len();
+        }
+    }
+    Ok(with_stage(len, |stage| {
+        gather(bufs, first, stage);
+        T::unpack_with::<C>(stage, offset % 8)
+    }))
+}
+
+/// Pack across buffers using [`DefaultCfg`]
+pub fn pack_vectored_out<T, B>(value: &T, bufs: &mut [B], offset: usize) -> Result<usize, PackError>
+where
+    T: Pack + ?Sized,
+    B: DerefMut<Target = [u8]>,
+{
+    pack_vectored_out_with::<DefaultCfg, T, B>(value, bufs, offset)
+}
+
+/// Unpack across buffers using [`DefaultCfg`]
+pub fn unpack_vectored<T, B>(bufs: &[B], offset: usize) -> Result<T, UnpackError>
+where
+    T: Unpack,
+    B: Deref<Target = [u8]>,
+{
+    unpack_vectored_with::<DefaultCfg, T, B>(bufs, offset)
+}
@@ -0,0 +1,113 @@
+use std::io::{IoSlice, IoSliceMut};
+
+use super::{pack_vectored_out, pack_vectored_out_with, unpack_vectored, unpack_vectored_with};
+use crate::cfg::Lsb0Le;
+use crate::{Pack, PackError, Packed, PackedSize, Reserved, UnpackErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Packed)]
+struct Frame {
+    kind: u8,
+    #[packed(bits = 12)]
+    len: u16,
+    seq: u32,
+    pad: Reserved<4>,
+}
+
+const FRAME: Frame = Frame {
+    kind: 0xa5,
+    len: 0x123,
+    seq: 0xdead_beef,
+    pad: Reserved,
+};
+
+#[test]
+fn straddles_seams() {
+    let mut whole = [0xffu8; 8];
+    FRAME.pack(&mut whole, 4);
+
+    let (mut a, mut b, mut c, mut d) = ([0xffu8; 2], [0xffu8; 1], [], [0xffu8; 5]);
+    let mut bufs = [
+        IoSliceMut::new(&mut a),
+        IoSliceMut::new(&mut b),
+        IoSliceMut::new(&mut c),
+        IoSliceMut::new(&mut d),
+    ];
+    assert_eq!(pack_vectored_out(&FRAME, &mut bufs, 4), Ok(4 + Frame::SIZE));
+    assert_eq!(unpack_vectored::<Frame, _>(&bufs, 4), Ok(FRAME));
+    assert_eq!([&a[..], &b, &c, &d].concat(), whole);
+}
+
+#[test]
+fn skips_leading_buffers() {
+    let mut bufs = vec![vec![0u8; 3], vec![0u8; 2], vec![0u8; 2]];
+    assert_eq!(
+        pack_vectored_out_with::<Lsb0Le, _, _>(&0x1234u16, &mut bufs, 30),
+        Ok(46)
+    );
+    let mut whole = [0u8; 7];
+    0x1234u16.pack_with::<Lsb0Le>(&mut whole, 30);
+    assert_eq!(bufs.concat(), whole);
+    assert_eq!(
+        unpack_vectored_with::<Lsb0Le, u16, _>(&bufs, 30),
+        Ok(0x1234)
+    );
+}
+
+#[test]
+fn large_values() {
+    let value = [0x5au8; 200];
+    let mut bufs = [[0u8; 64]; 4];
+    let mut slices = bufs.iter_mut().map(|b| &mut b[..]).collect::<Vec<_>>();
+    assert_eq!(pack_vectored_out(&value, &mut slices, 3), Ok(3 + 1600));
+    assert_eq!(unpack_vectored::<[u8; 200], _>(&slices, 3), Ok(value));
+}
+
+#[test]
+fn errors() {
+    let mut a = [0u8; 2];
+    let mut bufs = [IoSliceMut::new(&mut a)];
+    assert_eq!(
+        pack_vectored_out(&0u16, &mut bufs, 1),
+        Err(PackError::OutOfSpace {
+            offset: 1,
+            needed: 16
+        })
+    );
+    assert_eq!(
+        pack_vectored_out(&0u8, &mut bufs, usize::MAX),
+        Err(PackError::OutOfSpace {
+            offset: usize::MAX,
+            needed: 8
+        })
+    );
+
+    let (a, b) = ([0u8; 1], [0xffu8; 7]);
+    let bufs = [IoSlice::new(&a), IoSlice::new(&b)];
+    assert_eq!(
+        unpack_vectored::<u64, _>(&bufs, 1),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 64 }.into())
+    );
+    // reserved bits aren't checked
+    assert_eq!(
+        unpack_vectored::<Frame, _>(&bufs, 4).map(|f| f.kind),
+        Ok(0x0f)
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn matches_contiguous(
+        value: (u8, u32, bool, u16),
+        offset in 0usize..24,
+        lens in proptest::collection::vec(0usize..4, 1..12),
+    ) {
+        let total = lens.iter().sum::<usize>();
+        proptest::prop_assume!(total * 8 >= offset + 57);
+        let mut whole = vec![0x3cu8; total];
+        value.pack(&mut whole, offset);
+        let mut bufs = lens.iter().map(|&n| vec![0x3cu8; n]).collect::<Vec<_>>();
+        proptest::prop_assert_eq!(pack_vectored_out(&value, &mut bufs, offset), Ok(offset + 57));
+        proptest::prop_assert_eq!(bufs.concat(), whole);
+        proptest::prop_assert_eq!(unpack_vectored(&bufs, offset), Ok(value));
+    }
+}
@@ -0,0 +1,296 @@
+//! Unpacking whichever version of a struct a version field names.
+//!
+//! Formats which evolve usually start with a version number, followed by
+//! the struct for that version.  [`VersionSwitch`] is a [`Parser`] which
+//! reads the version, finds the struct in a tuple of [`Versioned`] types
+//! declaring that version, and returns it wrapped in an enum such as
+//! [`Version3`].  Older versions are upgraded to the latest by implementing
+//! `From` and calling [`Version3::upgrade`], rather than by matching on the
+//! version by hand.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::parse::{self, Parser};
+//! # use packed::version::{self, Version2, Versioned};
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct HelloV1 {
+//!     id: u8,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct HelloV2 {
+//!     id: u16,
+//!     flags: u8,
+//! }
+//!
+//! impl Versioned for HelloV1 {
+//!     const VERSION: u64 = 1;
+//! }
+//!
+//! impl Versioned for HelloV2 {
+//!     const VERSION: u64 = 2;
+//! }
+//!
+//! impl From<HelloV1> for HelloV2 {
+//!     fn from(old: HelloV1) -> Self {
+//!         Self { id: old.id.into(), flags: 0 }
+//!     }
+//! }
+//!
+//! let hello = version::switch::<_, (HelloV1, HelloV2)>(parse::fixed::<u8>());
+//! assert_eq!(hello.parse(&[1, 0x12], 0), Ok((Version2::V1(HelloV1 { id: 0x12 }), 16)));
+//! let (latest, _) = hello.parse(&[2, 0x00, 0x34, 0x80], 0).unwrap();
+//! assert_eq!(latest.upgrade::<HelloV2>(), HelloV2 { id: 0x34, flags: 0x80 });
+//! let (upgraded, _) = hello.parse(&[1, 0x56], 0).unwrap();
+//! assert_eq!(upgraded.upgrade::<HelloV2>(), HelloV2 { id: 0x56, flags: 0 });
+//! # }
+//! ```
+//!
+//! When versions only ever add fields to the end of a struct, one struct
+//! can describe all of them instead.  Fields marked `#[packed(since = N)]`
+//! in a derived struct are only read from version `N` on, and take their
+//! `#[packed(default = "...")]` expression, or `Default::default()`, in
+//! older versions.  The struct always packs as the latest version, and
+//! [`historical`] reads a version field followed by the layout it names.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! # use packed::prelude::*;
+//! # use packed::parse::{self, Parser};
+//! # use packed::version;
+//! #[derive(Debug, PartialEq, Packed)]
+//! struct Record {
+//!     id: u16,
+//!     #[packed(since = 2)]
+//!     flags: u8,
+//!     #[packed(since = 3, default = "60")]
+//!     ttl: u8,
+//! }
+//!
+//! let record = version::historical::<_, Record>(parse::fixed::<u8>());
+//! assert_eq!(
+//!     record.parse(&[1, 0x12, 0x34], 0),
+//!     Ok((Record { id: 0x1234, flags: 0, ttl: 60 }, 24)),
+//! );
+//! assert_eq!(
+//!     record.parse(&[3, 0x12, 0x34, 0x01, 0x0a], 0),
+//!     Ok((Record { id: 0x1234, flags: 1, ttl: 10 }, 40)),
+//! );
+//! # }
+//! ```
+
+#[cfg(all(test, feature = "derive"))]
+mod tests;
+
+use std::marker::PhantomData;
+
+use crate::cfg::Cfg;
+use crate::error::check_len;
+use crate::parse::{Limits, Parser, fixed};
+use crate::{Unpack, UnpackError, UnpackErrorKind};
+
+/// A struct which is one version of a format
+pub trait Versioned: Unpack {
+    /// The version number which selects this struct
+    const VERSION: u64;
+}
+
+/// A tuple of [`Versioned`] structs a [`VersionSwitch`] chooses from
+pub trait VersionSet {
+    /// The enum holding whichever version was read
+    type Output;
+
+    /// Parse the struct declaring `version` at `offset`, or `None` if no
+    /// struct in the set declares it
+    fn parse_version<C: Cfg>(
+        version: u64,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Option<Result<(Self::Output, usize), UnpackError>>;
+}
+
+/// Parser for a version field followed by that version of a struct, see
+/// [`switch`]
+pub struct VersionSwitch<V, T> {
+    version: V,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Parse a version number with `version`, then the struct in `T` declaring
+/// that version right after it.  If more than one struct declares the same
+/// version, the first one is used.  Fails with
+/// [`UnpackErrorKind::UnknownVersion`] if none does.
+pub fn switch<V, T>(version: V) -> VersionSwitch<V, T>
+where
+    V: Parser,
+    V::Output: Into<u64>,
+    T: VersionSet,
+{
+    VersionSwitch {
+        version,
+        _marker: PhantomData,
+    }
+}
+
+impl<V, T> Parser for VersionSwitch<V, T>
+where
+    V: Parser,
+    V::Output: Into<u64>,
+    T: VersionSet,
+{
+    type Output = T::Output;
+
+    fn parse_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        self.parse_limited_with::<C>(bytes, offset, Limits::NONE)
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(Self::Output, usize), UnpackError> {
+        let (version, len) = self
+            .version
+            .parse_limited_with::<C>(bytes, offset, limits)?;
+        let version = version.into();
+        let limits = limits.consume(offset + len, len)?;
+        match T::parse_version::<C>(version, bytes, offset + len, limits) {
+            Some(parsed) => parsed.map(|(x, rest)| (x, len + rest)),
+            None => Err(UnpackErrorKind::UnknownVersion { offset, version }.into()),
+        }
+    }
+}
+
+/// A struct which can be read in the layout of any of its versions, as
+/// derived for structs with `#[packed(since = N)]` fields
+pub trait VersionedLayout: Unpack {
+    /// The newest version, which is the layout [`Unpack`] reads
+    const LATEST: u64;
+
+    /// Amount of bits the layout of `version` takes up
+    fn size_for(version: u64) -> usize;
+
+    /// Unpack the layout of `version` at `offset`, giving fields added after
+    /// it their defaults.  `version` must be at most [`Self::LATEST`], and
+    /// `bytes` must hold [`Self::size_for`] bits at `offset`.
+    fn unpack_version_with<C: Cfg>(version: u64, bytes: &[u8], offset: usize) -> Self;
+}
+
+/// Parser for a version field followed by that layout of a struct, see
+/// [`historical`]
+pub struct Historical<V, T> {
+    version: V,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Parse a version number with `version`, then `T` in the layout of that
+/// version right after it.  Fails with [`UnpackErrorKind::UnknownVersion`]
+/// for versions newer than [`VersionedLayout::LATEST`].
+pub fn historical<V, T>(version: V) -> Historical<V, T>
+where
+    V: Parser,
+    V::Output: Into<u64>,
+    T: VersionedLayout,
+{
+    Historical {
+        version,
+        _marker: PhantomData,
+    }
+}
+
+impl<V, T> Parser for Historical<V, T>
+where
+    V: Parser,
+    V::Output: Into<u64>,
+    T: VersionedLayout,
+{
+    type Output = T;
+
+    fn parse_with<C: Cfg>(&self, bytes: &[u8], offset: usize) -> Result<(T, usize), UnpackError> {
+        self.parse_limited_with::<C>(bytes, offset, Limits::NONE)
+    }
+
+    fn parse_limited_with<C: Cfg>(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        limits: Limits,
+    ) -> Result<(T, usize), UnpackError> {
+        let (version, len) = self
+            .version
+            .parse_limited_with::<C>(bytes, offset, limits)?;
+        let version = version.into();
+        if version > T::LATEST {
+            return Err(UnpackErrorKind::UnknownVersion { offset, version }.into());
+        }
+        let limits = limits.consume(offset + len, len)?;
+        let size = T::size_for(version);
+        limits.consume(offset + len, size)?;
+        check_len(bytes, offset + len, size)?;
+        let value = T::unpack_version_with::<C>(version, bytes, offset + len);
+        Ok((value, len + size))
+    }
+}
+
+macro_rules! versions {
+    ($(#[$doc: meta])* $name: ident, $($v: ident: $t: ident),+) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name<$($t),+> {
+            $($v($t)),+
+        }
+
+        impl<$($t),+> $name<$($t),+> {
+            /// Convert whichever version was read into `L`, usually the
+            /// latest version
+            pub fn upgrade<L>(self) -> L
+            where
+                $($t: Into<L>),+
+            {
+                match self {
+                    $(Self::$v(x) => x.into()),+
+                }
+            }
+        }
+
+        impl<$($t),+> VersionSet for ($($t,)+)
+        where
+            $($t: Versioned),+
+        {
+            type Output = $name<$($t),+>;
+
+            fn parse_version<C: Cfg>(
+                version: u64,
+                bytes: &[u8],
+                offset: usize,
+                limits: Limits,
+            ) -> Option<Result<(Self::Output, usize), UnpackError>> {
+                $(if version == $t::VERSION {
+                    let parsed = fixed::<$t>().parse_limited_with::<C>(bytes, offset, limits);
+                    return Some(parsed.map(|(x, len)| ($name::$v(x), len)));
+                })+
+                None
+            }
+        }
+    };
+}
+
+versions!(
+    /// One of two versions, in the order the tuple lists them
+    Version2, V1: T1, V2: T2
+);
+versions!(
+    /// One of three versions, in the order the tuple lists them
+    Version3, V1: T1, V2: T2, V3: T3
+);
+versions!(
+    /// One of four versions, in the order the tuple lists them
+    Version4, V1: T1, V2: T2, V3: T3, V4: T4
+);
@@ -0,0 +1,241 @@
+use super::{Version3, Versioned, VersionedLayout, historical, switch};
+use crate::cfg::Lsb0Le;
+use crate::parse::{Limits, Parser, fixed, varint};
+use crate::{Pack, Packed, PackedSize, Unpack, UnpackErrorKind};
+
+#[derive(Debug, PartialEq, Packed)]
+struct V1 {
+    #[packed(bits = 4)]
+    level: u8,
+}
+
+#[derive(Debug, PartialEq, Packed)]
+struct V2 {
+    level: u8,
+    enabled: bool,
+}
+
+#[derive(Debug, PartialEq, Packed)]
+struct V5 {
+    level: u16,
+    enabled: bool,
+    mode: u8,
+}
+
+impl Versioned for V1 {
+    const VERSION: u64 = 1;
+}
+
+impl Versioned for V2 {
+    const VERSION: u64 = 2;
+}
+
+// versions don't have to be consecutive
+impl Versioned for V5 {
+    const VERSION: u64 = 5;
+}
+
+impl From<V1> for V5 {
+    fn from(old: V1) -> Self {
+        V2 {
+            level: old.level,
+            enabled: true,
+        }
+        .into()
+    }
+}
+
+impl From<V2> for V5 {
+    fn from(old: V2) -> Self {
+        Self {
+            level: old.level.into(),
+            enabled: old.enabled,
+            mode: 0,
+        }
+    }
+}
+
+#[test]
+fn dispatch() {
+    let parser = switch::<_, (V1, V2, V5)>(fixed::<u8>());
+    assert_eq!(
+        parser.parse(&[1, 0x70], 0),
+        Ok((Version3::V1(V1 { level: 7 }), 12))
+    );
+    assert_eq!(
+        parser.parse(&[2, 0x70, 0x80], 0),
+        Ok((
+            Version3::V2(V2 {
+                level: 0x70,
+                enabled: true
+            }),
+            17
+        ))
+    );
+    let (v5, len) = parser.parse(&[0xff, 5, 0x12, 0x34, 0x80, 0x80], 8).unwrap();
+    assert_eq!(len, 8 + 16 + 1 + 8);
+    assert_eq!(
+        v5,
+        Version3::V3(V5 {
+            level: 0x1234,
+            enabled: true,
+            mode: 1
+        })
+    );
+
+    // the version can come from any parser, here a varint in Lsb0 order
+    let parser = switch::<_, (V1, V2)>(varint());
+    let (v, len) = parser.parse_with::<Lsb0Le>(&[2, 0x12, 0x01], 0).unwrap();
+    assert_eq!(
+        v.upgrade::<V5>(),
+        V5 {
+            level: 0x12,
+            enabled: true,
+            mode: 0
+        }
+    );
+    assert_eq!(len, 17);
+}
+
+#[test]
+fn upgrade() {
+    let parser = switch::<_, (V1, V2, V5)>(fixed::<u8>());
+    let latest = |bytes: &[u8]| parser.parse(bytes, 0).unwrap().0.upgrade::<V5>();
+    let expected = V5 {
+        level: 3,
+        enabled: true,
+        mode: 0,
+    };
+    assert_eq!(latest(&[1, 0x30]), expected);
+    assert_eq!(latest(&[2, 3, 0x80]), expected);
+    assert_eq!(latest(&[5, 0, 3, 0x80, 0]), expected);
+}
+
+#[test]
+fn errors() {
+    let parser = switch::<_, (V1, V2, V5)>(fixed::<u8>());
+    let err = parser.parse(&[0, 3, 0x7f], 8).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &UnpackErrorKind::UnknownVersion {
+            offset: 8,
+            version: 3
+        }
+    );
+    assert_eq!(err.to_string(), "unknown version 3 at bit 8");
+    assert_eq!(
+        parser.parse(&[5, 0, 0], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 24 }.into())
+    );
+    assert_eq!(
+        parser.parse(&[], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 0 }.into())
+    );
+
+    // the version field and the struct both count against the limits
+    let limits = |max_bits| Limits {
+        max_bits,
+        ..Limits::NONE
+    };
+    let bytes = [2, 0x70, 0x80];
+    assert!(parser.parse_limited(&bytes, 0, limits(17)).is_ok());
+    assert_eq!(
+        parser.parse_limited(&bytes, 0, limits(16)),
+        Err(UnpackErrorKind::Limit { offset: 8 }.into())
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Packed)]
+struct Record {
+    #[packed(since = 0)]
+    id: u8,
+    #[packed(since = 2, bits = 4)]
+    kind: u8,
+    #[packed(since = 2)]
+    flags: [bool; 2],
+    #[packed(since = 4, default = "tags_default()")]
+    tags: Box<[u8; 2]>,
+}
+
+fn tags_default() -> Box<[u8; 2]> {
+    Box::new([0xff; 2])
+}
+
+#[test]
+fn layouts() {
+    assert_eq!(Record::LATEST, 4);
+    assert_eq!(
+        [0, 1, 2, 3, 4].map(Record::size_for),
+        [8, 8, 14, 14, Record::SIZE]
+    );
+
+    let parser = historical::<_, Record>(fixed::<u8>());
+    let old = Record {
+        id: 0x12,
+        kind: 0,
+        flags: [false; 2],
+        tags: tags_default(),
+    };
+    assert_eq!(parser.parse(&[1, 0x12], 0), Ok((old.clone(), 16)));
+    assert_eq!(
+        parser.parse_with::<Lsb0Le>(&[3, 0x12, 0b10_1010], 0),
+        Ok((
+            Record {
+                kind: 0b1010,
+                flags: [false, true],
+                ..old.clone()
+            },
+            22
+        ))
+    );
+
+    // the latest version reads like a plain unpack
+    let latest = Record {
+        id: 1,
+        kind: 2,
+        flags: [true, false],
+        tags: Box::new([3, 4]),
+    };
+    let mut buf = [4u8; 5];
+    latest.pack(&mut buf, 8);
+    assert_eq!(Record::unpack(&buf, 8), latest);
+    assert_eq!(parser.parse(&buf, 0), Ok((latest, 8 + Record::SIZE)));
+    assert_eq!(
+        Record::unpack_version_with::<Lsb0Le>(0, &[0x34], 0),
+        Record { id: 0x34, ..old }
+    );
+}
+
+#[test]
+fn layout_errors() {
+    let parser = historical::<_, Record>(fixed::<u8>());
+    assert_eq!(
+        parser.parse(&[5, 0, 0, 0, 0], 0),
+        Err(UnpackErrorKind::UnknownVersion {
+            offset: 0,
+            version: 5
+        }
+        .into())
+    );
+    assert_eq!(
+        parser.parse(&[2, 0x12], 0),
+        Err(UnpackErrorKind::UnexpectedEnd { offset: 16 }.into())
+    );
+    let limits = Limits {
+        max_bits: 15,
+        ..Limits::NONE
+    };
+    assert!(parser.parse_limited(&[1, 0x12], 0, limits).is_err());
+    assert!(
+        parser
+            .parse_limited(
+                &[0, 0x12],
+                0,
+                Limits {
+                    max_bits: 16,
+                    ..limits
+                }
+            )
+            .is_ok()
+    );
+}